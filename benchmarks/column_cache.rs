@@ -0,0 +1,103 @@
+//! Compare decoding the column cache lazily against decoding every column upfront, and opening
+//! a tile against opening it and decoding every feature's properties as it used to.
+//!
+//! The Open Vector Tiles are built from the OpenMapTiles MVT data in `benchmarks/data/omt/mvt`.
+//!
+//! ```bash
+//! cargo bench --bench column_cache
+//! ```
+
+use open_vector_tile::{
+    VectorFeatureMethods, VectorLayerMethods, VectorTile, base::BaseVectorTile,
+    mapbox::MapboxVectorTile, open::ColumnCacheReader, write_tile,
+};
+use pbf::Protobuf;
+use std::{fs, hint::black_box, time::Instant};
+
+const RUNS: u32 = 5;
+
+fn main() {
+    let mut paths: Vec<_> =
+        fs::read_dir("./benchmarks/data/omt/mvt").unwrap().map(|e| e.unwrap().path()).collect();
+    paths.sort();
+    let tiles: Vec<Vec<u8>> = paths
+        .iter()
+        .filter_map(|path| MapboxVectorTile::try_new(fs::read(path).unwrap(), None).ok())
+        .map(|mut mvt| write_tile(Some(&mut BaseVectorTile::from(&mut mvt)), None, None))
+        .collect();
+    let columns: Vec<Vec<u8>> = tiles.iter().map(|tile| column_cache(tile)).collect();
+    println!("{} tiles", tiles.len());
+
+    bench("column cache: eager", tiles.len(), || {
+        for data in &columns {
+            let mut cache = ColumnCacheReader::new();
+            Protobuf::from_input(data.clone()).read_fields(&mut cache, None);
+            black_box(cache);
+        }
+    });
+    bench("column cache: lazy", tiles.len(), || {
+        for data in &columns {
+            black_box(ColumnCacheReader::from_input(data.clone()));
+        }
+    });
+    bench("tile: open", tiles.len(), || {
+        for data in &tiles {
+            black_box(VectorTile::new(data.clone(), None));
+        }
+    });
+    bench("tile: open eagerly", tiles.len(), || {
+        for data in &tiles {
+            let mut tile = VectorTile::new(data.clone(), None);
+            for layer in tile.layers.values_mut() {
+                for i in 0..layer.len() {
+                    black_box(layer.feature(i).unwrap().properties());
+                }
+            }
+            black_box(tile);
+        }
+    });
+    bench("tile: read one layer", tiles.len(), || {
+        for data in &tiles {
+            let mut tile = VectorTile::new(data.clone(), None);
+            if let Some(layer) = tile.layers.values_mut().next() {
+                load_layer(layer);
+            }
+        }
+    });
+    bench("tile: read every layer", tiles.len(), || {
+        for data in &tiles {
+            let mut tile = VectorTile::new(data.clone(), None);
+            tile.layers.values_mut().for_each(load_layer);
+        }
+    });
+}
+
+/// Run the function `RUNS` times and print the average time per tile
+fn bench(name: &str, count: usize, mut f: impl FnMut()) {
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        f();
+    }
+    let per_tile = start.elapsed() / RUNS / count.max(1) as u32;
+    println!("{name:<24} {per_tile:>12.2?} / tile");
+}
+
+/// Load the geometry of every feature in the layer
+fn load_layer(layer: &mut impl VectorLayerMethods) {
+    for i in 0..layer.len() {
+        black_box(layer.feature(i).unwrap().load_geometry());
+    }
+}
+
+/// Pull the column cache message out of an Open Vector Tile
+fn column_cache(tile: &[u8]) -> Vec<u8> {
+    let mut pbf = Protobuf::from_input(tile.to_vec());
+    while pbf.get_pos() < pbf.len() {
+        let field = pbf.read_field();
+        if field.tag == 5 {
+            return pbf.read_bytes();
+        }
+        pbf.skip(field.r#type);
+    }
+    Vec::new()
+}
@@ -0,0 +1,63 @@
+//! Compare the size of Open Vector Tiles with the string and number columns sorted by reuse
+//! against keeping them in insertion order.
+//!
+//! The Open Vector Tiles are built from the OpenMapTiles MVT data in `benchmarks/data/omt/mvt`.
+//!
+//! ```bash
+//! cargo bench --bench column_sizes
+//! ```
+
+use open_vector_tile::{
+    base::BaseVectorTile,
+    mapbox::MapboxVectorTile,
+    open::{ColumnCacheWriter, write_layer, write_layer_name},
+};
+use pbf::Protobuf;
+use std::fs;
+
+fn main() {
+    let mut paths: Vec<_> =
+        fs::read_dir("./benchmarks/data/omt/mvt").unwrap().map(|e| e.unwrap().path()).collect();
+    paths.sort();
+
+    let (mut total_unsorted, mut total_sorted) = (0, 0);
+    for path in paths {
+        let Ok(mut mvt) = MapboxVectorTile::try_new(fs::read(&path).unwrap(), None) else {
+            continue;
+        };
+        let unsorted = encode(BaseVectorTile::from(&mut mvt), ColumnCacheWriter::unsorted());
+        let sorted = encode(BaseVectorTile::from(&mut mvt), ColumnCacheWriter::default());
+        total_unsorted += unsorted;
+        total_sorted += sorted;
+        let name = path.file_name().unwrap().to_string_lossy();
+        println!("{name:<24} {unsorted:>10} {sorted:>10} {:>8.2}%", saved(unsorted, sorted));
+    }
+    println!(
+        "{:<24} {total_unsorted:>10} {total_sorted:>10} {:>8.2}%",
+        "total",
+        saved(total_unsorted, total_sorted)
+    );
+}
+
+/// Encode the tile the same way `write_tile` does but with the given cache
+fn encode(mut tile: BaseVectorTile, mut cache: ColumnCacheWriter) -> usize {
+    let mut pbf = Protobuf::new();
+    let layers: Vec<_> = tile
+        .layers
+        .values_mut()
+        .map(|layer| (cache.add_string(layer.name.clone()), write_layer(layer, &mut cache)))
+        .collect();
+    cache.sort();
+    for (name, layer) in layers {
+        let mut data = write_layer_name(&name);
+        data.extend(layer);
+        pbf.write_bytes_field(4, &data);
+    }
+    pbf.write_message(5, &cache);
+    pbf.take().len()
+}
+
+/// Percentage of bytes saved by sorting
+fn saved(unsorted: usize, sorted: usize) -> f64 {
+    (unsorted as f64 - sorted as f64) / unsorted as f64 * 100.
+}
@@ -0,0 +1,75 @@
+//! Compare the size and decode time of MapLibre Tiles against the same tiles as Open Vector Tiles.
+//!
+//! The MapLibre Tiles are the Bing Maps tiles in `benchmarks/data/bing/mlt`, each decoded with
+//! its `.mlt.meta.pbf` tileset metadata and converted to an Open Vector Tile.
+//!
+//! ```bash
+//! cargo bench --bench mlt_vs_ovt
+//! ```
+
+use open_vector_tile::{
+    VectorFeatureMethods, VectorLayerMethods, VectorTile,
+    base::BaseVectorTile,
+    mlt::{MltVectorTile, TileSetMetadata},
+    write_tile,
+};
+use std::{fs, hint::black_box, time::Instant};
+
+const RUNS: u32 = 5;
+
+fn main() {
+    let mut paths: Vec<_> = fs::read_dir("./benchmarks/data/bing/mlt")
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "mlt"))
+        .collect();
+    paths.sort();
+
+    let mut tiles = vec![];
+    let (mut total_mlt, mut total_ovt) = (0, 0);
+    for path in paths {
+        let data = fs::read(&path).unwrap();
+        let metadata = fs::read(path.with_extension("mlt.meta.pbf")).unwrap();
+        let metadata = TileSetMetadata::from_bytes(&metadata).unwrap();
+        let mut mlt = MltVectorTile::new(&data, &metadata);
+        let ovt = write_tile(Some(&mut BaseVectorTile::from(&mut mlt)), None, None);
+        total_mlt += data.len();
+        total_ovt += ovt.len();
+        let name = path.file_name().unwrap().to_string_lossy();
+        println!("{name:<24} {:>10} {:>10}", data.len(), ovt.len());
+        tiles.push((data, metadata, ovt));
+    }
+    println!("{:<24} {total_mlt:>10} {total_ovt:>10}", "total");
+
+    bench("mlt: read every layer", tiles.len(), || {
+        for (data, metadata, _) in &tiles {
+            let mut tile = MltVectorTile::new(data, metadata);
+            tile.layers.values_mut().for_each(load_layer);
+        }
+    });
+    bench("ovt: read every layer", tiles.len(), || {
+        for (_, _, data) in &tiles {
+            let mut tile = VectorTile::new(data.clone(), None);
+            tile.layers.values_mut().for_each(load_layer);
+        }
+    });
+}
+
+/// Run the function `RUNS` times and print the average time per tile
+fn bench(name: &str, count: usize, mut f: impl FnMut()) {
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        f();
+    }
+    let per_tile = start.elapsed() / RUNS / count.max(1) as u32;
+    println!("{name:<24} {per_tile:>12.2?} / tile");
+}
+
+/// Load the geometry and properties of every feature in the layer
+fn load_layer(layer: &mut impl VectorLayerMethods) {
+    for i in 0..layer.len() {
+        let mut feature = layer.feature(i).unwrap();
+        black_box(feature.load_geometry());
+        black_box(feature.properties());
+    }
+}
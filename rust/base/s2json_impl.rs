@@ -6,9 +6,9 @@ use super::{
 use crate::{
     Extent, Point, Point3D, VectorLine3DWithOffset, VectorLineWithOffset, VectorLines3DWithOffset,
     VectorLinesWithOffset,
+    projection::{tile_xy_to_point, tile_xyz_to_point_3d},
 };
 use alloc::{vec, vec::Vec};
-use libm::round;
 use s2json::{
     MValueCompatible, Properties, VectorFeature, VectorLineString, VectorMultiLineString,
     VectorMultiPolygon, VectorPoint,
@@ -22,7 +22,6 @@ pub fn s2json_to_base<M: Clone, P: MValueCompatible, D: MValueCompatible>(
     extent: Extent,
 ) -> BaseVectorFeature {
     let id = vf.id;
-    let extent: f64 = extent.into();
     let properties: Properties = vf.properties.clone().into();
 
     match &vf.geometry {
@@ -143,7 +142,7 @@ pub fn s2json_to_base<M: Clone, P: MValueCompatible, D: MValueCompatible>(
 fn vmp_to_ls_w_off<D: MValueCompatible>(
     offsets: Option<&Vec<Vec<f64>>>,
     mp: &VectorMultiPolygon<D>,
-    extent: f64,
+    extent: Extent,
 ) -> Vec<VectorLinesWithOffset> {
     mp.iter()
         .enumerate()
@@ -155,7 +154,7 @@ fn vmp_to_ls_w_off<D: MValueCompatible>(
 fn vml_to_ls_w_off<D: MValueCompatible>(
     offsets: Option<&Vec<f64>>,
     vl: &VectorMultiLineString<D>,
-    extent: f64,
+    extent: Extent,
 ) -> VectorLinesWithOffset {
     vl.iter()
         .enumerate()
@@ -169,7 +168,7 @@ fn vml_to_ls_w_off<D: MValueCompatible>(
 fn vl_to_ls_w_off<D: MValueCompatible>(
     offset: f64,
     vl: &VectorLineString<D>,
-    extent: f64,
+    extent: Extent,
 ) -> VectorLineWithOffset {
     VectorLineWithOffset::new(offset, vl.iter().map(|p| vp_to_point(p, extent)).collect())
 }
@@ -178,7 +177,7 @@ fn vl_to_ls_w_off<D: MValueCompatible>(
 fn vmp_to_ls_w_off_3d<D: MValueCompatible>(
     offsets: Option<&Vec<Vec<f64>>>,
     mp: &VectorMultiPolygon<D>,
-    extent: f64,
+    extent: Extent,
 ) -> Vec<VectorLines3DWithOffset> {
     mp.iter()
         .enumerate()
@@ -190,7 +189,7 @@ fn vmp_to_ls_w_off_3d<D: MValueCompatible>(
 fn vml_to_ls_w_off_3d<D: MValueCompatible>(
     offsets: Option<&Vec<f64>>,
     vl: &VectorMultiLineString<D>,
-    extent: f64,
+    extent: Extent,
 ) -> VectorLines3DWithOffset {
     vl.iter()
         .enumerate()
@@ -208,24 +207,18 @@ fn vml_to_ls_w_off_3d<D: MValueCompatible>(
 fn vl_to_ls_w_off_3d<D: MValueCompatible>(
     offset: f64,
     vl: &VectorLineString<D>,
-    extent: f64,
+    extent: Extent,
 ) -> VectorLine3DWithOffset {
     VectorLine3DWithOffset::new(offset, vl.iter().map(|p| vp_to_point3d(p, extent)).collect())
 }
 
-fn vp_to_point<D: MValueCompatible>(vp: &VectorPoint<D>, extent: f64) -> Point {
-    Point {
-        x: round(vp.x * extent) as i32,
-        y: round(vp.y * extent) as i32,
-        m: vp.m.clone().map(|m| m.into()),
-    }
+fn vp_to_point<D: MValueCompatible>(vp: &VectorPoint<D>, extent: Extent) -> Point {
+    Point { m: vp.m.clone().map(|m| m.into()), ..tile_xy_to_point(vp.x, vp.y, extent) }
 }
 
-fn vp_to_point3d<D: MValueCompatible>(vp: &VectorPoint<D>, extent: f64) -> Point3D {
+fn vp_to_point3d<D: MValueCompatible>(vp: &VectorPoint<D>, extent: Extent) -> Point3D {
     Point3D {
-        x: round(vp.x * extent) as i32,
-        y: round(vp.y * extent) as i32,
-        z: round(vp.z.unwrap_or_default() * extent) as i32,
         m: vp.m.clone().map(|m| m.into()),
+        ..tile_xyz_to_point_3d(vp.x, vp.y, vp.z.unwrap_or_default(), extent)
     }
 }
@@ -1,8 +1,14 @@
 use crate::{
     Point, Point3D, VectorFeatureMethods, VectorGeometry, VectorLines3DWithOffset,
     VectorLinesWithOffset, VectorPoints, VectorPoints3D,
+    earcut::earcut,
     mapbox::MapboxVectorFeature,
-    open::{ColumnCacheWriter, FeatureType, encode_value},
+    mlt::MltVectorFeature,
+    open::{
+        ColumnCacheWriter, ColumnLookup, Extent, FeatureType, OpenVectorFeature, ValueMismatch,
+        try_encode_value,
+    },
+    projection::tile_xy_to_lon_lat,
     weave_2d, weave_3d, zigzag,
 };
 use alloc::vec::Vec;
@@ -17,7 +23,8 @@ pub trait BaseVectorFeatureMethods {
     fn properties(&self) -> &Properties;
     /// true if the feature has BBox
     fn has_bbox(&self) -> bool;
-    /// true if the feature has offsets
+    /// true if the feature has offsets. If so, an offset is encoded before every line, zero or
+    /// not, as the spec's `addGeometryToCache` does and the reader expects
     fn has_offsets(&self) -> bool;
     /// true if the feature has M values
     fn has_m_values(&self) -> bool;
@@ -25,8 +32,16 @@ pub trait BaseVectorFeatureMethods {
     fn load_geometry(&self) -> VectorGeometry;
     /// Get the M values of the feature
     fn m_values(&self) -> Option<LineStringMValues>;
-    /// Encode the feature to cache
-    fn encode_to_cache(&self, cache: &mut ColumnCacheWriter, m_shape: Option<&Shape>) -> usize;
+    /// Encode the feature to cache, panicking if an M-Value does not match the M-Value shape
+    fn encode_to_cache(&self, cache: &mut ColumnCacheWriter, m_shape: Option<&Shape>) -> usize {
+        self.try_encode_to_cache(cache, m_shape).unwrap_or_else(|err| panic!("{}", err))
+    }
+    /// Encode the feature to cache, returning where an M-Value does not match the M-Value shape
+    fn try_encode_to_cache(
+        &self,
+        cache: &mut ColumnCacheWriter,
+        m_shape: Option<&Shape>,
+    ) -> Result<usize, ValueMismatch>;
 }
 
 //? Points & Points3D
@@ -99,21 +114,25 @@ impl BaseVectorFeatureMethods for BaseVectorPointsFeature {
         )
     }
 
-    fn encode_to_cache(&self, cache: &mut ColumnCacheWriter, m_shape: Option<&Shape>) -> usize {
+    fn try_encode_to_cache(
+        &self,
+        cache: &mut ColumnCacheWriter,
+        m_shape: Option<&Shape>,
+    ) -> Result<usize, ValueMismatch> {
         let geometry = &self.geometry;
         if geometry.len() == 1 {
             let point = &geometry[0];
-            weave_2d(zigzag(point.x) as u16, zigzag(point.y) as u16) as usize
+            Ok(weave_2d(zigzag(point.x) as u16, zigzag(point.y) as u16) as usize)
         } else {
             let mut indices: Vec<u32> = Vec::new();
             indices.push(cache.add_points(geometry.to_vec()) as u32);
             // store the mvalues indexes if they exist
             if let (Some(m_values), Some(shape)) = (self.m_values(), m_shape) {
                 for m in m_values {
-                    indices.push(encode_value(&m, shape, cache) as u32);
+                    indices.push(try_encode_value(&m, shape, cache)? as u32);
                 }
             }
-            cache.add_indices(indices)
+            Ok(cache.add_indices(indices))
         }
     }
 }
@@ -177,22 +196,26 @@ impl BaseVectorFeatureMethods for BaseVectorPoints3DFeature {
         Some(self.geometry.iter().map(|g| g.m.clone().unwrap_or_default()).collect())
     }
 
-    fn encode_to_cache(&self, cache: &mut ColumnCacheWriter, m_shape: Option<&Shape>) -> usize {
+    fn try_encode_to_cache(
+        &self,
+        cache: &mut ColumnCacheWriter,
+        m_shape: Option<&Shape>,
+    ) -> Result<usize, ValueMismatch> {
         let geometry = &self.geometry;
         if geometry.len() == 1 {
             let point = &geometry[0];
-            weave_3d(zigzag(point.x) as u16, zigzag(point.y) as u16, zigzag(point.z) as u16)
-                as usize
+            Ok(weave_3d(zigzag(point.x) as u16, zigzag(point.y) as u16, zigzag(point.z) as u16)
+                as usize)
         } else {
             let mut indices: Vec<u32> = Vec::new();
             indices.push(cache.add_points_3d(geometry.to_vec()) as u32);
             // store the mvalues indexes if they exist
             if let (Some(m_values), Some(shape)) = (self.m_values(), m_shape) {
                 for m in m_values {
-                    indices.push(encode_value(&m, shape, cache) as u32);
+                    indices.push(try_encode_value(&m, shape, cache)? as u32);
                 }
             }
-            cache.add_indices(indices)
+            Ok(cache.add_indices(indices))
         }
     }
 }
@@ -259,14 +282,19 @@ impl BaseVectorFeatureMethods for BaseVectorLinesFeature {
         Some(self.geometry.iter().flat_map(|g| g.m_values().unwrap_or_default()).collect())
     }
 
-    fn encode_to_cache(&self, cache: &mut ColumnCacheWriter, m_shape: Option<&Shape>) -> usize {
+    fn try_encode_to_cache(
+        &self,
+        cache: &mut ColumnCacheWriter,
+        m_shape: Option<&Shape>,
+    ) -> Result<usize, ValueMismatch> {
         let geometry = &self.geometry;
+        let has_offsets = self.has_offsets();
         let mut indices: Vec<u32> = Vec::new();
         if geometry.len() != 1 {
             indices.push(geometry.len() as u32)
         }
         for line in geometry {
-            if line.has_offset() {
+            if has_offsets {
                 indices.push(encode_offset(line.offset));
             }
             indices.push(cache.add_points(line.geometry.clone()) as u32);
@@ -274,16 +302,16 @@ impl BaseVectorFeatureMethods for BaseVectorLinesFeature {
             if self.has_m_values() {
                 if let (Some(m_values), Some(shape)) = (line.m_values(), m_shape) {
                     for m in m_values {
-                        indices.push(encode_value(&m, shape, cache) as u32);
+                        indices.push(try_encode_value(&m, shape, cache)? as u32);
                     }
                 } else if let (None, Some(shape)) = (line.m_values(), m_shape) {
                     for _ in 0..line.geometry.len() {
-                        indices.push(encode_value(&Value::default(), shape, cache) as u32);
+                        indices.push(try_encode_value(&Value::default(), shape, cache)? as u32);
                     }
                 }
             }
         }
-        cache.add_indices(indices)
+        Ok(cache.add_indices(indices))
     }
 }
 
@@ -347,14 +375,19 @@ impl BaseVectorFeatureMethods for BaseVectorLines3DFeature {
         Some(self.geometry.iter().flat_map(|g| g.m_values().unwrap_or_default()).collect())
     }
 
-    fn encode_to_cache(&self, cache: &mut ColumnCacheWriter, m_shape: Option<&Shape>) -> usize {
+    fn try_encode_to_cache(
+        &self,
+        cache: &mut ColumnCacheWriter,
+        m_shape: Option<&Shape>,
+    ) -> Result<usize, ValueMismatch> {
         let geometry = &self.geometry;
+        let has_offsets = self.has_offsets();
         let mut indices: Vec<u32> = Vec::new();
         if geometry.len() != 1 {
             indices.push(geometry.len() as u32)
         }
         for line in geometry {
-            if line.has_offset() {
+            if has_offsets {
                 indices.push(encode_offset(line.offset));
             }
             indices.push(cache.add_points_3d(line.geometry.clone()) as u32);
@@ -362,16 +395,16 @@ impl BaseVectorFeatureMethods for BaseVectorLines3DFeature {
             if self.has_m_values() {
                 if let (Some(m_values), Some(shape)) = (line.m_values(), m_shape) {
                     for m in m_values {
-                        indices.push(encode_value(&m, shape, cache) as u32);
+                        indices.push(try_encode_value(&m, shape, cache)? as u32);
                     }
                 } else if let (None, Some(shape)) = (line.m_values(), m_shape) {
                     for _ in 0..line.geometry.len() {
-                        indices.push(encode_value(&Value::default(), shape, cache) as u32);
+                        indices.push(try_encode_value(&Value::default(), shape, cache)? as u32);
                     }
                 }
             }
         }
-        cache.add_indices(indices)
+        Ok(cache.add_indices(indices))
     }
 }
 
@@ -405,6 +438,15 @@ impl BaseVectorPolysFeature {
     ) -> Self {
         Self { id, geometry, properties, bbox, indices, tessellation }
     }
+
+    /// Triangulate the polygons with earcut if no indices are set yet
+    pub fn tessellate(&mut self) {
+        if self.indices.is_empty() {
+            self.indices = earcut_polys(self.geometry.iter().map(|poly| {
+                poly.iter().map(|line| line.geometry.iter().map(|p| (p.x, p.y)).collect()).collect()
+            }));
+        }
+    }
 }
 impl BaseVectorFeatureMethods for BaseVectorPolysFeature {
     /// Get the type of the feature
@@ -448,8 +490,13 @@ impl BaseVectorFeatureMethods for BaseVectorPolysFeature {
         )
     }
 
-    fn encode_to_cache(&self, cache: &mut ColumnCacheWriter, m_shape: Option<&Shape>) -> usize {
+    fn try_encode_to_cache(
+        &self,
+        cache: &mut ColumnCacheWriter,
+        m_shape: Option<&Shape>,
+    ) -> Result<usize, ValueMismatch> {
         let geometry = &self.geometry;
+        let has_offsets = self.has_offsets();
         let mut indices: Vec<u32> = Vec::new();
         if geometry.len() != 1 {
             indices.push(geometry.len() as u32)
@@ -457,7 +504,7 @@ impl BaseVectorFeatureMethods for BaseVectorPolysFeature {
         for poly in geometry {
             indices.push(poly.len() as u32);
             for line in poly {
-                if line.has_offset() {
+                if has_offsets {
                     indices.push(encode_offset(line.offset));
                 }
                 indices.push(cache.add_points(line.geometry.clone()) as u32);
@@ -465,17 +512,17 @@ impl BaseVectorFeatureMethods for BaseVectorPolysFeature {
                 if self.has_m_values() {
                     if let (Some(m_values), Some(shape)) = (line.m_values(), m_shape) {
                         for m in m_values {
-                            indices.push(encode_value(&m, shape, cache) as u32);
+                            indices.push(try_encode_value(&m, shape, cache)? as u32);
                         }
                     } else if let (None, Some(shape)) = (line.m_values(), m_shape) {
                         for _ in 0..line.geometry.len() {
-                            indices.push(encode_value(&Value::default(), shape, cache) as u32);
+                            indices.push(try_encode_value(&Value::default(), shape, cache)? as u32);
                         }
                     }
                 }
             }
         }
-        cache.add_indices(indices)
+        Ok(cache.add_indices(indices))
     }
 }
 
@@ -507,6 +554,15 @@ impl BaseVectorPolys3DFeature {
     ) -> Self {
         Self { id, geometry, properties, bbox, indices, tessellation }
     }
+
+    /// Triangulate the polygons with earcut if no indices are set yet. Only x and y are used
+    pub fn tessellate(&mut self) {
+        if self.indices.is_empty() {
+            self.indices = earcut_polys(self.geometry.iter().map(|poly| {
+                poly.iter().map(|line| line.geometry.iter().map(|p| (p.x, p.y)).collect()).collect()
+            }));
+        }
+    }
 }
 impl BaseVectorFeatureMethods for BaseVectorPolys3DFeature {
     /// Get the type of the feature
@@ -550,8 +606,13 @@ impl BaseVectorFeatureMethods for BaseVectorPolys3DFeature {
         )
     }
 
-    fn encode_to_cache(&self, cache: &mut ColumnCacheWriter, m_shape: Option<&Shape>) -> usize {
+    fn try_encode_to_cache(
+        &self,
+        cache: &mut ColumnCacheWriter,
+        m_shape: Option<&Shape>,
+    ) -> Result<usize, ValueMismatch> {
         let geometry = &self.geometry;
+        let has_offsets = self.has_offsets();
         let mut indices: Vec<u32> = Vec::new();
         if geometry.len() != 1 {
             indices.push(geometry.len() as u32)
@@ -559,7 +620,7 @@ impl BaseVectorFeatureMethods for BaseVectorPolys3DFeature {
         for poly in geometry {
             indices.push(poly.len() as u32);
             for line in poly {
-                if line.has_offset() {
+                if has_offsets {
                     indices.push(encode_offset(line.offset));
                 }
                 indices.push(cache.add_points_3d(line.geometry.clone()) as u32);
@@ -567,17 +628,17 @@ impl BaseVectorFeatureMethods for BaseVectorPolys3DFeature {
                 if self.has_m_values() {
                     if let (Some(m_values), Some(shape)) = (line.m_values(), m_shape) {
                         for m in m_values {
-                            indices.push(encode_value(&m, shape, cache) as u32);
+                            indices.push(try_encode_value(&m, shape, cache)? as u32);
                         }
                     } else if let (None, Some(shape)) = (line.m_values(), m_shape) {
                         for _ in 0..line.geometry.len() {
-                            indices.push(encode_value(&Value::default(), shape, cache) as u32);
+                            indices.push(try_encode_value(&Value::default(), shape, cache)? as u32);
                         }
                     }
                 }
             }
         }
-        cache.add_indices(indices)
+        Ok(cache.add_indices(indices))
     }
 }
 
@@ -623,7 +684,110 @@ pub enum BaseVectorFeature {
     /// 3D Polygons
     BaseVectorPolys3DFeature(BaseVectorPolys3DFeature),
 }
+/// Triangulate each polygon. Indices count every vertex of every ring (including the closing
+/// points) in order, matching the flattened geometry of `load_geometry_flat`
+fn earcut_polys(polys: impl Iterator<Item = Vec<Vec<(i32, i32)>>>) -> Vec<u32> {
+    let mut indices = Vec::new();
+    let mut offset = 0;
+    for poly in polys {
+        let mut data: Vec<f64> = Vec::new();
+        let mut holes = Vec::new();
+        for (i, ring) in poly.iter().enumerate() {
+            if i > 0 {
+                holes.push(data.len() / 2);
+            }
+            data.extend(ring.iter().flat_map(|(x, y)| [*x as f64, *y as f64]));
+        }
+        indices.extend(earcut(&data, &holes, 2).into_iter().map(|i| i + offset));
+        offset += (data.len() / 2) as u32;
+    }
+    indices
+}
+
+/// The lon/lat bbox of points in `extent` units of the Web Mercator tile `zoom`/`x`/`y`
+fn tile_bbox(
+    mut points: impl Iterator<Item = (i32, i32)>,
+    extent: Extent,
+    zoom: u8,
+    x: u32,
+    y: u32,
+) -> Option<BBox> {
+    let first = points.next()?;
+    let (min_x, min_y, max_x, max_y) = points.fold(
+        (first.0, first.1, first.0, first.1),
+        |(min_x, min_y, max_x, max_y), (px, py)| {
+            (min_x.min(px), min_y.min(py), max_x.max(px), max_y.max(py))
+        },
+    );
+    let extent: f64 = extent.into();
+    let to_lon_lat = |px: i32, py: i32| {
+        let (lon, lat) = tile_xy_to_lon_lat(px as f64 / extent, py as f64 / extent, zoom, x, y);
+        (lon.clamp(-180.0, 180.0), lat)
+    };
+    // y points down in the tile
+    let (left, top) = to_lon_lat(min_x, min_y);
+    let (right, bottom) = to_lon_lat(max_x, max_y);
+    Some(BBox { left, bottom, right, top })
+}
+
+/// The lon/lat bbox of 3D points in `extent` units of the Web Mercator tile `zoom`/`x`/`y`.
+/// near/far are the lowest and highest z values as stored in the geometry, in the same units as
+/// the points' z and without any projection
+fn tile_bbox_3d<'a>(
+    points: impl Iterator<Item = &'a Point3D> + Clone,
+    extent: Extent,
+    zoom: u8,
+    x: u32,
+    y: u32,
+) -> Option<BBox3D> {
+    let bbox = tile_bbox(points.clone().map(|p| (p.x, p.y)), extent, zoom, x, y)?;
+    let (near, far) =
+        points.fold((i32::MAX, i32::MIN), |(near, far), p| (near.min(p.z), far.max(p.z)));
+    let BBox { left, bottom, right, top } = bbox;
+    Some(BBox3D { left, bottom, right, top, near: near as f64, far: far as f64 })
+}
+
 impl BaseVectorFeature {
+    /// Compute the lon/lat bbox of the geometry, which is in `extent` units of the Web Mercator
+    /// tile `zoom`/`x`/`y`. A 3D bbox's near/far are the raw z values of the geometry. Returns
+    /// `None` if the geometry is empty
+    pub fn compute_bbox(&self, extent: Extent, zoom: u8, x: u32, y: u32) -> Option<BBOX> {
+        match self {
+            BaseVectorFeature::BaseVectorPointsFeature(f) => {
+                tile_bbox(f.geometry.iter().map(|p| (p.x, p.y)), extent, zoom, x, y).map(BBOX::BBox)
+            }
+            BaseVectorFeature::BaseVectorLinesFeature(f) => {
+                let points = f.geometry.iter().flat_map(|l| &l.geometry);
+                tile_bbox(points.map(|p| (p.x, p.y)), extent, zoom, x, y).map(BBOX::BBox)
+            }
+            BaseVectorFeature::BaseVectorPolysFeature(f) => {
+                let points = f.geometry.iter().flatten().flat_map(|l| &l.geometry);
+                tile_bbox(points.map(|p| (p.x, p.y)), extent, zoom, x, y).map(BBOX::BBox)
+            }
+            BaseVectorFeature::BaseVectorPoints3DFeature(f) => {
+                tile_bbox_3d(f.geometry.iter(), extent, zoom, x, y).map(BBOX::BBox3D)
+            }
+            BaseVectorFeature::BaseVectorLines3DFeature(f) => {
+                let points = f.geometry.iter().flat_map(|l| &l.geometry);
+                tile_bbox_3d(points, extent, zoom, x, y).map(BBOX::BBox3D)
+            }
+            BaseVectorFeature::BaseVectorPolys3DFeature(f) => {
+                let points = f.geometry.iter().flatten().flat_map(|l| &l.geometry);
+                tile_bbox_3d(points, extent, zoom, x, y).map(BBOX::BBox3D)
+            }
+        }
+    }
+
+    /// Triangulate polygon features with earcut if no indices are set yet. Other feature types
+    /// are left untouched
+    pub fn tessellate(&mut self) {
+        match self {
+            BaseVectorFeature::BaseVectorPolysFeature(f) => f.tessellate(),
+            BaseVectorFeature::BaseVectorPolys3DFeature(f) => f.tessellate(),
+            _ => {}
+        }
+    }
+
     /// check if the feature geometry has a single length
     pub fn single(&self) -> bool {
         match self {
@@ -718,6 +882,18 @@ impl BaseVectorFeature {
         }
     }
 
+    /// get the feature geometry
+    pub fn load_geometry(&self) -> VectorGeometry {
+        match self {
+            BaseVectorFeature::BaseVectorPointsFeature(f) => f.load_geometry(),
+            BaseVectorFeature::BaseVectorLinesFeature(f) => f.load_geometry(),
+            BaseVectorFeature::BaseVectorPolysFeature(f) => f.load_geometry(),
+            BaseVectorFeature::BaseVectorPoints3DFeature(f) => f.load_geometry(),
+            BaseVectorFeature::BaseVectorLines3DFeature(f) => f.load_geometry(),
+            BaseVectorFeature::BaseVectorPolys3DFeature(f) => f.load_geometry(),
+        }
+    }
+
     /// get the feature bbox
     pub fn bbox(&self) -> Option<BBOX> {
         match self {
@@ -741,15 +917,26 @@ impl BaseVectorFeature {
         }
     }
 
-    /// encode the feature to cache
+    /// encode the feature to cache, panicking if an M-Value does not match the M-Value shape
     pub fn encode_to_cache(&self, cache: &mut ColumnCacheWriter, m_shape: Option<&Shape>) -> usize {
+        self.try_encode_to_cache(cache, m_shape).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// encode the feature to cache, returning where an M-Value does not match the M-Value shape
+    pub fn try_encode_to_cache(
+        &self,
+        cache: &mut ColumnCacheWriter,
+        m_shape: Option<&Shape>,
+    ) -> Result<usize, ValueMismatch> {
         match self {
-            BaseVectorFeature::BaseVectorPointsFeature(f) => f.encode_to_cache(cache, m_shape),
-            BaseVectorFeature::BaseVectorLinesFeature(f) => f.encode_to_cache(cache, m_shape),
-            BaseVectorFeature::BaseVectorPolysFeature(f) => f.encode_to_cache(cache, m_shape),
-            BaseVectorFeature::BaseVectorPoints3DFeature(f) => f.encode_to_cache(cache, m_shape),
-            BaseVectorFeature::BaseVectorLines3DFeature(f) => f.encode_to_cache(cache, m_shape),
-            BaseVectorFeature::BaseVectorPolys3DFeature(f) => f.encode_to_cache(cache, m_shape),
+            BaseVectorFeature::BaseVectorPointsFeature(f) => f.try_encode_to_cache(cache, m_shape),
+            BaseVectorFeature::BaseVectorLinesFeature(f) => f.try_encode_to_cache(cache, m_shape),
+            BaseVectorFeature::BaseVectorPolysFeature(f) => f.try_encode_to_cache(cache, m_shape),
+            BaseVectorFeature::BaseVectorPoints3DFeature(f) => {
+                f.try_encode_to_cache(cache, m_shape)
+            }
+            BaseVectorFeature::BaseVectorLines3DFeature(f) => f.try_encode_to_cache(cache, m_shape),
+            BaseVectorFeature::BaseVectorPolys3DFeature(f) => f.try_encode_to_cache(cache, m_shape),
         }
     }
 }
@@ -778,6 +965,85 @@ impl From<&mut MapboxVectorFeature> for BaseVectorFeature {
     }
 }
 
+impl From<&mut MltVectorFeature> for BaseVectorFeature {
+    fn from(mlt: &mut MltVectorFeature) -> Self {
+        let id = mlt.id;
+        let properties: Properties = (&mlt.properties).into();
+
+        match mlt.load_geometry() {
+            VectorGeometry::VectorPoints(geo) => BaseVectorFeature::BaseVectorPointsFeature(
+                BaseVectorPointsFeature::new(id, geo, properties, None),
+            ),
+            VectorGeometry::VectorLines(geo) => BaseVectorFeature::BaseVectorLinesFeature(
+                BaseVectorLinesFeature::new(id, geo, properties, None),
+            ),
+            VectorGeometry::VectorPolys(geo) => BaseVectorFeature::BaseVectorPolysFeature(
+                BaseVectorPolysFeature::new(id, geo, properties, None, Vec::new(), Vec::new()),
+            ),
+            _ => panic!("unexpected geometry type"),
+        }
+    }
+}
+
+/// A lone point is woven into the geometry index when written, which has no room for an
+/// M-Value, so single point features come back without their M-Value
+impl<C: ColumnLookup> From<&mut OpenVectorFeature<C>> for BaseVectorFeature {
+    fn from(ovt: &mut OpenVectorFeature<C>) -> Self {
+        let id = ovt.id();
+        let properties = ovt.properties();
+        let bbox = ovt.bbox();
+        let bbox_2d: Option<BBox> = bbox.map(|bbox| match bbox {
+            BBOX::BBox(bbox) => bbox,
+            BBOX::BBox3D(bbox) => bbox.into(),
+        });
+        let bbox_3d: Option<BBox3D> = bbox.map(|bbox| match bbox {
+            BBOX::BBox(bbox) => bbox.into(),
+            BBOX::BBox3D(bbox) => bbox,
+        });
+        let indices = ovt.read_indices();
+        let mut tessellation_floats: Vec<f64> = Vec::new();
+
+        match ovt.load_geometry() {
+            VectorGeometry::VectorPoints(geo) => BaseVectorFeature::BaseVectorPointsFeature(
+                BaseVectorPointsFeature::new(id, geo, properties, bbox_2d),
+            ),
+            VectorGeometry::VectorLines(geo) => BaseVectorFeature::BaseVectorLinesFeature(
+                BaseVectorLinesFeature::new(id, geo, properties, bbox_2d),
+            ),
+            VectorGeometry::VectorPolys(geo) => {
+                ovt.add_tessellation(&mut tessellation_floats, 1.0);
+                let tessellation = tess_to_points(tessellation_floats);
+                BaseVectorFeature::BaseVectorPolysFeature(BaseVectorPolysFeature::new(
+                    id,
+                    geo,
+                    properties,
+                    bbox_2d,
+                    indices,
+                    tessellation,
+                ))
+            }
+            VectorGeometry::VectorPoints3D(geo) => BaseVectorFeature::BaseVectorPoints3DFeature(
+                BaseVectorPoints3DFeature::new(id, geo, properties, bbox_3d),
+            ),
+            VectorGeometry::VectorLines3D(geo) => BaseVectorFeature::BaseVectorLines3DFeature(
+                BaseVectorLines3DFeature::new(id, geo, properties, bbox_3d),
+            ),
+            VectorGeometry::VectorPolys3D(geo) => {
+                ovt.add_tessellation_3d(&mut tessellation_floats, 1.0);
+                let tessellation = tess_to_points_3d(tessellation_floats);
+                BaseVectorFeature::BaseVectorPolys3DFeature(BaseVectorPolys3DFeature::new(
+                    id,
+                    geo,
+                    properties,
+                    bbox_3d,
+                    indices,
+                    tessellation,
+                ))
+            }
+        }
+    }
+}
+
 /// Taking input tesselation data, migrate it back to a Point
 pub fn tess_to_points(tess: Vec<f64>) -> Vec<Point> {
     tess.chunks(2).map(|chunk| Point::new(round(chunk[0]) as i32, round(chunk[1]) as i32)).collect()
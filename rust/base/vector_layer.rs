@@ -1,4 +1,10 @@
-use crate::{base::BaseVectorFeature, mapbox::MapboxVectorLayer, open::Extent};
+use crate::{
+    VectorFeature, VectorLayerMethods,
+    base::BaseVectorFeature,
+    mapbox::MapboxVectorLayer,
+    mlt::MltVectorLayer,
+    open::{Extent, OpenVectorLayer},
+};
 use alloc::{string::String, vec::Vec};
 use s2json::Shape;
 
@@ -22,6 +28,11 @@ pub struct BaseVectorLayer {
     pub m_shape: Option<Shape>,
     /// the features in the layer
     pub features: Vec<BaseVectorFeature>,
+    /// if true, polygons without indices are triangulated with earcut when the layer is written
+    pub tessellate: bool,
+    /// if true, a spatial index of the features is written with the layer. The index is field 7
+    /// of the layer message, which decoders that predate it fail to read
+    pub spatial_index: bool,
 }
 impl BaseVectorLayer {
     /// Create a new BaseVectorLayer
@@ -41,6 +52,8 @@ impl BaseVectorLayer {
             shape: shape.unwrap_or_default(),
             m_shape,
             features,
+            tessellate: false,
+            spatial_index: false,
         }
     }
 
@@ -89,9 +102,58 @@ impl From<&mut MapboxVectorLayer> for BaseVectorLayer {
             shape: Shape::default(),
             m_shape: None,
             features: Vec::new(),
+            tessellate: false,
+            spatial_index: false,
         };
 
-        for feature in mvt.features.values_mut() {
+        // features are read lazily, so each is loaded as it is converted
+        for i in 0..mvt.len() {
+            if let Some(VectorFeature::Mapbox(feature)) = mvt.feature(i) {
+                bvt.add_feature(feature.into());
+            }
+        }
+
+        bvt
+    }
+}
+impl From<&mut OpenVectorLayer> for BaseVectorLayer {
+    fn from(ovt: &mut OpenVectorLayer) -> Self {
+        let mut bvt = Self {
+            version: ovt.version as u8,
+            name: ovt.name.clone(),
+            extent: ovt.extent,
+            shape_defined: ovt.shape().is_some(),
+            m_shape_defined: ovt.m_shape().is_some(),
+            shape: ovt.shape().cloned().unwrap_or_default(),
+            m_shape: ovt.m_shape().cloned(),
+            features: Vec::new(),
+            tessellate: false,
+            spatial_index: ovt.has_spatial_index(),
+        };
+
+        for feature in ovt.features.iter_mut() {
+            bvt.add_feature(feature.into());
+        }
+
+        bvt
+    }
+}
+impl From<&mut MltVectorLayer> for BaseVectorLayer {
+    fn from(mlt: &mut MltVectorLayer) -> Self {
+        let mut bvt = Self {
+            version: 1,
+            name: mlt.name.clone(),
+            extent: mlt.extent.into(),
+            shape_defined: false,
+            m_shape_defined: false,
+            shape: Shape::default(),
+            m_shape: None,
+            features: Vec::new(),
+            tessellate: false,
+            spatial_index: false,
+        };
+
+        for feature in mlt.features.iter_mut() {
             bvt.add_feature(feature.into());
         }
 
@@ -1,5 +1,6 @@
 use crate::{
     VectorLayer, VectorTile, base::BaseVectorLayer, mapbox::vector_tile::MapboxVectorTile,
+    mlt::MltVectorTile,
 };
 use alloc::{collections::BTreeMap, string::String};
 
@@ -18,13 +19,15 @@ impl BaseVectorTile {
     }
 }
 impl From<&mut VectorTile> for BaseVectorTile {
-    /// Convert from Mapbox vector tile
+    /// Convert from a vector tile containing Mapbox and/or Open vector layers
     fn from(vector_tile: &mut VectorTile) -> Self {
-        let mut tile = BaseVectorTile { layers: BTreeMap::new() };
+        let mut tile = BaseVectorTile::default();
         for (name, layer) in vector_tile.layers.iter_mut() {
-            if let VectorLayer::Mapbox(layer) = layer {
-                tile.layers.insert(name.clone(), layer.into());
-            }
+            let layer = match layer {
+                VectorLayer::Mapbox(layer) => layer.into(),
+                VectorLayer::Open(layer) => layer.into(),
+            };
+            tile.layers.insert(name.clone(), layer);
         }
         tile
     }
@@ -32,7 +35,17 @@ impl From<&mut VectorTile> for BaseVectorTile {
 impl From<&mut MapboxVectorTile> for BaseVectorTile {
     /// Convert from Mapbox vector layer
     fn from(vector_tile: &mut MapboxVectorTile) -> Self {
-        let mut tile = BaseVectorTile { layers: BTreeMap::new() };
+        let mut tile = BaseVectorTile::default();
+        for (name, layer) in vector_tile.layers.iter_mut() {
+            tile.layers.insert(name.clone(), layer.into());
+        }
+        tile
+    }
+}
+impl From<&mut MltVectorTile> for BaseVectorTile {
+    /// Convert from a MapLibre Tile
+    fn from(vector_tile: &mut MltVectorTile) -> Self {
+        let mut tile = BaseVectorTile::default();
         for (name, layer) in vector_tile.layers.iter_mut() {
             tile.layers.insert(name.clone(), layer.into());
         }
@@ -0,0 +1,297 @@
+//! # ovt
+//!
+//! Inspect, convert and validate Mapbox (MVT), Open Vector Tile (OVT) and Open Vector Flat Tile
+//! (OFVT) files. Every command accepts files or directories, which are walked recursively.
+//!
+//! ```bash
+//! cargo run --features cli --bin ovt -- info tests/fixtures/12-1143-1497.vector.pbf
+//! cargo run --features cli --bin ovt -- dump tile.ovt --tile 12/1143/1497
+//! cargo run --features cli --bin ovt -- convert mvt_dir ovt_dir --to ovt
+//! cargo run --features cli --bin ovt -- validate ovt_dir
+//! ```
+
+use open_vector_tile::{
+    GridData, ImageData, OColumnName, VectorLayer, VectorLayerFeatures, VectorLayerMethods,
+    VectorTile, WriteOptions,
+    base::BaseVectorTile,
+    export::ExportMetadata,
+    export::feature_to_s2json,
+    mapbox, try_write_tile,
+    validate::{Severity, validate_bytes},
+};
+use pbf::Protobuf;
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    slice,
+};
+
+const USAGE: &str = "\
+Usage: ovt <command> [options]
+
+Commands:
+  info <path>...                      list the layers, feature counts and column sizes
+  dump <path> [--tile z/x/y]          print the features as S2JSON, one per line, or as a
+                                      GeoJSON FeatureCollection if the tile's z/x/y is given
+  convert <input> <output> --to <fmt> convert tiles to mvt, ovt or ofvt
+  validate <path>...                  check tiles against the specification
+";
+
+/// The formats a tile can be converted to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Mvt,
+    Ovt,
+    Ofvt,
+}
+impl Format {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "mvt" | "pbf" => Ok(Format::Mvt),
+            "ovt" => Ok(Format::Ovt),
+            "ofvt" => Ok(Format::Ofvt),
+            _ => Err(format!("unknown format \"{name}\", expected mvt, ovt or ofvt")),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Mvt => "mvt",
+            Format::Ovt => "ovt",
+            Format::Ofvt => "ofvt",
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let res = match args.split_first() {
+        Some((command, args)) => match command.as_str() {
+            "info" => each_file(args, info),
+            "dump" => dump(args),
+            "convert" => convert(args),
+            "validate" => each_file(args, validate),
+            "-h" | "--help" | "help" => {
+                print!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ => Err(format!("unknown command \"{command}\"")),
+        },
+        None => Err("missing command".into()),
+    };
+    match res {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Run `command` on every file found in `paths`. Returns false if it failed for any of them
+fn each_file(
+    paths: &[String],
+    command: impl Fn(&Path, Vec<u8>) -> Result<(), String>,
+) -> Result<bool, String> {
+    if paths.is_empty() {
+        return Err("missing path".into());
+    }
+    let mut ok = true;
+    for path in paths {
+        for file in walk(Path::new(path))? {
+            let res = fs::read(&file).map_err(|e| e.to_string()).and_then(|d| command(&file, d));
+            if let Err(err) = res {
+                eprintln!("{}: {err}", file.display());
+                ok = false;
+            }
+        }
+    }
+    Ok(ok)
+}
+
+/// The files at `path`, recursing into directories in name order
+fn walk(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if !path.is_dir() {
+        return match path.exists() {
+            true => Ok(vec![path.to_path_buf()]),
+            false => Err(format!("{} does not exist", path.display())),
+        };
+    }
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(|e| format!("{}: {e}", path.display()))?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .collect();
+    entries.sort();
+    let mut files = vec![];
+    for entry in entries {
+        files.extend(walk(&entry)?);
+    }
+    Ok(files)
+}
+
+fn read_tile(data: Vec<u8>) -> Result<VectorTile, String> {
+    VectorTile::try_new(data, None).map_err(|e| e.to_string())
+}
+
+fn info(path: &Path, data: Vec<u8>) -> Result<(), String> {
+    let tile = read_tile(data.clone())?;
+    // the tile was checked when it was read, so the column cache can be walked safely
+    let (size, columns) = (data.len(), column_sizes(&data));
+    let mut out = io::stdout().lock();
+    let w = |e: io::Error| e.to_string();
+    writeln!(out, "{}: {size} bytes", path.display()).map_err(w)?;
+    for (name, layer) in tile.layers.iter() {
+        let kind = match layer {
+            VectorLayer::Open(_) => "ovt",
+            VectorLayer::Mapbox(layer) if layer.is_s2() => "ofvt",
+            VectorLayer::Mapbox(_) => "mvt",
+        };
+        let (version, extent, len) = (layer.version(), layer.extent(), layer.len());
+        writeln!(out, "  layer {name}: {kind} v{version}, extent {extent}, {len} features")
+            .map_err(w)?;
+    }
+    for (name, image) in tile.images.iter() {
+        let (kind, width, height) = (image.image_type, image.width, image.height);
+        writeln!(out, "  image {name}: {kind} {width}x{height}").map_err(w)?;
+    }
+    for (name, grid) in tile.grids.iter() {
+        let (extent, size, min, max) = (usize::from(grid.extent), grid.size, grid.min, grid.max);
+        writeln!(out, "  grid {name}: extent {extent}, size {size}, range {min}->{max}")
+            .map_err(w)?;
+    }
+    for (name, (count, bytes)) in columns {
+        writeln!(out, "  column {name:?}: {count} entries, {bytes} bytes").map_err(w)?;
+    }
+    Ok(())
+}
+
+/// The number of entries and bytes used by each column of an OVT tile's column cache
+fn column_sizes(data: &[u8]) -> BTreeMap<OColumnName, (usize, usize)> {
+    let mut sizes = BTreeMap::new();
+    let mut pb = Protobuf::from_input(data.to_vec());
+    while pb.get_pos() < pb.len() {
+        let field = pb.read_field();
+        if field.tag != 5 {
+            pb.skip(field.r#type);
+            continue;
+        }
+        let mut cache = Protobuf::from_input(pb.read_bytes());
+        while cache.get_pos() < cache.len() {
+            let start = cache.get_pos();
+            let field = cache.read_field();
+            cache.skip(field.r#type);
+            let size = sizes.entry(field.tag.into()).or_insert((0, 0));
+            *size = (size.0 + 1, size.1 + cache.get_pos() - start);
+        }
+    }
+    sizes
+}
+
+fn dump(args: &[String]) -> Result<bool, String> {
+    let (path, tile) = match args {
+        [path] => (path, None),
+        [path, flag, zxy] if flag == "--tile" => (path, Some(parse_zxy(zxy)?)),
+        _ => return Err("expected dump <path> [--tile z/x/y]".into()),
+    };
+    each_file(slice::from_ref(path), |_, data| {
+        let mut vector_tile = read_tile(data)?;
+        let mut out = io::stdout().lock();
+        let json = |e: serde_json::Error| e.to_string();
+        if let Some((zoom, x, y)) = tile {
+            let collection = vector_tile.to_feature_collection(zoom, x, y);
+            let collection = serde_json::to_string(&collection).map_err(json)?;
+            return writeln!(out, "{collection}").map_err(|e| e.to_string());
+        }
+        for layer in vector_tile.iter_layers() {
+            let (name, extent) = (layer.name(), layer.extent());
+            for mut feature in layer.features() {
+                let mut feature = feature_to_s2json(&mut feature, extent);
+                feature.metadata = Some(ExportMetadata { layer: name.clone() });
+                let feature = serde_json::to_string(&feature).map_err(json)?;
+                writeln!(out, "{feature}").map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    })
+}
+
+fn parse_zxy(zxy: &str) -> Result<(u8, u32, u32), String> {
+    let err = || format!("invalid tile \"{zxy}\", expected z/x/y");
+    let parts: Vec<&str> = zxy.split('/').collect();
+    let [z, x, y] = parts[..] else { return Err(err()) };
+    Ok((
+        z.parse().map_err(|_| err())?,
+        x.parse().map_err(|_| err())?,
+        y.parse().map_err(|_| err())?,
+    ))
+}
+
+fn convert(args: &[String]) -> Result<bool, String> {
+    let [input, output, flag, to] = args else {
+        return Err("expected convert <input> <output> --to <mvt|ovt|ofvt>".into());
+    };
+    if flag != "--to" {
+        return Err(format!("unexpected argument \"{flag}\""));
+    }
+    let format = Format::parse(to)?;
+    let (input, output) = (Path::new(input), Path::new(output));
+    each_file(&[input.display().to_string()], |file, data| {
+        // a directory is converted into the same layout under the output directory
+        let target = match file.strip_prefix(input) {
+            Ok(relative) if input.is_dir() => {
+                output.join(relative).with_extension(format.extension())
+            }
+            _ => output.to_path_buf(),
+        };
+        let data = convert_tile(file, data, format)?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&target, data).map_err(|e| format!("{}: {e}", target.display()))
+    })
+}
+
+fn convert_tile(path: &Path, data: Vec<u8>, format: Format) -> Result<Vec<u8>, String> {
+    let mut tile = read_tile(data)?;
+    let mut base = BaseVectorTile::from(&mut tile);
+    let res = match format {
+        Format::Ovt => {
+            let images: Vec<ImageData> = tile.images.values_mut().map(ImageData::from).collect();
+            let grids: Vec<GridData> = tile.grids.values_mut().map(GridData::from).collect();
+            try_write_tile(
+                Some(&mut base),
+                Some(images.iter().collect()),
+                Some(grids.iter().collect()),
+                &WriteOptions::default(),
+            )
+        }
+        Format::Mvt | Format::Ofvt => {
+            if !tile.images.is_empty() || !tile.grids.is_empty() {
+                eprintln!(
+                    "{}: images and grids are dropped converting to {format:?}",
+                    path.display()
+                );
+            }
+            mapbox::try_write_tile(&mut base, format == Format::Mvt)
+        }
+    };
+    res.map_err(|e| e.to_string())
+}
+
+fn validate(path: &Path, data: Vec<u8>) -> Result<(), String> {
+    let violations = validate_bytes(data);
+    if violations.is_empty() {
+        println!("{}: ok", path.display());
+    }
+    for violation in &violations {
+        println!("{}: {violation}", path.display());
+    }
+    match violations.iter().filter(|v| v.severity == Severity::Error).count() {
+        0 => Ok(()),
+        errors => Err(format!("{errors} errors")),
+    }
+}
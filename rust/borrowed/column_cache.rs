@@ -0,0 +1,125 @@
+use crate::{
+    VectorPoints, VectorPoints3D,
+    error::{DecodeError, ProtoMessage},
+    open::{
+        Column, ColumnLookup, OColumnName, decode_bbox, decode_indices, decode_points,
+        decode_points_3d, decode_shapes, read_columns,
+    },
+    reader::{Reader, Result},
+};
+use alloc::{borrow::Cow, vec::Vec};
+use s2json::BBOX;
+
+/// The column cache of a [`crate::borrowed::BorrowedVectorTile`].
+/// Strings borrow from the tile and numbers are read upfront. The points, indices, shapes and
+/// bbox columns only store their position and are decoded from the tile every time they are
+/// accessed. Lookups past the end of a column return a default value.
+#[derive(Debug, Default, Clone)]
+pub struct BorrowedColumns<'a> {
+    /// the data holding the column cache message
+    data: &'a [u8],
+    /// strings are stored in a column of strings
+    string: Vec<Cow<'a, str>>,
+    /// unsigned whole numbers are stored in unsigned
+    unsigned: Vec<u64>,
+    /// negative numbers are stored in signed
+    signed: Vec<i64>,
+    /// non-whole 32-bit numbers are stored in float
+    float: Vec<f32>,
+    /// non-whole numbers greater than 32-bit are stored in double
+    double: Vec<f64>,
+    /// positions of the points columns
+    points: Vec<usize>,
+    /// positions of the 3D points columns
+    points_3d: Vec<usize>,
+    /// positions of the indices columns
+    indices: Vec<usize>,
+    /// positions of the shapes columns
+    shapes: Vec<usize>,
+    /// positions of the bbox columns
+    bbox: Vec<usize>,
+}
+impl<'a> BorrowedColumns<'a> {
+    /// Read the contents of a column cache message, returning an error if it is malformed
+    pub fn try_new(data: &'a [u8]) -> core::result::Result<Self, DecodeError> {
+        Self::read(Reader::new(data, 0, data.len(), ProtoMessage::ColumnCache))
+    }
+
+    /// Read the column cache message covered by `pb`
+    pub(crate) fn read(pb: Reader<'a>) -> Result<Self> {
+        let mut columns = BorrowedColumns { data: pb.buf(), ..Default::default() };
+        read_columns(pb, |column| match column {
+            Column::String(s) => columns.string.push(s),
+            Column::Unsigned(u) => columns.unsigned.push(u),
+            Column::Signed(s) => columns.signed.push(s),
+            Column::Float(f) => columns.float.push(f),
+            Column::Double(d) => columns.double.push(d),
+            Column::Points(pos) => columns.points.push(pos),
+            Column::Points3D(pos) => columns.points_3d.push(pos),
+            Column::Indices(pos) => columns.indices.push(pos),
+            Column::Shapes(pos) => columns.shapes.push(pos),
+            Column::BBox(pos) => columns.bbox.push(pos),
+        })?;
+        Ok(columns)
+    }
+
+    /// get a string without copying it out of the tile
+    pub fn string(&self, index: usize) -> Cow<'a, str> {
+        self.string.get(index).cloned().unwrap_or_default()
+    }
+
+    /// Decode the packed column at `column[index]`
+    fn get<T: Default>(&self, column: &[usize], index: usize, decode: fn(&[u8]) -> T) -> T {
+        let Some(&pos) = column.get(index) else { return T::default() };
+        Reader::new(self.data, pos, self.data.len(), ProtoMessage::ColumnCache)
+            .bytes()
+            .map(decode)
+            .unwrap_or_default()
+    }
+}
+impl ColumnLookup for BorrowedColumns<'_> {
+    fn column_len(&self, column: OColumnName) -> usize {
+        match column {
+            OColumnName::String => self.string.len(),
+            OColumnName::Unsigned => self.unsigned.len(),
+            OColumnName::Signed => self.signed.len(),
+            OColumnName::Float => self.float.len(),
+            OColumnName::Double => self.double.len(),
+            OColumnName::Points => self.points.len(),
+            OColumnName::Points3D => self.points_3d.len(),
+            OColumnName::Indices => self.indices.len(),
+            OColumnName::Shapes => self.shapes.len(),
+            OColumnName::BBox => self.bbox.len(),
+        }
+    }
+    fn get_string(&self, index: usize) -> Cow<'_, str> {
+        self.string.get(index).map_or(Cow::Borrowed(""), |s| Cow::Borrowed(s))
+    }
+    fn get_unsigned(&self, index: usize) -> u64 {
+        self.unsigned.get(index).copied().unwrap_or_default()
+    }
+    fn get_signed(&self, index: usize) -> i64 {
+        self.signed.get(index).copied().unwrap_or_default()
+    }
+    fn get_float(&self, index: usize) -> f32 {
+        self.float.get(index).copied().unwrap_or_default()
+    }
+    fn get_double(&self, index: usize) -> f64 {
+        self.double.get(index).copied().unwrap_or_default()
+    }
+    fn get_points(&self, index: usize) -> VectorPoints {
+        self.get(&self.points, index, decode_points)
+    }
+    fn get_points_3d(&self, index: usize) -> VectorPoints3D {
+        self.get(&self.points_3d, index, decode_points_3d)
+    }
+    fn get_indices(&self, index: usize) -> Vec<u32> {
+        self.get(&self.indices, index, decode_indices)
+    }
+    fn get_shapes(&self, index: usize) -> Vec<usize> {
+        self.get(&self.shapes, index, decode_shapes)
+    }
+    fn get_bbox(&self, index: usize) -> BBOX {
+        self.get(&self.bbox, index, decode_bbox)
+    }
+}
@@ -0,0 +1,13 @@
+/// Column cache lookups over borrowed bytes
+pub mod column_cache;
+/// Features read from borrowed bytes
+pub mod vector_feature;
+/// Vector, image and grid layers read from borrowed bytes
+pub mod vector_layer;
+/// A Tile read from borrowed bytes
+pub mod vector_tile;
+
+pub use column_cache::*;
+pub use vector_feature::*;
+pub use vector_layer::*;
+pub use vector_tile::*;
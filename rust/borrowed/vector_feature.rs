@@ -0,0 +1,354 @@
+use crate::{
+    FeatureType, OpenVectorFeature, VectorFeatureMethods, VectorGeometry, VectorLines3DWithOffset,
+    VectorLinesWithOffset, VectorPoints, VectorPoints3D,
+    borrowed::BorrowedColumns,
+    error::{DecodeError, ProtoMessage},
+    mapbox::{
+        FeatureFields, FeatureType as MapboxFeatureType, decode_geometry, decode_indices,
+        decode_tessellation, flatten_polys, geometry_lines, geometry_points, geometry_polys,
+        read_feature_fields, read_tags, read_value,
+    },
+    reader::Reader,
+    shared::Shared,
+};
+use alloc::{borrow::Cow, string::ToString, vec::Vec};
+use s2json::{BBOX, Properties, ValueType};
+
+/// A Mapbox feature read from borrowed bytes. The properties are decoded from the layer's keys
+/// and values each time they are read, as is the geometry each time it is loaded.
+#[derive(Debug, Clone)]
+pub struct BorrowedMapboxFeature<'a> {
+    /// the id of the feature
+    pub id: Option<u64>,
+    /// the version of the vector tile
+    pub version: u16,
+    /// the extent
+    pub extent: usize,
+    /// the feature type
+    pub r#type: MapboxFeatureType,
+    /// whether the feature is using the S2 spec
+    pub is_s2: bool,
+    /// the tile data holding the feature message
+    data: &'a [u8],
+    /// the layer's key store
+    keys: Shared<[Cow<'a, str>]>,
+    /// the layer's encoded values
+    values: Shared<[&'a [u8]]>,
+    /// positions of the properties, geometry, indices and tessellation in the tile data
+    fields: FeatureFields,
+}
+impl<'a> BorrowedMapboxFeature<'a> {
+    /// Read a feature message given the layer's keys and values
+    pub fn new(
+        data: &'a [u8],
+        is_s2: bool,
+        extent: usize,
+        version: u16,
+        keys: Shared<[Cow<'a, str>]>,
+        values: Shared<[&'a [u8]]>,
+    ) -> Self {
+        BorrowedMapboxFeature::try_new(data, is_s2, extent, version, keys, values)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Read a feature message given the layer's keys and values, returning an error if it is
+    /// malformed
+    pub fn try_new(
+        data: &'a [u8],
+        is_s2: bool,
+        extent: usize,
+        version: u16,
+        keys: Shared<[Cow<'a, str>]>,
+        values: Shared<[&'a [u8]]>,
+    ) -> Result<Self, DecodeError> {
+        let pb = Reader::new(data, 0, data.len(), ProtoMessage::MapboxFeature);
+        let fields = read_feature_fields(pb, is_s2, keys.len(), values.len())?;
+        Ok(BorrowedMapboxFeature::from_fields(data, fields, is_s2, extent, version, keys, values))
+    }
+
+    /// Create a feature from the fields read from `data`
+    pub(crate) fn from_fields(
+        data: &'a [u8],
+        fields: FeatureFields,
+        is_s2: bool,
+        extent: usize,
+        version: u16,
+        keys: Shared<[Cow<'a, str>]>,
+        values: Shared<[&'a [u8]]>,
+    ) -> Self {
+        BorrowedMapboxFeature {
+            id: fields.id,
+            version,
+            extent,
+            r#type: fields.r#type.clone(),
+            is_s2,
+            data,
+            keys,
+            values,
+            fields,
+        }
+    }
+}
+impl VectorFeatureMethods for BorrowedMapboxFeature<'_> {
+    fn id(&self) -> Option<u64> {
+        self.id
+    }
+    fn version(&self) -> u16 {
+        self.version
+    }
+    fn properties(&self) -> Properties {
+        let mut properties = Properties::new();
+        let Some(pos) = self.fields.tags else { return properties };
+        for (key, value) in read_tags(self.data, pos) {
+            if let (Some(key), Some(&value)) = (self.keys.get(key), self.values.get(value)) {
+                properties.insert(key.to_string(), ValueType::Primitive(read_value(value)));
+            }
+        }
+        properties
+    }
+    fn extent(&self) -> usize {
+        self.extent
+    }
+    fn get_type(&self) -> FeatureType {
+        (&self.r#type).into()
+    }
+    fn bbox(&self) -> Option<BBOX> {
+        None
+    }
+    fn has_m_values(&self) -> bool {
+        false
+    }
+    fn is_points(&self) -> bool {
+        self.r#type == MapboxFeatureType::Point
+    }
+    fn is_lines(&self) -> bool {
+        self.r#type == MapboxFeatureType::Line
+    }
+    fn is_polygons(&self) -> bool {
+        self.r#type == MapboxFeatureType::Polygon || self.r#type == MapboxFeatureType::MultiPolygon
+    }
+    fn is_points_3d(&self) -> bool {
+        false
+    }
+    fn is_lines_3d(&self) -> bool {
+        false
+    }
+    fn is_polygons_3d(&self) -> bool {
+        false
+    }
+    fn load_points(&mut self) -> VectorPoints {
+        geometry_points(self.load_geometry())
+    }
+    fn load_points_3d(&mut self) -> VectorPoints3D {
+        panic!("unexpected geometry type")
+    }
+    fn load_lines(&mut self) -> VectorLinesWithOffset {
+        geometry_lines(self.load_geometry())
+    }
+    fn load_lines_3d(&mut self) -> VectorLines3DWithOffset {
+        panic!("unexpected geometry type")
+    }
+    fn load_polys(&mut self) -> Vec<VectorLinesWithOffset> {
+        geometry_polys(self.load_geometry())
+    }
+    fn load_polys_3d(&mut self) -> Vec<VectorLines3DWithOffset> {
+        panic!("unexpected geometry type")
+    }
+    fn load_geometry_flat(&mut self) -> (Vec<f64>, Vec<u32>) {
+        let multiplier: f64 = 1.0 / self.extent as f64;
+        let mut geometry = flatten_polys(self.load_geometry(), multiplier);
+        let indices = self.read_indices();
+        self.add_tessellation(&mut geometry, multiplier);
+
+        (geometry, indices)
+    }
+    fn load_geometry(&mut self) -> VectorGeometry {
+        decode_geometry(self.data, self.fields.geometry, &self.r#type, self.is_s2)
+    }
+    fn read_indices(&mut self) -> Vec<u32> {
+        match self.fields.indices {
+            Some(pos) => decode_indices(self.data, pos),
+            None => Vec::new(),
+        }
+    }
+    fn add_tessellation(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
+        if let Some(pos) = self.fields.tessellation {
+            decode_tessellation(self.data, pos, geometry, multiplier);
+        }
+    }
+    fn add_tessellation_3d(&mut self, _geometry: &mut Vec<f64>, _multiplier: f64) {
+        panic!("unexpected geometry type")
+    }
+}
+
+/// A feature read from borrowed bytes, either mapbox or open.
+/// Implements the [`VectorFeatureMethods`] trait
+#[derive(Debug, Clone)]
+pub enum BorrowedVectorFeature<'a> {
+    /// Mapbox Vector Feature
+    Mapbox(BorrowedMapboxFeature<'a>),
+    /// Open Vector Feature
+    Open(OpenVectorFeature<BorrowedColumns<'a>>),
+}
+impl<'a> From<BorrowedMapboxFeature<'a>> for BorrowedVectorFeature<'a> {
+    fn from(value: BorrowedMapboxFeature<'a>) -> Self {
+        BorrowedVectorFeature::Mapbox(value)
+    }
+}
+impl<'a> From<OpenVectorFeature<BorrowedColumns<'a>>> for BorrowedVectorFeature<'a> {
+    fn from(value: OpenVectorFeature<BorrowedColumns<'a>>) -> Self {
+        BorrowedVectorFeature::Open(value)
+    }
+}
+impl VectorFeatureMethods for BorrowedVectorFeature<'_> {
+    fn id(&self) -> Option<u64> {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.id(),
+            BorrowedVectorFeature::Open(feature) => feature.id(),
+        }
+    }
+    fn version(&self) -> u16 {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.version(),
+            BorrowedVectorFeature::Open(feature) => feature.version(),
+        }
+    }
+    fn properties(&self) -> Properties {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.properties(),
+            BorrowedVectorFeature::Open(feature) => feature.properties(),
+        }
+    }
+    fn extent(&self) -> usize {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.extent(),
+            BorrowedVectorFeature::Open(feature) => feature.extent(),
+        }
+    }
+    fn get_type(&self) -> FeatureType {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.get_type(),
+            BorrowedVectorFeature::Open(feature) => feature.get_type(),
+        }
+    }
+    fn bbox(&self) -> Option<BBOX> {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.bbox(),
+            BorrowedVectorFeature::Open(feature) => feature.bbox(),
+        }
+    }
+    fn has_m_values(&self) -> bool {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.has_m_values(),
+            BorrowedVectorFeature::Open(feature) => feature.has_m_values(),
+        }
+    }
+    fn is_points(&self) -> bool {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.is_points(),
+            BorrowedVectorFeature::Open(feature) => feature.is_points(),
+        }
+    }
+    fn is_lines(&self) -> bool {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.is_lines(),
+            BorrowedVectorFeature::Open(feature) => feature.is_lines(),
+        }
+    }
+    fn is_polygons(&self) -> bool {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.is_polygons(),
+            BorrowedVectorFeature::Open(feature) => feature.is_polygons(),
+        }
+    }
+    fn is_points_3d(&self) -> bool {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.is_points_3d(),
+            BorrowedVectorFeature::Open(feature) => feature.is_points_3d(),
+        }
+    }
+    fn is_lines_3d(&self) -> bool {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.is_lines_3d(),
+            BorrowedVectorFeature::Open(feature) => feature.is_lines_3d(),
+        }
+    }
+    fn is_polygons_3d(&self) -> bool {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.is_polygons_3d(),
+            BorrowedVectorFeature::Open(feature) => feature.is_polygons_3d(),
+        }
+    }
+    fn load_points(&mut self) -> VectorPoints {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.load_points(),
+            BorrowedVectorFeature::Open(feature) => feature.load_points(),
+        }
+    }
+    fn load_points_3d(&mut self) -> VectorPoints3D {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.load_points_3d(),
+            BorrowedVectorFeature::Open(feature) => feature.load_points_3d(),
+        }
+    }
+    fn load_lines(&mut self) -> VectorLinesWithOffset {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.load_lines(),
+            BorrowedVectorFeature::Open(feature) => feature.load_lines(),
+        }
+    }
+    fn load_lines_3d(&mut self) -> VectorLines3DWithOffset {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.load_lines_3d(),
+            BorrowedVectorFeature::Open(feature) => feature.load_lines_3d(),
+        }
+    }
+    fn load_polys(&mut self) -> Vec<VectorLinesWithOffset> {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.load_polys(),
+            BorrowedVectorFeature::Open(feature) => feature.load_polys(),
+        }
+    }
+    fn load_polys_3d(&mut self) -> Vec<VectorLines3DWithOffset> {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.load_polys_3d(),
+            BorrowedVectorFeature::Open(feature) => feature.load_polys_3d(),
+        }
+    }
+    fn load_geometry_flat(&mut self) -> (Vec<f64>, Vec<u32>) {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.load_geometry_flat(),
+            BorrowedVectorFeature::Open(feature) => feature.load_geometry_flat(),
+        }
+    }
+    fn load_geometry(&mut self) -> VectorGeometry {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.load_geometry(),
+            BorrowedVectorFeature::Open(feature) => feature.load_geometry(),
+        }
+    }
+    fn read_indices(&mut self) -> Vec<u32> {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => feature.read_indices(),
+            BorrowedVectorFeature::Open(feature) => feature.read_indices(),
+        }
+    }
+    fn add_tessellation(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => {
+                feature.add_tessellation(geometry, multiplier)
+            }
+            BorrowedVectorFeature::Open(feature) => feature.add_tessellation(geometry, multiplier),
+        }
+    }
+    fn add_tessellation_3d(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
+        match self {
+            BorrowedVectorFeature::Mapbox(feature) => {
+                feature.add_tessellation_3d(geometry, multiplier)
+            }
+            BorrowedVectorFeature::Open(feature) => {
+                feature.add_tessellation_3d(geometry, multiplier)
+            }
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::{
+    OpenVectorFeature,
+    borrowed::{BorrowedColumns, BorrowedMapboxFeature, BorrowedVectorFeature},
+    error::{DecodeError, ProtoMessage},
+    mapbox::{self, read_feature_fields},
+    open::{self, Extent, FeatureFields, ImageType, decode_grid, read_grid, read_image},
+    reader::{Reader, Result},
+    shared::Shared,
+    spatial_index::SpatialIndex,
+};
+use alloc::{borrow::Cow, vec::Vec};
+use s2json::Shape;
+
+/// A Mapbox layer read from borrowed bytes. Keys, values and features are stored as slices of
+/// the tile and only decoded once a feature is read.
+#[derive(Debug, Clone)]
+pub struct BorrowedMapboxLayer<'a> {
+    /// the version of the vector tile layer.
+    pub version: u16,
+    /// the name of the layer
+    pub name: Cow<'a, str>,
+    /// the extent of the vector layer
+    pub extent: usize,
+    /// whether or not the layer is an s2 layer
+    is_s2: bool,
+    /// the tile data holding the layer message
+    data: &'a [u8],
+    /// positions of the feature messages
+    features: Vec<usize>,
+    /// key store used by features
+    keys: Shared<[Cow<'a, str>]>,
+    /// the encoded values used by features
+    values: Shared<[&'a [u8]]>,
+}
+impl<'a> BorrowedMapboxLayer<'a> {
+    /// Read a layer message
+    pub fn new(data: &'a [u8], is_s2: bool) -> Self {
+        BorrowedMapboxLayer::try_new(data, is_s2).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Read a layer message, returning an error if it is malformed
+    pub fn try_new(data: &'a [u8], is_s2: bool) -> core::result::Result<Self, DecodeError> {
+        Self::read(Reader::new(data, 0, data.len(), ProtoMessage::MapboxLayer), is_s2)
+    }
+
+    /// Read the layer message covered by `layer`
+    pub(crate) fn read(layer: Reader<'a>, is_s2: bool) -> Result<Self> {
+        let data = layer.buf();
+        let fields = mapbox::read_layer(layer, is_s2)?;
+        Ok(BorrowedMapboxLayer {
+            version: fields.version,
+            name: fields.name,
+            extent: fields.extent,
+            is_s2,
+            data,
+            features: fields.features,
+            keys: fields.keys.into(),
+            values: fields.values.into(),
+        })
+    }
+
+    /// Read a feature from the layer
+    pub fn feature(&self, i: usize) -> Option<BorrowedMapboxFeature<'a>> {
+        let feature = Reader::at(self.data, *self.features.get(i)?, 2, ProtoMessage::MapboxFeature);
+        // the layer checked its features when it was read
+        let fields = feature
+            .and_then(|pb| read_feature_fields(pb, self.is_s2, self.keys.len(), self.values.len()))
+            .ok()?;
+        Some(BorrowedMapboxFeature::from_fields(
+            self.data,
+            fields,
+            self.is_s2,
+            self.extent,
+            self.version,
+            self.keys.clone(),
+            self.values.clone(),
+        ))
+    }
+}
+
+/// An Open layer read from borrowed bytes. Features are stored as slices of the tile and only
+/// decoded once they are read.
+#[derive(Debug, Clone)]
+pub struct BorrowedOpenLayer<'a> {
+    /// the version of the vector tile
+    pub version: u16,
+    /// the name of the layer
+    pub name: Cow<'a, str>,
+    /// the extent of the vector layer
+    pub extent: Extent,
+    shape: Option<Shared<Shape>>,
+    m_shape: Option<Shared<Shape>>,
+    /// shared by features when the layer has no shape or no M-Value shape
+    empty_shape: Shared<Shape>,
+    /// the fields of each feature message
+    features: Vec<FeatureFields>,
+    cache: Shared<BorrowedColumns<'a>>,
+    index: Option<SpatialIndex>,
+}
+impl<'a> BorrowedOpenLayer<'a> {
+    /// Read a layer message given the tile's column cache
+    pub fn new(data: &'a [u8], cache: Shared<BorrowedColumns<'a>>) -> Self {
+        BorrowedOpenLayer::try_new(data, cache).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Read a layer message given the tile's column cache, returning an error if it is malformed
+    /// or refers to columns the cache does not have
+    pub fn try_new(
+        data: &'a [u8],
+        cache: Shared<BorrowedColumns<'a>>,
+    ) -> core::result::Result<Self, DecodeError> {
+        Self::read(Reader::new(data, 0, data.len(), ProtoMessage::OpenLayer), cache)
+    }
+
+    /// Read the layer message covered by `layer`
+    pub(crate) fn read(layer: Reader<'a>, cache: Shared<BorrowedColumns<'a>>) -> Result<Self> {
+        let mut features = Vec::new();
+        let fields = open::read_layer(layer, &*cache, |_, feature| features.push(feature))?;
+        Ok(BorrowedOpenLayer {
+            version: fields.version,
+            name: cache.string(fields.name),
+            extent: fields.extent,
+            shape: fields.shape.map(Into::into),
+            m_shape: fields.m_shape.map(Into::into),
+            empty_shape: Default::default(),
+            features,
+            cache,
+            index: fields.index,
+        })
+    }
+
+    /// The shape used to describe the features properties in the layer
+    pub fn shape(&self) -> Option<&Shape> {
+        self.shape.as_deref()
+    }
+
+    /// The shape of each feature's M-Values
+    pub fn m_shape(&self) -> Option<&Shape> {
+        self.m_shape.as_deref()
+    }
+
+    /// The spatial index stored with the layer, see [`crate::base::BaseVectorLayer::spatial_index`].
+    /// Nothing is built for layers written without one
+    pub fn spatial_index(&self) -> Option<&SpatialIndex> {
+        self.index.as_ref()
+    }
+
+    /// Read a feature from the layer
+    pub fn feature(&self, i: usize) -> Option<BorrowedVectorFeature<'a>> {
+        Some(
+            OpenVectorFeature::from_fields(
+                self.features.get(i)?.clone(),
+                self.extent,
+                self.cache.clone(),
+                self.shape.as_ref().unwrap_or(&self.empty_shape).clone(),
+                self.m_shape.as_ref().unwrap_or(&self.empty_shape).clone(),
+            )
+            .into(),
+        )
+    }
+}
+
+/// Layer container supporting both mapbox and open vector layers read from borrowed bytes
+#[derive(Debug, Clone)]
+pub enum BorrowedVectorLayer<'a> {
+    /// Mapbox vector layer
+    Mapbox(BorrowedMapboxLayer<'a>),
+    /// Open vector layer
+    Open(BorrowedOpenLayer<'a>),
+}
+impl<'a> BorrowedVectorLayer<'a> {
+    /// the version of the vector tile layer.
+    pub fn version(&self) -> u16 {
+        match self {
+            BorrowedVectorLayer::Mapbox(layer) => layer.version,
+            BorrowedVectorLayer::Open(layer) => layer.version,
+        }
+    }
+
+    /// the name of the layer
+    pub fn name(&self) -> &str {
+        match self {
+            BorrowedVectorLayer::Mapbox(layer) => &layer.name,
+            BorrowedVectorLayer::Open(layer) => &layer.name,
+        }
+    }
+
+    /// the extent of the vector tile
+    pub fn extent(&self) -> usize {
+        match self {
+            BorrowedVectorLayer::Mapbox(layer) => layer.extent,
+            BorrowedVectorLayer::Open(layer) => layer.extent.into(),
+        }
+    }
+
+    /// Read a feature from the layer. Nothing is cached, so reading the same feature twice
+    /// decodes it twice
+    pub fn feature(&self, i: usize) -> Option<BorrowedVectorFeature<'a>> {
+        match self {
+            BorrowedVectorLayer::Mapbox(layer) => layer.feature(i).map(Into::into),
+            BorrowedVectorLayer::Open(layer) => layer.feature(i),
+        }
+    }
+
+    /// Iterate over every feature in the layer, in order
+    pub fn features(&self) -> impl Iterator<Item = BorrowedVectorFeature<'a>> + '_ {
+        (0..self.len()).filter_map(|i| self.feature(i))
+    }
+
+    /// length (feature count)
+    pub fn len(&self) -> usize {
+        match self {
+            BorrowedVectorLayer::Mapbox(layer) => layer.features.len(),
+            BorrowedVectorLayer::Open(layer) => layer.features.len(),
+        }
+    }
+
+    /// empty (feature count is 0)
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Image layer read from borrowed bytes. The image is a slice of the tile.
+#[derive(Debug, Default, Clone)]
+pub struct BorrowedImageLayer<'a> {
+    /// The name of the image
+    pub name: Cow<'a, str>,
+    /// The image type
+    pub image_type: ImageType,
+    /// The image width
+    pub width: u32,
+    /// The image height
+    pub height: u32,
+    /// The image data
+    pub image: &'a [u8],
+}
+impl<'a> BorrowedImageLayer<'a> {
+    /// Read an image message
+    pub fn new(data: &'a [u8]) -> Self {
+        BorrowedImageLayer::try_new(data).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Read an image message, returning an error if it is malformed
+    pub fn try_new(data: &'a [u8]) -> core::result::Result<Self, DecodeError> {
+        Self::read(Reader::new(data, 0, data.len(), ProtoMessage::ImageData))
+    }
+
+    /// Read the image message covered by `image`
+    pub(crate) fn read(image: Reader<'a>) -> Result<Self> {
+        let data = image.buf();
+        let fields = read_image(image)?;
+        let (start, end) = fields.image;
+        Ok(BorrowedImageLayer {
+            name: fields.name,
+            image_type: fields.image_type,
+            width: fields.width,
+            height: fields.height,
+            image: &data[start..end],
+        })
+    }
+}
+
+/// Gridded data layer read from borrowed bytes. The grid is decoded each time
+/// [`BorrowedGridLayer::data`] is called.
+#[derive(Debug, Default, Clone)]
+pub struct BorrowedGridLayer<'a> {
+    /// The name of the gridded data
+    pub name: Cow<'a, str>,
+    /// The extent for remapping the data with a value between 0 and extent
+    pub extent: Extent,
+    /// The size of the tile (width and height)
+    pub size: f64,
+    /// The minimum grid value
+    pub min: f64,
+    /// The maximum grid value
+    pub max: f64,
+    /// the packed grid values
+    data: &'a [u8],
+}
+impl<'a> BorrowedGridLayer<'a> {
+    /// Read a grid message
+    pub fn new(data: &'a [u8]) -> Self {
+        BorrowedGridLayer::try_new(data).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Read a grid message, returning an error if it is malformed
+    pub fn try_new(data: &'a [u8]) -> core::result::Result<Self, DecodeError> {
+        Self::read(Reader::new(data, 0, data.len(), ProtoMessage::GridData))
+    }
+
+    /// Read the grid message covered by `grid`
+    pub(crate) fn read(grid: Reader<'a>) -> Result<Self> {
+        let data = grid.buf();
+        let fields = read_grid(grid)?;
+        let (start, end) = fields.data;
+        Ok(BorrowedGridLayer {
+            name: fields.name,
+            extent: fields.extent,
+            size: fields.size,
+            min: fields.min,
+            max: fields.max,
+            data: &data[start..end],
+        })
+    }
+
+    /// Decode the grid data
+    pub fn data(&self) -> Vec<f64> {
+        decode_grid(self.data, self.min, self.max, self.extent)
+    }
+}
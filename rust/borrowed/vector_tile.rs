@@ -0,0 +1,93 @@
+use crate::{
+    borrowed::{
+        BorrowedColumns, BorrowedGridLayer, BorrowedImageLayer, BorrowedMapboxLayer,
+        BorrowedOpenLayer, BorrowedVectorLayer,
+    },
+    error::{DecodeError, ProtoMessage},
+    reader::Reader,
+    shared::shared,
+    vector_tile::read_tile,
+};
+use alloc::{borrow::Cow, collections::BTreeMap};
+
+/// # Borrowed Vector Tile
+///
+/// ## Description
+/// A Vector Tile that reads Mapbox or OpenVector Tile Layers from a borrowed byte slice (e.g. a
+/// memory-mapped archive). Feature messages, strings and image payloads are never copied out of
+/// the input, so a large tile can be inspected with very few allocations.
+///
+/// ## Usage
+/// ```rust,ignore
+/// use open_vector_tile::{VectorFeatureMethods, borrowed::BorrowedVectorTile};
+///
+/// let data: &[u8] = &[];
+/// let tile = BorrowedVectorTile::new(data, None);
+///
+/// let landuse = tile.layer("landuse").unwrap();
+/// let mut feature = landuse.feature(0).unwrap();
+/// let geometry = feature.load_geometry();
+///
+/// let satellite = tile.images.get("satellite").unwrap();
+/// let image: &[u8] = satellite.image;
+/// ```
+#[derive(Debug, Clone)]
+pub struct BorrowedVectorTile<'a> {
+    /// the layers in the vector tile
+    pub layers: BTreeMap<Cow<'a, str>, BorrowedVectorLayer<'a>>,
+    /// Gridded data
+    pub grids: BTreeMap<Cow<'a, str>, BorrowedGridLayer<'a>>,
+    /// Image data
+    pub images: BTreeMap<Cow<'a, str>, BorrowedImageLayer<'a>>,
+}
+impl<'a> BorrowedVectorTile<'a> {
+    /// Create a new vector tile
+    ///
+    /// # Panics
+    /// If the data is not a valid tile. Use [`BorrowedVectorTile::try_new`] for untrusted input.
+    pub fn new(data: &'a [u8], end: Option<usize>) -> Self {
+        Self::try_new(data, end).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create a new vector tile, returning an error if the data is truncated or malformed.
+    ///
+    /// The whole tile is checked up front, so a tile returned here will not panic while it is
+    /// being read.
+    pub fn try_new(data: &'a [u8], end: Option<usize>) -> Result<Self, DecodeError> {
+        let messages = read_tile(data, end, true)?;
+
+        let mut tile = BorrowedVectorTile {
+            layers: BTreeMap::new(),
+            grids: BTreeMap::new(),
+            images: BTreeMap::new(),
+        };
+        for (layer, is_s2) in messages.mapbox {
+            let layer = BorrowedMapboxLayer::read(layer, is_s2)?;
+            tile.layers.insert(layer.name.clone(), BorrowedVectorLayer::Mapbox(layer));
+        }
+        for grid in messages.grids {
+            let grid = BorrowedGridLayer::read(grid)?;
+            tile.grids.insert(grid.name.clone(), grid);
+        }
+        for image in messages.images {
+            let image = BorrowedImageLayer::read(image)?;
+            tile.images.insert(image.name.clone(), image);
+        }
+        // without a column cache the open layers are never decoded
+        if let Some(columns) = messages.columns {
+            let cache = shared(BorrowedColumns::read(columns)?);
+            for pos in messages.open {
+                let layer = Reader::at(data, pos, 4, ProtoMessage::OpenLayer)?;
+                let layer = BorrowedOpenLayer::read(layer, cache.clone())?;
+                tile.layers.insert(layer.name.clone(), BorrowedVectorLayer::Open(layer));
+            }
+        }
+
+        Ok(tile)
+    }
+
+    /// Get a layer given the name
+    pub fn layer(&self, name: &str) -> Option<&BorrowedVectorLayer<'a>> {
+        self.layers.get(name)
+    }
+}
@@ -0,0 +1,305 @@
+#[cfg(feature = "brotli")]
+use crate::borrowed::BorrowedVectorTile;
+use crate::{
+    DecodeError, VectorTile,
+    base::BaseVectorTile,
+    open::{GridData, ImageData},
+    write_tile,
+};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The most bytes [`decompress`] and [`decompress_with`] will decompress data into, 64 MiB. Use
+/// [`decompress_limited`] or [`decompress_with_limit`] to choose a different limit.
+pub const MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+/// A compression codec that tiles are commonly stored or served with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// Gzip (RFC 1952), requires the `gzip` feature
+    Gzip,
+    /// Zlib (RFC 1950), requires the `zlib` feature
+    Zlib,
+    /// Zstandard, requires the `zstd` feature
+    Zstd,
+    /// Brotli, requires the `brotli` feature
+    Brotli,
+}
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Codec::Gzip => "gzip",
+            Codec::Zlib => "zlib",
+            Codec::Zstd => "zstd",
+            Codec::Brotli => "brotli",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An error decompressing, compressing or decoding a compressed tile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompressionError {
+    /// The codec's cargo feature is not enabled
+    Unsupported(Codec),
+    /// The data could not be decompressed with the codec
+    Corrupt(Codec),
+    /// The decompressed data is larger than the limit it was decompressed with
+    TooLarge(Codec),
+    /// The decompressed data is not a valid tile
+    Decode(DecodeError),
+}
+impl fmt::Display for CompressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompressionError::Unsupported(codec) => {
+                write!(f, "{} compressed data requires the \"{}\" feature", codec, codec)
+            }
+            CompressionError::Corrupt(codec) => write!(f, "invalid {} compressed data", codec),
+            CompressionError::TooLarge(codec) => {
+                write!(f, "{} compressed data decompresses past the size limit", codec)
+            }
+            CompressionError::Decode(err) => write!(f, "{}", err),
+        }
+    }
+}
+impl core::error::Error for CompressionError {}
+impl From<DecodeError> for CompressionError {
+    fn from(err: DecodeError) -> Self {
+        CompressionError::Decode(err)
+    }
+}
+
+/// The codec `data` is compressed with, found from its magic number. Brotli streams have no magic
+/// number so they are never detected here, see [`decompress`].
+pub fn detect(data: &[u8]) -> Option<Codec> {
+    match data {
+        [0x1f, 0x8b, ..] => Some(Codec::Gzip),
+        [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Codec::Zstd),
+        // deflate with a window of at most 32 KiB and a valid header checksum
+        [cmf, flg, ..]
+            if cmf & 0x0f == 8
+                && cmf >> 4 <= 7
+                && (*cmf as u16 * 256 + *flg as u16).is_multiple_of(31) =>
+        {
+            Some(Codec::Zlib)
+        }
+        _ => None,
+    }
+}
+
+/// Decompress `data` if it is compressed, otherwise return it as is. Decompresses into at most
+/// [`MAX_DECOMPRESSED_SIZE`] bytes.
+///
+/// None of the detected magic numbers can start an encoded tile. With the `brotli` feature, data
+/// that has no magic number and is not a valid tile is decompressed as brotli if possible.
+pub fn decompress(data: Vec<u8>) -> Result<Vec<u8>, CompressionError> {
+    decompress_limited(data, MAX_DECOMPRESSED_SIZE)
+}
+
+/// [`decompress`] into at most `limit` bytes, returning [`CompressionError::TooLarge`] past it
+pub fn decompress_limited(data: Vec<u8>, limit: usize) -> Result<Vec<u8>, CompressionError> {
+    if let Some(codec) = detect(&data) {
+        return decompress_with_limit(&data, codec, limit);
+    }
+    #[cfg(feature = "brotli")]
+    if BorrowedVectorTile::try_new(&data, None).is_err() {
+        match decompress_with_limit(&data, Codec::Brotli, limit) {
+            Ok(res) => return Ok(res),
+            Err(err @ CompressionError::TooLarge(_)) => return Err(err),
+            Err(_) => {}
+        }
+    }
+    Ok(data)
+}
+
+/// Decompress `data` with `codec` into at most [`MAX_DECOMPRESSED_SIZE`] bytes
+pub fn decompress_with(data: &[u8], codec: Codec) -> Result<Vec<u8>, CompressionError> {
+    decompress_with_limit(data, codec, MAX_DECOMPRESSED_SIZE)
+}
+
+/// Decompress `data` with `codec` into at most `limit` bytes, returning
+/// [`CompressionError::TooLarge`] past it
+#[cfg_attr(
+    not(any(feature = "gzip", feature = "zlib", feature = "zstd", feature = "brotli")),
+    allow(unused_variables)
+)]
+pub fn decompress_with_limit(
+    data: &[u8],
+    codec: Codec,
+    limit: usize,
+) -> Result<Vec<u8>, CompressionError> {
+    match codec {
+        #[cfg(feature = "gzip")]
+        Codec::Gzip => gzip::decompress(data, limit),
+        #[cfg(feature = "zlib")]
+        Codec::Zlib => miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(data, limit)
+            .map_err(|err| inflate_error(err, codec)),
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => {
+            use ruzstd::{decoding::StreamingDecoder, io::Read};
+            let mut res = Vec::new();
+            StreamingDecoder::new(data)
+                .map_err(|_| CompressionError::Corrupt(codec))?
+                .take((limit as u64).saturating_add(1))
+                .read_to_end(&mut res)
+                .map_err(|_| CompressionError::Corrupt(codec))?;
+            limited(res, codec, limit)
+        }
+        #[cfg(feature = "brotli")]
+        Codec::Brotli => {
+            use std::io::Read;
+            let mut res = Vec::new();
+            brotli::Decompressor::new(data, 4096)
+                .take((limit as u64).saturating_add(1))
+                .read_to_end(&mut res)
+                .map_err(|_| CompressionError::Corrupt(codec))?;
+            limited(res, codec, limit)
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(CompressionError::Unsupported(codec)),
+    }
+}
+
+/// Reject output read one byte past `limit`
+#[cfg(any(feature = "zstd", feature = "brotli"))]
+fn limited(res: Vec<u8>, codec: Codec, limit: usize) -> Result<Vec<u8>, CompressionError> {
+    if res.len() > limit { Err(CompressionError::TooLarge(codec)) } else { Ok(res) }
+}
+
+/// Map a miniz_oxide error, which reports a full output buffer as `HasMoreOutput`
+#[cfg(any(feature = "gzip", feature = "zlib"))]
+fn inflate_error(err: miniz_oxide::inflate::DecompressError, codec: Codec) -> CompressionError {
+    match err.status {
+        miniz_oxide::inflate::TINFLStatus::HasMoreOutput => CompressionError::TooLarge(codec),
+        _ => CompressionError::Corrupt(codec),
+    }
+}
+
+/// Compress `data` with `codec`
+#[cfg_attr(
+    not(any(feature = "gzip", feature = "zlib", feature = "zstd", feature = "brotli")),
+    allow(unused_variables)
+)]
+pub fn compress(data: &[u8], codec: Codec) -> Result<Vec<u8>, CompressionError> {
+    match codec {
+        #[cfg(feature = "gzip")]
+        Codec::Gzip => Ok(gzip::compress(data)),
+        #[cfg(feature = "zlib")]
+        Codec::Zlib => Ok(miniz_oxide::deflate::compress_to_vec_zlib(data, 6)),
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => {
+            Ok(ruzstd::encoding::compress_to_vec(data, ruzstd::encoding::CompressionLevel::Fastest))
+        }
+        #[cfg(feature = "brotli")]
+        Codec::Brotli => {
+            let mut res = Vec::new();
+            let params = brotli::enc::BrotliEncoderParams::default();
+            brotli::BrotliCompress(&mut &data[..], &mut res, &params)
+                .map_err(|_| CompressionError::Corrupt(codec))?;
+            Ok(res)
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(CompressionError::Unsupported(codec)),
+    }
+}
+
+impl VectorTile {
+    /// Create a new vector tile from data that may be compressed with any [`Codec`], see
+    /// [`decompress`]. Uncompressed data is decoded as is.
+    pub fn from_compressed(data: Vec<u8>) -> Result<Self, CompressionError> {
+        Ok(VectorTile::try_new(decompress(data)?, None)?)
+    }
+}
+
+/// [`write_tile`] followed by compressing the encoded tile with `codec`
+pub fn write_tile_compressed(
+    tile: Option<&mut BaseVectorTile>,
+    images: Option<Vec<&ImageData>>,
+    grids: Option<Vec<&GridData>>,
+    codec: Codec,
+) -> Result<Vec<u8>, CompressionError> {
+    compress(&write_tile(tile, images, grids), codec)
+}
+
+/// Gzip members built on miniz_oxide's raw deflate
+#[cfg(feature = "gzip")]
+mod gzip {
+    use super::{Codec, CompressionError, inflate_error};
+    use alloc::vec::Vec;
+    use miniz_oxide::{deflate::compress_to_vec, inflate::decompress_to_vec_with_limit};
+
+    const FHCRC: u8 = 2;
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+
+    /// Decompress a single member gzip stream into at most `limit` bytes, checking its length and
+    /// CRC
+    pub fn decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, CompressionError> {
+        let corrupt = CompressionError::Corrupt(Codec::Gzip);
+        let (Some(pos), Some(trailer)) = (header_len(data), data.last_chunk::<8>()) else {
+            return Err(corrupt);
+        };
+        let deflate = data.get(pos..).ok_or(corrupt.clone())?;
+        let res = decompress_to_vec_with_limit(deflate, limit)
+            .map_err(|err| inflate_error(err, Codec::Gzip))?;
+        let [c0, c1, c2, c3, s0, s1, s2, s3] = *trailer;
+        let crc = u32::from_le_bytes([c0, c1, c2, c3]);
+        let size = u32::from_le_bytes([s0, s1, s2, s3]);
+        (crc == crc32(&res) && size == res.len() as u32).then_some(res).ok_or(corrupt)
+    }
+
+    /// The length of the gzip header, None if it is truncated or not deflate
+    fn header_len(data: &[u8]) -> Option<usize> {
+        if data.len() < 18 || data[2] != 8 {
+            return None;
+        }
+        let flags = data[3];
+        let mut pos = 10;
+        if flags & FEXTRA != 0 {
+            let len = u16::from_le_bytes([*data.get(pos)?, *data.get(pos + 1)?]) as usize;
+            pos += 2 + len;
+        }
+        for flag in [FNAME, FCOMMENT] {
+            if flags & flag != 0 {
+                pos += data.get(pos..)?.iter().position(|b| *b == 0)? + 1;
+            }
+        }
+        if flags & FHCRC != 0 {
+            pos += 2;
+        }
+        Some(pos)
+    }
+
+    /// Compress `data` into a single gzip member
+    pub fn compress(data: &[u8]) -> Vec<u8> {
+        let mut res = Vec::from([0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff]);
+        res.extend(compress_to_vec(data, 6));
+        res.extend(crc32(data).to_le_bytes());
+        res.extend((data.len() as u32).to_le_bytes());
+        res
+    }
+
+    /// CRC-32 (ISO 3309) lookup table
+    const CRC_TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut n = 0;
+        while n < 256 {
+            let mut c = n as u32;
+            let mut k = 0;
+            while k < 8 {
+                c = if c & 1 != 0 { 0xedb88320 ^ (c >> 1) } else { c >> 1 };
+                k += 1;
+            }
+            table[n] = c;
+            n += 1;
+        }
+        table
+    };
+
+    /// CRC-32 of `data`
+    fn crc32(data: &[u8]) -> u32 {
+        !data.iter().fold(!0, |crc, b| CRC_TABLE[((crc ^ *b as u32) & 0xff) as usize] ^ (crc >> 8))
+    }
+}
@@ -0,0 +1,528 @@
+use alloc::{vec, vec::Vec};
+
+/// Triangulate a polygon with the earcut algorithm (a port of mapbox/earcut).
+///
+/// `data` is a flat array of vertex coordinates with `dim` values per vertex (only the first two
+/// are used), `hole_indices` are the vertex indexes where each hole starts. Returns the vertex
+/// indexes of every triangle, three per triangle. Rings may be closed or open.
+pub fn earcut(data: &[f64], hole_indices: &[usize], dim: usize) -> Vec<u32> {
+    let mut earcut = Earcut { data, dim, nodes: Vec::new(), triangles: Vec::new() };
+    let outer_len = hole_indices.first().map(|h| h * dim).unwrap_or(data.len());
+    let Some(mut outer) = earcut.linked_list(0, outer_len, true) else {
+        return vec![];
+    };
+    if earcut.next(outer) == earcut.prev(outer) {
+        return vec![];
+    }
+    if !hole_indices.is_empty() {
+        outer = earcut.eliminate_holes(hole_indices, outer);
+    }
+    earcut.earcut_linked(Some(outer), 0);
+
+    earcut.triangles
+}
+
+/// A vertex in a circular doubly linked list
+#[derive(Debug, Clone)]
+struct Node {
+    /// the vertex index
+    i: usize,
+    x: f64,
+    y: f64,
+    prev: usize,
+    next: usize,
+    /// a single point hole, which is never removed
+    steiner: bool,
+}
+
+/// Triangulation state. Nodes are kept in an arena and linked by index.
+struct Earcut<'a> {
+    data: &'a [f64],
+    dim: usize,
+    nodes: Vec<Node>,
+    triangles: Vec<u32>,
+}
+impl Earcut<'_> {
+    fn next(&self, n: usize) -> usize {
+        self.nodes[n].next
+    }
+
+    fn prev(&self, n: usize) -> usize {
+        self.nodes[n].prev
+    }
+
+    fn xy(&self, n: usize) -> (f64, f64) {
+        (self.nodes[n].x, self.nodes[n].y)
+    }
+
+    /// Create a circular linked list from the ring in `data[start..end]` in the given winding
+    fn linked_list(&mut self, start: usize, end: usize, clockwise: bool) -> Option<usize> {
+        let dim = self.dim;
+        if end < start + dim {
+            return None;
+        }
+        let mut last = None;
+        let vertices: Vec<usize> = (start..end).step_by(dim).collect();
+        if clockwise == (signed_area(self.data, start, end, dim) > 0.0) {
+            for i in vertices {
+                last = Some(self.insert_node(i / dim, self.data[i], self.data[i + 1], last));
+            }
+        } else {
+            for i in vertices.into_iter().rev() {
+                last = Some(self.insert_node(i / dim, self.data[i], self.data[i + 1], last));
+            }
+        }
+        if let Some(l) = last
+            && self.equals(l, self.next(l))
+        {
+            let next = self.next(l);
+            self.remove_node(l);
+            last = Some(next);
+        }
+        last
+    }
+
+    /// Remove duplicate and collinear points
+    fn filter_points(&mut self, start: Option<usize>, end: Option<usize>) -> Option<usize> {
+        let start = start?;
+        let mut end = end.unwrap_or(start);
+        let mut p = start;
+        loop {
+            let mut again = false;
+            let (prev, next) = (self.prev(p), self.next(p));
+            if !self.nodes[p].steiner && (self.equals(p, next) || self.area(prev, p, next) == 0.0) {
+                self.remove_node(p);
+                p = prev;
+                end = prev;
+                if p == self.next(p) {
+                    break;
+                }
+                again = true;
+            } else {
+                p = next;
+            }
+            if !again && p == end {
+                break;
+            }
+        }
+        Some(end)
+    }
+
+    /// Main ear slicing loop, which triangulates a polygon given as a linked list
+    fn earcut_linked(&mut self, ear: Option<usize>, pass: u8) {
+        let Some(mut ear) = ear else { return };
+        let mut stop = ear;
+        while self.prev(ear) != self.next(ear) {
+            let (prev, next) = (self.prev(ear), self.next(ear));
+            if self.is_ear(ear) {
+                self.triangles.extend([
+                    self.nodes[prev].i as u32,
+                    self.nodes[ear].i as u32,
+                    self.nodes[next].i as u32,
+                ]);
+                self.remove_node(ear);
+                ear = self.next(next);
+                stop = ear;
+                continue;
+            }
+            ear = next;
+            if ear == stop {
+                match pass {
+                    // try filtering points and slicing again
+                    0 => {
+                        let ear = self.filter_points(Some(ear), None);
+                        self.earcut_linked(ear, 1);
+                    }
+                    // try to cure small local self intersections
+                    1 => {
+                        let filtered = self.filter_points(Some(ear), None);
+                        let ear = filtered.and_then(|e| self.cure_local_intersections(e));
+                        self.earcut_linked(ear, 2);
+                    }
+                    // as a last resort, try splitting the remaining polygon into two
+                    _ => self.split_earcut(ear),
+                }
+                break;
+            }
+        }
+    }
+
+    /// Check whether a polygon node forms a valid ear with adjacent nodes
+    fn is_ear(&self, ear: usize) -> bool {
+        let (a, b, c) = (self.prev(ear), ear, self.next(ear));
+        if self.area(a, b, c) >= 0.0 {
+            // reflex, can't be an ear
+            return false;
+        }
+        let ((ax, ay), (bx, by), (cx, cy)) = (self.xy(a), self.xy(b), self.xy(c));
+        let (x0, x1) = (ax.min(bx).min(cx), ax.max(bx).max(cx));
+        let (y0, y1) = (ay.min(by).min(cy), ay.max(by).max(cy));
+        // make sure we don't have other points inside the potential ear
+        let mut p = self.next(c);
+        while p != a {
+            let (px, py) = self.xy(p);
+            if px >= x0
+                && px <= x1
+                && py >= y0
+                && py <= y1
+                && point_in_triangle(ax, ay, bx, by, cx, cy, px, py)
+                && self.area(self.prev(p), p, self.next(p)) >= 0.0
+            {
+                return false;
+            }
+            p = self.next(p);
+        }
+        true
+    }
+
+    /// Go through all polygon nodes and cure small local self-intersections
+    fn cure_local_intersections(&mut self, start: usize) -> Option<usize> {
+        let mut start = start;
+        let mut p = start;
+        loop {
+            let a = self.prev(p);
+            let b = self.next(self.next(p));
+            if !self.equals(a, b)
+                && self.intersects(a, p, self.next(p), b)
+                && self.locally_inside(a, b)
+                && self.locally_inside(b, a)
+            {
+                self.triangles.extend([
+                    self.nodes[a].i as u32,
+                    self.nodes[p].i as u32,
+                    self.nodes[b].i as u32,
+                ]);
+                let next = self.next(p);
+                self.remove_node(p);
+                self.remove_node(next);
+                p = b;
+                start = b;
+            }
+            p = self.next(p);
+            if p == start {
+                break;
+            }
+        }
+        self.filter_points(Some(p), None)
+    }
+
+    /// Try splitting the polygon into two and triangulate them independently
+    fn split_earcut(&mut self, start: usize) {
+        let mut a = start;
+        loop {
+            let mut b = self.next(self.next(a));
+            while b != self.prev(a) {
+                if self.nodes[a].i != self.nodes[b].i && self.is_valid_diagonal(a, b) {
+                    let c = self.split_polygon(a, b);
+                    let a = self.filter_points(Some(a), Some(self.next(a)));
+                    let c = self.filter_points(Some(c), Some(self.next(c)));
+                    self.earcut_linked(a, 0);
+                    self.earcut_linked(c, 0);
+                    return;
+                }
+                b = self.next(b);
+            }
+            a = self.next(a);
+            if a == start {
+                break;
+            }
+        }
+    }
+
+    /// Link every hole into the outer loop, producing a single-ring polygon without holes
+    fn eliminate_holes(&mut self, hole_indices: &[usize], mut outer: usize) -> usize {
+        let mut queue = vec![];
+        for (i, hole) in hole_indices.iter().enumerate() {
+            let start = hole * self.dim;
+            let end = hole_indices.get(i + 1).map(|h| h * self.dim).unwrap_or(self.data.len());
+            if let Some(list) = self.linked_list(start, end, false) {
+                if list == self.next(list) {
+                    self.nodes[list].steiner = true;
+                }
+                queue.push(self.get_leftmost(list));
+            }
+        }
+        queue.sort_by(|a, b| self.nodes[*a].x.total_cmp(&self.nodes[*b].x));
+        for hole in queue {
+            outer = self.eliminate_hole(hole, outer);
+        }
+        outer
+    }
+
+    /// Find a bridge between a hole and the outer polygon and link it
+    fn eliminate_hole(&mut self, hole: usize, outer: usize) -> usize {
+        let Some(bridge) = self.find_hole_bridge(hole, outer) else {
+            return outer;
+        };
+        let bridge_reverse = self.split_polygon(bridge, hole);
+        self.filter_points(Some(bridge_reverse), Some(self.next(bridge_reverse)));
+        self.filter_points(Some(bridge), Some(self.next(bridge))).unwrap_or(bridge)
+    }
+
+    /// David Eberly's algorithm for finding a bridge between a hole and the outer polygon
+    fn find_hole_bridge(&self, hole: usize, outer: usize) -> Option<usize> {
+        let (hx, hy) = self.xy(hole);
+        let mut qx = f64::NEG_INFINITY;
+        let mut m = None;
+        // find a segment intersected by a ray from the hole's leftmost point to the left;
+        // the segment's endpoint with the lesser x will be the potential connection point
+        let mut p = outer;
+        loop {
+            let (px, py) = self.xy(p);
+            let (nx, ny) = self.xy(self.next(p));
+            if hy <= py && hy >= ny && ny != py {
+                let x = px + (hy - py) * (nx - px) / (ny - py);
+                if x <= hx && x > qx {
+                    qx = x;
+                    m = Some(if px < nx { p } else { self.next(p) });
+                    if x == hx {
+                        // the hole touches the outer segment
+                        return m;
+                    }
+                }
+            }
+            p = self.next(p);
+            if p == outer {
+                break;
+            }
+        }
+        let mut m = m?;
+
+        // look for points inside the triangle of hole point, segment intersection and endpoint;
+        // if there are none, the endpoint is the connection point, otherwise pick the point of
+        // the minimum angle with the ray as the connection point
+        let stop = m;
+        let (mx, my) = self.xy(m);
+        let mut tan_min = f64::INFINITY;
+        p = m;
+        loop {
+            let (px, py) = self.xy(p);
+            let (ax, cx) = if hy < my { (hx, qx) } else { (qx, hx) };
+            if hx >= px && px >= mx && hx != px && point_in_triangle(ax, hy, mx, my, cx, hy, px, py)
+            {
+                let tan = (hy - py).abs() / (hx - px);
+                let m_x = self.nodes[m].x;
+                if self.locally_inside(p, hole)
+                    && (tan < tan_min
+                        || (tan == tan_min
+                            && (px > m_x || (px == m_x && self.sector_contains_sector(m, p)))))
+                {
+                    m = p;
+                    tan_min = tan;
+                }
+            }
+            p = self.next(p);
+            if p == stop {
+                break;
+            }
+        }
+        Some(m)
+    }
+
+    /// Whether sector in vertex m contains sector in vertex p in the same coordinates
+    fn sector_contains_sector(&self, m: usize, p: usize) -> bool {
+        self.area(self.prev(m), m, self.prev(p)) < 0.0
+            && self.area(self.next(p), m, self.next(m)) < 0.0
+    }
+
+    /// Find the leftmost node of a polygon ring
+    fn get_leftmost(&self, start: usize) -> usize {
+        let mut p = start;
+        let mut leftmost = start;
+        loop {
+            let (px, py) = self.xy(p);
+            let (lx, ly) = self.xy(leftmost);
+            if px < lx || (px == lx && py < ly) {
+                leftmost = p;
+            }
+            p = self.next(p);
+            if p == start {
+                break;
+            }
+        }
+        leftmost
+    }
+
+    /// Check if a diagonal between two polygon nodes is valid (lies in polygon interior)
+    fn is_valid_diagonal(&self, a: usize, b: usize) -> bool {
+        let (an, ap) = (self.next(a), self.prev(a));
+        let (bn, bp) = (self.next(b), self.prev(b));
+        // doesn't intersect other edges
+        self.nodes[an].i != self.nodes[b].i
+            && self.nodes[ap].i != self.nodes[b].i
+            && !self.intersects_polygon(a, b)
+            // locally visible, does not create opposite-facing sectors
+            && ((self.locally_inside(a, b)
+                && self.locally_inside(b, a)
+                && self.middle_inside(a, b)
+                && (self.area(ap, a, bp) != 0.0 || self.area(a, bp, b) != 0.0))
+                // special zero-length case
+                || (self.equals(a, b) && self.area(ap, a, an) > 0.0 && self.area(bp, b, bn) > 0.0))
+    }
+
+    /// Signed area of a triangle
+    fn area(&self, p: usize, q: usize, r: usize) -> f64 {
+        let ((px, py), (qx, qy), (rx, ry)) = (self.xy(p), self.xy(q), self.xy(r));
+        (qy - py) * (rx - qx) - (qx - px) * (ry - qy)
+    }
+
+    fn equals(&self, p1: usize, p2: usize) -> bool {
+        self.xy(p1) == self.xy(p2)
+    }
+
+    /// Check if two segments intersect
+    fn intersects(&self, p1: usize, q1: usize, p2: usize, q2: usize) -> bool {
+        let o1 = sign(self.area(p1, q1, p2));
+        let o2 = sign(self.area(p1, q1, q2));
+        let o3 = sign(self.area(p2, q2, p1));
+        let o4 = sign(self.area(p2, q2, q1));
+        (o1 != o2 && o3 != o4)
+            // p1, q1 and p2 are collinear and p2 lies on p1q1
+            || (o1 == 0 && self.on_segment(p1, p2, q1))
+            // p1, q1 and q2 are collinear and q2 lies on p1q1
+            || (o2 == 0 && self.on_segment(p1, q2, q1))
+            // p2, q2 and p1 are collinear and p1 lies on p2q2
+            || (o3 == 0 && self.on_segment(p2, p1, q2))
+            // p2, q2 and q1 are collinear and q1 lies on p2q2
+            || (o4 == 0 && self.on_segment(p2, q1, q2))
+    }
+
+    /// For collinear points p, q, r, check if point q lies on segment pr
+    fn on_segment(&self, p: usize, q: usize, r: usize) -> bool {
+        let ((px, py), (qx, qy), (rx, ry)) = (self.xy(p), self.xy(q), self.xy(r));
+        qx <= px.max(rx) && qx >= px.min(rx) && qy <= py.max(ry) && qy >= py.min(ry)
+    }
+
+    /// Check if a polygon diagonal intersects any polygon segments
+    fn intersects_polygon(&self, a: usize, b: usize) -> bool {
+        let (ai, bi) = (self.nodes[a].i, self.nodes[b].i);
+        let mut p = a;
+        loop {
+            let next = self.next(p);
+            let (pi, ni) = (self.nodes[p].i, self.nodes[next].i);
+            if pi != ai && ni != ai && pi != bi && ni != bi && self.intersects(p, next, a, b) {
+                return true;
+            }
+            p = next;
+            if p == a {
+                return false;
+            }
+        }
+    }
+
+    /// Check if a polygon diagonal is locally inside the polygon
+    fn locally_inside(&self, a: usize, b: usize) -> bool {
+        let (ap, an) = (self.prev(a), self.next(a));
+        if self.area(ap, a, an) < 0.0 {
+            self.area(a, b, an) >= 0.0 && self.area(a, ap, b) >= 0.0
+        } else {
+            self.area(a, b, ap) < 0.0 || self.area(a, an, b) < 0.0
+        }
+    }
+
+    /// Check if the middle point of a polygon diagonal is inside the polygon
+    fn middle_inside(&self, a: usize, b: usize) -> bool {
+        let ((ax, ay), (bx, by)) = (self.xy(a), self.xy(b));
+        let (px, py) = ((ax + bx) / 2.0, (ay + by) / 2.0);
+        let mut inside = false;
+        let mut p = a;
+        loop {
+            let ((x, y), (nx, ny)) = (self.xy(p), self.xy(self.next(p)));
+            if ((y > py) != (ny > py)) && ny != y && (px < (nx - x) * (py - y) / (ny - y) + x) {
+                inside = !inside;
+            }
+            p = self.next(p);
+            if p == a {
+                return inside;
+            }
+        }
+    }
+
+    /// Link two polygon vertices with a bridge. If the vertices belong to the same ring, it
+    /// splits the polygon into two; if one belongs to the outer ring and another to a hole, it
+    /// merges them into a single ring.
+    fn split_polygon(&mut self, a: usize, b: usize) -> usize {
+        let a2 = self.nodes.len();
+        let b2 = a2 + 1;
+        let (an, bp) = (self.next(a), self.prev(b));
+        let mut na = self.nodes[a].clone();
+        let mut nb = self.nodes[b].clone();
+        na.steiner = false;
+        nb.steiner = false;
+        self.nodes.push(na);
+        self.nodes.push(nb);
+
+        self.nodes[a].next = b;
+        self.nodes[b].prev = a;
+
+        self.nodes[a2].next = an;
+        self.nodes[an].prev = a2;
+
+        self.nodes[b2].next = a2;
+        self.nodes[a2].prev = b2;
+
+        self.nodes[bp].next = b2;
+        self.nodes[b2].prev = bp;
+
+        b2
+    }
+
+    /// Create a node and optionally link it with the previous one
+    fn insert_node(&mut self, i: usize, x: f64, y: f64, last: Option<usize>) -> usize {
+        let p = self.nodes.len();
+        let mut node = Node { i, x, y, prev: p, next: p, steiner: false };
+        if let Some(last) = last {
+            let last_next = self.next(last);
+            node.next = last_next;
+            node.prev = last;
+            self.nodes[last_next].prev = p;
+            self.nodes[last].next = p;
+        }
+        self.nodes.push(node);
+        p
+    }
+
+    fn remove_node(&mut self, p: usize) {
+        let (prev, next) = (self.prev(p), self.next(p));
+        self.nodes[next].prev = prev;
+        self.nodes[prev].next = next;
+    }
+}
+
+/// Signed area of the ring in `data[start..end]`
+fn signed_area(data: &[f64], start: usize, end: usize, dim: usize) -> f64 {
+    let mut sum = 0.0;
+    let mut j = end - dim;
+    for i in (start..end).step_by(dim) {
+        sum += (data[j] - data[i]) * (data[i + 1] + data[j + 1]);
+        j = i;
+    }
+    sum
+}
+
+/// Check if a point lies within a triangle
+#[allow(clippy::too_many_arguments)]
+fn point_in_triangle(
+    ax: f64,
+    ay: f64,
+    bx: f64,
+    by: f64,
+    cx: f64,
+    cy: f64,
+    px: f64,
+    py: f64,
+) -> bool {
+    (cx - px) * (ay - py) >= (ax - px) * (cy - py)
+        && (ax - px) * (by - py) >= (bx - px) * (ay - py)
+        && (bx - px) * (cy - py) >= (cx - px) * (by - py)
+}
+
+fn sign(n: f64) -> i8 {
+    if n > 0.0 {
+        1
+    } else if n < 0.0 {
+        -1
+    } else {
+        0
+    }
+}
@@ -0,0 +1,273 @@
+use crate::open::FeatureType;
+use alloc::string::String;
+use core::fmt;
+use s2json::{PrimitiveShape, PrimitiveValue, ShapeType, ValueType};
+
+/// The encoded message that was being read when a [`DecodeError`] occurred
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtoMessage {
+    /// The top level tile message
+    Tile,
+    /// A Mapbox (or legacy S2) vector layer
+    MapboxLayer,
+    /// A Mapbox (or legacy S2) vector feature
+    MapboxFeature,
+    /// A value stored in a Mapbox layer's value store
+    MapboxValue,
+    /// An Open Vector layer
+    OpenLayer,
+    /// An Open Vector feature
+    OpenFeature,
+    /// The column cache shared by all Open Vector layers
+    ColumnCache,
+    /// Gridded data
+    GridData,
+    /// Image data
+    ImageData,
+    /// A layer's spatial index
+    SpatialIndex,
+}
+impl fmt::Display for ProtoMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ProtoMessage::Tile => "tile",
+            ProtoMessage::MapboxLayer => "mapbox layer",
+            ProtoMessage::MapboxFeature => "mapbox feature",
+            ProtoMessage::MapboxValue => "mapbox value",
+            ProtoMessage::OpenLayer => "open layer",
+            ProtoMessage::OpenFeature => "open feature",
+            ProtoMessage::ColumnCache => "column cache",
+            ProtoMessage::GridData => "grid data",
+            ProtoMessage::ImageData => "image data",
+            ProtoMessage::SpatialIndex => "spatial index",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An error found while decoding a tile.
+///
+/// Every variant records the message being read, the field tag (if the message uses tags) and
+/// the byte offset into the input buffer where the problem was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The buffer (or message) ended before a value was complete
+    UnexpectedEof {
+        /// the message being read
+        message: ProtoMessage,
+        /// the field being read
+        tag: Option<u64>,
+        /// byte offset of the value that could not be read
+        offset: usize,
+    },
+    /// A field was encoded with a wire type that protobuf does not define, or with one the
+    /// field can not be read as
+    InvalidWireType {
+        /// the message being read
+        message: ProtoMessage,
+        /// the field being read
+        tag: u64,
+        /// the wire type found
+        wire_type: u8,
+        /// byte offset of the field key
+        offset: usize,
+    },
+    /// A field tag that is not part of the message specification
+    UnknownTag {
+        /// the message being read
+        message: ProtoMessage,
+        /// the unknown tag
+        tag: u64,
+        /// byte offset of the field value
+        offset: usize,
+    },
+    /// An enumerated value (feature type, image type, ...) is not recognized
+    InvalidEnum {
+        /// the message being read
+        message: ProtoMessage,
+        /// the field being read
+        tag: Option<u64>,
+        /// the value found
+        value: u64,
+        /// byte offset of the value
+        offset: usize,
+    },
+    /// A reference into a column, key store or value store is out of range
+    OutOfRange {
+        /// the message being read
+        message: ProtoMessage,
+        /// the field being read
+        tag: Option<u64>,
+        /// the index that was referenced
+        index: u64,
+        /// the length of the store being referenced
+        len: usize,
+        /// byte offset of the message holding the reference
+        offset: usize,
+    },
+    /// An unknown geometry command
+    UnknownCommand {
+        /// the message being read
+        message: ProtoMessage,
+        /// the field being read
+        tag: Option<u64>,
+        /// the command found
+        command: u64,
+        /// byte offset of the command
+        offset: usize,
+    },
+    /// The data is structurally valid protobuf but can not describe a valid value
+    Malformed {
+        /// the message being read
+        message: ProtoMessage,
+        /// the field being read
+        tag: Option<u64>,
+        /// byte offset of the value
+        offset: usize,
+        /// what is wrong with the value
+        reason: &'static str,
+    },
+}
+impl DecodeError {
+    /// the message that was being read
+    pub fn message(&self) -> ProtoMessage {
+        match self {
+            DecodeError::UnexpectedEof { message, .. }
+            | DecodeError::InvalidWireType { message, .. }
+            | DecodeError::UnknownTag { message, .. }
+            | DecodeError::InvalidEnum { message, .. }
+            | DecodeError::OutOfRange { message, .. }
+            | DecodeError::UnknownCommand { message, .. }
+            | DecodeError::Malformed { message, .. } => *message,
+        }
+    }
+
+    /// the field tag that was being read, if the message uses tags
+    pub fn tag(&self) -> Option<u64> {
+        match self {
+            DecodeError::InvalidWireType { tag, .. } | DecodeError::UnknownTag { tag, .. } => {
+                Some(*tag)
+            }
+            DecodeError::UnexpectedEof { tag, .. }
+            | DecodeError::InvalidEnum { tag, .. }
+            | DecodeError::OutOfRange { tag, .. }
+            | DecodeError::UnknownCommand { tag, .. }
+            | DecodeError::Malformed { tag, .. } => *tag,
+        }
+    }
+
+    /// the byte offset into the input where the error was found
+    pub fn offset(&self) -> usize {
+        match self {
+            DecodeError::UnexpectedEof { offset, .. }
+            | DecodeError::InvalidWireType { offset, .. }
+            | DecodeError::UnknownTag { offset, .. }
+            | DecodeError::InvalidEnum { offset, .. }
+            | DecodeError::OutOfRange { offset, .. }
+            | DecodeError::UnknownCommand { offset, .. }
+            | DecodeError::Malformed { offset, .. } => *offset,
+        }
+    }
+}
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEof { .. } => write!(f, "unexpected end of data")?,
+            DecodeError::InvalidWireType { wire_type, .. } => {
+                write!(f, "invalid wire type {}", wire_type)?
+            }
+            DecodeError::UnknownTag { .. } => write!(f, "unknown tag")?,
+            DecodeError::InvalidEnum { value, .. } => write!(f, "unknown value: {}", value)?,
+            DecodeError::OutOfRange { index, len, .. } => {
+                write!(f, "index {} out of range for length {}", index, len)?
+            }
+            DecodeError::UnknownCommand { command, .. } => write!(f, "unknown cmd: {}", command)?,
+            DecodeError::Malformed { reason, .. } => write!(f, "{}", reason)?,
+        }
+        write!(f, " in {} message", self.message())?;
+        if let Some(tag) = self.tag() {
+            write!(f, " (tag {})", tag)?;
+        }
+        write!(f, " at byte {}", self.offset())
+    }
+}
+impl core::error::Error for DecodeError {}
+
+/// An error found while encoding a tile.
+///
+/// Every variant names the layer and the index of the feature (in the order it was added to the
+/// layer) that could not be encoded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodeError {
+    /// A property (or M-Value) is not the primitive type the layer's shape expects
+    ShapeMismatch {
+        /// the layer name
+        layer: String,
+        /// the index of the feature in the layer
+        feature: usize,
+        /// the path to the property, e.g. `a.b[2]`. M-Values are prefixed with `m_values.`
+        key: String,
+        /// the primitive the shape expects
+        expected: PrimitiveShape,
+        /// the value found
+        actual: PrimitiveValue,
+    },
+    /// A property (or M-Value) is a primitive, array or object where the shape expects another
+    StructureMismatch {
+        /// the layer name
+        layer: String,
+        /// the index of the feature in the layer
+        feature: usize,
+        /// the path to the property, e.g. `a.b[2]`. M-Values are prefixed with `m_values.`
+        key: String,
+        /// the shape expected
+        expected: ShapeType,
+        /// the value found
+        actual: ValueType,
+    },
+    /// The feature's geometry can not be stored in the target format (3D in a Mapbox tile)
+    UnsupportedGeometry {
+        /// the layer name
+        layer: String,
+        /// the index of the feature in the layer
+        feature: usize,
+        /// the type of the feature
+        feature_type: FeatureType,
+    },
+}
+impl EncodeError {
+    /// the name of the layer holding the feature
+    pub fn layer(&self) -> &str {
+        match self {
+            EncodeError::ShapeMismatch { layer, .. }
+            | EncodeError::StructureMismatch { layer, .. }
+            | EncodeError::UnsupportedGeometry { layer, .. } => layer,
+        }
+    }
+
+    /// the index of the feature in its layer
+    pub fn feature(&self) -> usize {
+        match self {
+            EncodeError::ShapeMismatch { feature, .. }
+            | EncodeError::StructureMismatch { feature, .. }
+            | EncodeError::UnsupportedGeometry { feature, .. } => *feature,
+        }
+    }
+}
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::ShapeMismatch { key, expected, actual, .. } => {
+                write!(f, "property \"{}\" expected {:?} but found {:?}", key, expected, actual)?
+            }
+            EncodeError::StructureMismatch { key, expected, actual, .. } => {
+                write!(f, "property \"{}\" expected {:?} but found {:?}", key, expected, actual)?
+            }
+            EncodeError::UnsupportedGeometry { feature_type, .. } => {
+                write!(f, "unsupported geometry type {:?}", feature_type)?
+            }
+        }
+        write!(f, " in feature {} of layer \"{}\"", self.feature(), self.layer())
+    }
+}
+impl core::error::Error for EncodeError {}
@@ -0,0 +1,315 @@
+use crate::{Point, Point3D, VectorFeatureMethods, VectorGeometry, projection::tile_xy_to_lon_lat};
+use alloc::{string::String, vec, vec::Vec};
+use s2json::{
+    BBOX, BBox3D, BaseGeometry, Feature, Geometry, GeometryType, MValue, Point as GeoPoint,
+    Point3D as GeoPoint3D, VectorBaseGeometry, VectorFeature, VectorGeometry as S2Geometry,
+    VectorGeometryType, VectorPoint,
+};
+use serde::{Deserialize, Serialize};
+
+/// Metadata attached to each feature exported by [`crate::VectorTile::to_feature_collection`]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportMetadata {
+    /// the name of the layer the feature was found in
+    pub layer: String,
+}
+
+/// Convert a decoded feature to an S2JSON vector feature. The coordinates (including z) are
+/// divided by `extent`, so passing the layer's extent places them in the tile's 0->1 space.
+/// This is the inverse of [`crate::base::s2json_to_base`]. The metadata is left empty.
+pub fn feature_to_s2json<M, F: VectorFeatureMethods + ?Sized>(
+    feature: &mut F,
+    extent: usize,
+) -> VectorFeature<M> {
+    let scale = 1.0 / extent as f64;
+    let bbox = feature.bbox().map(|bbox| match bbox {
+        BBOX::BBox(bbox) => bbox.into(),
+        BBOX::BBox3D(bbox) => bbox,
+    });
+    let mut geometry = match feature.load_geometry() {
+        VectorGeometry::VectorPoints(points) => from_points(vector_points(&points, scale), bbox),
+        VectorGeometry::VectorPoints3D(points) => from_points(vector_points(&points, scale), bbox),
+        VectorGeometry::VectorLines(lines) => from_lines(
+            lines.iter().map(|l| (l.offset, vector_points(&l.geometry, scale))).collect(),
+            bbox,
+        ),
+        VectorGeometry::VectorLines3D(lines) => from_lines(
+            lines.iter().map(|l| (l.offset, vector_points(&l.geometry, scale))).collect(),
+            bbox,
+        ),
+        VectorGeometry::VectorPolys(polys) => from_polys(
+            polys
+                .iter()
+                .map(|p| p.iter().map(|l| (l.offset, vector_points(&l.geometry, scale))).collect())
+                .collect(),
+            bbox,
+        ),
+        VectorGeometry::VectorPolys3D(polys) => from_polys(
+            polys
+                .iter()
+                .map(|p| p.iter().map(|l| (l.offset, vector_points(&l.geometry, scale))).collect())
+                .collect(),
+            bbox,
+        ),
+    };
+    if feature.is_polygons() || feature.is_polygons_3d() {
+        let indices = feature.read_indices();
+        if !indices.is_empty() {
+            geometry.set_indices(indices);
+        }
+        let mut tessellation = vec![];
+        if feature.is_polygons() {
+            feature.add_tessellation(&mut tessellation, scale);
+        } else {
+            feature.add_tessellation_3d(&mut tessellation, scale);
+        }
+        if !tessellation.is_empty() {
+            geometry.set_tess(tessellation);
+        }
+    }
+
+    VectorFeature::new_wm(feature.id(), feature.properties(), geometry, None)
+}
+
+/// Convert an S2JSON vector feature whose coordinates are in the 0->1 space of the Web Mercator
+/// tile `zoom`/`x`/`y` to a GeoJSON feature with lon/lat coordinates. Z values are kept as is.
+pub fn s2json_to_geojson<M>(feature: VectorFeature<M>, zoom: u8, x: u32, y: u32) -> Feature<M> {
+    let project = |p: &VectorPoint<MValue>| tile_xy_to_lon_lat(p.x, p.y, zoom, x, y);
+    let geometry = match feature.geometry {
+        S2Geometry::Point(g) => {
+            let (lon, lat) = project(&g.coordinates);
+            match g.coordinates.z {
+                Some(z) => Geometry::Point3D(BaseGeometry {
+                    _type: GeometryType::Point3D,
+                    coordinates: GeoPoint3D(lon, lat, z),
+                    m_values: g.coordinates.m,
+                    bbox: g.bbox,
+                }),
+                None => Geometry::Point(BaseGeometry {
+                    _type: GeometryType::Point,
+                    coordinates: GeoPoint(lon, lat),
+                    m_values: g.coordinates.m,
+                    bbox: g.bbox.map(Into::into),
+                }),
+            }
+        }
+        S2Geometry::MultiPoint(g) if g.is_3d => Geometry::MultiPoint3D(BaseGeometry {
+            _type: GeometryType::MultiPoint3D,
+            coordinates: line_3d(&g.coordinates, project),
+            m_values: line_m_values(&g.coordinates),
+            bbox: g.bbox,
+        }),
+        S2Geometry::MultiPoint(g) => Geometry::MultiPoint(BaseGeometry {
+            _type: GeometryType::MultiPoint,
+            coordinates: line(&g.coordinates, project),
+            m_values: line_m_values(&g.coordinates),
+            bbox: g.bbox.map(Into::into),
+        }),
+        S2Geometry::LineString(g) if g.is_3d => Geometry::LineString3D(BaseGeometry {
+            _type: GeometryType::LineString3D,
+            coordinates: line_3d(&g.coordinates, project),
+            m_values: line_m_values(&g.coordinates),
+            bbox: g.bbox,
+        }),
+        S2Geometry::LineString(g) => Geometry::LineString(BaseGeometry {
+            _type: GeometryType::LineString,
+            coordinates: line(&g.coordinates, project),
+            m_values: line_m_values(&g.coordinates),
+            bbox: g.bbox.map(Into::into),
+        }),
+        S2Geometry::MultiLineString(g) if g.is_3d => Geometry::MultiLineString3D(BaseGeometry {
+            _type: GeometryType::MultiLineString3D,
+            coordinates: g.coordinates.iter().map(|l| line_3d(l, project)).collect(),
+            m_values: lines_m_values(&g.coordinates),
+            bbox: g.bbox,
+        }),
+        S2Geometry::MultiLineString(g) => Geometry::MultiLineString(BaseGeometry {
+            _type: GeometryType::MultiLineString,
+            coordinates: g.coordinates.iter().map(|l| line(l, project)).collect(),
+            m_values: lines_m_values(&g.coordinates),
+            bbox: g.bbox.map(Into::into),
+        }),
+        S2Geometry::Polygon(g) if g.is_3d => Geometry::Polygon3D(BaseGeometry {
+            _type: GeometryType::Polygon3D,
+            coordinates: g.coordinates.iter().map(|l| line_3d(l, project)).collect(),
+            m_values: lines_m_values(&g.coordinates),
+            bbox: g.bbox,
+        }),
+        S2Geometry::Polygon(g) => Geometry::Polygon(BaseGeometry {
+            _type: GeometryType::Polygon,
+            coordinates: g.coordinates.iter().map(|l| line(l, project)).collect(),
+            m_values: lines_m_values(&g.coordinates),
+            bbox: g.bbox.map(Into::into),
+        }),
+        S2Geometry::MultiPolygon(g) if g.is_3d => Geometry::MultiPolygon3D(BaseGeometry {
+            _type: GeometryType::MultiPolygon3D,
+            coordinates: g
+                .coordinates
+                .iter()
+                .map(|p| p.iter().map(|l| line_3d(l, project)).collect())
+                .collect(),
+            m_values: polys_m_values(&g.coordinates),
+            bbox: g.bbox,
+        }),
+        S2Geometry::MultiPolygon(g) => Geometry::MultiPolygon(BaseGeometry {
+            _type: GeometryType::MultiPolygon,
+            coordinates: g
+                .coordinates
+                .iter()
+                .map(|p| p.iter().map(|l| line(l, project)).collect())
+                .collect(),
+            m_values: polys_m_values(&g.coordinates),
+            bbox: g.bbox.map(Into::into),
+        }),
+    };
+
+    Feature::new(feature.id, feature.properties, geometry, feature.metadata)
+}
+
+/// A point found in a decoded feature
+trait TilePoint {
+    /// convert to a vector point, scaling the coordinates
+    fn to_vector_point(&self, scale: f64) -> VectorPoint;
+}
+impl TilePoint for Point {
+    fn to_vector_point(&self, scale: f64) -> VectorPoint {
+        VectorPoint::new_xy(self.x as f64 * scale, self.y as f64 * scale, self.m.clone())
+    }
+}
+impl TilePoint for Point3D {
+    fn to_vector_point(&self, scale: f64) -> VectorPoint {
+        VectorPoint::new_xyz(
+            self.x as f64 * scale,
+            self.y as f64 * scale,
+            self.z as f64 * scale,
+            self.m.clone(),
+        )
+    }
+}
+
+fn vector_points(points: &[impl TilePoint], scale: f64) -> Vec<VectorPoint> {
+    points.iter().map(|p| p.to_vector_point(scale)).collect()
+}
+
+/// Build a point or multipoint geometry
+pub(crate) fn from_points(mut points: Vec<VectorPoint>, bbox: Option<BBox3D>) -> S2Geometry {
+    if points.len() == 1 {
+        S2Geometry::new_point(points.remove(0), bbox)
+    } else {
+        S2Geometry::new_multipoint(points, bbox)
+    }
+}
+
+/// Build a linestring or multilinestring geometry, keeping the offsets if any are set
+pub(crate) fn from_lines(
+    mut lines: Vec<(f64, Vec<VectorPoint>)>,
+    bbox: Option<BBox3D>,
+) -> S2Geometry {
+    let is_3d = lines.iter().any(|(_, l)| l.iter().any(|p| p.z.is_some()));
+    if lines.len() == 1 {
+        let (offset, coordinates) = lines.remove(0);
+        S2Geometry::LineString(VectorBaseGeometry {
+            _type: VectorGeometryType::LineString,
+            is_3d,
+            coordinates,
+            offset: (offset != 0.0).then_some(offset),
+            bbox,
+            ..Default::default()
+        })
+    } else {
+        let offsets: Vec<f64> = lines.iter().map(|(offset, _)| *offset).collect();
+        S2Geometry::MultiLineString(VectorBaseGeometry {
+            _type: VectorGeometryType::MultiLineString,
+            is_3d,
+            coordinates: lines.into_iter().map(|(_, l)| l).collect(),
+            offset: offsets.iter().any(|o| *o != 0.0).then_some(offsets),
+            bbox,
+            ..Default::default()
+        })
+    }
+}
+
+/// Build a polygon or multipolygon geometry, keeping the offsets if any are set
+pub(crate) fn from_polys(
+    mut polys: Vec<Vec<(f64, Vec<VectorPoint>)>>,
+    bbox: Option<BBox3D>,
+) -> S2Geometry {
+    let is_3d = polys.iter().flatten().any(|(_, l)| l.iter().any(|p| p.z.is_some()));
+    let offsets: Vec<Vec<f64>> =
+        polys.iter().map(|p| p.iter().map(|(offset, _)| *offset).collect()).collect();
+    let has_offsets = offsets.iter().flatten().any(|o| *o != 0.0);
+    if polys.len() == 1 {
+        S2Geometry::Polygon(VectorBaseGeometry {
+            _type: VectorGeometryType::Polygon,
+            is_3d,
+            coordinates: polys.remove(0).into_iter().map(|(_, l)| l).collect(),
+            offset: has_offsets.then(|| offsets[0].clone()),
+            bbox,
+            ..Default::default()
+        })
+    } else {
+        S2Geometry::MultiPolygon(VectorBaseGeometry {
+            _type: VectorGeometryType::MultiPolygon,
+            is_3d,
+            coordinates: polys
+                .into_iter()
+                .map(|p| p.into_iter().map(|(_, l)| l).collect())
+                .collect(),
+            offset: has_offsets.then_some(offsets),
+            bbox,
+            ..Default::default()
+        })
+    }
+}
+
+fn line(points: &[VectorPoint], project: impl Fn(&VectorPoint) -> (f64, f64)) -> Vec<GeoPoint> {
+    points
+        .iter()
+        .map(|p| {
+            let (lon, lat) = project(p);
+            GeoPoint(lon, lat)
+        })
+        .collect()
+}
+
+fn line_3d(
+    points: &[VectorPoint],
+    project: impl Fn(&VectorPoint) -> (f64, f64),
+) -> Vec<GeoPoint3D> {
+    points
+        .iter()
+        .map(|p| {
+            let (lon, lat) = project(p);
+            GeoPoint3D(lon, lat, p.z.unwrap_or_default())
+        })
+        .collect()
+}
+
+/// The M-Values of a line, if any of its points have one
+fn line_m_values(points: &[VectorPoint]) -> Option<Vec<MValue>> {
+    points
+        .iter()
+        .any(|p| p.m.is_some())
+        .then(|| points.iter().map(|p| p.m.clone().unwrap_or_default()).collect())
+}
+
+/// The M-Values of a set of lines, if any of their points have one
+fn lines_m_values(lines: &[Vec<VectorPoint>]) -> Option<Vec<Vec<MValue>>> {
+    lines.iter().any(|l| line_m_values(l).is_some()).then(|| {
+        lines.iter().map(|l| l.iter().map(|p| p.m.clone().unwrap_or_default()).collect()).collect()
+    })
+}
+
+/// The M-Values of a set of polygons, if any of their points have one
+fn polys_m_values(polys: &[Vec<Vec<VectorPoint>>]) -> Option<Vec<Vec<Vec<MValue>>>> {
+    polys.iter().any(|p| lines_m_values(p).is_some()).then(|| {
+        polys
+            .iter()
+            .map(|p| {
+                p.iter()
+                    .map(|l| l.iter().map(|p| p.m.clone().unwrap_or_default()).collect())
+                    .collect()
+            })
+            .collect()
+    })
+}
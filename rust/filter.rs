@@ -0,0 +1,113 @@
+use crate::{VectorFeatureMethods, open::FeatureType};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::cmp::Ordering;
+use s2json::{PrimitiveValue, Properties, ValueType};
+
+/// # Filter
+///
+/// ## Description
+/// A filter expression to select features by their properties and geometry type. Keys are the
+/// top level keys of the feature's properties, only primitive values are compared.
+///
+/// Numbers are compared by value regardless of their type, strings lexicographically and bools
+/// only for (in)equality. Comparing different types never matches.
+///
+/// ## Usage
+/// ```rust
+/// use open_vector_tile::{filter::Filter, open::FeatureType};
+/// use s2json::Properties;
+///
+/// let filter = Filter::All(vec![
+///     Filter::Type(FeatureType::Polygons),
+///     Filter::Eq("class".into(), "park".into()),
+///     Filter::Gte("area".into(), 1_000_u64.into()),
+/// ]);
+/// let properties =
+///     Properties::from([("class".into(), "park".into()), ("area".into(), 2_500.5.into())]);
+/// assert!(filter.matches(&properties, FeatureType::Polygons));
+/// assert!(!filter.matches(&properties, FeatureType::Lines));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// The property equals the value
+    Eq(String, PrimitiveValue),
+    /// The property does not equal the value, or is missing
+    NotEq(String, PrimitiveValue),
+    /// The property is less than the value
+    Lt(String, PrimitiveValue),
+    /// The property is less than or equal to the value
+    Lte(String, PrimitiveValue),
+    /// The property is greater than the value
+    Gt(String, PrimitiveValue),
+    /// The property is greater than or equal to the value
+    Gte(String, PrimitiveValue),
+    /// The property equals one of the values
+    In(String, Vec<PrimitiveValue>),
+    /// The property exists
+    Has(String),
+    /// Every filter matches
+    All(Vec<Filter>),
+    /// At least one filter matches
+    Any(Vec<Filter>),
+    /// The filter does not match
+    Not(Box<Filter>),
+    /// The feature is of the given type. 2D and 3D types are distinct
+    Type(FeatureType),
+}
+impl Filter {
+    /// Check if a feature with the given properties and type matches the filter
+    pub fn matches(&self, properties: &Properties, r#type: FeatureType) -> bool {
+        self.eval(&mut |key| properties.get(key).cloned(), r#type)
+    }
+
+    /// Check if a feature matches the filter
+    pub fn matches_feature<F: VectorFeatureMethods + ?Sized>(&self, feature: &F) -> bool {
+        self.matches(&feature.properties(), feature.get_type())
+    }
+
+    /// Evaluate the filter, looking up each property with `get`
+    pub(crate) fn eval(
+        &self,
+        get: &mut dyn FnMut(&str) -> Option<ValueType>,
+        r#type: FeatureType,
+    ) -> bool {
+        let mut cmp = |key: &str, value: &PrimitiveValue| match get(key) {
+            Some(ValueType::Primitive(prop)) => compare(&prop, value),
+            _ => None,
+        };
+        match self {
+            Filter::Eq(key, value) => cmp(key, value) == Some(Ordering::Equal),
+            Filter::NotEq(key, value) => cmp(key, value) != Some(Ordering::Equal),
+            Filter::Lt(key, value) => cmp(key, value) == Some(Ordering::Less),
+            Filter::Lte(key, value) => {
+                matches!(cmp(key, value), Some(Ordering::Less | Ordering::Equal))
+            }
+            Filter::Gt(key, value) => cmp(key, value) == Some(Ordering::Greater),
+            Filter::Gte(key, value) => {
+                matches!(cmp(key, value), Some(Ordering::Greater | Ordering::Equal))
+            }
+            Filter::In(key, values) => match get(key) {
+                Some(ValueType::Primitive(prop)) => {
+                    values.iter().any(|v| compare(&prop, v) == Some(Ordering::Equal))
+                }
+                _ => false,
+            },
+            Filter::Has(key) => get(key).is_some(),
+            Filter::All(filters) => filters.iter().all(|f| f.eval(get, r#type)),
+            Filter::Any(filters) => filters.iter().any(|f| f.eval(get, r#type)),
+            Filter::Not(filter) => !filter.eval(get, r#type),
+            Filter::Type(t) => *t == r#type,
+        }
+    }
+}
+
+/// Compare two primitives, None if they are of different types
+fn compare(a: &PrimitiveValue, b: &PrimitiveValue) -> Option<Ordering> {
+    match (a, b) {
+        (PrimitiveValue::String(a), PrimitiveValue::String(b)) => Some(a.cmp(b)),
+        (PrimitiveValue::Bool(a), PrimitiveValue::Bool(b)) => (a == b).then_some(Ordering::Equal),
+        (PrimitiveValue::Null, PrimitiveValue::Null) => Some(Ordering::Equal),
+        (a, b) if a.is_number() && b.is_number() => a.to_f64()?.partial_cmp(&b.to_f64()?),
+        _ => None,
+    }
+}
@@ -17,11 +17,14 @@
 //! ### Reading
 //!
 //! ```rust,ignore
-//! use open_vector_tile::{VectorTile, VectorLayerMethods};
+//! use open_vector_tile::{VectorTile, VectorLayerFeatures, VectorLayerMethods};
 //!
 //! let data: Vec<u8> = vec![];
 //! let mut tile = VectorTile::new(data, None);
 //!
+//! // If the data is untrusted, `try_new` returns a `DecodeError` instead of panicking
+//! // let mut tile = VectorTile::try_new(data, None)?;
+//!
 //! // VECTOR API
 //!
 //! let landuse = tile.layer("landuse").unwrap();
@@ -39,17 +42,38 @@
 //! // and you're loading the data for a renderer, you can grab the pre-tessellated geometry
 //! let (geometry_flat, indices) = firstFeature.load_geometry_flat();
 //!
+//! // or iterate over the features of a layer, or of the whole tile as (layer name, feature) pairs
+//! for feature in landuse.features() {}
+//! for (layer_name, feature) in tile.iter_features() {}
+//!
+//! // `get_feature` and `features` only need `&self` and borrow the features from the layer,
+//! // so with the `sync` feature enabled a decoded tile can be shared across threads
+//! let feature = landuse.get_feature(0).unwrap();
+//!
+//! // To read a tile without copying its features, strings or images (e.g. from a memory-mapped
+//! // archive), borrow the bytes instead
+//! let borrowed_tile = open_vector_tile::borrowed::BorrowedVectorTile::new(&data, None);
+//!
+//! // To debug a tile in standard GIS tools, export a feature to S2JSON (tile space coordinates)
+//! // or the whole tile to a GeoJSON FeatureCollection (lon/lat coordinates)
+//! let vector_feature = firstFeature.to_s2json(landuse.extent());
+//! let collection = tile.to_feature_collection(12, 1143, 1497);
+//!
+//! // To edit a tile, convert it to the base types and write it back out again
+//! let mut base_tile = BaseVectorTile::from(&mut tile);
+//! let open_tile_bytes = write_tile(Some(&mut base_tile), None, None);
+//!
 //! // IMAGE API
 //!
-//! let satellite = tile.images.get("satellite").unwrap();
+//! let satellite = tile.images.get_mut("satellite").unwrap();
 //! // grab the image data
-//! let data = &satellite.image;
+//! let data = satellite.image();
 //!
 //! // GRID API
 //!
-//! let elevation = tile.grids.get("elevation").unwrap();
+//! let elevation = tile.grids.get_mut("elevation").unwrap();
 //! // grab the grid data
-//! let data = &elevation.data;
+//! let data = elevation.data();
 //! ```
 //!
 //! ### Writing
@@ -123,6 +147,8 @@
 //!
 //! // Lastly build the tile:
 //! let open_tile_bytes = write_tile(Some(&mut tile), None, None);
+//! // `try_write_tile` returns an `EncodeError` instead of panicking if a feature's properties do
+//! // not match the layer's shape
 //!
 //!
 //! // WRITE IMAGE DATA //-//-//-//-//-//-//-//-//-//-//
@@ -148,22 +174,70 @@
 
 extern crate alloc;
 extern crate pbf;
+#[cfg(any(
+    feature = "sync",
+    feature = "brotli",
+    feature = "mbtiles",
+    feature = "pmtiles",
+    feature = "s2tiles"
+))]
+extern crate std;
 
 /// Base Vector containers for Tiles, Layers, and Features
 pub mod base;
+/// Tiles read from borrowed bytes without copying their features, strings or images
+pub mod borrowed;
+/// Detect, decompress and compress gzip, zlib, zstd and brotli compressed tiles
+pub mod compression;
+/// Polygon triangulation
+pub mod earcut;
+/// Errors that may occur while decoding or encoding tiles
+pub mod error;
+/// Export decoded features to S2JSON and GeoJSON for debugging in standard GIS tools
+pub mod export;
+/// Filter expressions to select features by their properties and geometry type
+pub mod filter;
 /// Geometry utilities
 pub mod geometry;
 /// Mapbox specification for Layers and Features
 pub mod mapbox;
+/// MBTiles archives of tiles
+#[cfg(feature = "mbtiles")]
+pub mod mbtiles;
+/// MapLibre Tile (MLT) specification for Layers and Features
+pub mod mlt;
 /// Open specification for Layers and Features
 pub mod open;
+/// PMTiles v3 archives of tiles
+#[cfg(feature = "pmtiles")]
+pub mod pmtiles;
+/// Web Mercator and S2 projections to and from tile coordinates
+pub mod projection;
+/// Spatial queries to find the features at a point or in a box
+pub mod query;
+/// Protobuf reading over borrowed bytes
+mod reader;
+/// A simple archive of S2 tiles covering all six faces
+#[cfg(feature = "s2tiles")]
+pub mod s2tiles;
+/// Handles to the data shared by a tile's layers and features
+pub mod shared;
+/// Packed Hilbert R-tree over the bounds of a layer's features
+pub mod spatial_index;
+/// Web Mercator and S2 tile IDs with parent, child and neighbor navigation
+pub mod tile_id;
+/// Generate tiles from S2JSON and GeoJSON features
+pub mod tiler;
 /// Utilities/functions that are useful across all specifications
 pub mod util;
+/// Validate tiles against the Open Vector Tile specification
+pub mod validate;
 /// The vector feature struct that covers both "open" and "mapbox" specifications
 pub mod vector_feature;
 /// The vector tile struct that covers both "open" and "mapbox" specifications
 pub mod vector_tile;
 
+pub use error::*;
 pub use geometry::*;
 pub use open::*;
 pub use util::*;
@@ -3,11 +3,13 @@ use crate::{
     VectorLinesWithOffset, VectorPoints, VectorPoints3D,
     base::{BaseVectorFeature, TessellationWrapper},
     command_encode,
+    error::{DecodeError, ProtoMessage},
     open::FeatureType as OpenFeatureType,
+    reader::{BYTES, Reader, Result, VARINT},
+    shared::{Lazy, Shared},
     zigzag,
 };
-use alloc::{collections::BTreeMap, rc::Rc, string::String, vec, vec::Vec};
-use core::cell::RefCell;
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
 use pbf::{BitCast, ProtoRead, Protobuf};
 use s2json::{BBOX, MapboxProperties, PrimitiveValue, Properties};
 
@@ -28,23 +30,23 @@ pub struct MapboxVectorFeature {
     /// the open spec
     pub is_s2: bool,
     indices_index: Option<usize>,
-    indices: Option<Vec<u32>>,
-    geometry_index: usize,
-    geometry: Option<VectorGeometry>,
+    indices: Lazy<Vec<u32>>,
+    geometry_index: Option<usize>,
+    geometry: Lazy<VectorGeometry>,
     tessellation_index: Option<usize>,
-    keys: Rc<RefCell<Vec<String>>>,
-    values: Rc<RefCell<Vec<PrimitiveValue>>>,
-    pbf: Rc<RefCell<Protobuf>>,
+    keys: Shared<Vec<String>>,
+    values: Shared<Vec<PrimitiveValue>>,
+    data: Shared<[u8]>,
 }
 impl MapboxVectorFeature {
     /// Create a new MapboxVectorFeature
     pub fn new(
-        pbf: Rc<RefCell<Protobuf>>,
+        data: Shared<[u8]>,
         is_s2: bool,
         extent: usize,
         version: u16,
-        keys: Rc<RefCell<Vec<String>>>,
-        values: Rc<RefCell<Vec<PrimitiveValue>>>,
+        keys: Shared<Vec<String>>,
+        values: Shared<Vec<PrimitiveValue>>,
     ) -> MapboxVectorFeature {
         MapboxVectorFeature {
             id: None,
@@ -55,17 +57,34 @@ impl MapboxVectorFeature {
             is_s2,
             // tmp pbf until after reading in attributes
             indices_index: None,
-            indices: None,
-            geometry_index: 0,
-            geometry: None,
+            indices: Lazy::new(),
+            geometry_index: None,
+            geometry: Lazy::new(),
             tessellation_index: None,
             keys,
             values,
-            pbf,
+            data,
         }
     }
+
+    /// Set the fields read from the feature message. Geometry, indices and tessellation only
+    /// store their position, so they are decoded from the tile data when they are loaded
+    pub(crate) fn set_fields(&mut self, fields: FeatureFields) {
+        self.id = fields.id;
+        self.r#type = fields.r#type;
+        if let Some(pos) = fields.tags {
+            for (key, value) in read_tags(&self.data, pos) {
+                if let (Some(key), Some(value)) = (self.keys.get(key), self.values.get(value)) {
+                    self.properties.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        self.geometry_index = fields.geometry;
+        self.indices_index = fields.indices;
+        self.tessellation_index = fields.tessellation;
+    }
 }
-impl VectorFeatureMethods for MapboxVectorFeature {
+impl VectorFeatureMethods for &MapboxVectorFeature {
     /// get the feature id
     fn id(&self) -> Option<u64> {
         self.id
@@ -133,17 +152,7 @@ impl VectorFeatureMethods for MapboxVectorFeature {
 
     /// regardless of the type, we return a flattend point array
     fn load_points(&mut self) -> VectorPoints {
-        match self.load_geometry() {
-            VectorGeometry::VectorPoints(p) => p,
-            VectorGeometry::VectorLines(lines) => {
-                lines.iter().flat_map(|p| p.geometry.clone()).collect()
-            }
-            VectorGeometry::VectorPolys(polys) => polys
-                .iter()
-                .flat_map(|p| p.iter().flat_map(|p| p.geometry[..p.geometry.len() - 1].to_vec()))
-                .collect(),
-            _ => panic!("unexpected geometry type"),
-        }
+        geometry_points(self.load_geometry())
     }
 
     fn load_points_3d(&mut self) -> VectorPoints3D {
@@ -152,11 +161,7 @@ impl VectorFeatureMethods for MapboxVectorFeature {
 
     /// an array of lines. The offsets will be set to 0
     fn load_lines(&mut self) -> VectorLinesWithOffset {
-        match self.load_geometry() {
-            VectorGeometry::VectorLines(lines) => lines,
-            VectorGeometry::VectorPolys(polys) => polys.iter().flat_map(|p| p.clone()).collect(),
-            _ => panic!("unexpected geometry type"),
-        }
+        geometry_lines(self.load_geometry())
     }
 
     /// an array of 3D lines. The offsets will be set to 0
@@ -166,10 +171,7 @@ impl VectorFeatureMethods for MapboxVectorFeature {
 
     /// an array of polys
     fn load_polys(&mut self) -> Vec<VectorLinesWithOffset> {
-        match self.load_geometry() {
-            VectorGeometry::VectorPolys(polys) => polys,
-            _ => panic!("unexpected geometry type"),
-        }
+        geometry_polys(self.load_geometry())
     }
 
     /// an array of 3D polys
@@ -182,20 +184,7 @@ impl VectorFeatureMethods for MapboxVectorFeature {
         // build a multiplier
         let multiplier: f64 = 1.0 / self.extent as f64;
         // grab the geometry, flatten it, and mutate to an f64
-        let mut geometry: Vec<f64> = match self.load_geometry() {
-            VectorGeometry::VectorPolys(polys) => polys
-                .iter()
-                .flat_map(|p| {
-                    p.iter().flat_map(|p| {
-                        p.geometry
-                            .clone()
-                            .into_iter()
-                            .flat_map(|p| vec![p.x as f64 * multiplier, p.y as f64 * multiplier])
-                    })
-                })
-                .collect(),
-            _ => panic!("unexpected geometry type"),
-        };
+        let mut geometry = flatten_polys(self.load_geometry(), multiplier);
         // if a poly, check if we should load indices
         let indices = self.read_indices();
         // if a poly, check if we should load tessellation
@@ -206,181 +195,521 @@ impl VectorFeatureMethods for MapboxVectorFeature {
 
     /// load the geometry
     fn load_geometry(&mut self) -> VectorGeometry {
-        if let Some(geometry) = &self.geometry {
-            return geometry.clone();
+        self.geometry
+            .get_or_init(|| {
+                decode_geometry(&self.data, self.geometry_index, &self.r#type, self.is_s2)
+            })
+            .clone()
+    }
+
+    /// load the indices
+    fn read_indices(&mut self) -> Vec<u32> {
+        let Some(indices_index) = self.indices_index else {
+            return vec![];
+        };
+        self.indices.get_or_init(|| decode_indices(&self.data, indices_index)).clone()
+    }
+
+    /// Add tessellation data to the geometry
+    fn add_tessellation(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
+        if let Some(tessellation_index) = self.tessellation_index {
+            decode_tessellation(&self.data, tessellation_index, geometry, multiplier);
         }
+    }
 
-        let mut pbf = self.pbf.borrow_mut();
-        pbf.set_pos(self.geometry_index);
+    /// Add 3D tessellation data to the geometry
+    fn add_tessellation_3d(&mut self, _geometry: &mut Vec<f64>, _multiplier: f64) {
+        panic!("unexpected geometry type")
+    }
+}
+// features are decoded and cached through `&self`, so the owned feature borrows itself
+impl VectorFeatureMethods for MapboxVectorFeature {
+    fn id(&self) -> Option<u64> {
+        <&Self>::id(&self)
+    }
 
-        let end: usize = pbf.read_varint::<usize>() + pbf.get_pos();
-        let mut cmd: usize = 1;
-        let mut length: isize = 0;
-        let mut x: i32 = 0;
-        let mut y: i32 = 0;
+    fn version(&self) -> u16 {
+        <&Self>::version(&self)
+    }
 
-        let mut points: VectorPoints = vec![];
-        let mut lines: VectorLinesWithOffset = vec![];
-        let mut polys: Vec<VectorLinesWithOffset> = vec![];
+    fn properties(&self) -> Properties {
+        <&Self>::properties(&self)
+    }
 
-        while pbf.get_pos() < end {
-            if length <= 0 {
-                let cmd_len: usize = pbf.read_varint();
-                cmd = cmd_len & 0x7;
-                length = (cmd_len >> 3) as isize;
-            }
+    fn extent(&self) -> usize {
+        <&Self>::extent(&self)
+    }
 
-            length -= 1;
+    fn get_type(&self) -> OpenFeatureType {
+        <&Self>::get_type(&self)
+    }
+
+    fn bbox(&self) -> Option<BBOX> {
+        <&Self>::bbox(&self)
+    }
+
+    fn has_m_values(&self) -> bool {
+        <&Self>::has_m_values(&self)
+    }
+
+    fn is_points(&self) -> bool {
+        <&Self>::is_points(&self)
+    }
 
-            if cmd == 1 || cmd == 2 {
-                x += pbf.read_s_varint::<i32>();
-                y += pbf.read_s_varint::<i32>();
+    fn is_lines(&self) -> bool {
+        <&Self>::is_lines(&self)
+    }
 
-                if cmd == 1 {
-                    // moveTo
-                    if !points.is_empty() && self.r#type != FeatureType::Point {
-                        lines.push((&points[..]).into());
-                        points = vec![];
+    fn is_polygons(&self) -> bool {
+        <&Self>::is_polygons(&self)
+    }
+
+    fn is_points_3d(&self) -> bool {
+        <&Self>::is_points_3d(&self)
+    }
+
+    fn is_lines_3d(&self) -> bool {
+        <&Self>::is_lines_3d(&self)
+    }
+
+    fn is_polygons_3d(&self) -> bool {
+        <&Self>::is_polygons_3d(&self)
+    }
+
+    fn load_points(&mut self) -> VectorPoints {
+        <&Self>::load_points(&mut &*self)
+    }
+
+    fn load_points_3d(&mut self) -> VectorPoints3D {
+        <&Self>::load_points_3d(&mut &*self)
+    }
+
+    fn load_lines(&mut self) -> VectorLinesWithOffset {
+        <&Self>::load_lines(&mut &*self)
+    }
+
+    fn load_lines_3d(&mut self) -> VectorLines3DWithOffset {
+        <&Self>::load_lines_3d(&mut &*self)
+    }
+
+    fn load_polys(&mut self) -> Vec<VectorLinesWithOffset> {
+        <&Self>::load_polys(&mut &*self)
+    }
+
+    fn load_polys_3d(&mut self) -> Vec<VectorLines3DWithOffset> {
+        <&Self>::load_polys_3d(&mut &*self)
+    }
+
+    fn load_geometry_flat(&mut self) -> (Vec<f64>, Vec<u32>) {
+        <&Self>::load_geometry_flat(&mut &*self)
+    }
+
+    fn load_geometry(&mut self) -> VectorGeometry {
+        <&Self>::load_geometry(&mut &*self)
+    }
+
+    fn read_indices(&mut self) -> Vec<u32> {
+        <&Self>::read_indices(&mut &*self)
+    }
+
+    fn add_tessellation(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
+        <&Self>::add_tessellation(&mut &*self, geometry, multiplier)
+    }
+
+    fn add_tessellation_3d(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
+        <&Self>::add_tessellation_3d(&mut &*self, geometry, multiplier)
+    }
+}
+impl ProtoRead for MapboxVectorFeature {
+    fn read(&mut self, tag: u64, pb: &mut Protobuf) {
+        match feature_field(tag, self.is_s2) {
+            1 => self.id = Some(pb.read_varint::<u64>()),
+            2 => {
+                let tags = pb.read_packed::<usize>();
+                for [key, value] in tags.as_chunks::<2>().0 {
+                    if let (Some(key), Some(value)) = (self.keys.get(*key), self.values.get(*value))
+                    {
+                        self.properties.insert(key.clone(), value.clone());
                     }
                 }
-                points.push(Point::new(x, y));
-            } else if cmd == 4 {
-                // close poly
-                if !points.is_empty() {
-                    lines.push((&points[..]).into());
+            }
+            3 => self.r#type = pb.read_varint::<FeatureType>(),
+            4 => self.geometry_index = Some(pb.get_pos()),
+            5 => self.indices_index = Some(pb.get_pos()),
+            6 => self.tessellation_index = Some(pb.get_pos()),
+            _ => {}
+        }
+    }
+}
+
+/// The field of a feature message a tag refers to. The S2 spec moves the id to field 15 and
+/// shifts every other field down by one
+fn feature_field(tag: u64, is_s2: bool) -> u64 {
+    match (is_s2, tag) {
+        (true, 15) => 1,
+        (true, 1..=5) => tag + 1,
+        (true, _) => 0,
+        (false, _) => tag,
+    }
+}
+
+/// The fields of a Mapbox feature message, read by [`read_feature_fields`]. The tags, geometry,
+/// indices and tessellation are the positions of their fields in the tile data
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FeatureFields {
+    pub id: Option<u64>,
+    pub r#type: FeatureType,
+    pub tags: Option<usize>,
+    pub geometry: Option<usize>,
+    pub indices: Option<usize>,
+    pub tessellation: Option<usize>,
+}
+
+/// Read a feature message given the number of keys and values in its layer. The properties,
+/// geometry, indices and tessellation are checked so they can be decoded later without failing
+pub(crate) fn read_feature_fields(
+    mut feature: Reader,
+    is_s2: bool,
+    keys: usize,
+    values: usize,
+) -> Result<FeatureFields> {
+    let mut fields = FeatureFields::default();
+    while !feature.done() {
+        let (tag, wire_type) = feature.field()?;
+        let start = feature.pos();
+        let field = feature_field(tag, is_s2);
+        match field {
+            1 | 3 => feature.expect(wire_type, VARINT)?,
+            2 | 4..=6 => feature.expect(wire_type, BYTES)?,
+            _ => return Err(feature.unknown_tag(start)),
+        }
+        match field {
+            1 => fields.id = Some(feature.varint()?),
+            2 => {
+                fields.tags = Some(start);
+                let mut tags = feature.packed()?;
+                while !tags.done() {
+                    for len in [keys, values] {
+                        let pos = tags.pos();
+                        let index = tags.varint()?;
+                        if index >= len as u64 {
+                            return Err(tags.out_of_range(index, len, pos));
+                        }
+                    }
                 }
-                polys.push(lines);
-                lines = vec![];
-                points = vec![];
-            } else if cmd == 7 {
-                // close path
-                if !points.is_empty() {
-                    points.push(points[0].clone());
-                    lines.push((&points[..]).into());
-                    points = vec![];
+            }
+            3 => {
+                let value = feature.varint()?;
+                if !(1..=4).contains(&value) {
+                    return Err(feature.invalid_enum(value, start));
                 }
-            } else {
-                panic!("unknown cmd: {}", cmd);
+                fields.r#type = FeatureType::from_u64(value);
+            }
+            4 => {
+                fields.geometry = Some(start);
+                check_geometry(feature.packed()?)?;
+            }
+            5 => {
+                fields.indices = Some(start);
+                check_indices(feature.packed()?)?;
+            }
+            _ => {
+                fields.tessellation = Some(start);
+                check_points(feature.packed()?)?;
             }
         }
+    }
+    if fields.geometry.is_none() {
+        return Err(feature.malformed(feature.pos(), "feature is missing its geometry"));
+    }
 
-        let geometry = if self.r#type == FeatureType::Point {
-            VectorGeometry::VectorPoints(points)
-        } else {
-            if !points.is_empty() {
-                lines.push(VectorLineWithOffset::new(0.0, points.clone()));
-            }
-            if self.r#type == FeatureType::Line {
-                VectorGeometry::VectorLines(lines)
-            } else if (self.r#type == FeatureType::MultiPolygon
-                || self.r#type == FeatureType::Polygon)
-                && !self.is_s2
-            {
-                VectorGeometry::VectorPolys(classify_rings(&lines))
-            } else {
-                VectorGeometry::VectorPolys(polys)
+    Ok(fields)
+}
+
+/// Check a command encoded geometry: every command is known and no coordinate overflows
+fn check_geometry(mut geometry: Reader) -> Result<()> {
+    let (mut x, mut y): (i32, i32) = (0, 0);
+    let mut cmd: u64 = 1;
+    let mut length: i64 = 0;
+
+    while !geometry.done() {
+        if length <= 0 {
+            let start = geometry.pos();
+            let cmd_len = geometry.varint()?;
+            cmd = cmd_len & 0x7;
+            length = (cmd_len >> 3) as i64;
+            if !matches!(cmd, 1 | 2 | 4 | 7) {
+                return Err(DecodeError::UnknownCommand {
+                    message: ProtoMessage::MapboxFeature,
+                    tag: Some(geometry.tag()),
+                    command: cmd,
+                    offset: start,
+                });
             }
-        };
+        }
+        length -= 1;
+        if cmd == 1 || cmd == 2 {
+            let start = geometry.pos();
+            let dx = geometry.s_varint_i32()?;
+            let dy = geometry.s_varint_i32()?;
+            x = x
+                .checked_add(dx)
+                .ok_or_else(|| geometry.malformed(start, "coordinate overflow"))?;
+            y = y
+                .checked_add(dy)
+                .ok_or_else(|| geometry.malformed(start, "coordinate overflow"))?;
+        } else {
+            // the spec asks for close commands with a count of 1, but the decoder closes each
+            // ring once whatever the count, so larger counts are harmless
+            length = 0;
+        }
+    }
 
-        self.geometry = Some(geometry.clone());
-        geometry
+    Ok(())
+}
+
+/// Check delta encoded indices do not overflow
+fn check_indices(mut indices: Reader) -> Result<()> {
+    let mut curr: i32 = 0;
+    while !indices.done() {
+        let start = indices.pos();
+        curr = curr
+            .checked_add(indices.s_varint_i32()?)
+            .ok_or_else(|| indices.malformed(start, "index overflows 32 bits"))?;
     }
+    Ok(())
+}
 
-    /// load the indices
-    fn read_indices(&mut self) -> Vec<u32> {
-        if let Some(indices) = &self.indices {
-            return indices.clone();
-        } else if self.indices_index.is_none() {
-            return vec![];
-        }
+/// Check a delta encoded run of points does not overflow
+fn check_points(mut points: Reader) -> Result<()> {
+    let (mut x, mut y): (i32, i32) = (0, 0);
+    while !points.done() {
+        let start = points.pos();
+        let dx = points.s_varint_i32()?;
+        let dy = points.s_varint_i32()?;
+        x = x.checked_add(dx).ok_or_else(|| points.malformed(start, "coordinate overflow"))?;
+        y = y.checked_add(dy).ok_or_else(|| points.malformed(start, "coordinate overflow"))?;
+    }
+    Ok(())
+}
+
+/// The (key, value) index pairs of the properties found at `pos` in the tile data
+pub(crate) fn read_tags(data: &[u8], pos: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let mut tags = Reader::at(data, pos, 2, ProtoMessage::MapboxFeature).ok();
+    core::iter::from_fn(move || {
+        let tags = tags.as_mut()?;
+        Some((tags.usize().ok()?, tags.usize().ok()?))
+    })
+}
 
-        let mut pbf = self.pbf.borrow_mut();
-        pbf.set_pos(self.indices_index.unwrap());
+/// A reader for the length prefixed field found at `pos` in the tile data, empty if there is none
+fn field_at(data: &[u8], pos: Option<usize>) -> Reader<'_> {
+    pos.and_then(|pos| Reader::at(data, pos, 0, ProtoMessage::MapboxFeature).ok())
+        .unwrap_or_else(|| Reader::new(data, 0, 0, ProtoMessage::MapboxFeature))
+}
 
-        let mut curr: i32 = 0;
-        let end = pbf.read_varint::<usize>() + pbf.get_pos();
-        // build indices
-        let mut indices: Vec<u32> = vec![];
-        while pbf.get_pos() < end {
-            curr += pbf.read_s_varint::<i32>();
-            indices.push(curr as u32);
+/// Decode the command encoded geometry found at `pos`. Decoding stops at the first unknown
+/// command or incomplete coordinate
+pub(crate) fn decode_geometry(
+    data: &[u8],
+    pos: Option<usize>,
+    r#type: &FeatureType,
+    is_s2: bool,
+) -> VectorGeometry {
+    let mut pbf = field_at(data, pos);
+    let mut cmd: u64 = 1;
+    let mut length: i64 = 0;
+    let mut x: i32 = 0;
+    let mut y: i32 = 0;
+
+    let mut points: VectorPoints = vec![];
+    let mut lines: VectorLinesWithOffset = vec![];
+    let mut polys: Vec<VectorLinesWithOffset> = vec![];
+
+    while !pbf.done() {
+        if length <= 0 {
+            let Ok(cmd_len) = pbf.varint() else { break };
+            cmd = cmd_len & 0x7;
+            length = (cmd_len >> 3) as i64;
         }
 
-        self.indices = Some(indices.clone());
-        indices
-    }
+        length -= 1;
 
-    /// Add tessellation data to the geometry
-    fn add_tessellation(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
-        if self.tessellation_index.is_none() {
-            return;
-        }
+        if cmd == 1 || cmd == 2 {
+            let (Ok(dx), Ok(dy)) = (pbf.s_varint(), pbf.s_varint()) else { break };
+            x = x.wrapping_add(dx as i32);
+            y = y.wrapping_add(dy as i32);
 
-        let mut pbf = self.pbf.borrow_mut();
-        pbf.set_pos(self.tessellation_index.unwrap());
-
-        let end = pbf.read_varint::<usize>() + pbf.get_pos();
-        let mut x = 0;
-        let mut y = 0;
-        while pbf.get_pos() < end {
-            x += pbf.read_s_varint::<i32>();
-            y += pbf.read_s_varint::<i32>();
-            geometry.push(x as f64 * multiplier);
-            geometry.push(y as f64 * multiplier);
+            if cmd == 1 {
+                // moveTo
+                if !points.is_empty() && *r#type != FeatureType::Point {
+                    lines.push((&points[..]).into());
+                    points = vec![];
+                }
+            }
+            points.push(Point::new(x, y));
+        } else if cmd == 4 {
+            // close poly, once whatever the count
+            length = 0;
+            if !points.is_empty() {
+                lines.push((&points[..]).into());
+            }
+            polys.push(lines);
+            lines = vec![];
+            points = vec![];
+        } else if cmd == 7 {
+            // close path, once whatever the count
+            length = 0;
+            if !points.is_empty() {
+                points.push(points[0].clone());
+                lines.push((&points[..]).into());
+                points = vec![];
+            }
+        } else {
+            break;
         }
     }
 
-    /// Add 3D tessellation data to the geometry
-    fn add_tessellation_3d(&mut self, _geometry: &mut Vec<f64>, _multiplier: f64) {
-        panic!("unexpected geometry type")
+    if *r#type == FeatureType::Point {
+        VectorGeometry::VectorPoints(points)
+    } else {
+        if !points.is_empty() {
+            lines.push(VectorLineWithOffset::new(0.0, points.clone()));
+        }
+        if *r#type == FeatureType::Line {
+            VectorGeometry::VectorLines(lines)
+        } else if (*r#type == FeatureType::MultiPolygon || *r#type == FeatureType::Polygon)
+            && !is_s2
+        {
+            VectorGeometry::VectorPolys(classify_rings(&lines))
+        } else {
+            VectorGeometry::VectorPolys(polys)
+        }
     }
 }
-impl ProtoRead for MapboxVectorFeature {
-    fn read(&mut self, tag: u64, pb: &mut Protobuf) {
-        if self.is_s2 {
-            match tag {
-                15 => self.id = Some(pb.read_varint::<u64>()),
-                1 => {
-                    let end = pb.get_pos() + pb.read_varint::<usize>();
 
-                    while pb.get_pos() < end {
-                        let key = &self.keys.borrow()[pb.read_varint::<usize>()];
-                        let value = &self.values.borrow()[pb.read_varint::<usize>()];
+/// Decode the delta encoded indices found at `pos`
+pub(crate) fn decode_indices(data: &[u8], pos: usize) -> Vec<u32> {
+    let mut pbf = field_at(data, Some(pos));
 
-                        self.properties.insert(key.clone(), value.clone());
-                    }
-                }
-                2 => self.r#type = pb.read_varint::<FeatureType>(),
-                3 => self.geometry_index = pb.get_pos(),
-                4 => self.indices_index = Some(pb.get_pos()),
-                5 => self.tessellation_index = Some(pb.get_pos()),
+    let mut curr: i32 = 0;
+    // build indices
+    let mut indices: Vec<u32> = vec![];
+    while let Ok(delta) = pbf.s_varint() {
+        curr = curr.wrapping_add(delta as i32);
+        indices.push(curr as u32);
+    }
 
-                _ => panic!("unknown tag: {}", tag),
-            }
-        } else {
-            match tag {
-                1 => self.id = Some(pb.read_varint::<u64>()),
-                2 => {
-                    let end = pb.get_pos() + pb.read_varint::<usize>();
+    indices
+}
 
-                    while pb.get_pos() < end {
-                        let key = &self.keys.borrow()[pb.read_varint::<usize>()];
-                        let value = &self.values.borrow()[pb.read_varint::<usize>()];
+/// Decode the tessellation found at `pos`, adding it to the flattened geometry
+pub(crate) fn decode_tessellation(
+    data: &[u8],
+    pos: usize,
+    geometry: &mut Vec<f64>,
+    multiplier: f64,
+) {
+    let mut pbf = field_at(data, Some(pos));
 
-                        self.properties.insert(key.clone(), value.clone());
-                    }
-                }
-                3 => self.r#type = pb.read_varint::<FeatureType>(),
-                4 => self.geometry_index = pb.get_pos(),
-                5 => self.indices_index = Some(pb.get_pos()),
-                6 => self.tessellation_index = Some(pb.get_pos()),
+    let mut x: i32 = 0;
+    let mut y: i32 = 0;
+    while let (Ok(dx), Ok(dy)) = (pbf.s_varint(), pbf.s_varint()) {
+        x = x.wrapping_add(dx as i32);
+        y = y.wrapping_add(dy as i32);
+        geometry.push(x as f64 * multiplier);
+        geometry.push(y as f64 * multiplier);
+    }
+}
 
-                _ => panic!("unknown tag: {}", tag),
-            }
+/// Decode a value from a Mapbox layer's value store, a string or number found by its tag.
+/// Anything else is null
+pub(crate) fn read_value(data: &[u8]) -> PrimitiveValue {
+    let mut value = PrimitiveValue::Null;
+    let mut pb = Reader::new(data, 0, data.len(), ProtoMessage::MapboxValue);
+    while let Ok((tag, wire_type)) = pb.field() {
+        let read = match tag {
+            1 => pb.string().map(|s| PrimitiveValue::String(s.into_owned())),
+            2 => pb.read_varint().map(PrimitiveValue::F32),
+            3 => pb.read_varint().map(PrimitiveValue::F64),
+            5 => pb.read_varint().map(PrimitiveValue::U64),
+            4 | 6 => pb.s_varint().map(PrimitiveValue::I64),
+            7 => pb.read_varint().map(PrimitiveValue::Bool),
+            _ => pb.skip(wire_type).map(|_| PrimitiveValue::Null),
+        };
+        let Ok(read) = read else { break };
+        value = read;
+    }
+    value
+}
+
+/// Check a value in a Mapbox layer's value store is complete
+pub(crate) fn check_mapbox_value(mut value: Reader) -> Result<()> {
+    while !value.done() {
+        let (tag, wire_type) = value.field()?;
+        match tag {
+            1 => _ = value.bytes()?,
+            2..=7 => _ = value.varint()?,
+            // anything else is decoded as null
+            _ => value.skip(wire_type)?,
+        }
+    }
+    Ok(())
+}
+
+/// regardless of the type, we return a flattend point array
+pub(crate) fn geometry_points(geometry: VectorGeometry) -> VectorPoints {
+    match geometry {
+        VectorGeometry::VectorPoints(p) => p,
+        VectorGeometry::VectorLines(lines) => {
+            lines.iter().flat_map(|p| p.geometry.clone()).collect()
         }
+        VectorGeometry::VectorPolys(polys) => polys
+            .iter()
+            .flat_map(|p| {
+                p.iter().flat_map(|p| p.geometry[..p.geometry.len().saturating_sub(1)].to_vec())
+            })
+            .collect(),
+        _ => panic!("unexpected geometry type"),
     }
 }
 
+/// an array of lines. The offsets will be set to 0
+pub(crate) fn geometry_lines(geometry: VectorGeometry) -> VectorLinesWithOffset {
+    match geometry {
+        VectorGeometry::VectorLines(lines) => lines,
+        VectorGeometry::VectorPolys(polys) => polys.iter().flat_map(|p| p.clone()).collect(),
+        _ => panic!("unexpected geometry type"),
+    }
+}
+
+/// an array of polys
+pub(crate) fn geometry_polys(geometry: VectorGeometry) -> Vec<VectorLinesWithOffset> {
+    match geometry {
+        VectorGeometry::VectorPolys(polys) => polys,
+        _ => panic!("unexpected geometry type"),
+    }
+}
+
+/// flatten the polygons and scale them by the multiplier
+pub(crate) fn flatten_polys(geometry: VectorGeometry, multiplier: f64) -> Vec<f64> {
+    match geometry {
+        VectorGeometry::VectorPolys(polys) => polys
+            .iter()
+            .flat_map(|p| {
+                p.iter().flat_map(|p| {
+                    p.geometry
+                        .clone()
+                        .into_iter()
+                        .flat_map(|p| vec![p.x as f64 * multiplier, p.y as f64 * multiplier])
+                })
+            })
+            .collect(),
+        _ => panic!("unexpected geometry type"),
+    }
+}
+
+/// Group rings into polygons by their winding order. Rings without an area are skipped
 fn classify_rings(rings: &VectorLinesWithOffset) -> Vec<VectorLinesWithOffset> {
     let mut polygons: Vec<VectorLinesWithOffset> = vec![];
     let mut polygon: VectorLinesWithOffset = vec![];
@@ -390,6 +719,7 @@ fn classify_rings(rings: &VectorLinesWithOffset) -> Vec<VectorLinesWithOffset> {
     while i < rings.len() {
         let area = signed_area(&rings[i].geometry);
         if area == 0 {
+            i += 1;
             continue;
         }
         if ccw.is_none() {
@@ -417,14 +747,14 @@ fn classify_rings(rings: &VectorLinesWithOffset) -> Vec<VectorLinesWithOffset> {
     polygons
 }
 
-fn signed_area(ring: &[Point]) -> i32 {
-    let mut sum: i32 = 0;
+fn signed_area(ring: &[Point]) -> i64 {
+    let mut sum: i64 = 0;
     let mut i: usize = 0;
     let mut j = ring.len() - 1;
     while i < ring.len() {
         let p1 = &ring[i];
         let p2 = &ring[j];
-        sum += (p2.x - p1.x) * (p1.y + p2.y);
+        sum += (p2.x as i64 - p1.x as i64) * (p1.y as i64 + p2.y as i64);
 
         j = i;
         i += 1;
@@ -434,9 +764,10 @@ fn signed_area(ring: &[Point]) -> i32 {
 }
 
 /// Mapbox Vector Feature types.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub enum FeatureType {
     /// Point Feature
+    #[default]
     Point = 1,
     /// Line Feature
     Line = 2,
@@ -465,7 +796,15 @@ impl BitCast for FeatureType {
             2 => FeatureType::Line,
             3 => FeatureType::Polygon,
             4 => FeatureType::MultiPolygon,
-            _ => panic!("unknown value: {}", value),
+            _ => panic!(
+                "{}",
+                DecodeError::InvalidEnum {
+                    message: ProtoMessage::MapboxFeature,
+                    tag: None,
+                    value,
+                    offset: 0,
+                }
+            ),
         }
     }
 }
@@ -1,16 +1,19 @@
 use crate::{
-    VectorFeature, VectorLayerMethods,
+    Point, VectorFeature, VectorFeatureMethods, VectorFeatureRef, VectorLayerFeatures,
+    VectorLayerMethods,
     base::BaseVectorLayer,
-    mapbox::{MapboxVectorFeature, write_feature},
+    error::{EncodeError, ProtoMessage},
+    mapbox::{
+        MapboxVectorFeature, check_mapbox_value, read_feature_fields, read_value, write_feature,
+    },
+    open::FeatureType,
+    query::{intersects_bbox, intersects_point},
+    reader::{BYTES, Reader, Result, VARINT},
+    shared::{Lazy, Shared, shared},
+    spatial_index::SpatialIndex,
 };
-use alloc::{
-    collections::{BTreeMap, btree_map::Entry},
-    rc::Rc,
-    string::String,
-    vec::Vec,
-};
-use core::cell::RefCell;
-use pbf::{ProtoRead, Protobuf};
+use alloc::{borrow::Cow, collections::BTreeMap, string::String, vec::Vec};
+use pbf::Protobuf;
 use s2json::PrimitiveValue;
 
 /// Mapbox specification for a Layer
@@ -22,35 +25,54 @@ pub struct MapboxVectorLayer {
     pub name: String,
     /// the extent of the vector layer
     pub extent: usize,
-    /// the features in the layer
-    pub features: BTreeMap<usize, MapboxVectorFeature>,
+    /// the features in the layer, read from the tile data on first access
+    features: Vec<Lazy<MapboxVectorFeature>>,
     /// track the positions of the features
-    pub feature_positions: Vec<usize>,
+    feature_positions: Vec<usize>,
     /// whether or not the layer is an s2 layer. This is an extension to the Mapbox spec and not used
     /// in production by most tools
     is_s2: bool,
-    /// a reference to the pbf
-    pbf: Rc<RefCell<Protobuf>>,
+    /// the tile data the features are read from
+    data: Shared<[u8]>,
     /// key store used by features
-    keys: Rc<RefCell<Vec<String>>>,
+    keys: Shared<Vec<String>>,
     /// value store used by features
-    values: Rc<RefCell<Vec<PrimitiveValue>>>,
+    values: Shared<Vec<PrimitiveValue>>,
+    /// spatial index of the features, built on first use
+    index: Option<SpatialIndex>,
 }
 impl MapboxVectorLayer {
     /// Create a new MapboxVectorLayer
-    pub fn new(pbf: Rc<RefCell<Protobuf>>, is_s2: bool) -> MapboxVectorLayer {
+    pub fn new(data: Shared<[u8]>, is_s2: bool) -> MapboxVectorLayer {
         MapboxVectorLayer {
             version: 5,
             name: String::new(),
             extent: 4_096,
             is_s2,
-            pbf: pbf.clone(),
-            keys: Rc::new(RefCell::new(Vec::new())),
-            values: Rc::new(RefCell::new(Vec::new())),
-            features: BTreeMap::new(),
+            data,
+            keys: shared(Vec::new()),
+            values: shared(Vec::new()),
+            features: Vec::new(),
             feature_positions: Vec::new(),
+            index: None,
         }
     }
+
+    /// True if the layer was stored as an Open Vector Flat Tile (S2) layer instead of a Mapbox one
+    pub fn is_s2(&self) -> bool {
+        self.is_s2
+    }
+
+    /// The spatial index of the features, built from their geometry on first use
+    pub fn spatial_index(&mut self) -> &SpatialIndex {
+        if self.index.is_none() {
+            let geometries: Vec<_> = (0..self.len())
+                .filter_map(|i| self.feature(i).map(|mut f| f.load_geometry()))
+                .collect();
+            self.index = Some(SpatialIndex::from_geometries(geometries.into_iter()));
+        }
+        self.index.get_or_insert_default()
+    }
 }
 impl VectorLayerMethods for MapboxVectorLayer {
     fn version(&self) -> u16 {
@@ -76,48 +98,148 @@ impl VectorLayerMethods for MapboxVectorLayer {
     }
 
     fn feature(&mut self, i: usize) -> Option<VectorFeature<'_>> {
-        // First check if self.features already has the feature
-        if let Entry::Vacant(e) = self.features.entry(i) {
-            // Read the feature
-            let mut feature = MapboxVectorFeature::new(
-                self.pbf.clone(),
+        // read the feature into its slot if it was not read yet
+        self.get_feature(i)?;
+        self.features.get_mut(i)?.get_mut().map(Into::into)
+    }
+
+    fn query_point(&mut self, x: f64, y: f64, tolerance: f64) -> Vec<usize> {
+        let candidates = self.spatial_index().search_point(x, y, tolerance);
+        candidates
+            .into_iter()
+            .filter(|&i| {
+                self.feature(i)
+                    .is_some_and(|mut f| intersects_point(&f.load_geometry(), x, y, tolerance))
+            })
+            .collect()
+    }
+
+    fn query_bbox(&mut self, min: Point, max: Point) -> Vec<usize> {
+        let candidates = self.spatial_index().search(min.x, min.y, max.x, max.y);
+        candidates
+            .into_iter()
+            .filter(|&i| {
+                self.feature(i).is_some_and(|mut f| intersects_bbox(&f.load_geometry(), &min, &max))
+            })
+            .collect()
+    }
+}
+impl VectorLayerFeatures for MapboxVectorLayer {
+    fn get_feature(&self, i: usize) -> Option<VectorFeatureRef<'_>> {
+        let pos = *self.feature_positions.get(i)?;
+        let feature = self.features.get(i)?.get_or_init(|| {
+            read_feature(
+                &self.data,
+                pos,
                 self.is_s2,
                 self.extent,
                 self.version,
-                self.keys.clone(),
-                self.values.clone(),
-            );
-            let mut pbf = self.pbf.borrow_mut();
-            pbf.set_pos(self.feature_positions[i]);
-            pbf.read_message(&mut feature);
-            e.insert(feature);
-
-            // Now safely retrieve the inserted feature
-            self.features.get_mut(&i).map(Into::into)
-        } else {
-            // Safe to unwrap since we just checked the key exists
-            self.features.get_mut(&i).map(Into::into)
-        }
+                &self.keys,
+                &self.values,
+            )
+        });
+        Some(feature.into())
     }
 }
-impl ProtoRead for MapboxVectorLayer {
-    fn read(&mut self, tag: u64, pb: &mut Protobuf) {
+
+/// Read the feature found at `pos` in the tile data
+fn read_feature(
+    data: &Shared<[u8]>,
+    pos: usize,
+    is_s2: bool,
+    extent: usize,
+    version: u16,
+    keys: &Shared<Vec<String>>,
+    values: &Shared<Vec<PrimitiveValue>>,
+) -> MapboxVectorFeature {
+    let mut feature = MapboxVectorFeature::new(
+        data.clone(),
+        is_s2,
+        extent,
+        version,
+        keys.clone(),
+        values.clone(),
+    );
+    // the layer checked its features when it was read
+    let fields = Reader::at(data, pos, 2, ProtoMessage::MapboxFeature)
+        .and_then(|pb| read_feature_fields(pb, is_s2, keys.len(), values.len()))
+        .unwrap_or_default();
+    feature.set_fields(fields);
+    feature
+}
+
+/// The fields of a Mapbox layer message, read by [`read_layer`]. Features are stored by their
+/// position in the tile data and values by their encoded message
+#[derive(Debug)]
+pub(crate) struct LayerFields<'a> {
+    pub version: u16,
+    pub name: Cow<'a, str>,
+    pub extent: usize,
+    pub features: Vec<usize>,
+    pub keys: Vec<Cow<'a, str>>,
+    pub values: Vec<&'a [u8]>,
+}
+
+/// Read a Mapbox layer message. Once the key and value stores are known, every feature is
+/// checked against them so features can be read later without failing
+pub(crate) fn read_layer(mut layer: Reader<'_>, is_s2: bool) -> Result<LayerFields<'_>> {
+    let mut fields = LayerFields {
+        version: 5,
+        name: Cow::Borrowed(""),
+        extent: 4_096,
+        features: Vec::new(),
+        keys: Vec::new(),
+        values: Vec::new(),
+    };
+    while !layer.done() {
+        let (tag, wire_type) = layer.field()?;
+        let start = layer.pos();
         match tag {
-            15 => self.version = pb.read_varint::<u16>(),
-            1 => self.name = pb.read_string(),
-            2 => self.feature_positions.push(pb.get_pos()),
-            3 => {
-                self.keys.borrow_mut().push(pb.read_string());
+            5 | 15 => layer.expect(wire_type, VARINT)?,
+            1..=4 => layer.expect(wire_type, BYTES)?,
+            _ => return Err(layer.unknown_tag(start)),
+        }
+        match tag {
+            15 => fields.version = layer.read_varint()?,
+            1 => fields.name = layer.string()?,
+            2 => {
+                fields.features.push(start);
+                layer.skip(wire_type)?;
             }
+            3 => fields.keys.push(layer.string()?),
             4 => {
-                let mut value = PrimitiveValue::Null;
-                pb.read_message(&mut value);
-                self.values.borrow_mut().push(value);
+                let value = layer.message(ProtoMessage::MapboxValue)?;
+                fields.values.push(&layer.buf()[value.pos()..layer.pos()]);
+                check_mapbox_value(value)?;
             }
-            5 => self.extent = pb.read_varint::<usize>(),
-            _ => panic!("Unknown layer type"),
+            _ => fields.extent = layer.read_varint()?,
         }
     }
+    for &pos in &fields.features {
+        let feature = Reader::at(layer.buf(), pos, 2, ProtoMessage::MapboxFeature)?;
+        read_feature_fields(feature, is_s2, fields.keys.len(), fields.values.len())?;
+    }
+
+    Ok(fields)
+}
+
+impl MapboxVectorLayer {
+    /// Read the layer message covered by `layer` from the tile data
+    pub(crate) fn read(data: &Shared<[u8]>, layer: Reader, is_s2: bool) -> Result<Self> {
+        let fields = read_layer(layer, is_s2)?;
+        Ok(MapboxVectorLayer {
+            version: fields.version,
+            name: fields.name.into_owned(),
+            extent: fields.extent,
+            is_s2,
+            data: data.clone(),
+            keys: shared(fields.keys.into_iter().map(Cow::into_owned).collect()),
+            values: shared(fields.values.into_iter().map(read_value).collect()),
+            features: fields.features.iter().map(|_| Lazy::new()).collect(),
+            feature_positions: fields.features,
+            index: None,
+        })
+    }
 }
 
 /// Write a layer to a protobuffer using the S2 Specification
@@ -132,13 +254,13 @@ pub fn write_layer(layer: &BaseVectorLayer, mapbox_support: bool) -> Vec<u8> {
         pbf.write_bytes_field(2, &write_feature(feature, &mut keys, &mut values, mapbox_support));
     }
     let mut keys: Vec<(String, usize)> = keys.into_iter().collect();
-    keys.sort_by(|a, b| a.1.cmp(&b.1));
+    keys.sort_by_key(|a| a.1);
     // keys and values
     for (key, _) in keys.iter() {
         pbf.write_string_field(3, key);
     }
     let mut values: Vec<(PrimitiveValue, usize)> = values.into_iter().collect();
-    values.sort_by(|a, b| a.1.cmp(&b.1));
+    values.sort_by_key(|a| a.1);
     for (value, _) in values.iter() {
         pbf.write_message(4, value);
     }
@@ -146,3 +268,22 @@ pub fn write_layer(layer: &BaseVectorLayer, mapbox_support: bool) -> Vec<u8> {
 
     pbf.take()
 }
+
+/// Check that every feature in the layer can be stored in a Mapbox layer (which is 2D only)
+pub(crate) fn check_layer(layer: &BaseVectorLayer) -> core::result::Result<(), EncodeError> {
+    for (i, feature) in layer.features.iter().enumerate() {
+        let feature_type = feature.get_type();
+        if matches!(
+            feature_type,
+            FeatureType::Points3D | FeatureType::Lines3D | FeatureType::Polygons3D
+        ) {
+            return Err(EncodeError::UnsupportedGeometry {
+                layer: layer.name.clone(),
+                feature: i,
+                feature_type,
+            });
+        }
+    }
+
+    Ok(())
+}
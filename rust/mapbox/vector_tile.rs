@@ -1,29 +1,38 @@
 use crate::{
     base::BaseVectorTile,
-    mapbox::{MapboxVectorLayer, write_layer},
+    error::{DecodeError, EncodeError},
+    mapbox::{MapboxVectorLayer, check_layer, write_layer},
+    shared::Shared,
+    vector_tile::read_tile,
 };
-use alloc::{collections::BTreeMap, rc::Rc, string::String, vec::Vec};
-use core::cell::RefCell;
-use pbf::{ProtoRead, Protobuf};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use pbf::Protobuf;
 
 /// The vector tile struct that covers both "open" and "mapbox" specifications
 #[derive(Debug)]
 pub struct MapboxVectorTile {
     /// the layers in the vector tile
     pub layers: BTreeMap<String, MapboxVectorLayer>,
-    /// the protobuf for the vector tile
-    pbf: Rc<RefCell<Protobuf>>,
 }
 impl MapboxVectorTile {
     /// Create a new vector tile
+    ///
+    /// # Panics
+    /// If the data is not a valid tile. Use [`MapboxVectorTile::try_new`] for untrusted input.
     pub fn new(data: Vec<u8>, end: Option<usize>) -> Self {
-        let pbf = Rc::new(RefCell::new(data.into()));
-        let mut vt = MapboxVectorTile { pbf: pbf.clone(), layers: BTreeMap::new() };
+        Self::try_new(data, end).unwrap_or_else(|err| panic!("{}", err))
+    }
 
-        let mut tmp_pbf = pbf.borrow_mut();
-        tmp_pbf.read_fields(&mut vt, end);
+    /// Create a new vector tile, returning an error if the data is truncated or malformed
+    pub fn try_new(data: Vec<u8>, end: Option<usize>) -> Result<Self, DecodeError> {
+        let data: Shared<[u8]> = data.into();
+        let mut vt = MapboxVectorTile { layers: BTreeMap::new() };
+        for (layer, is_s2) in read_tile(&data, end, false)?.mapbox {
+            let layer = MapboxVectorLayer::read(&data, layer, is_s2)?;
+            vt.layers.insert(layer.name.clone(), layer);
+        }
 
-        vt
+        Ok(vt)
     }
 
     /// Get a layer given the name
@@ -31,19 +40,6 @@ impl MapboxVectorTile {
         self.layers.get_mut(name)
     }
 }
-impl ProtoRead for MapboxVectorTile {
-    fn read(&mut self, tag: u64, pb: &mut Protobuf) {
-        match tag {
-            1 | 3 => {
-                let mut layer = MapboxVectorLayer::new(self.pbf.clone(), tag == 1);
-                pb.read_message(&mut layer);
-                self.layers.insert(layer.name.clone(), layer);
-            }
-            _ => panic!("unknown tag: {}", tag),
-        }
-    }
-}
-
 /// writer for converting a BaseVectorTile to encoded bytes of the Open Vector Flat Tile format or Mapbox Vector Tile
 pub fn write_tile(tile: &mut BaseVectorTile, mapbox_support: bool) -> Vec<u8> {
     let mut pbf = Protobuf::new();
@@ -58,3 +54,16 @@ pub fn write_tile(tile: &mut BaseVectorTile, mapbox_support: bool) -> Vec<u8> {
 
     pbf.take()
 }
+
+/// Fallible version of [`write_tile`]. Returns an error naming the first feature that can not be
+/// stored in a Mapbox layer (3D geometry) instead of panicking.
+pub fn try_write_tile(
+    tile: &mut BaseVectorTile,
+    mapbox_support: bool,
+) -> Result<Vec<u8>, EncodeError> {
+    for layer in tile.layers.values() {
+        check_layer(layer)?;
+    }
+
+    Ok(write_tile(tile, mapbox_support))
+}
@@ -0,0 +1,258 @@
+use crate::{
+    DecodeError, EncodeError, VectorTile, WriteOptions,
+    base::BaseVectorTile,
+    compression::{self, Codec, CompressionError},
+    mapbox, try_write_tile,
+};
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde_json::{Value, json};
+use std::path::Path;
+
+/// An error reading or writing an MBTiles archive
+#[derive(Debug)]
+pub enum MBTilesError {
+    /// The SQLite database could not be read or written
+    Sqlite(rusqlite::Error),
+    /// A tile could not be compressed or decompressed
+    Compression(CompressionError),
+    /// A tile could not be decoded
+    Decode(DecodeError),
+    /// A tile could not be encoded
+    Encode(EncodeError),
+    /// A tile is past zoom 30 or its x or y is outside of its zoom
+    InvalidTile(u8, u32, u32),
+}
+impl fmt::Display for MBTilesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MBTilesError::Sqlite(err) => write!(f, "mbtiles: {}", err),
+            MBTilesError::Compression(err) => write!(f, "mbtiles: {}", err),
+            MBTilesError::Decode(err) => write!(f, "mbtiles: {}", err),
+            MBTilesError::Encode(err) => write!(f, "mbtiles: {}", err),
+            MBTilesError::InvalidTile(zoom, x, y) => {
+                write!(f, "mbtiles: invalid tile {}/{}/{}", zoom, x, y)
+            }
+        }
+    }
+}
+impl core::error::Error for MBTilesError {}
+impl From<rusqlite::Error> for MBTilesError {
+    fn from(err: rusqlite::Error) -> Self {
+        MBTilesError::Sqlite(err)
+    }
+}
+impl From<CompressionError> for MBTilesError {
+    fn from(err: CompressionError) -> Self {
+        MBTilesError::Compression(err)
+    }
+}
+impl From<DecodeError> for MBTilesError {
+    fn from(err: DecodeError) -> Self {
+        MBTilesError::Decode(err)
+    }
+}
+impl From<EncodeError> for MBTilesError {
+    fn from(err: EncodeError) -> Self {
+        MBTilesError::Encode(err)
+    }
+}
+
+/// The encoding tiles are written with, stored as the `format` metadata
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TileFormat {
+    /// Open Vector Tiles written by [`crate::write_tile`]
+    #[default]
+    Ovt,
+    /// Mapbox Vector Tiles written by [`crate::mapbox::write_tile`]
+    Pbf,
+}
+impl TileFormat {
+    /// The value of the `format` metadata
+    pub fn name(&self) -> &'static str {
+        match self {
+            TileFormat::Ovt => "ovt",
+            TileFormat::Pbf => "pbf",
+        }
+    }
+}
+
+/// # MBTiles
+///
+/// ## Description
+/// An [MBTiles](https://github.com/mapbox/mbtiles-spec) archive: a SQLite database with a
+/// `metadata` table of name/value pairs and a `tiles` table addressed by zoom, column and row.
+///
+/// Tiles are addressed here by their XYZ `zoom`/`x`/`y`, the TMS row flip of the archive is
+/// handled internally. Gzip compressed tiles are decompressed on read and every tile is gzip
+/// compressed on write.
+///
+/// ## Usage
+/// ```rust,ignore
+/// use open_vector_tile::mbtiles::{MBTiles, TileFormat};
+///
+/// let mut archive = MBTiles::create("tiles.mbtiles")?;
+/// archive.write_tiles(vec![(0, 0, 0, base_tile)], TileFormat::Ovt)?;
+///
+/// let archive = MBTiles::open("tiles.mbtiles")?;
+/// let format = archive.metadata()?.get("format").cloned();
+/// let tile = archive.tile(0, 0, 0)?;
+/// ```
+#[derive(Debug)]
+pub struct MBTiles {
+    conn: Connection,
+}
+impl MBTiles {
+    /// Open an existing archive
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, MBTilesError> {
+        Ok(MBTiles { conn: Connection::open(path)? })
+    }
+
+    /// Open an archive, creating the file and its tables if they do not exist yet
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, MBTilesError> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS metadata (name TEXT, value TEXT);
+            CREATE UNIQUE INDEX IF NOT EXISTS name ON metadata (name);
+            CREATE TABLE IF NOT EXISTS tiles (
+                zoom_level INTEGER, tile_column INTEGER, tile_row INTEGER, tile_data BLOB
+            );
+            CREATE UNIQUE INDEX IF NOT EXISTS tile_index
+                ON tiles (zoom_level, tile_column, tile_row);",
+        )?;
+        Ok(MBTiles { conn })
+    }
+
+    /// The underlying SQLite connection
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Every name/value pair of the `metadata` table
+    pub fn metadata(&self) -> Result<BTreeMap<String, String>, MBTilesError> {
+        let mut stmt = self.conn.prepare("SELECT name, value FROM metadata")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Set (or replace) a metadata value
+    pub fn set_metadata(&self, name: &str, value: &str) -> Result<(), MBTilesError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO metadata (name, value) VALUES (?1, ?2)",
+            params![name, value],
+        )?;
+        Ok(())
+    }
+
+    /// The (decompressed) bytes of a tile, None if the archive does not have it. Tiles past zoom
+    /// 30 or outside of their zoom are an [`MBTilesError::InvalidTile`]
+    pub fn tile_data(&self, zoom: u8, x: u32, y: u32) -> Result<Option<Vec<u8>>, MBTilesError> {
+        let data: Option<Vec<u8>> = self
+            .conn
+            .query_row(
+                "SELECT tile_data FROM tiles
+                WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+                params![zoom, x, tms_y(zoom, x, y)?],
+                |row| row.get(0),
+            )
+            .optional()?;
+        data.map(decompress).transpose()
+    }
+
+    /// Decode a tile, None if the archive does not have it
+    pub fn tile(&self, zoom: u8, x: u32, y: u32) -> Result<Option<VectorTile>, MBTilesError> {
+        match self.tile_data(zoom, x, y)? {
+            Some(data) => Ok(Some(VectorTile::try_new(data, None)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Store the bytes of a tile as they are, replacing any tile already at `zoom`/`x`/`y`. Tiles
+    /// past zoom 30 or outside of their zoom are an [`MBTilesError::InvalidTile`]
+    pub fn put_tile_data(&self, zoom: u8, x: u32, y: u32, data: &[u8]) -> Result<(), MBTilesError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO tiles (zoom_level, tile_column, tile_row, tile_data)
+            VALUES (?1, ?2, ?3, ?4)",
+            params![zoom, x, tms_y(zoom, x, y)?, data],
+        )?;
+        Ok(())
+    }
+
+    /// Encode and store a set of tiles in a single transaction. The `format`, `minzoom` and
+    /// `maxzoom` metadata are set, along with a `json` row listing the `vector_layers`
+    pub fn write_tiles(
+        &mut self,
+        tiles: impl IntoIterator<Item = (u8, u32, u32, BaseVectorTile)>,
+        format: TileFormat,
+    ) -> Result<(), MBTilesError> {
+        let tx = self.conn.transaction()?;
+        // layer name -> (minzoom, maxzoom)
+        let mut layers: BTreeMap<String, (u8, u8)> = BTreeMap::new();
+        let mut zooms: Option<(u8, u8)> = None;
+        for (zoom, x, y, mut tile) in tiles {
+            for name in tile.layers.keys() {
+                let range = layers.entry(name.clone()).or_insert((zoom, zoom));
+                *range = (range.0.min(zoom), range.1.max(zoom));
+            }
+            zooms = Some(zooms.map_or((zoom, zoom), |(min, max)| (min.min(zoom), max.max(zoom))));
+            let data = match format {
+                TileFormat::Ovt => {
+                    try_write_tile(Some(&mut tile), None, None, &WriteOptions::default())?
+                }
+                TileFormat::Pbf => mapbox::try_write_tile(&mut tile, true)?,
+            };
+            tx.execute(
+                "INSERT OR REPLACE INTO tiles (zoom_level, tile_column, tile_row, tile_data)
+                VALUES (?1, ?2, ?3, ?4)",
+                params![zoom, x, tms_y(zoom, x, y)?, compress(&data)?],
+            )?;
+        }
+
+        let mut metadata = vec![("format", format.name().to_string())];
+        if let Some((min, max)) = zooms {
+            metadata.push(("minzoom", min.to_string()));
+            metadata.push(("maxzoom", max.to_string()));
+        }
+        let vector_layers: Vec<Value> = layers
+            .iter()
+            .map(|(id, (min, max))| json!({ "id": id, "fields": {}, "minzoom": min, "maxzoom": max }))
+            .collect();
+        metadata.push(("json", json!({ "vector_layers": vector_layers }).to_string()));
+        for (name, value) in metadata {
+            tx.execute(
+                "INSERT OR REPLACE INTO metadata (name, value) VALUES (?1, ?2)",
+                params![name, value],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Flip between the XYZ and TMS row of a tile, or [`MBTilesError::InvalidTile`] if the tile is
+/// past zoom 30 or outside of its zoom
+fn tms_y(zoom: u8, x: u32, y: u32) -> Result<i64, MBTilesError> {
+    if zoom > 30 || x >= 1 << zoom || y >= 1 << zoom {
+        return Err(MBTilesError::InvalidTile(zoom, x, y));
+    }
+    Ok((1_i64 << zoom) - 1 - y as i64)
+}
+
+/// Gzip compress a tile
+fn compress(data: &[u8]) -> Result<Vec<u8>, MBTilesError> {
+    Ok(compression::compress(data, Codec::Gzip)?)
+}
+
+/// Decompress a tile if it is gzip compressed
+fn decompress(data: Vec<u8>) -> Result<Vec<u8>, MBTilesError> {
+    match compression::detect(&data) {
+        Some(Codec::Gzip) => Ok(compression::decompress_with(&data, Codec::Gzip)?),
+        _ => Ok(data),
+    }
+}
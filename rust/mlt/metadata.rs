@@ -0,0 +1,252 @@
+use crate::mlt::{MltError, stream::Reader};
+use alloc::{string::String, vec::Vec};
+use pbf::{ProtoWrite, Protobuf};
+
+/// The physical type of a scalar column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScalarType {
+    /// true or false
+    Boolean = 0,
+    /// signed 8 bit integer
+    Int8 = 1,
+    /// unsigned 8 bit integer
+    UInt8 = 2,
+    /// signed 32 bit integer
+    Int32 = 3,
+    /// unsigned 32 bit integer
+    UInt32 = 4,
+    /// signed 64 bit integer
+    Int64 = 5,
+    /// unsigned 64 bit integer
+    UInt64 = 6,
+    /// 32 bit float
+    Float = 7,
+    /// 64 bit float
+    Double = 8,
+    /// UTF-8 string
+    String = 9,
+}
+impl ScalarType {
+    fn from_u64(value: u64) -> Option<Self> {
+        Some(match value {
+            0 => ScalarType::Boolean,
+            1 => ScalarType::Int8,
+            2 => ScalarType::UInt8,
+            3 => ScalarType::Int32,
+            4 => ScalarType::UInt32,
+            5 => ScalarType::Int64,
+            6 => ScalarType::UInt64,
+            7 => ScalarType::Float,
+            8 => ScalarType::Double,
+            9 => ScalarType::String,
+            _ => return None,
+        })
+    }
+
+    /// true if the type holds signed integers
+    pub fn is_signed(&self) -> bool {
+        matches!(self, ScalarType::Int8 | ScalarType::Int32 | ScalarType::Int64)
+    }
+}
+
+/// The type of a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// a single value per feature
+    Scalar(ScalarType),
+    /// the geometry of each feature
+    Geometry,
+    /// a logical, nested or vertex scoped column. Layers using one can not be decoded
+    Unsupported,
+}
+
+/// A column of a feature table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    /// the name of the column, the property key for scalar columns
+    pub name: String,
+    /// whether some features have no value, in which case a present stream comes first
+    pub nullable: bool,
+    /// the type of the column
+    pub r#type: ColumnType,
+}
+impl Column {
+    /// Create a new column
+    pub fn new(name: impl Into<String>, nullable: bool, r#type: ColumnType) -> Self {
+        Column { name: name.into(), nullable, r#type }
+    }
+
+    fn read(data: &[u8]) -> Result<Self, MltError> {
+        let mut column = Column::new("", false, ColumnType::Unsupported);
+        let mut feature_scoped = true;
+        read_fields(data, |tag, field| {
+            match (tag, field) {
+                (1, Field::Bytes(bytes)) => column.name = read_string(bytes)?,
+                (2, Field::Varint(nullable)) => column.nullable = nullable != 0,
+                (3, Field::Varint(scope)) => feature_scoped = scope == 0,
+                (4, Field::Bytes(bytes)) => {
+                    let mut r#type = ColumnType::Unsupported;
+                    read_fields(bytes, |tag, field| {
+                        if let (4, Field::Varint(physical)) = (tag, field)
+                            && let Some(scalar) = ScalarType::from_u64(physical)
+                        {
+                            r#type = ColumnType::Scalar(scalar);
+                        }
+                        Ok(())
+                    })?;
+                    column.r#type = r#type;
+                }
+                (5, Field::Bytes(bytes)) => {
+                    let mut r#type = ColumnType::Unsupported;
+                    read_fields(bytes, |tag, field| {
+                        if let (4, Field::Varint(COMPLEX_GEOMETRY)) = (tag, field) {
+                            r#type = ColumnType::Geometry;
+                        }
+                        Ok(())
+                    })?;
+                    column.r#type = r#type;
+                }
+                _ => {}
+            }
+            Ok(())
+        })?;
+        // only feature scoped columns are supported
+        if !feature_scoped {
+            column.r#type = ColumnType::Unsupported;
+        }
+        Ok(column)
+    }
+}
+impl ProtoWrite for Column {
+    fn write(&self, pb: &mut Protobuf) {
+        pb.write_string_field(1, &self.name);
+        if self.nullable {
+            pb.write_varint_field(2, true);
+        }
+        let mut inner = Protobuf::new();
+        match self.r#type {
+            ColumnType::Scalar(scalar) => {
+                inner.write_varint_field(4, scalar as u8);
+                pb.write_bytes_field(4, &inner.take());
+            }
+            ColumnType::Geometry => {
+                inner.write_varint_field(4, COMPLEX_GEOMETRY);
+                pb.write_bytes_field(5, &inner.take());
+            }
+            ColumnType::Unsupported => {}
+        }
+    }
+}
+
+/// The complex column type of geometry columns
+const COMPLEX_GEOMETRY: u64 = 2;
+
+/// The columns of the layers that reference this feature table
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FeatureTableSchema {
+    /// the name of the layer
+    pub name: String,
+    /// the columns, in the order they are stored
+    pub columns: Vec<Column>,
+}
+impl FeatureTableSchema {
+    fn read(data: &[u8]) -> Result<Self, MltError> {
+        let mut schema = FeatureTableSchema::default();
+        read_fields(data, |tag, field| {
+            match (tag, field) {
+                (1, Field::Bytes(bytes)) => schema.name = read_string(bytes)?,
+                (2, Field::Bytes(bytes)) => schema.columns.push(Column::read(bytes)?),
+                _ => {}
+            }
+            Ok(())
+        })?;
+        Ok(schema)
+    }
+}
+impl ProtoWrite for FeatureTableSchema {
+    fn write(&self, pb: &mut Protobuf) {
+        pb.write_string_field(1, &self.name);
+        for column in &self.columns {
+            pb.write_message(2, column);
+        }
+    }
+}
+
+/// The tileset metadata (usually stored as a `.mlt.meta.pbf` file next to the tiles) describing
+/// the columns of every feature table the tiles reference
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TileSetMetadata {
+    /// the version of the metadata
+    pub version: u32,
+    /// the feature tables, referenced by layers by their index
+    pub feature_tables: Vec<FeatureTableSchema>,
+}
+impl TileSetMetadata {
+    /// Decode the tileset metadata protobuf
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MltError> {
+        let mut metadata = TileSetMetadata::default();
+        read_fields(data, |tag, field| {
+            match (tag, field) {
+                (1, Field::Varint(version)) => metadata.version = version as u32,
+                (2, Field::Bytes(bytes)) => {
+                    metadata.feature_tables.push(FeatureTableSchema::read(bytes)?)
+                }
+                _ => {}
+            }
+            Ok(())
+        })?;
+        Ok(metadata)
+    }
+
+    /// Encode the tileset metadata protobuf
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut pbf = Protobuf::new();
+        pbf.write_fields(self);
+        pbf.take()
+    }
+}
+impl ProtoWrite for TileSetMetadata {
+    fn write(&self, pb: &mut Protobuf) {
+        pb.write_varint_field(1, self.version);
+        for table in &self.feature_tables {
+            pb.write_message(2, table);
+        }
+    }
+}
+
+/// A protobuf field value. Fixed size fields are skipped
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Call `f` with every field of the message in `data`. Unlike [`Protobuf`] this never panics on
+/// malformed data
+fn read_fields<'a>(
+    data: &'a [u8],
+    mut f: impl FnMut(u64, Field<'a>) -> Result<(), MltError>,
+) -> Result<(), MltError> {
+    let mut r = Reader::new(data);
+    while !r.is_done() {
+        let offset = r.pos();
+        let key = r.varint()?;
+        let field = match key & 0x7 {
+            0 => Field::Varint(r.varint()?),
+            1 => r.bytes(8).map(|_| Field::Fixed)?,
+            2 => {
+                let len = r.usize()?;
+                Field::Bytes(r.bytes(len)?)
+            }
+            5 => r.bytes(4).map(|_| Field::Fixed)?,
+            _ => return Err(MltError::Malformed { offset, reason: "unknown protobuf wire type" }),
+        };
+        f(key >> 3, field)?;
+    }
+    Ok(())
+}
+
+fn read_string(bytes: &[u8]) -> Result<String, MltError> {
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| MltError::Malformed { offset: 0, reason: "metadata string is not UTF-8" })
+}
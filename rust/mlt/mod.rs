@@ -0,0 +1,61 @@
+/// The tileset metadata describing the columns of every MLT layer
+pub mod metadata;
+/// The streams MLT columns are stored in
+mod stream;
+/// MapLibre Tile specification for Features
+pub mod vector_feature;
+/// MapLibre Tile specification for Layers
+pub mod vector_layer;
+/// MapLibre Tile specification for a Tile
+pub mod vector_tile;
+
+pub use metadata::*;
+pub use vector_feature::*;
+pub use vector_layer::*;
+pub use vector_tile::*;
+
+use core::fmt;
+
+/// An error found while decoding an MLT tile or its tileset metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MltError {
+    /// The data ended before a value was complete
+    UnexpectedEof {
+        /// byte offset of the value that could not be read
+        offset: usize,
+    },
+    /// A layer references a feature table that the tileset metadata does not describe
+    UnknownFeatureTable(u64),
+    /// The data uses an encoding or column type that is not supported (e.g. FastPFOR, FSST or
+    /// nested columns)
+    Unsupported {
+        /// byte offset of the stream or column
+        offset: usize,
+        /// what is not supported
+        reason: &'static str,
+    },
+    /// The data can not describe a valid value
+    Malformed {
+        /// byte offset of the value
+        offset: usize,
+        /// what is wrong with the value
+        reason: &'static str,
+    },
+}
+impl fmt::Display for MltError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MltError::UnexpectedEof { offset } => {
+                write!(f, "mlt: unexpected end of data at byte {}", offset)
+            }
+            MltError::UnknownFeatureTable(id) => write!(f, "mlt: unknown feature table {}", id),
+            MltError::Unsupported { offset, reason } => {
+                write!(f, "mlt: unsupported {} at byte {}", reason, offset)
+            }
+            MltError::Malformed { offset, reason } => {
+                write!(f, "mlt: {} at byte {}", reason, offset)
+            }
+        }
+    }
+}
+impl core::error::Error for MltError {}
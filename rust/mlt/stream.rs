@@ -0,0 +1,580 @@
+use crate::{Point, mlt::MltError};
+use alloc::{vec, vec::Vec};
+use pbf::{zagzig, zigzag};
+
+/// The kind of data a stream holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PhysicalStreamType {
+    /// whether each feature has a value
+    Present = 0,
+    /// the values themselves
+    Data = 1,
+    /// indices into a dictionary
+    Offset = 2,
+    /// lengths of strings or geometry parts
+    Length = 3,
+}
+
+/// `Data` streams holding the values as is
+pub(crate) const DICTIONARY_NONE: u8 = 0;
+/// `Data` streams holding a string dictionary
+pub(crate) const DICTIONARY_SINGLE: u8 = 1;
+/// `Data` streams holding a vertex buffer
+pub(crate) const DICTIONARY_VERTEX: u8 = 3;
+/// `Data` streams holding a morton encoded vertex dictionary
+pub(crate) const DICTIONARY_MORTON: u8 = 4;
+/// `Offset` streams indexing into a vertex dictionary
+pub(crate) const OFFSET_VERTEX: u8 = 0;
+/// `Offset` streams indexing into a string dictionary
+pub(crate) const OFFSET_STRING: u8 = 2;
+/// `Length` streams of strings or the geometry types
+pub(crate) const LENGTH_VAR_BINARY: u8 = 0;
+/// `Length` streams of the number of geometries in a multi geometry
+pub(crate) const LENGTH_GEOMETRIES: u8 = 1;
+/// `Length` streams of the number of rings in a polygon or vertices in a line
+pub(crate) const LENGTH_PARTS: u8 = 2;
+/// `Length` streams of the number of vertices in a ring
+pub(crate) const LENGTH_RINGS: u8 = 3;
+/// `Length` streams of the strings in a dictionary
+pub(crate) const LENGTH_DICTIONARY: u8 = 6;
+
+/// How values are transformed before they are packed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LogicalTechnique {
+    None = 0,
+    Delta = 1,
+    ComponentwiseDelta = 2,
+    Rle = 3,
+    Morton = 4,
+    Pde = 5,
+}
+impl LogicalTechnique {
+    fn from_bits(bits: u8, offset: usize) -> Result<Self, MltError> {
+        Ok(match bits {
+            0 => LogicalTechnique::None,
+            1 => LogicalTechnique::Delta,
+            2 => LogicalTechnique::ComponentwiseDelta,
+            3 => LogicalTechnique::Rle,
+            4 => LogicalTechnique::Morton,
+            5 => LogicalTechnique::Pde,
+            _ => return Err(MltError::Malformed { offset, reason: "unknown logical technique" }),
+        })
+    }
+}
+
+/// How values are packed into bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PhysicalTechnique {
+    None = 0,
+    FastPfor = 1,
+    Varint = 2,
+    Alp = 3,
+}
+
+/// The header in front of every stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StreamMetadata {
+    pub physical_type: PhysicalStreamType,
+    /// the dictionary, offset or length type, depending on the physical type
+    pub logical_type: u8,
+    pub technique1: LogicalTechnique,
+    pub technique2: LogicalTechnique,
+    pub physical_technique: PhysicalTechnique,
+    /// the number of values packed in the stream
+    pub num_values: usize,
+    pub byte_length: usize,
+    /// RLE runs and the number of values once the runs are expanded
+    pub runs: usize,
+    pub num_rle_values: usize,
+    /// the bits per coordinate and the shift applied to make coordinates positive
+    pub morton: (u32, i32),
+    /// byte offset of the stream
+    pub offset: usize,
+}
+impl StreamMetadata {
+    /// A stream header with no logical techniques
+    pub fn new(physical_type: PhysicalStreamType, logical_type: u8) -> Self {
+        StreamMetadata {
+            physical_type,
+            logical_type,
+            technique1: LogicalTechnique::None,
+            technique2: LogicalTechnique::None,
+            physical_technique: PhysicalTechnique::Varint,
+            num_values: 0,
+            byte_length: 0,
+            runs: 0,
+            num_rle_values: 0,
+            morton: (0, 0),
+            offset: 0,
+        }
+    }
+
+    /// Read a stream header
+    pub fn read(r: &mut Reader) -> Result<Self, MltError> {
+        let offset = r.pos();
+        let stream_type = r.u8()?;
+        let physical_type = match stream_type >> 4 {
+            0 => PhysicalStreamType::Present,
+            1 => PhysicalStreamType::Data,
+            2 => PhysicalStreamType::Offset,
+            3 => PhysicalStreamType::Length,
+            _ => return Err(MltError::Malformed { offset, reason: "unknown stream type" }),
+        };
+        let encodings = r.u8()?;
+        let technique1 = LogicalTechnique::from_bits(encodings >> 5, offset)?;
+        let technique2 = LogicalTechnique::from_bits((encodings >> 2) & 0x7, offset)?;
+        let physical_technique = match encodings & 0x3 {
+            0 => PhysicalTechnique::None,
+            1 => PhysicalTechnique::FastPfor,
+            2 => PhysicalTechnique::Varint,
+            _ => PhysicalTechnique::Alp,
+        };
+        let mut meta = StreamMetadata {
+            physical_type,
+            logical_type: stream_type & 0xf,
+            technique1,
+            technique2,
+            physical_technique,
+            num_values: r.usize()?,
+            byte_length: r.usize()?,
+            runs: 0,
+            num_rle_values: 0,
+            morton: (0, 0),
+            offset,
+        };
+        if technique1 == LogicalTechnique::Morton {
+            meta.morton = (r.varint()? as u32, r.varint()? as i32);
+        } else if (technique1 == LogicalTechnique::Rle || technique2 == LogicalTechnique::Rle)
+            && physical_technique != PhysicalTechnique::None
+        {
+            meta.runs = r.usize()?;
+            meta.num_rle_values = r.usize()?;
+        }
+        Ok(meta)
+    }
+
+    /// Write the header followed by the stream's bytes
+    pub fn write(&self, buf: &mut Vec<u8>, data: &[u8]) {
+        buf.push(((self.physical_type as u8) << 4) | self.logical_type);
+        buf.push(
+            ((self.technique1 as u8) << 5)
+                | ((self.technique2 as u8) << 2)
+                | self.physical_technique as u8,
+        );
+        write_varint(buf, self.num_values as u64);
+        write_varint(buf, data.len() as u64);
+        if self.technique1 == LogicalTechnique::Morton {
+            write_varint(buf, self.morton.0 as u64);
+            write_varint(buf, self.morton.1 as u64);
+        } else if (self.technique1 == LogicalTechnique::Rle
+            || self.technique2 == LogicalTechnique::Rle)
+            && self.physical_technique != PhysicalTechnique::None
+        {
+            write_varint(buf, self.runs as u64);
+            write_varint(buf, self.num_rle_values as u64);
+        }
+        buf.extend_from_slice(data);
+    }
+
+    fn malformed(&self, reason: &'static str) -> MltError {
+        MltError::Malformed { offset: self.offset, reason }
+    }
+
+    fn unsupported(&self, reason: &'static str) -> MltError {
+        MltError::Unsupported { offset: self.offset, reason }
+    }
+}
+
+/// A cursor over MLT data that returns an error instead of reading past the end
+#[derive(Debug, Clone)]
+pub(crate) struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+impl<'a> Reader<'a> {
+    /// Create a reader at the start of the buffer
+    pub fn new(buf: &'a [u8]) -> Self {
+        Reader { buf, pos: 0 }
+    }
+
+    /// the current position
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// move to a position
+    pub fn set_pos(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /// true once every byte has been read
+    pub fn is_done(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+
+    /// Read a byte
+    pub fn u8(&mut self) -> Result<u8, MltError> {
+        let byte = *self.buf.get(self.pos).ok_or(MltError::UnexpectedEof { offset: self.pos })?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    /// Read a varint
+    pub fn varint(&mut self) -> Result<u64, MltError> {
+        let offset = self.pos;
+        let mut val = 0;
+        for shift in (0..70).step_by(7) {
+            let byte = self.u8()?;
+            val |= ((byte & 0x7f) as u64) << shift.min(63);
+            if byte < 0x80 {
+                return Ok(val);
+            }
+        }
+        Err(MltError::Malformed { offset, reason: "varint is too long" })
+    }
+
+    /// Read a varint that counts or indexes something in the buffer
+    pub fn usize(&mut self) -> Result<usize, MltError> {
+        let offset = self.pos;
+        usize::try_from(self.varint()?)
+            .map_err(|_| MltError::Malformed { offset, reason: "length is too large" })
+    }
+
+    /// Read `len` bytes without copying them
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], MltError> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.buf.get(self.pos..end))
+            .ok_or(MltError::UnexpectedEof { offset: self.pos })?;
+        self.pos += len;
+        Ok(bytes)
+    }
+}
+
+/// Read the present stream at the reader's position if there is one
+pub(crate) fn read_present(r: &mut Reader) -> Result<Option<Vec<bool>>, MltError> {
+    let pos = r.pos();
+    let meta = StreamMetadata::read(r)?;
+    if meta.physical_type != PhysicalStreamType::Present {
+        r.set_pos(pos);
+        return Ok(None);
+    }
+    decode_booleans(r, &meta).map(Some)
+}
+
+/// Decode a stream of booleans packed into bits with byte RLE
+pub(crate) fn decode_booleans(
+    r: &mut Reader,
+    meta: &StreamMetadata,
+) -> Result<Vec<bool>, MltError> {
+    let mut data = Reader::new(r.bytes(meta.byte_length)?);
+    let mut bytes = Vec::with_capacity(meta.num_values.div_ceil(8));
+    while !data.is_done() {
+        let header = data.u8()?;
+        if header < 0x80 {
+            // a run of the next byte
+            let byte = data.u8()?;
+            bytes.extend(core::iter::repeat_n(byte, header as usize + 3));
+        } else {
+            // literal bytes
+            bytes.extend_from_slice(data.bytes(256 - header as usize)?);
+        }
+    }
+    if bytes.len() * 8 < meta.num_values {
+        return Err(meta.malformed("boolean stream is too short"));
+    }
+    Ok((0..meta.num_values).map(|i| bytes[i / 8] & (1 << (i % 8)) != 0).collect())
+}
+
+/// Decode a stream of integers. Signed values are returned as their two's complement bits
+pub(crate) fn decode_ints(
+    r: &mut Reader,
+    meta: &StreamMetadata,
+    signed: bool,
+) -> Result<Vec<u64>, MltError> {
+    let values = read_varints(r, meta)?;
+    let decode = |value: u64| if signed { zagzig(value) as u64 } else { value };
+    match (meta.technique1, meta.technique2) {
+        (LogicalTechnique::None, _) => Ok(values.into_iter().map(decode).collect()),
+        (LogicalTechnique::Rle, _) => {
+            Ok(decode_rle(&values, meta)?.into_iter().map(decode).collect())
+        }
+        (LogicalTechnique::Delta, technique) => {
+            let values = if technique == LogicalTechnique::Rle {
+                decode_rle(&values, meta)?
+            } else {
+                values
+            };
+            let mut prev = 0_u64;
+            Ok(values
+                .into_iter()
+                .map(|delta| {
+                    prev = prev.wrapping_add(zagzig(delta) as u64);
+                    prev
+                })
+                .collect())
+        }
+        _ => Err(meta.unsupported("logical technique for integers")),
+    }
+}
+
+/// Decode a vertex buffer or morton encoded vertex dictionary
+pub(crate) fn decode_vertices(
+    r: &mut Reader,
+    meta: &StreamMetadata,
+) -> Result<Vec<Point>, MltError> {
+    let values = read_varints(r, meta)?;
+    match meta.technique1 {
+        LogicalTechnique::ComponentwiseDelta | LogicalTechnique::None => {
+            let delta = meta.technique1 == LogicalTechnique::ComponentwiseDelta;
+            let (mut x, mut y) = (0_i32, 0_i32);
+            Ok(values
+                .as_chunks::<2>()
+                .0
+                .iter()
+                .map(|[dx, dy]| {
+                    let (dx, dy) = (zagzig(*dx) as i32, zagzig(*dy) as i32);
+                    (x, y) =
+                        if delta { (x.wrapping_add(dx), y.wrapping_add(dy)) } else { (dx, dy) };
+                    Point::new(x, y)
+                })
+                .collect())
+        }
+        LogicalTechnique::Morton => {
+            let (bits, shift) = meta.morton;
+            if bits > 32 {
+                return Err(meta.malformed("morton codes have too many bits"));
+            }
+            let delta = meta.technique2 == LogicalTechnique::Delta;
+            let mut code = 0_u64;
+            Ok(values
+                .into_iter()
+                .map(|value| {
+                    code = if delta { code.wrapping_add(zagzig(value) as u64) } else { value };
+                    let (mut x, mut y) = (0_i64, 0_i64);
+                    for bit in 0..bits {
+                        x |= ((code >> (2 * bit)) & 1) as i64 * (1 << bit);
+                        y |= ((code >> (2 * bit + 1)) & 1) as i64 * (1 << bit);
+                    }
+                    Point::new((x - shift as i64) as i32, (y - shift as i64) as i32)
+                })
+                .collect())
+        }
+        _ => Err(meta.unsupported("logical technique for vertices")),
+    }
+}
+
+/// Decode a stream of little endian 32 bit floats
+pub(crate) fn decode_floats(r: &mut Reader, meta: &StreamMetadata) -> Result<Vec<f32>, MltError> {
+    if meta.physical_technique != PhysicalTechnique::None {
+        return Err(meta.unsupported("physical technique for floats"));
+    }
+    let bytes = r.bytes(meta.byte_length)?;
+    if meta.num_values.checked_mul(4) != Some(bytes.len()) {
+        return Err(meta.malformed("float stream length does not match its values"));
+    }
+    Ok(bytes.as_chunks::<4>().0.iter().map(|bytes| f32::from_le_bytes(*bytes)).collect())
+}
+
+/// Decode a stream of little endian 64 bit floats
+pub(crate) fn decode_doubles(r: &mut Reader, meta: &StreamMetadata) -> Result<Vec<f64>, MltError> {
+    if meta.physical_technique != PhysicalTechnique::None {
+        return Err(meta.unsupported("physical technique for doubles"));
+    }
+    let bytes = r.bytes(meta.byte_length)?;
+    if meta.num_values.checked_mul(8) != Some(bytes.len()) {
+        return Err(meta.malformed("double stream length does not match its values"));
+    }
+    Ok(bytes.as_chunks::<8>().0.iter().map(|bytes| f64::from_le_bytes(*bytes)).collect())
+}
+
+/// Unpack the varints of a stream
+fn read_varints(r: &mut Reader, meta: &StreamMetadata) -> Result<Vec<u64>, MltError> {
+    match meta.physical_technique {
+        PhysicalTechnique::Varint => {}
+        PhysicalTechnique::FastPfor => return Err(meta.unsupported("FastPFOR stream")),
+        _ => return Err(meta.unsupported("physical technique for integers")),
+    }
+    let mut data = Reader::new(r.bytes(meta.byte_length)?);
+    // every varint is at least one byte
+    let mut values = Vec::with_capacity(meta.num_values.min(meta.byte_length));
+    while !data.is_done() {
+        values.push(data.varint()?);
+    }
+    if values.len() != meta.num_values {
+        return Err(meta.malformed("stream length does not match its values"));
+    }
+    Ok(values)
+}
+
+/// Expand runs stored as all of their lengths followed by all of their values
+fn decode_rle(values: &[u64], meta: &StreamMetadata) -> Result<Vec<u64>, MltError> {
+    if values.len() != meta.runs * 2 {
+        return Err(meta.malformed("RLE stream does not match its runs"));
+    }
+    let (lengths, values) = values.split_at(meta.runs);
+    let mut res = Vec::new();
+    for (length, value) in lengths.iter().zip(values) {
+        let remaining = meta.num_rle_values - res.len();
+        if *length > remaining as u64 {
+            return Err(meta.malformed("RLE runs are longer than the stream"));
+        }
+        res.extend(core::iter::repeat_n(*value, *length as usize));
+    }
+    if res.len() != meta.num_rle_values {
+        return Err(meta.malformed("RLE runs are shorter than the stream"));
+    }
+    Ok(res)
+}
+
+/// Write a varint
+pub(crate) fn write_varint(buf: &mut Vec<u8>, mut val: u64) {
+    while val >= 0x80 {
+        buf.push((val as u8 & 0x7f) | 0x80);
+        val >>= 7;
+    }
+    buf.push(val as u8);
+}
+
+/// Write a stream of integers with whichever of plain, delta, RLE or delta RLE encoding is smallest.
+/// Signed values are given as their two's complement bits
+pub(crate) fn encode_ints(
+    buf: &mut Vec<u8>,
+    physical_type: PhysicalStreamType,
+    logical_type: u8,
+    values: &[u64],
+    signed: bool,
+) {
+    let encode = |value: u64| if signed { zigzag(value as i64) } else { value };
+    let plain: Vec<u64> = values.iter().map(|value| encode(*value)).collect();
+    let mut prev = 0_u64;
+    let deltas: Vec<u64> = values
+        .iter()
+        .map(|value| {
+            let delta = zigzag(value.wrapping_sub(prev) as i64);
+            prev = *value;
+            delta
+        })
+        .collect();
+
+    let mut best = StreamMetadata::new(physical_type, logical_type);
+    let mut best_data = pack_varints(&plain);
+    let mut candidates = vec![(LogicalTechnique::Delta, LogicalTechnique::None, deltas.clone())];
+    if let Some((rle, runs)) = encode_rle(&plain) {
+        candidates.push((LogicalTechnique::Rle, LogicalTechnique::None, rle));
+        if let Some((delta_rle, delta_runs)) = encode_rle(&deltas)
+            && delta_runs < runs
+        {
+            candidates.push((LogicalTechnique::Delta, LogicalTechnique::Rle, delta_rle));
+        }
+    }
+    for (technique1, technique2, encoded) in candidates {
+        let data = pack_varints(&encoded);
+        // RLE headers cost a couple of bytes, so only switch when it is smaller
+        if data.len() + 2 < best_data.len() {
+            best.technique1 = technique1;
+            best.technique2 = technique2;
+            best.num_values = encoded.len();
+            if technique1 == LogicalTechnique::Rle || technique2 == LogicalTechnique::Rle {
+                best.runs = encoded.len() / 2;
+                best.num_rle_values = values.len();
+            }
+            best_data = data;
+        }
+    }
+    if best.technique1 == LogicalTechnique::None {
+        best.num_values = values.len();
+    }
+    best.write(buf, &best_data);
+}
+
+/// Write a stream of booleans packed into bits with byte RLE
+pub(crate) fn encode_booleans(buf: &mut Vec<u8>, physical_type: PhysicalStreamType, bits: &[bool]) {
+    let mut bytes = vec![0_u8; bits.len().div_ceil(8)];
+    for (i, _) in bits.iter().enumerate().filter(|(_, bit)| **bit) {
+        bytes[i / 8] |= 1 << (i % 8);
+    }
+    let mut data = Vec::with_capacity(bytes.len() + 2);
+    let mut literals: Vec<u8> = vec![];
+    let flush = |data: &mut Vec<u8>, literals: &mut Vec<u8>| {
+        for chunk in literals.chunks(128) {
+            data.push((256 - chunk.len()) as u8);
+            data.extend_from_slice(chunk);
+        }
+        literals.clear();
+    };
+    let mut i = 0;
+    while i < bytes.len() {
+        let run = bytes[i..].iter().take(130).take_while(|byte| **byte == bytes[i]).count();
+        if run >= 3 {
+            flush(&mut data, &mut literals);
+            data.push((run - 3) as u8);
+            data.push(bytes[i]);
+            i += run;
+        } else {
+            literals.push(bytes[i]);
+            i += 1;
+        }
+    }
+    flush(&mut data, &mut literals);
+
+    let mut meta = StreamMetadata::new(physical_type, DICTIONARY_NONE);
+    meta.technique1 = LogicalTechnique::Rle;
+    meta.physical_technique = PhysicalTechnique::None;
+    meta.num_values = bits.len();
+    meta.write(buf, &data);
+}
+
+/// Write a vertex buffer with each coordinate delta encoded against the previous vertex
+pub(crate) fn encode_vertices(buf: &mut Vec<u8>, vertices: &[Point]) {
+    let (mut x, mut y) = (0_i32, 0_i32);
+    let mut values = Vec::with_capacity(vertices.len() * 2);
+    for point in vertices {
+        values.push(zigzag(point.x.wrapping_sub(x) as i64));
+        values.push(zigzag(point.y.wrapping_sub(y) as i64));
+        (x, y) = (point.x, point.y);
+    }
+    let mut meta = StreamMetadata::new(PhysicalStreamType::Data, DICTIONARY_VERTEX);
+    meta.technique1 = LogicalTechnique::ComponentwiseDelta;
+    meta.num_values = values.len();
+    meta.write(buf, &pack_varints(&values));
+}
+
+/// Write a `Data` stream of raw little endian values
+pub(crate) fn encode_bytes(buf: &mut Vec<u8>, logical_type: u8, num_values: usize, data: &[u8]) {
+    let mut meta = StreamMetadata::new(PhysicalStreamType::Data, logical_type);
+    meta.physical_technique = PhysicalTechnique::None;
+    meta.num_values = num_values;
+    meta.write(buf, data);
+}
+
+/// Pack values as varints
+fn pack_varints(values: &[u64]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(values.len());
+    for value in values {
+        write_varint(&mut data, *value);
+    }
+    data
+}
+
+/// Run lengths followed by the run values, and the number of runs. None if every run is one value
+fn encode_rle(values: &[u64]) -> Option<(Vec<u64>, usize)> {
+    let mut lengths: Vec<u64> = vec![];
+    let mut run_values: Vec<u64> = vec![];
+    for value in values {
+        match run_values.last() {
+            Some(last) if last == value => *lengths.last_mut().unwrap() += 1,
+            _ => {
+                lengths.push(1);
+                run_values.push(*value);
+            }
+        }
+    }
+    let runs = lengths.len();
+    if runs == values.len() {
+        return None;
+    }
+    lengths.extend(run_values);
+    Some((lengths, runs))
+}
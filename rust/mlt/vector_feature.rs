@@ -0,0 +1,283 @@
+use crate::{
+    VectorFeatureMethods, VectorGeometry, VectorLines3DWithOffset, VectorLinesWithOffset,
+    VectorPoints, VectorPoints3D,
+    mapbox::{flatten_polys, geometry_lines, geometry_points, geometry_polys},
+    open::FeatureType as OpenFeatureType,
+};
+use alloc::{vec, vec::Vec};
+use s2json::{BBOX, MapboxProperties, Properties};
+
+/// The geometry type of an MLT feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeometryType {
+    /// a single point
+    Point = 0,
+    /// a single line
+    LineString = 1,
+    /// a single polygon
+    Polygon = 2,
+    /// a collection of points
+    MultiPoint = 3,
+    /// a collection of lines
+    MultiLineString = 4,
+    /// a collection of polygons
+    MultiPolygon = 5,
+}
+impl GeometryType {
+    /// Parse a geometry type from its stored value
+    pub fn from_u64(value: u64) -> Option<Self> {
+        Some(match value {
+            0 => GeometryType::Point,
+            1 => GeometryType::LineString,
+            2 => GeometryType::Polygon,
+            3 => GeometryType::MultiPoint,
+            4 => GeometryType::MultiLineString,
+            5 => GeometryType::MultiPolygon,
+            _ => return None,
+        })
+    }
+}
+impl From<&GeometryType> for OpenFeatureType {
+    fn from(value: &GeometryType) -> Self {
+        match value {
+            GeometryType::Point | GeometryType::MultiPoint => OpenFeatureType::Points,
+            GeometryType::LineString | GeometryType::MultiLineString => OpenFeatureType::Lines,
+            GeometryType::Polygon | GeometryType::MultiPolygon => OpenFeatureType::Polygons,
+        }
+    }
+}
+
+/// MapLibre Tile specification for a Feature. MLT tiles are columnar, so every feature is decoded
+/// with its layer
+#[derive(Debug, Clone)]
+pub struct MltVectorFeature {
+    /// the id of the feature
+    pub id: Option<u64>,
+    /// the version of the vector tile
+    pub version: u16,
+    /// the properties
+    pub properties: MapboxProperties,
+    /// the extent
+    pub extent: usize,
+    /// the geometry type
+    pub r#type: GeometryType,
+    /// the geometry. Polygon rings are closed
+    pub geometry: VectorGeometry,
+}
+impl VectorFeatureMethods for &MltVectorFeature {
+    /// get the feature id
+    fn id(&self) -> Option<u64> {
+        self.id
+    }
+
+    /// get the feature version
+    fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// get the feature properties
+    fn properties(&self) -> Properties {
+        (&self.properties).into()
+    }
+
+    /// get the feature extent
+    fn extent(&self) -> usize {
+        self.extent
+    }
+
+    /// get the feature type
+    fn get_type(&self) -> OpenFeatureType {
+        (&self.r#type).into()
+    }
+
+    /// get the bbox
+    fn bbox(&self) -> Option<BBOX> {
+        None
+    }
+
+    /// whether the feature has m values
+    fn has_m_values(&self) -> bool {
+        false
+    }
+
+    /// whether the feature is a points type
+    fn is_points(&self) -> bool {
+        matches!(self.r#type, GeometryType::Point | GeometryType::MultiPoint)
+    }
+
+    /// whether the feature is a line type
+    fn is_lines(&self) -> bool {
+        matches!(self.r#type, GeometryType::LineString | GeometryType::MultiLineString)
+    }
+
+    /// whether the feature is a polygon type
+    fn is_polygons(&self) -> bool {
+        matches!(self.r#type, GeometryType::Polygon | GeometryType::MultiPolygon)
+    }
+
+    /// whether the feature is a points 3D type
+    fn is_points_3d(&self) -> bool {
+        false
+    }
+
+    /// whether the feature is a line 3D type
+    fn is_lines_3d(&self) -> bool {
+        false
+    }
+
+    /// whether the feature is a polygon 3D type
+    fn is_polygons_3d(&self) -> bool {
+        false
+    }
+
+    /// regardless of the type, we return a flattend point array
+    fn load_points(&mut self) -> VectorPoints {
+        geometry_points(self.load_geometry())
+    }
+
+    fn load_points_3d(&mut self) -> VectorPoints3D {
+        panic!("unexpected geometry type")
+    }
+
+    /// an array of lines. The offsets will be set to 0
+    fn load_lines(&mut self) -> VectorLinesWithOffset {
+        geometry_lines(self.load_geometry())
+    }
+
+    /// an array of 3D lines. The offsets will be set to 0
+    fn load_lines_3d(&mut self) -> VectorLines3DWithOffset {
+        panic!("unexpected geometry type")
+    }
+
+    /// an array of polys
+    fn load_polys(&mut self) -> Vec<VectorLinesWithOffset> {
+        geometry_polys(self.load_geometry())
+    }
+
+    /// an array of 3D polys
+    fn load_polys_3d(&mut self) -> Vec<VectorLines3DWithOffset> {
+        panic!("unexpected geometry type")
+    }
+
+    /// (flattened geometry & tesslation if applicable, indices)
+    fn load_geometry_flat(&mut self) -> (Vec<f64>, Vec<u32>) {
+        let multiplier: f64 = 1.0 / self.extent as f64;
+        (flatten_polys(self.load_geometry(), multiplier), vec![])
+    }
+
+    /// load the geometry
+    fn load_geometry(&mut self) -> VectorGeometry {
+        self.geometry.clone()
+    }
+
+    /// load the indices
+    fn read_indices(&mut self) -> Vec<u32> {
+        vec![]
+    }
+
+    /// Add tessellation data to the geometry
+    fn add_tessellation(&mut self, _geometry: &mut Vec<f64>, _multiplier: f64) {}
+
+    /// Add 3D tessellation data to the geometry
+    fn add_tessellation_3d(&mut self, _geometry: &mut Vec<f64>, _multiplier: f64) {
+        panic!("unexpected geometry type")
+    }
+}
+// the borrowed feature holds the implementation, like the other vector features
+impl VectorFeatureMethods for MltVectorFeature {
+    fn id(&self) -> Option<u64> {
+        <&Self>::id(&self)
+    }
+
+    fn version(&self) -> u16 {
+        <&Self>::version(&self)
+    }
+
+    fn properties(&self) -> Properties {
+        <&Self>::properties(&self)
+    }
+
+    fn extent(&self) -> usize {
+        <&Self>::extent(&self)
+    }
+
+    fn get_type(&self) -> OpenFeatureType {
+        <&Self>::get_type(&self)
+    }
+
+    fn bbox(&self) -> Option<BBOX> {
+        <&Self>::bbox(&self)
+    }
+
+    fn has_m_values(&self) -> bool {
+        <&Self>::has_m_values(&self)
+    }
+
+    fn is_points(&self) -> bool {
+        <&Self>::is_points(&self)
+    }
+
+    fn is_lines(&self) -> bool {
+        <&Self>::is_lines(&self)
+    }
+
+    fn is_polygons(&self) -> bool {
+        <&Self>::is_polygons(&self)
+    }
+
+    fn is_points_3d(&self) -> bool {
+        <&Self>::is_points_3d(&self)
+    }
+
+    fn is_lines_3d(&self) -> bool {
+        <&Self>::is_lines_3d(&self)
+    }
+
+    fn is_polygons_3d(&self) -> bool {
+        <&Self>::is_polygons_3d(&self)
+    }
+
+    fn load_points(&mut self) -> VectorPoints {
+        <&Self>::load_points(&mut &*self)
+    }
+
+    fn load_points_3d(&mut self) -> VectorPoints3D {
+        <&Self>::load_points_3d(&mut &*self)
+    }
+
+    fn load_lines(&mut self) -> VectorLinesWithOffset {
+        <&Self>::load_lines(&mut &*self)
+    }
+
+    fn load_lines_3d(&mut self) -> VectorLines3DWithOffset {
+        <&Self>::load_lines_3d(&mut &*self)
+    }
+
+    fn load_polys(&mut self) -> Vec<VectorLinesWithOffset> {
+        <&Self>::load_polys(&mut &*self)
+    }
+
+    fn load_polys_3d(&mut self) -> Vec<VectorLines3DWithOffset> {
+        <&Self>::load_polys_3d(&mut &*self)
+    }
+
+    fn load_geometry_flat(&mut self) -> (Vec<f64>, Vec<u32>) {
+        <&Self>::load_geometry_flat(&mut &*self)
+    }
+
+    fn load_geometry(&mut self) -> VectorGeometry {
+        <&Self>::load_geometry(&mut &*self)
+    }
+
+    fn read_indices(&mut self) -> Vec<u32> {
+        <&Self>::read_indices(&mut &*self)
+    }
+
+    fn add_tessellation(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
+        <&Self>::add_tessellation(&mut &*self, geometry, multiplier)
+    }
+
+    fn add_tessellation_3d(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
+        <&Self>::add_tessellation_3d(&mut &*self, geometry, multiplier)
+    }
+}
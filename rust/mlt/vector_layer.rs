@@ -0,0 +1,660 @@
+use crate::{
+    Point, VectorFeature, VectorFeatureRef, VectorGeometry, VectorLayerFeatures,
+    VectorLayerMethods, VectorLineWithOffset, VectorLinesWithOffset,
+    base::{BaseVectorFeature, BaseVectorLayer},
+    error::EncodeError,
+    mapbox::check_layer,
+    mlt::{
+        Column, ColumnType, FeatureTableSchema, GeometryType, MltError, MltVectorFeature,
+        ScalarType, TileSetMetadata,
+        stream::{
+            DICTIONARY_MORTON, DICTIONARY_NONE, DICTIONARY_SINGLE, DICTIONARY_VERTEX,
+            LENGTH_DICTIONARY, LENGTH_GEOMETRIES, LENGTH_PARTS, LENGTH_RINGS, LENGTH_VAR_BINARY,
+            OFFSET_STRING, OFFSET_VERTEX, PhysicalStreamType, Reader, StreamMetadata,
+            decode_booleans, decode_doubles, decode_floats, decode_ints, decode_vertices,
+            encode_booleans, encode_bytes, encode_ints, encode_vertices, read_present,
+            write_varint,
+        },
+    },
+};
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use s2json::{MapboxProperties, PrimitiveShape, PrimitiveValue};
+
+/// `Length` streams of triangle counts, which tessellated polygons carry
+const LENGTH_TRIANGLES: u8 = 4;
+/// `Offset` streams of triangle indices, which tessellated polygons carry
+const OFFSET_INDEX: u8 = 1;
+
+/// MapLibre Tile specification for a Layer
+#[derive(Debug, Clone)]
+pub struct MltVectorLayer {
+    /// the version of the MLT layer
+    pub version: u16,
+    /// the name of the layer
+    pub name: String,
+    /// the extent of the vector layer
+    pub extent: usize,
+    /// the features in the layer
+    pub features: Vec<MltVectorFeature>,
+}
+impl VectorLayerMethods for MltVectorLayer {
+    fn version(&self) -> u16 {
+        self.version
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn extent(&self) -> usize {
+        self.extent
+    }
+
+    /// the number of features in the layer
+    fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Check if the layer is empty
+    fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    fn feature(&mut self, i: usize) -> Option<VectorFeature<'_>> {
+        self.features.get_mut(i).map(Into::into)
+    }
+}
+impl VectorLayerFeatures for MltVectorLayer {
+    fn get_feature(&self, i: usize) -> Option<VectorFeatureRef<'_>> {
+        self.features.get(i).map(Into::into)
+    }
+}
+
+/// Decode the layer at the reader's position
+pub(crate) fn read_layer(
+    r: &mut Reader,
+    metadata: &TileSetMetadata,
+) -> Result<MltVectorLayer, MltError> {
+    let offset = r.pos();
+    let version = r.u8()? as u16;
+    if version != 1 {
+        return Err(MltError::Unsupported { offset, reason: "layer version" });
+    }
+    let table_id = r.varint()?;
+    let extent = r.usize()?;
+    // the largest coordinate in the layer, which is only needed to pick bit widths
+    r.varint()?;
+    let num_features = r.usize()?;
+    let schema = usize::try_from(table_id)
+        .ok()
+        .and_then(|id| metadata.feature_tables.get(id))
+        .ok_or(MltError::UnknownFeatureTable(table_id))?;
+
+    let mut ids: Vec<Option<u64>> = vec![];
+    let mut geometry: Option<Vec<(GeometryType, VectorGeometry)>> = None;
+    let mut properties: Vec<MapboxProperties> = vec![];
+    for (i, column) in schema.columns.iter().enumerate() {
+        let offset = r.pos();
+        let num_streams = r.usize()?;
+        match column.r#type {
+            ColumnType::Scalar(_) if i == 0 && column.name == "id" => {
+                let present = read_present(r)?;
+                let meta = StreamMetadata::read(r)?;
+                let values = decode_ints(r, &meta, false)?;
+                ids = with_present(values, present, num_features, offset)?;
+            }
+            ColumnType::Geometry => geometry = Some(read_geometry(r, num_streams, num_features)?),
+            ColumnType::Scalar(scalar) => {
+                let present = read_present(r)?;
+                let values =
+                    read_values(r, scalar, num_streams.saturating_sub(present.is_some() as usize))?;
+                let values = with_present(values, present, num_features, offset)?;
+                if properties.is_empty() {
+                    properties = vec![MapboxProperties::new(); num_features];
+                }
+                for (properties, value) in properties.iter_mut().zip(values) {
+                    if let Some(value) = value {
+                        properties.insert(column.name.clone(), value);
+                    }
+                }
+            }
+            ColumnType::Unsupported => {
+                return Err(MltError::Unsupported { offset, reason: "column type" });
+            }
+        }
+    }
+    let geometry =
+        geometry.ok_or(MltError::Malformed { offset, reason: "layer has no geometry" })?;
+    if properties.is_empty() {
+        properties = vec![MapboxProperties::new(); num_features];
+    }
+    if ids.is_empty() {
+        ids = vec![None; num_features];
+    }
+
+    let features = geometry
+        .into_iter()
+        .zip(ids)
+        .zip(properties)
+        .map(|(((r#type, geometry), id), properties)| MltVectorFeature {
+            id,
+            version,
+            properties,
+            extent,
+            r#type,
+            geometry,
+        })
+        .collect();
+    Ok(MltVectorLayer { version, name: schema.name.clone(), extent, features })
+}
+
+/// Spread the values of a column over the features that have one
+fn with_present<T>(
+    values: Vec<T>,
+    present: Option<Vec<bool>>,
+    num_features: usize,
+    offset: usize,
+) -> Result<Vec<Option<T>>, MltError> {
+    let wrong_len = MltError::Malformed { offset, reason: "column does not match its features" };
+    let Some(present) = present else {
+        if values.len() != num_features {
+            return Err(wrong_len);
+        }
+        return Ok(values.into_iter().map(Some).collect());
+    };
+    if present.len() != num_features || present.iter().filter(|p| **p).count() != values.len() {
+        return Err(wrong_len);
+    }
+    let mut values = values.into_iter();
+    Ok(present.into_iter().map(|p| if p { values.next() } else { None }).collect())
+}
+
+/// Decode the data streams of a property column
+fn read_values(
+    r: &mut Reader,
+    scalar: ScalarType,
+    num_streams: usize,
+) -> Result<Vec<PrimitiveValue>, MltError> {
+    if scalar == ScalarType::String {
+        return read_strings(r, num_streams);
+    }
+    let meta = StreamMetadata::read(r)?;
+    Ok(match scalar {
+        ScalarType::Boolean => {
+            decode_booleans(r, &meta)?.into_iter().map(PrimitiveValue::Bool).collect()
+        }
+        ScalarType::Float => {
+            decode_floats(r, &meta)?.into_iter().map(PrimitiveValue::F32).collect()
+        }
+        ScalarType::Double => {
+            decode_doubles(r, &meta)?.into_iter().map(PrimitiveValue::F64).collect()
+        }
+        _ if scalar.is_signed() => decode_ints(r, &meta, true)?
+            .into_iter()
+            .map(|value| PrimitiveValue::I64(value as i64))
+            .collect(),
+        _ => decode_ints(r, &meta, false)?.into_iter().map(PrimitiveValue::U64).collect(),
+    })
+}
+
+/// Decode the streams of a plain or dictionary encoded string column
+fn read_strings(r: &mut Reader, num_streams: usize) -> Result<Vec<PrimitiveValue>, MltError> {
+    let offset = r.pos();
+    let mut lengths = None;
+    let mut indices = None;
+    let mut data: &[u8] = &[];
+    for _ in 0..num_streams {
+        let meta = StreamMetadata::read(r)?;
+        match (meta.physical_type, meta.logical_type) {
+            (PhysicalStreamType::Length, LENGTH_VAR_BINARY | LENGTH_DICTIONARY) => {
+                lengths = Some(decode_ints(r, &meta, false)?)
+            }
+            (PhysicalStreamType::Offset, OFFSET_STRING) => {
+                indices = Some(decode_ints(r, &meta, false)?)
+            }
+            (PhysicalStreamType::Data, DICTIONARY_NONE | DICTIONARY_SINGLE) => {
+                data = r.bytes(meta.byte_length)?
+            }
+            _ => {
+                return Err(MltError::Unsupported { offset: meta.offset, reason: "string stream" });
+            }
+        }
+    }
+    let lengths =
+        lengths.ok_or(MltError::Malformed { offset, reason: "strings have no lengths" })?;
+    let mut strings = Vec::with_capacity(lengths.len());
+    let mut start = 0_usize;
+    for length in lengths {
+        let string = usize::try_from(length)
+            .ok()
+            .and_then(|length| data.get(start..start.checked_add(length)?))
+            .ok_or(MltError::Malformed { offset, reason: "string is longer than its data" })?;
+        start += string.len();
+        let string = core::str::from_utf8(string)
+            .map_err(|_| MltError::Malformed { offset, reason: "string is not UTF-8" })?;
+        strings.push(PrimitiveValue::String(string.into()));
+    }
+    match indices {
+        None => Ok(strings),
+        Some(indices) => indices
+            .into_iter()
+            .map(|i| {
+                strings
+                    .get(i as usize)
+                    .cloned()
+                    .ok_or(MltError::Malformed { offset, reason: "string index out of range" })
+            })
+            .collect(),
+    }
+}
+
+/// Hands out the values of a geometry stream in order
+struct Lengths<'a> {
+    values: &'a [u64],
+    pos: usize,
+    offset: usize,
+}
+impl Lengths<'_> {
+    fn next(&mut self) -> Result<usize, MltError> {
+        let value = self.values.get(self.pos).ok_or(MltError::Malformed {
+            offset: self.offset,
+            reason: "geometry streams are shorter than the geometry types",
+        })?;
+        self.pos += 1;
+        usize::try_from(*value)
+            .map_err(|_| MltError::Malformed { offset: self.offset, reason: "length is too large" })
+    }
+}
+
+/// Decode the streams of the geometry column
+fn read_geometry(
+    r: &mut Reader,
+    num_streams: usize,
+    num_features: usize,
+) -> Result<Vec<(GeometryType, VectorGeometry)>, MltError> {
+    let offset = r.pos();
+    let meta = StreamMetadata::read(r)?;
+    let types = decode_ints(r, &meta, false)?;
+    if types.len() != num_features {
+        return Err(MltError::Malformed { offset, reason: "geometry types do not match features" });
+    }
+    let (mut geometries, mut parts, mut rings, mut offsets) = (vec![], vec![], vec![], None);
+    let mut vertices = vec![];
+    for _ in 1..num_streams {
+        let meta = StreamMetadata::read(r)?;
+        match (meta.physical_type, meta.logical_type) {
+            (PhysicalStreamType::Length, LENGTH_GEOMETRIES) => {
+                geometries = decode_ints(r, &meta, false)?
+            }
+            (PhysicalStreamType::Length, LENGTH_PARTS) => parts = decode_ints(r, &meta, false)?,
+            (PhysicalStreamType::Length, LENGTH_RINGS) => rings = decode_ints(r, &meta, false)?,
+            (PhysicalStreamType::Offset, OFFSET_VERTEX) => {
+                offsets = Some(decode_ints(r, &meta, false)?)
+            }
+            (PhysicalStreamType::Data, DICTIONARY_VERTEX | DICTIONARY_MORTON) => {
+                vertices = decode_vertices(r, &meta)?
+            }
+            // pre-tessellated polygons are triangulated again when needed
+            (PhysicalStreamType::Length, LENGTH_TRIANGLES)
+            | (PhysicalStreamType::Offset, OFFSET_INDEX) => {
+                r.bytes(meta.byte_length)?;
+            }
+            _ => {
+                return Err(MltError::Unsupported {
+                    offset: meta.offset,
+                    reason: "geometry stream",
+                });
+            }
+        }
+    }
+
+    let mut geometries = Lengths { values: &geometries, pos: 0, offset };
+    let mut parts = Lengths { values: &parts, pos: 0, offset };
+    let mut rings = Lengths { values: &rings, pos: 0, offset };
+    let mut offsets = offsets.as_deref().map(|values| Lengths { values, pos: 0, offset });
+    let mut next_vertex = 0;
+    let mut vertex = || -> Result<Point, MltError> {
+        let i = match offsets.as_mut() {
+            Some(offsets) => offsets.next()?,
+            None => {
+                next_vertex += 1;
+                next_vertex - 1
+            }
+        };
+        vertices
+            .get(i)
+            .cloned()
+            .ok_or(MltError::Malformed { offset, reason: "vertex out of range" })
+    };
+    let mut line = |len: usize| -> Result<VectorLineWithOffset, MltError> {
+        let points = (0..len).map(|_| vertex()).collect::<Result<Vec<_>, _>>()?;
+        Ok(VectorLineWithOffset::new(0.0, points))
+    };
+    // lines take their vertex counts from the rings when the layer also has polygons
+    let has_polygons = types.iter().any(|t| *t == 2 || *t == 5);
+
+    let mut res = Vec::with_capacity(num_features);
+    for r#type in types {
+        let r#type = GeometryType::from_u64(r#type)
+            .ok_or(MltError::Malformed { offset, reason: "unknown geometry type" })?;
+        let count = match r#type {
+            GeometryType::MultiPoint
+            | GeometryType::MultiLineString
+            | GeometryType::MultiPolygon => geometries.next()?,
+            _ => 1,
+        };
+        let geometry = match r#type {
+            GeometryType::Point | GeometryType::MultiPoint => VectorGeometry::VectorPoints(
+                (0..count)
+                    .map(|_| line(1).map(|l| l.geometry[0].clone()))
+                    .collect::<Result<_, _>>()?,
+            ),
+            GeometryType::LineString | GeometryType::MultiLineString => {
+                let mut lines: VectorLinesWithOffset = vec![];
+                for _ in 0..count {
+                    let len = if has_polygons { rings.next()? } else { parts.next()? };
+                    lines.push(line(len)?);
+                }
+                VectorGeometry::VectorLines(lines)
+            }
+            GeometryType::Polygon | GeometryType::MultiPolygon => {
+                let mut polys: Vec<VectorLinesWithOffset> = vec![];
+                for _ in 0..count {
+                    let mut poly: VectorLinesWithOffset = vec![];
+                    for _ in 0..parts.next()? {
+                        let mut ring = line(rings.next()?)?;
+                        // rings are stored without their closing point
+                        if let Some(first) = ring.geometry.first().cloned() {
+                            ring.geometry.push(first);
+                        }
+                        poly.push(ring);
+                    }
+                    polys.push(poly);
+                }
+                VectorGeometry::VectorPolys(polys)
+            }
+        };
+        res.push((r#type, geometry));
+    }
+    Ok(res)
+}
+
+/// Write a layer as an MLT layer referencing feature table `table_id`. Returns the encoded layer
+/// and the schema to store as that feature table in the tileset metadata
+pub fn write_layer(
+    layer: &BaseVectorLayer,
+    table_id: u64,
+) -> Result<(Vec<u8>, FeatureTableSchema), EncodeError> {
+    check_layer(layer)?;
+    let num_features = layer.features.len();
+    let properties: Vec<MapboxProperties> =
+        layer.features.iter().map(|f| f.properties().clone().into()).collect();
+    let mut columns = vec![];
+    let mut buf = vec![];
+
+    // id
+    let ids: Vec<Option<u64>> = layer.features.iter().map(BaseVectorFeature::id).collect();
+    if ids.iter().any(Option::is_some) {
+        let nullable = ids.iter().any(Option::is_none);
+        let large = ids.iter().flatten().any(|id| *id > u32::MAX as u64);
+        let scalar = if large { ScalarType::UInt64 } else { ScalarType::UInt32 };
+        columns.push(Column::new("id", nullable, ColumnType::Scalar(scalar)));
+        write_varint(&mut buf, 1 + nullable as u64);
+        if nullable {
+            let present: Vec<bool> = ids.iter().map(Option::is_some).collect();
+            encode_booleans(&mut buf, PhysicalStreamType::Present, &present);
+        }
+        let ids: Vec<u64> = ids.into_iter().flatten().collect();
+        encode_ints(&mut buf, PhysicalStreamType::Data, DICTIONARY_NONE, &ids, false);
+    }
+
+    // geometry
+    columns.push(Column::new("geometry", false, ColumnType::Geometry));
+    let max_extent = write_geometry(&mut buf, layer);
+
+    // properties
+    let mut shapes: BTreeMap<&String, ScalarType> = BTreeMap::new();
+    for (i, properties) in properties.iter().enumerate() {
+        for (key, value) in properties.iter() {
+            let scalar = match shapes.get(key) {
+                Some(scalar) => merge_scalar(*scalar, value)
+                    .ok_or_else(|| shape_mismatch(layer, i, key, *scalar, value))?,
+                None => match value_scalar(value) {
+                    Some(scalar) => scalar,
+                    None => continue,
+                },
+            };
+            shapes.insert(key, scalar);
+        }
+    }
+    for (key, scalar) in shapes {
+        let values: Vec<Option<&PrimitiveValue>> =
+            properties.iter().map(|p| p.get(key).filter(|v| **v != PrimitiveValue::Null)).collect();
+        let nullable = values.iter().any(Option::is_none);
+        columns.push(Column::new(key.clone(), nullable, ColumnType::Scalar(scalar)));
+        let mut streams = vec![];
+        if nullable {
+            let present: Vec<bool> = values.iter().map(Option::is_some).collect();
+            encode_booleans(&mut streams, PhysicalStreamType::Present, &present);
+        }
+        let values: Vec<&PrimitiveValue> = values.into_iter().flatten().collect();
+        let num_streams = write_values(&mut streams, scalar, &values).map_err(|i| {
+            let feature =
+                properties.iter().position(|p| p.get(key) == Some(values[i])).unwrap_or(0);
+            shape_mismatch(layer, feature, key, scalar, values[i])
+        })?;
+        write_varint(&mut buf, (num_streams + nullable as usize) as u64);
+        buf.extend(streams);
+    }
+
+    let mut res = vec![1];
+    write_varint(&mut res, table_id);
+    write_varint(&mut res, layer.extent as u64);
+    write_varint(&mut res, max_extent as u64);
+    write_varint(&mut res, num_features as u64);
+    res.extend(buf);
+    Ok((res, FeatureTableSchema { name: layer.name.clone(), columns }))
+}
+
+/// The column type a value starts out as
+fn value_scalar(value: &PrimitiveValue) -> Option<ScalarType> {
+    Some(match value {
+        PrimitiveValue::String(_) => ScalarType::String,
+        PrimitiveValue::U64(_) => ScalarType::UInt64,
+        PrimitiveValue::I64(_) => ScalarType::Int64,
+        PrimitiveValue::F32(_) => ScalarType::Float,
+        PrimitiveValue::F64(_) => ScalarType::Double,
+        PrimitiveValue::Bool(_) => ScalarType::Boolean,
+        PrimitiveValue::Null => return None,
+    })
+}
+
+/// The column type that holds both the column's values and `value`. Numbers widen to signed
+/// integers or doubles, strings and booleans can not be mixed with anything else
+fn merge_scalar(scalar: ScalarType, value: &PrimitiveValue) -> Option<ScalarType> {
+    let Some(other) = value_scalar(value) else {
+        return Some(scalar);
+    };
+    use ScalarType::*;
+    Some(match (scalar, other) {
+        (a, b) if a == b => a,
+        (String | Boolean, _) | (_, String | Boolean) => return None,
+        (UInt64 | Int64, UInt64 | Int64) => Int64,
+        _ => Double,
+    })
+}
+
+fn shape_mismatch(
+    layer: &BaseVectorLayer,
+    feature: usize,
+    key: &str,
+    scalar: ScalarType,
+    value: &PrimitiveValue,
+) -> EncodeError {
+    let expected = match scalar {
+        ScalarType::String => PrimitiveShape::String,
+        ScalarType::Boolean => PrimitiveShape::Bool,
+        ScalarType::Float => PrimitiveShape::F32,
+        ScalarType::Double => PrimitiveShape::F64,
+        s if s.is_signed() => PrimitiveShape::I64,
+        _ => PrimitiveShape::U64,
+    };
+    EncodeError::ShapeMismatch {
+        layer: layer.name.clone(),
+        feature,
+        key: key.to_string(),
+        expected,
+        actual: value.clone(),
+    }
+}
+
+/// Write the data streams of a property column, returning how many there are or the index of a
+/// value the column type can not hold
+fn write_values(
+    buf: &mut Vec<u8>,
+    scalar: ScalarType,
+    values: &[&PrimitiveValue],
+) -> Result<usize, usize> {
+    match scalar {
+        ScalarType::String => {
+            let mut dictionary: BTreeMap<&str, u64> = BTreeMap::new();
+            let mut strings: Vec<&str> = vec![];
+            let mut indices = Vec::with_capacity(values.len());
+            for value in values {
+                let PrimitiveValue::String(string) = value else { unreachable!() };
+                let index = *dictionary.entry(string).or_insert_with(|| {
+                    strings.push(string);
+                    strings.len() as u64 - 1
+                });
+                indices.push(index);
+            }
+            let lengths: Vec<u64> = strings.iter().map(|s| s.len() as u64).collect();
+            encode_ints(buf, PhysicalStreamType::Length, LENGTH_DICTIONARY, &lengths, false);
+            encode_ints(buf, PhysicalStreamType::Offset, OFFSET_STRING, &indices, false);
+            let data = strings.concat();
+            encode_bytes(buf, DICTIONARY_SINGLE, strings.len(), data.as_bytes());
+            Ok(3)
+        }
+        ScalarType::Boolean => {
+            let bits: Vec<bool> =
+                values.iter().map(|v| matches!(v, PrimitiveValue::Bool(true))).collect();
+            encode_booleans(buf, PhysicalStreamType::Data, &bits);
+            Ok(1)
+        }
+        ScalarType::Float | ScalarType::Double => {
+            let mut data = vec![];
+            for value in values {
+                let value = match value {
+                    PrimitiveValue::U64(v) => *v as f64,
+                    PrimitiveValue::I64(v) => *v as f64,
+                    PrimitiveValue::F32(v) => *v as f64,
+                    PrimitiveValue::F64(v) => *v,
+                    _ => unreachable!(),
+                };
+                if scalar == ScalarType::Float {
+                    data.extend((value as f32).to_le_bytes());
+                } else {
+                    data.extend(value.to_le_bytes());
+                }
+            }
+            encode_bytes(buf, DICTIONARY_NONE, values.len(), &data);
+            Ok(1)
+        }
+        _ => {
+            let signed = scalar.is_signed();
+            let mut ints = Vec::with_capacity(values.len());
+            for (i, value) in values.iter().enumerate() {
+                ints.push(match value {
+                    PrimitiveValue::U64(v) if signed && *v > i64::MAX as u64 => return Err(i),
+                    PrimitiveValue::U64(v) => *v,
+                    PrimitiveValue::I64(v) => *v as u64,
+                    _ => unreachable!(),
+                });
+            }
+            encode_ints(buf, PhysicalStreamType::Data, DICTIONARY_NONE, &ints, signed);
+            Ok(1)
+        }
+    }
+}
+
+/// Write the geometry column, returning the largest coordinate
+fn write_geometry(buf: &mut Vec<u8>, layer: &BaseVectorLayer) -> i32 {
+    let mut types = vec![];
+    let (mut geometries, mut parts, mut rings) = (vec![], vec![], vec![]);
+    let mut vertices: Vec<Point> = vec![];
+    let has_polygons =
+        layer.features.iter().any(|f| matches!(f, BaseVectorFeature::BaseVectorPolysFeature(_)));
+    for feature in &layer.features {
+        match feature.load_geometry() {
+            VectorGeometry::VectorPoints(points) => {
+                if points.len() == 1 {
+                    types.push(GeometryType::Point as u64);
+                } else {
+                    types.push(GeometryType::MultiPoint as u64);
+                    geometries.push(points.len() as u64);
+                }
+                vertices.extend(points);
+            }
+            VectorGeometry::VectorLines(lines) => {
+                if lines.len() == 1 {
+                    types.push(GeometryType::LineString as u64);
+                } else {
+                    types.push(GeometryType::MultiLineString as u64);
+                    geometries.push(lines.len() as u64);
+                }
+                for line in lines {
+                    let lengths = if has_polygons { &mut rings } else { &mut parts };
+                    lengths.push(line.geometry.len() as u64);
+                    vertices.extend(line.geometry);
+                }
+            }
+            VectorGeometry::VectorPolys(polys) => {
+                if polys.len() == 1 {
+                    types.push(GeometryType::Polygon as u64);
+                } else {
+                    types.push(GeometryType::MultiPolygon as u64);
+                    geometries.push(polys.len() as u64);
+                }
+                for poly in polys {
+                    parts.push(poly.len() as u64);
+                    for ring in poly {
+                        let mut ring = ring.geometry;
+                        // rings are stored without their closing point
+                        if ring.len() > 1 && ring.first() == ring.last() {
+                            ring.pop();
+                        }
+                        rings.push(ring.len() as u64);
+                        vertices.extend(ring);
+                    }
+                }
+            }
+            // 3D geometry is rejected by `check_layer`
+            _ => unreachable!(),
+        }
+    }
+
+    let mut streams = vec![];
+    encode_ints(&mut streams, PhysicalStreamType::Length, LENGTH_VAR_BINARY, &types, false);
+    let mut num_streams = 1;
+    for (lengths, logical_type) in
+        [(geometries, LENGTH_GEOMETRIES), (parts, LENGTH_PARTS), (rings, LENGTH_RINGS)]
+    {
+        if !lengths.is_empty() {
+            encode_ints(&mut streams, PhysicalStreamType::Length, logical_type, &lengths, false);
+            num_streams += 1;
+        }
+    }
+    if !vertices.is_empty() {
+        encode_vertices(&mut streams, &vertices);
+        num_streams += 1;
+    }
+    write_varint(buf, num_streams);
+    buf.extend(streams);
+
+    vertices.iter().map(|p| p.x.max(p.y)).max().unwrap_or(0).max(0)
+}
@@ -0,0 +1,59 @@
+use crate::{
+    base::BaseVectorTile,
+    error::EncodeError,
+    mlt::{
+        MltError, MltVectorLayer, TileSetMetadata,
+        stream::Reader,
+        vector_layer::{read_layer, write_layer},
+    },
+};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+/// A MapLibre Tile. Columns are described by the tileset metadata rather than the tile, so it is
+/// needed to decode the tile
+#[derive(Debug, Clone, Default)]
+pub struct MltVectorTile {
+    /// the layers in the vector tile
+    pub layers: BTreeMap<String, MltVectorLayer>,
+}
+impl MltVectorTile {
+    /// Create a new vector tile
+    ///
+    /// # Panics
+    /// If the data is not a valid tile. Use [`MltVectorTile::try_new`] for untrusted input.
+    pub fn new(data: &[u8], metadata: &TileSetMetadata) -> Self {
+        Self::try_new(data, metadata).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create a new vector tile, returning an error if the data is truncated, malformed or uses
+    /// encodings that are not supported
+    pub fn try_new(data: &[u8], metadata: &TileSetMetadata) -> Result<Self, MltError> {
+        let mut r = Reader::new(data);
+        let mut layers = BTreeMap::new();
+        while !r.is_done() {
+            let layer = read_layer(&mut r, metadata)?;
+            layers.insert(layer.name.clone(), layer);
+        }
+
+        Ok(MltVectorTile { layers })
+    }
+
+    /// Get a layer given the name
+    pub fn layer(&mut self, name: &str) -> Option<&mut MltVectorLayer> {
+        self.layers.get_mut(name)
+    }
+}
+
+/// writer for converting a BaseVectorTile to a MapLibre Tile. Returns the encoded tile and the
+/// tileset metadata needed to decode it, with one feature table per layer
+pub fn write_tile(tile: &BaseVectorTile) -> Result<(Vec<u8>, TileSetMetadata), EncodeError> {
+    let mut data = Vec::new();
+    let mut metadata = TileSetMetadata { version: 1, feature_tables: Vec::new() };
+    for (i, layer) in tile.layers.values().enumerate() {
+        let (layer, schema) = write_layer(layer, i as u64)?;
+        data.extend(layer);
+        metadata.feature_tables.push(schema);
+    }
+
+    Ok((data, metadata))
+}
@@ -1,12 +1,16 @@
 use crate::{
     BBoxQuantization, CustomOrdWrapper, Point, Point3D, VectorPoints, VectorPoints3D,
-    delta_decode_array, delta_encode_array, unweave_and_delta_decode_3d_array,
-    unweave_and_delta_decode_array, weave_and_delta_encode_3d_array, weave_and_delta_encode_array,
+    delta_decode_array, delta_encode_array,
+    error::{DecodeError, ProtoMessage},
+    reader::{BYTES, Reader, Result, VARINT},
+    shared::{Lazy, Shared},
+    unweave_and_delta_decode_3d_array, unweave_and_delta_decode_array,
+    weave_and_delta_encode_3d_array, weave_and_delta_encode_array,
 };
-use alloc::{collections::BTreeMap, string::String, vec::Vec};
-use core::cell::RefCell;
-use pbf::{ProtoRead, ProtoWrite, Protobuf};
-use s2json::BBOX;
+use alloc::{borrow::Cow, collections::BTreeMap, rc::Rc, string::String, vec::Vec};
+use core::{cell::RefCell, cmp::Ordering};
+use pbf::{BitCast, ProtoRead, ProtoWrite, Protobuf};
+use s2json::{BBOX, BBox, BBox3D};
 
 /// Column Types take up 3 bits.
 /// ColumnNames define various common data structures to be stored in a column fashion
@@ -79,18 +83,138 @@ impl From<OColumnName> for u64 {
 /// Store either data itself or a reference to the position in the protobuf to deserialize
 #[derive(Debug)]
 pub enum ColumnContainer<T> {
-    /// reference to a position in the protobuf
-    Pos(usize),
+    /// reference to a position in the protobuf, holding the data once it is decoded
+    Pos(usize, Lazy<T>),
     /// data itself
     Data(T),
 }
+impl<T: Clone + Default> ColumnContainer<T> {
+    /// Get the data, decoding it from the column cache message and storing it on first access
+    fn get(&self, data: &[u8], decode: impl FnOnce(&[u8]) -> T) -> T {
+        match self {
+            ColumnContainer::Pos(pos, lazy) => {
+                lazy.get_or_init(|| packed_at(data, *pos).map(decode).unwrap_or_default()).clone()
+            }
+            ColumnContainer::Data(data) => data.clone(),
+        }
+    }
+}
+
+/// A column read from a column cache message by [`read_columns`]. Packed columns are only
+/// checked, and are found at their position in the data when they are decoded
+#[derive(Debug)]
+pub(crate) enum Column<'a> {
+    String(Cow<'a, str>),
+    Unsigned(u64),
+    Signed(i64),
+    Float(f32),
+    Double(f64),
+    Points(usize),
+    Points3D(usize),
+    Indices(usize),
+    Shapes(usize),
+    BBox(usize),
+}
+
+/// Read a column cache message, handing each column to `add` in order
+pub(crate) fn read_columns<'a>(mut pb: Reader<'a>, mut add: impl FnMut(Column<'a>)) -> Result<()> {
+    while !pb.done() {
+        let (tag, wire_type) = pb.field()?;
+        let start = pb.pos();
+        match tag {
+            2..=5 => pb.expect(wire_type, VARINT)?,
+            1 | 6..=10 => pb.expect(wire_type, BYTES)?,
+            _ => return Err(pb.unknown_tag(start)),
+        }
+        let column = match OColumnName::from(tag) {
+            OColumnName::String => Column::String(pb.string()?),
+            OColumnName::Unsigned => Column::Unsigned(pb.varint()?),
+            OColumnName::Signed => Column::Signed(pb.s_varint()?),
+            OColumnName::Float => Column::Float(pb.read_varint()?),
+            OColumnName::Double => Column::Double(pb.read_varint()?),
+            OColumnName::Points => Column::Points(packed(&mut pb, start)?),
+            OColumnName::Points3D => Column::Points3D(packed(&mut pb, start)?),
+            OColumnName::Indices => Column::Indices(packed(&mut pb, start)?),
+            OColumnName::Shapes => Column::Shapes(packed(&mut pb, start)?),
+            OColumnName::BBox => {
+                let len = pb.packed_len()?;
+                if len != 12 && len < 20 {
+                    return Err(pb.malformed(start, "a bbox must be 12 or 20 bytes"));
+                }
+                Column::BBox(start)
+            }
+        };
+        add(column);
+    }
+    Ok(())
+}
+
+/// Check a packed column without decoding it, returning its position
+fn packed(pb: &mut Reader, start: usize) -> Result<usize> {
+    pb.packed_len()?;
+    Ok(start)
+}
+
+/// The contents of the packed column found at `pos` in the data
+fn packed_at(data: &[u8], pos: usize) -> Option<&[u8]> {
+    Reader::new(data, pos, data.len(), ProtoMessage::ColumnCache).bytes().ok()
+}
+
+/// Decode a packed array of varints, stopping at the first incomplete one
+fn unpack<T: BitCast>(data: &[u8]) -> Vec<T> {
+    let mut pb = Reader::new(data, 0, data.len(), ProtoMessage::ColumnCache);
+    let mut res = Vec::new();
+    while let Ok(value) = pb.read_varint() {
+        res.push(value);
+    }
+    res
+}
+
+/// Decode a weaved and delta encoded points column
+pub(crate) fn decode_points(data: &[u8]) -> VectorPoints {
+    unweave_and_delta_decode_array(&unpack::<u64>(data))
+}
+
+/// Decode a weaved and delta encoded 3D points column
+pub(crate) fn decode_points_3d(data: &[u8]) -> VectorPoints3D {
+    unweave_and_delta_decode_3d_array(&unpack::<u64>(data))
+}
+
+/// Decode a delta encoded indices column
+pub(crate) fn decode_indices(data: &[u8]) -> Vec<u32> {
+    delta_decode_array(&unpack::<u32>(data))
+}
+
+/// Decode a shapes column
+pub(crate) fn decode_shapes(data: &[u8]) -> Vec<usize> {
+    unpack::<usize>(data)
+}
+
+/// Decode a bbox column, which holds a quantized [`BBox`] in 12 bytes or a [`BBox3D`] in 20
+pub(crate) fn decode_bbox(data: &[u8]) -> BBOX {
+    dequantize_bbox(&unpack::<u8>(data))
+}
+
+/// Dequantize a bbox, or return the default if it has neither length
+fn dequantize_bbox(bytes: &[u8]) -> BBOX {
+    match bytes.len() {
+        12 => BBOX::BBox(BBox::dequantize(bytes)),
+        20.. => BBOX::BBox3D(BBox3D::dequantize(bytes)),
+        _ => BBOX::default(),
+    }
+}
 
 /// Column Cache Reader
 /// Stores all data in a column format.
-/// Upon construction, all columns are decoded from the protobuf.
-/// This allows for quick and easy access to data in a column format.
+/// When built with [`ColumnCacheReader::from_input`] or read as part of a tile, the points,
+/// indices, shapes and bbox columns only store their position in the protobuf and are decoded
+/// the first time they are accessed.
+/// Reading the cache as a message with [`ProtoRead`] decodes every column upfront.
+/// Lookups past the end of a column return a default value.
 #[derive(Debug, Default)]
 pub struct ColumnCacheReader {
+    /// the data holding the column cache message, used to decode columns on demand
+    data: Shared<[u8]>,
     /// strings are stored in a column of strings
     string: Vec<String>,
     /// unsigned whole numbers are stored in unsigned
@@ -102,15 +226,15 @@ pub struct ColumnCacheReader {
     /// non-whole numbers greater than 32-bit are stored in double
     double: Vec<f64>,
     /// for geometry types each column is individually weaved and delta encoded
-    points: Vec<VectorPoints>,
+    points: Vec<ColumnContainer<VectorPoints>>,
     /// for geometry types each column is individually weaved and delta encoded
-    points_3d: Vec<VectorPoints3D>,
+    points_3d: Vec<ColumnContainer<VectorPoints3D>>,
     /// store M-Value indices>, geometry indices>, and geometry shapes
-    indices: Vec<Vec<u32>>,
+    indices: Vec<ColumnContainer<Vec<u32>>>,
     /// shapes and possibly value indices are stored in a number[] to be decoded by readShape
-    shapes: Vec<Vec<usize>>,
+    shapes: Vec<ColumnContainer<Vec<usize>>>,
     /// Stores both BBox and BBox3D in a single column
-    bbox: Vec<BBOX>,
+    bbox: Vec<ColumnContainer<BBOX>>,
 }
 impl ColumnCacheReader {
     /// create an instance
@@ -118,54 +242,170 @@ impl ColumnCacheReader {
         ColumnCacheReader { ..Default::default() }
     }
 
+    /// create an instance from the contents of a column cache message. Points, indices, shapes
+    /// and bboxes are decoded lazily. Panics if the message is malformed, see
+    /// [`ColumnCacheReader::try_from_input`]
+    pub fn from_input(data: Vec<u8>) -> Self {
+        Self::try_from_input(data).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// create an instance from the contents of a column cache message, returning an error if the
+    /// message is malformed
+    pub fn try_from_input(data: Vec<u8>) -> core::result::Result<Self, DecodeError> {
+        let data: Shared<[u8]> = data.into();
+        Self::read(&data, Reader::new(&data, 0, data.len(), ProtoMessage::ColumnCache))
+    }
+
+    /// Read the column cache message covered by `pb`, which reads from `data`
+    pub(crate) fn read(data: &Shared<[u8]>, pb: Reader) -> Result<Self> {
+        let mut cache = ColumnCacheReader { data: data.clone(), ..Default::default() };
+        read_columns(pb, |column| match column {
+            Column::String(s) => cache.string.push(s.into_owned()),
+            Column::Unsigned(u) => cache.unsigned.push(u),
+            Column::Signed(s) => cache.signed.push(s),
+            Column::Float(f) => cache.float.push(f),
+            Column::Double(d) => cache.double.push(d),
+            Column::Points(pos) => cache.points.push(ColumnContainer::Pos(pos, Lazy::new())),
+            Column::Points3D(pos) => cache.points_3d.push(ColumnContainer::Pos(pos, Lazy::new())),
+            Column::Indices(pos) => cache.indices.push(ColumnContainer::Pos(pos, Lazy::new())),
+            Column::Shapes(pos) => cache.shapes.push(ColumnContainer::Pos(pos, Lazy::new())),
+            Column::BBox(pos) => cache.bbox.push(ColumnContainer::Pos(pos, Lazy::new())),
+        })?;
+        Ok(cache)
+    }
+
     /// get a string
-    pub fn get_string(&mut self, index: usize) -> String {
-        self.string[index].clone()
+    pub fn get_string(&self, index: usize) -> String {
+        self.string.get(index).cloned().unwrap_or_default()
     }
 
     /// get an unsigned integer
     pub fn get_unsigned(&self, index: usize) -> u64 {
-        self.unsigned[index]
+        self.unsigned.get(index).copied().unwrap_or_default()
     }
 
     /// get a signed integer
     pub fn get_signed(&self, index: usize) -> i64 {
-        self.signed[index]
+        self.signed.get(index).copied().unwrap_or_default()
     }
 
     /// get a float
     pub fn get_float(&self, index: usize) -> f32 {
-        self.float[index]
+        self.float.get(index).copied().unwrap_or_default()
     }
 
     /// get a double
     pub fn get_double(&self, index: usize) -> f64 {
-        self.double[index]
+        self.double.get(index).copied().unwrap_or_default()
     }
 
     /// get a vector of points used by all geometry types
-    pub fn get_points(&mut self, index: usize) -> VectorPoints {
-        self.points[index].clone()
+    pub fn get_points(&self, index: usize) -> VectorPoints {
+        get(&self.points, index, &self.data, decode_points)
     }
 
     /// get a vector of 3D points used by all geometry types
-    pub fn get_points_3d(&mut self, index: usize) -> VectorPoints3D {
-        self.points_3d[index].clone()
+    pub fn get_points_3d(&self, index: usize) -> VectorPoints3D {
+        get(&self.points_3d, index, &self.data, decode_points_3d)
     }
 
     /// get a vector of indices used by all geometry types
-    pub fn get_indices(&mut self, index: usize) -> Vec<u32> {
-        self.indices[index].clone()
+    pub fn get_indices(&self, index: usize) -> Vec<u32> {
+        get(&self.indices, index, &self.data, decode_indices)
     }
 
     /// get a vector of encoded data that helps decode shapes
-    pub fn get_shapes(&mut self, index: usize) -> Vec<usize> {
-        self.shapes[index].clone()
+    pub fn get_shapes(&self, index: usize) -> Vec<usize> {
+        get(&self.shapes, index, &self.data, decode_shapes)
     }
 
     /// get a BBox
-    pub fn get_bbox(&mut self, index: usize) -> BBOX {
-        self.bbox[index]
+    pub fn get_bbox(&self, index: usize) -> BBOX {
+        get(&self.bbox, index, &self.data, decode_bbox)
+    }
+}
+
+/// Get an entry of a lazily decoded column, or the default if there is none
+fn get<T: Clone + Default>(
+    column: &[ColumnContainer<T>],
+    index: usize,
+    data: &[u8],
+    decode: impl FnOnce(&[u8]) -> T,
+) -> T {
+    column.get(index).map(|c| c.get(data, decode)).unwrap_or_default()
+}
+
+/// Lookups into a column cache used to decode shapes, values and geometry. Implemented by the
+/// owned [`ColumnCacheReader`] and by the columns of a [`crate::borrowed::BorrowedVectorTile`].
+/// Lookups past the end of a column return a default value
+pub trait ColumnLookup {
+    /// the number of entries in a column
+    fn column_len(&self, column: OColumnName) -> usize;
+    /// get a string, borrowed from the cache
+    fn get_string(&self, index: usize) -> Cow<'_, str>;
+    /// get an unsigned integer
+    fn get_unsigned(&self, index: usize) -> u64;
+    /// get a signed integer
+    fn get_signed(&self, index: usize) -> i64;
+    /// get a float
+    fn get_float(&self, index: usize) -> f32;
+    /// get a double
+    fn get_double(&self, index: usize) -> f64;
+    /// get a vector of points used by all geometry types
+    fn get_points(&self, index: usize) -> VectorPoints;
+    /// get a vector of 3D points used by all geometry types
+    fn get_points_3d(&self, index: usize) -> VectorPoints3D;
+    /// get a vector of indices used by all geometry types
+    fn get_indices(&self, index: usize) -> Vec<u32>;
+    /// get a vector of encoded data that helps decode shapes
+    fn get_shapes(&self, index: usize) -> Vec<usize>;
+    /// get a BBox
+    fn get_bbox(&self, index: usize) -> BBOX;
+}
+impl ColumnLookup for ColumnCacheReader {
+    fn column_len(&self, column: OColumnName) -> usize {
+        match column {
+            OColumnName::String => self.string.len(),
+            OColumnName::Unsigned => self.unsigned.len(),
+            OColumnName::Signed => self.signed.len(),
+            OColumnName::Float => self.float.len(),
+            OColumnName::Double => self.double.len(),
+            OColumnName::Points => self.points.len(),
+            OColumnName::Points3D => self.points_3d.len(),
+            OColumnName::Indices => self.indices.len(),
+            OColumnName::Shapes => self.shapes.len(),
+            OColumnName::BBox => self.bbox.len(),
+        }
+    }
+    fn get_string(&self, index: usize) -> Cow<'_, str> {
+        self.string.get(index).map_or(Cow::Borrowed(""), |s| Cow::Borrowed(s))
+    }
+    fn get_unsigned(&self, index: usize) -> u64 {
+        ColumnCacheReader::get_unsigned(self, index)
+    }
+    fn get_signed(&self, index: usize) -> i64 {
+        ColumnCacheReader::get_signed(self, index)
+    }
+    fn get_float(&self, index: usize) -> f32 {
+        ColumnCacheReader::get_float(self, index)
+    }
+    fn get_double(&self, index: usize) -> f64 {
+        ColumnCacheReader::get_double(self, index)
+    }
+    fn get_points(&self, index: usize) -> VectorPoints {
+        ColumnCacheReader::get_points(self, index)
+    }
+    fn get_points_3d(&self, index: usize) -> VectorPoints3D {
+        ColumnCacheReader::get_points_3d(self, index)
+    }
+    fn get_indices(&self, index: usize) -> Vec<u32> {
+        ColumnCacheReader::get_indices(self, index)
+    }
+    fn get_shapes(&self, index: usize) -> Vec<usize> {
+        ColumnCacheReader::get_shapes(self, index)
+    }
+    fn get_bbox(&self, index: usize) -> BBOX {
+        ColumnCacheReader::get_bbox(self, index)
     }
 }
 impl ProtoRead for ColumnCacheReader {
@@ -177,15 +417,21 @@ impl ProtoRead for ColumnCacheReader {
             OColumnName::Signed => self.signed.push(pb.read_s_varint::<i64>()),
             OColumnName::Float => self.float.push(pb.read_varint::<f32>()),
             OColumnName::Double => self.double.push(pb.read_varint::<f64>()),
-            OColumnName::Points => {
-                self.points.push(unweave_and_delta_decode_array(&pb.read_packed::<u64>()))
+            OColumnName::Points => self.points.push(ColumnContainer::Data(
+                unweave_and_delta_decode_array(&pb.read_packed::<u64>()),
+            )),
+            OColumnName::Points3D => self.points_3d.push(ColumnContainer::Data(
+                unweave_and_delta_decode_3d_array(&pb.read_packed::<u64>()),
+            )),
+            OColumnName::Indices => self
+                .indices
+                .push(ColumnContainer::Data(delta_decode_array(&pb.read_packed::<u32>()))),
+            OColumnName::Shapes => {
+                self.shapes.push(ColumnContainer::Data(pb.read_packed::<usize>()))
             }
-            OColumnName::Points3D => {
-                self.points_3d.push(unweave_and_delta_decode_3d_array(&pb.read_packed::<u64>()))
+            OColumnName::BBox => {
+                self.bbox.push(ColumnContainer::Data(dequantize_bbox(&pb.read_packed::<u8>())))
             }
-            OColumnName::Indices => self.indices.push(delta_decode_array(&pb.read_packed::<u32>())),
-            OColumnName::Shapes => self.shapes.push(pb.read_packed::<usize>()),
-            OColumnName::BBox => self.bbox.push(BBOX::dequantize(&pb.read_packed::<u8>()[..])),
         }
     }
 }
@@ -201,38 +447,71 @@ pub struct OColumnBaseChunk {
     /// track how many times this chunk is reused
     pub count: usize,
 }
+/// A chunk shared between its column and every value that references it
+pub type OColumnChunk = Rc<RefCell<OColumnBaseChunk>>;
 /// A value is a collection of lookup devices. A number is decoded by the appropriate function,
 /// but the object is a reference to one of the number columns.
-/// Number types are eventually sorted, so we track the column and index with the data.
-#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord)]
+/// Number and string columns are eventually sorted, so we track the column chunk with the data.
+/// Column references compare by identity, as the chunk's index changes once sorted.
+#[derive(Debug, Clone)]
 pub enum ColumnValue {
     /// raw number index pointing to a location in the cache column
     Number(usize),
     /// a reference to a column
-    Column(RefCell<OColumnBaseChunk>),
+    Column(OColumnChunk),
+}
+impl ColumnValue {
+    /// The current index in the cache column
+    pub fn index(&self) -> usize {
+        match self {
+            ColumnValue::Number(n) => *n,
+            ColumnValue::Column(c) => c.borrow().index,
+        }
+    }
+}
+impl PartialEq for ColumnValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for ColumnValue {}
+impl PartialOrd for ColumnValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for ColumnValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (ColumnValue::Number(a), ColumnValue::Number(b)) => a.cmp(b),
+            (ColumnValue::Number(_), ColumnValue::Column(_)) => Ordering::Less,
+            (ColumnValue::Column(_), ColumnValue::Number(_)) => Ordering::Greater,
+            (ColumnValue::Column(a), ColumnValue::Column(b)) => Rc::as_ptr(a).cmp(&Rc::as_ptr(b)),
+        }
+    }
 }
 impl From<usize> for ColumnValue {
     fn from(index: usize) -> Self {
         ColumnValue::Number(index)
     }
 }
-impl From<RefCell<OColumnBaseChunk>> for ColumnValue {
-    fn from(chunk: RefCell<OColumnBaseChunk>) -> Self {
+impl From<OColumnChunk> for ColumnValue {
+    fn from(chunk: OColumnChunk) -> Self {
         ColumnValue::Column(chunk)
     }
 }
 /// A building block for all column types.
-pub type OColumnBaseWrite<K> = BTreeMap<K, RefCell<OColumnBaseChunk>>;
+pub type OColumnBaseWrite<K> = BTreeMap<K, OColumnChunk>;
 
 /// A building block for all number column types.
-pub type OColumnBaseFloatWrite<K> = BTreeMap<CustomOrdWrapper<K>, RefCell<OColumnBaseChunk>>;
+pub type OColumnBaseFloatWrite<K> = BTreeMap<CustomOrdWrapper<K>, OColumnChunk>;
 
 /// The cache where all data is stored in a column format.
 /// Each column type has its own array of data.
 /// Number types maintain their own index for sorting purposes.
 #[derive(Debug, Default)]
 pub struct ColumnCacheWriter {
-    /// strings are grouped by their bytes.
+    /// strings are grouped by their bytes and sorted prior to storing
     string: OColumnBaseWrite<String>,
     /// Unsigned integers are sorted prior to storing
     unsigned: OColumnBaseWrite<u64>,
@@ -252,30 +531,38 @@ pub struct ColumnCacheWriter {
     shapes: OColumnBaseWrite<Vec<ColumnValue>>,
     /// Features should be sorted by id prior to building a column
     bbox: OColumnBaseWrite<BBOX>,
+    /// Keep strings and numbers in insertion order
+    unsorted: bool,
 }
 impl ColumnCacheWriter {
+    /// Create a cache that keeps strings and numbers in insertion order instead of sorting them
+    /// by reuse. Only useful to measure what the sorting saves.
+    pub fn unsorted() -> Self {
+        Self { unsorted: true, ..Default::default() }
+    }
+
     /// add string to cache
-    pub fn add_string(&mut self, value: String) -> usize {
-        add(&mut self.string, value)
+    pub fn add_string(&mut self, value: String) -> OColumnChunk {
+        add_number(&mut self.string, value)
     }
 
     /// add u64 to cache
-    pub fn add_u64(&mut self, value: u64) -> RefCell<OColumnBaseChunk> {
+    pub fn add_u64(&mut self, value: u64) -> OColumnChunk {
         add_number(&mut self.unsigned, value)
     }
 
     /// add i64 to cache
-    pub fn add_i64(&mut self, value: i64) -> RefCell<OColumnBaseChunk> {
+    pub fn add_i64(&mut self, value: i64) -> OColumnChunk {
         add_number(&mut self.signed, value)
     }
 
     /// add f32 to cache
-    pub fn add_f32(&mut self, value: f32) -> RefCell<OColumnBaseChunk> {
+    pub fn add_f32(&mut self, value: f32) -> OColumnChunk {
         add_number(&mut self.float, CustomOrdWrapper(value))
     }
 
     /// add f64 to cache
-    pub fn add_f64(&mut self, value: f64) -> RefCell<OColumnBaseChunk> {
+    pub fn add_f64(&mut self, value: f64) -> OColumnChunk {
         add_number(&mut self.double, CustomOrdWrapper(value))
     }
 
@@ -303,94 +590,69 @@ impl ColumnCacheWriter {
     pub fn add_bbox(&mut self, value: BBOX) -> usize {
         add(&mut self.bbox, value)
     }
+
+    /// Sort the string and number columns by how often each value is reused, so the most common
+    /// values get the smallest indices. Every chunk handed out is updated in place, so indices
+    /// read after this call are final. Called automatically when the cache is written.
+    pub fn sort(&self) {
+        if self.unsorted {
+            return;
+        }
+        sort_column(&self.string);
+        sort_column(&self.unsigned);
+        sort_column(&self.signed);
+        sort_column(&self.float);
+        sort_column(&self.double);
+    }
 }
 impl ProtoWrite for ColumnCacheWriter {
     fn write(&self, pbf: &mut Protobuf) {
-        // setup
-        let mut strings: Vec<(&String, &RefCell<OColumnBaseChunk>)> = self.string.iter().collect();
-        let mut unsigned: Vec<(&u64, &RefCell<OColumnBaseChunk>)> = self.unsigned.iter().collect();
-        let mut signed: Vec<(&i64, &RefCell<OColumnBaseChunk>)> = self.signed.iter().collect();
-        let mut float: Vec<(&CustomOrdWrapper<f32>, &RefCell<OColumnBaseChunk>)> =
-            self.float.iter().collect();
-        let mut double: Vec<(&CustomOrdWrapper<f64>, &RefCell<OColumnBaseChunk>)> =
-            self.double.iter().collect();
-        let mut points: Vec<(&Vec<Point>, &RefCell<OColumnBaseChunk>)> =
-            self.points.iter().collect();
-        let mut points_3d: Vec<(&Vec<Point3D>, &RefCell<OColumnBaseChunk>)> =
-            self.points_3d.iter().collect();
-        let mut indices: Vec<(&Vec<u32>, &RefCell<OColumnBaseChunk>)> =
-            self.indices.iter().collect();
-        let mut shapes: Vec<(&Vec<ColumnValue>, &RefCell<OColumnBaseChunk>)> =
-            self.shapes.iter().collect();
-        let mut bbox: Vec<(&BBOX, &RefCell<OColumnBaseChunk>)> = self.bbox.iter().collect();
-
-        // sort them
-        // TODO: bring this back (It needs to be an Rc<RefCell<__>>)
-        // sort_column(&mut unsigned);
-        // sort_column(&mut signed);
-        // sort_column(&mut float);
-        // sort_column(&mut double);
-        strings.sort_by(|a, b| a.1.borrow().index.cmp(&b.1.borrow().index));
-        unsigned.sort_by(|a, b| a.1.borrow().index.cmp(&b.1.borrow().index));
-        signed.sort_by(|a, b| a.1.borrow().index.cmp(&b.1.borrow().index));
-        float.sort_by(|a, b| a.1.borrow().index.cmp(&b.1.borrow().index));
-        double.sort_by(|a, b| a.1.borrow().index.cmp(&b.1.borrow().index));
-        points.sort_by(|a, b| a.1.borrow().index.cmp(&b.1.borrow().index));
-        points_3d.sort_by(|a, b| a.1.borrow().index.cmp(&b.1.borrow().index));
-        indices.sort_by(|a, b| a.1.borrow().index.cmp(&b.1.borrow().index));
-        shapes.sort_by(|a, b| a.1.borrow().index.cmp(&b.1.borrow().index));
-        bbox.sort_by(|a, b| a.1.borrow().index.cmp(&b.1.borrow().index));
-
+        // sort first so the shapes reference the final indices
+        self.sort();
         // store
         // strings
-        for string in strings {
-            pbf.write_string_field(OColumnName::String.into(), string.0);
+        for string in by_index(&self.string) {
+            pbf.write_string_field(OColumnName::String.into(), string);
         }
         // u64
-        for u in unsigned {
-            pbf.write_varint_field(OColumnName::Unsigned.into(), *u.0);
+        for u in by_index(&self.unsigned) {
+            pbf.write_varint_field(OColumnName::Unsigned.into(), *u);
         }
         // i64
-        for s in signed {
-            pbf.write_s_varint_field(OColumnName::Signed.into(), *s.0);
+        for s in by_index(&self.signed) {
+            pbf.write_s_varint_field(OColumnName::Signed.into(), *s);
         }
         // f32
-        for f in float {
-            pbf.write_varint_field(OColumnName::Float.into(), f.0.0);
+        for f in by_index(&self.float) {
+            pbf.write_varint_field(OColumnName::Float.into(), f.0);
         }
         // f64
-        for d in double {
-            pbf.write_varint_field(OColumnName::Double.into(), d.0.0);
+        for d in by_index(&self.double) {
+            pbf.write_varint_field(OColumnName::Double.into(), d.0);
         }
         // points
-        for p in points {
-            pbf.write_packed_varint(OColumnName::Points.into(), &weave_and_delta_encode_array(p.0));
+        for p in by_index(&self.points) {
+            pbf.write_packed_varint(OColumnName::Points.into(), &weave_and_delta_encode_array(p));
         }
         // points 3D
-        for p_3d in points_3d {
+        for p_3d in by_index(&self.points_3d) {
             pbf.write_packed_varint(
                 OColumnName::Points3D.into(),
-                &weave_and_delta_encode_3d_array(p_3d.0),
+                &weave_and_delta_encode_3d_array(p_3d),
             );
         }
         // indices
-        for i in indices {
-            pbf.write_packed_varint(OColumnName::Indices.into(), &delta_encode_array(i.0));
+        for i in by_index(&self.indices) {
+            pbf.write_packed_varint(OColumnName::Indices.into(), &delta_encode_array(i));
         }
         // shapes
-        for s in shapes {
-            let packed: Vec<usize> =
-                s.0.iter()
-                    .map(|v| match v {
-                        ColumnValue::Number(n) => *n,
-                        ColumnValue::Column(c) => c.borrow().index,
-                    })
-                    .collect();
+        for s in by_index(&self.shapes) {
+            let packed: Vec<usize> = s.iter().map(ColumnValue::index).collect();
             pbf.write_packed_varint(OColumnName::Shapes.into(), &packed);
         }
         // bbox
-        for bbox in bbox {
-            pbf.write_packed_varint(OColumnName::BBox.into(), &bbox.0.quantize());
+        for bbox in by_index(&self.bbox) {
+            pbf.write_packed_varint(OColumnName::BBox.into(), &bbox.quantize());
         }
     }
 }
@@ -406,46 +668,40 @@ where
         chunk.index
     } else {
         let index = col.len();
-        col.insert(value, RefCell::new(OColumnBaseChunk { index, count: 1 }));
+        col.insert(value, Rc::new(RefCell::new(OColumnBaseChunk { index, count: 1 })));
         index
     }
 }
 
-/// Add a **number** value to column and return index
-pub fn add_number<T>(col: &mut OColumnBaseWrite<T>, value: T) -> RefCell<OColumnBaseChunk>
+/// Add a **number** or string value to column and return its chunk. The chunk is shared with the column
+/// so its index follows the column when it is sorted.
+pub fn add_number<T>(col: &mut OColumnBaseWrite<T>, value: T) -> OColumnChunk
 where
     T: Ord,
 {
-    if let Some(chunk) = col.get_mut(&value) {
-        {
-            let mut chunk_mut = chunk.borrow_mut();
-            chunk_mut.count += 1;
-        }
+    if let Some(chunk) = col.get(&value) {
+        chunk.borrow_mut().count += 1;
         chunk.clone()
     } else {
         let index = col.len();
-        let new_chunk = RefCell::new(OColumnBaseChunk { index, count: 1 });
+        let new_chunk = Rc::new(RefCell::new(OColumnBaseChunk { index, count: 1 }));
         col.insert(value, new_chunk.clone());
         new_chunk
     }
 }
 
-// /// Sort number types and value types by index then update the index of each row for better
-// /// compression down the line.
-// pub fn sort_column<T: CustomOrd + core::fmt::Debug>(
-//     input: &mut [(&T, &RefCell<OColumnBaseChunk>)],
-// ) {
-//     // first sort
-//     input.sort_by(|a, b| {
-//         // First sort by count in descending order
-//         match b.1.borrow().count.cmp(&a.1.borrow().count) {
-//             Ordering::Equal => a.0.custom_cmp(b.0), // Then sort by data if counts are equal
-//             other => other,
-//         }
-//     });
-//     // than update indexes
-//     input
-//         .iter_mut()
-//         .enumerate()
-//         .for_each(|(i, v)| v.1.borrow_mut().index = i);
-// }
+/// Sort a column by count in descending order, then by value, and update the index of each
+/// chunk for better compression down the line.
+pub fn sort_column<T: Ord>(col: &OColumnBaseWrite<T>) {
+    // the map is already ordered by value, so a stable sort settles ties by value
+    let mut chunks: Vec<&OColumnChunk> = col.values().collect();
+    chunks.sort_by_key(|c| core::cmp::Reverse(c.borrow().count));
+    chunks.iter().enumerate().for_each(|(i, c)| c.borrow_mut().index = i);
+}
+
+/// Collect a column's values in index order
+fn by_index<T>(col: &OColumnBaseWrite<T>) -> Vec<&T> {
+    let mut values: Vec<(&T, usize)> = col.iter().map(|(v, c)| (v, c.borrow().index)).collect();
+    values.sort_by_key(|v| v.1);
+    values.into_iter().map(|v| v.0).collect()
+}
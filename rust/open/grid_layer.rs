@@ -1,10 +1,14 @@
-use crate::{delta_decode_array, delta_encode_array, open::Extent};
-use alloc::{string::String, vec::Vec};
+use crate::{
+    delta_decode_array, delta_encode_array,
+    error::{DecodeError, ProtoMessage},
+    open::Extent,
+    reader::{BYTES, Reader, VARINT},
+    shared::Shared,
+};
+use alloc::{borrow::Cow, string::String, vec::Vec};
 use libm::round;
 use pbf::{ProtoRead, ProtoWrite, Protobuf};
 
-// TODO: This could be faster if we don't read in the grid data on parsing but only if the user needs it
-
 /// Gridded data object to read from
 #[derive(Default, Debug, PartialEq)]
 pub struct GridData {
@@ -48,10 +52,27 @@ impl ProtoRead for GridData {
                     .collect()
             }
             6 => self.name = pb.read_string(),
-            _ => panic!("unknown tag: {}", tag),
+            _ => {}
         }
     }
 }
+impl TryFrom<&[u8]> for GridData {
+    type Error = DecodeError;
+
+    /// Decode a grid data message, returning an error if it is truncated or malformed
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let grid = read_grid(Reader::new(data, 0, data.len(), ProtoMessage::GridData))?;
+        let (start, end) = grid.data;
+        Ok(GridData {
+            name: grid.name.into_owned(),
+            data: decode_grid(&data[start..end], grid.min, grid.max, grid.extent),
+            extent: grid.extent,
+            size: grid.size,
+            min: grid.min,
+            max: grid.max,
+        })
+    }
+}
 impl ProtoWrite for GridData {
     fn write(&self, pb: &mut Protobuf) {
         let max = self.data.iter().fold(f64::MIN, |a, b| f64::max(a, *b));
@@ -69,13 +90,132 @@ impl ProtoWrite for GridData {
     }
 }
 
+/// Gridded data layer found in a vector tile.
+/// The name, extent, size, min and max are read upfront while the grid itself is only decoded
+/// the first time [`GridLayer::data`] is called.
+#[derive(Debug)]
+pub struct GridLayer {
+    /// The name of the gridded data
+    pub name: String,
+    /// The extent for remapping the data with a value between 0 and extent
+    pub extent: Extent,
+    /// The size of the tile (width and height)
+    pub size: f64,
+    /// The minimum grid value
+    pub min: f64,
+    /// The maximum grid value
+    pub max: f64,
+    /// position of the encoded grid in the tile data
+    data_range: (usize, usize),
+    /// the grid data once decoded
+    data: Option<Vec<f64>>,
+    /// the tile data the grid is read from
+    tile_data: Shared<[u8]>,
+}
+impl GridLayer {
+    /// Create a new GridLayer
+    pub fn new(tile_data: Shared<[u8]>) -> Self {
+        GridLayer {
+            name: String::new(),
+            extent: Extent::default(),
+            size: 0.0,
+            min: 0.0,
+            max: 0.0,
+            data_range: (0, 0),
+            data: None,
+            tile_data,
+        }
+    }
+
+    /// Get the grid data, decoding it on first access
+    pub fn data(&mut self) -> &[f64] {
+        if self.data.is_none() {
+            let (start, end) = self.data_range;
+            let encoded = self.tile_data.get(start..end).unwrap_or_default();
+            self.data = Some(decode_grid(encoded, self.min, self.max, self.extent));
+        }
+        self.data.as_deref().unwrap_or_default()
+    }
+}
+impl GridLayer {
+    /// Read the grid message covered by `grid` from the tile data
+    pub(crate) fn read(tile_data: &Shared<[u8]>, grid: Reader) -> crate::reader::Result<Self> {
+        let grid = read_grid(grid)?;
+        Ok(GridLayer {
+            name: grid.name.into_owned(),
+            extent: grid.extent,
+            size: grid.size,
+            min: grid.min,
+            max: grid.max,
+            data_range: grid.data,
+            data: None,
+            tile_data: tile_data.clone(),
+        })
+    }
+}
+
+/// The fields of a grid message, read by [`read_grid`]. The grid itself is the range of its
+/// packed values in the data being read
+#[derive(Debug, Default)]
+pub(crate) struct GridFields<'a> {
+    pub name: Cow<'a, str>,
+    pub extent: Extent,
+    pub size: f64,
+    pub min: f64,
+    pub max: f64,
+    pub data: (usize, usize),
+}
+
+/// Read a grid message. The grid is only skipped, see [`decode_grid`] to decode it
+pub(crate) fn read_grid(mut grid: Reader) -> crate::reader::Result<GridFields> {
+    let mut fields = GridFields::default();
+    while !grid.done() {
+        let (tag, wire_type) = grid.field()?;
+        let start = grid.pos();
+        match tag {
+            1..=4 => grid.expect(wire_type, VARINT)?,
+            5 | 6 => grid.expect(wire_type, BYTES)?,
+            _ => return Err(grid.unknown_tag(start)),
+        }
+        match tag {
+            1 => fields.extent = grid.read_varint()?,
+            2 => fields.size = grid.read_varint()?,
+            3 => fields.min = grid.read_varint()?,
+            4 => fields.max = grid.read_varint()?,
+            5 => fields.data = grid.range()?,
+            _ => fields.name = grid.string()?,
+        }
+    }
+    Ok(fields)
+}
+
+/// Decode packed grid values, stopping at the first incomplete one
+pub(crate) fn decode_grid(encoded: &[u8], min: f64, max: f64, extent: Extent) -> Vec<f64> {
+    let mut pb = Reader::new(encoded, 0, encoded.len(), ProtoMessage::GridData);
+    let mut values: Vec<u32> = Vec::new();
+    while let Ok(value) = pb.read_varint() {
+        values.push(value);
+    }
+    delta_decode_array(&values)
+        .into_iter()
+        .map(|v| unmap_value(v as f64, min, max, extent.into()))
+        .collect()
+}
+
+impl From<&mut GridLayer> for GridData {
+    fn from(grid: &mut GridLayer) -> Self {
+        let data = grid.data().to_vec();
+        GridData::new(grid.name.clone(), grid.extent, grid.size, grid.min, grid.max, data)
+    }
+}
+
 /// map the value to the range 0->extent
 fn remap_value(value: f64, min: f64, max: f64, extent: f64) -> u32 {
     round(((value - min) * extent) / (max - min)) as u32
 }
 
 /// map the value back to floats
-fn unmap_value(value: f64, min: f64, max: f64, extent: f64) -> f64 {
+pub(crate) fn unmap_value(value: f64, min: f64, max: f64, extent: f64) -> f64 {
     (value * (max - min)) / extent + min
 }
 
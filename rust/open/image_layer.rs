@@ -1,8 +1,11 @@
-use alloc::{fmt, str::FromStr, string::String, vec::Vec};
+use crate::{
+    error::{DecodeError, ProtoMessage},
+    reader::{BYTES, Reader, VARINT},
+    shared::Shared,
+};
+use alloc::{borrow::Cow, fmt, str::FromStr, string::String, vec::Vec};
 use pbf::{BitCast, ProtoRead, ProtoWrite, Protobuf};
 
-// TODO: This could be faster if we don't read in the grid data on parsing but only if the user needs it
-
 /// Track the image type
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ImageType {
@@ -41,7 +44,15 @@ impl BitCast for ImageType {
             6 => ImageType::BMP,
             7 => ImageType::RAW,
             8 => ImageType::UNKNOWN,
-            _ => panic!("unknown value: {}", value),
+            _ => panic!(
+                "{}",
+                DecodeError::InvalidEnum {
+                    message: ProtoMessage::ImageData,
+                    tag: Some(1),
+                    value,
+                    offset: 0,
+                }
+            ),
         }
     }
 }
@@ -119,10 +130,26 @@ impl ProtoRead for ImageData {
             3 => self.height = pb.read_varint(),
             4 => self.image = pb.read_bytes(),
             5 => self.name = pb.read_string(),
-            _ => panic!("unknown tag: {}", tag),
+            _ => {}
         }
     }
 }
+impl TryFrom<&[u8]> for ImageData {
+    type Error = DecodeError;
+
+    /// Decode an image data message, returning an error if it is truncated or malformed
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let image = read_image(Reader::new(data, 0, data.len(), ProtoMessage::ImageData))?;
+        let (start, end) = image.image;
+        Ok(ImageData {
+            name: image.name.into_owned(),
+            image_type: image.image_type,
+            width: image.width,
+            height: image.height,
+            image: data[start..end].to_vec(),
+        })
+    }
+}
 impl ProtoWrite for ImageData {
     fn write(&self, pb: &mut Protobuf) {
         pb.write_varint_field(1, self.image_type);
@@ -132,3 +159,108 @@ impl ProtoWrite for ImageData {
         pb.write_string_field(5, &self.name);
     }
 }
+
+/// Image layer found in a vector tile.
+/// The name, type, width and height are read upfront while the image itself is only copied out
+/// of the tile the first time [`ImageLayer::image`] is called.
+#[derive(Debug)]
+pub struct ImageLayer {
+    /// The name of the image
+    pub name: String,
+    /// The image type
+    pub image_type: ImageType,
+    /// The image width
+    pub width: u32,
+    /// The image height
+    pub height: u32,
+    /// position of the image bytes in the tile data
+    image_range: (usize, usize),
+    /// the image data once read
+    image: Option<Vec<u8>>,
+    /// the tile data the image is read from
+    tile_data: Shared<[u8]>,
+}
+impl ImageLayer {
+    /// Create a new ImageLayer
+    pub fn new(tile_data: Shared<[u8]>) -> Self {
+        ImageLayer {
+            name: String::new(),
+            image_type: ImageType::default(),
+            width: 0,
+            height: 0,
+            image_range: (0, 0),
+            image: None,
+            tile_data,
+        }
+    }
+
+    /// Get the image data, reading it on first access
+    pub fn image(&mut self) -> &[u8] {
+        if self.image.is_none() {
+            let (start, end) = self.image_range;
+            self.image = Some(self.tile_data.get(start..end).unwrap_or_default().to_vec());
+        }
+        self.image.as_deref().unwrap_or_default()
+    }
+}
+impl ImageLayer {
+    /// Read the image message covered by `image` from the tile data
+    pub(crate) fn read(tile_data: &Shared<[u8]>, image: Reader) -> crate::reader::Result<Self> {
+        let image = read_image(image)?;
+        Ok(ImageLayer {
+            name: image.name.into_owned(),
+            image_type: image.image_type,
+            width: image.width,
+            height: image.height,
+            image_range: image.image,
+            image: None,
+            tile_data: tile_data.clone(),
+        })
+    }
+}
+
+/// The fields of an image message, read by [`read_image`]. The image itself is the range of
+/// its bytes in the data being read
+#[derive(Debug, Default)]
+pub(crate) struct ImageFields<'a> {
+    pub name: Cow<'a, str>,
+    pub image_type: ImageType,
+    pub width: u32,
+    pub height: u32,
+    pub image: (usize, usize),
+}
+
+/// Read an image message without copying the image
+pub(crate) fn read_image(mut image: Reader) -> crate::reader::Result<ImageFields> {
+    let mut fields = ImageFields::default();
+    while !image.done() {
+        let (tag, wire_type) = image.field()?;
+        let start = image.pos();
+        match tag {
+            1..=3 => image.expect(wire_type, VARINT)?,
+            4 | 5 => image.expect(wire_type, BYTES)?,
+            _ => return Err(image.unknown_tag(start)),
+        }
+        match tag {
+            1 => {
+                let value = image.varint()?;
+                if value > 8 {
+                    return Err(image.invalid_enum(value, start));
+                }
+                fields.image_type = ImageType::from_u64(value);
+            }
+            2 => fields.width = image.read_varint()?,
+            3 => fields.height = image.read_varint()?,
+            4 => fields.image = image.range()?,
+            _ => fields.name = image.string()?,
+        }
+    }
+    Ok(fields)
+}
+
+impl From<&mut ImageLayer> for ImageData {
+    fn from(image: &mut ImageLayer) -> Self {
+        let data = image.image().to_vec();
+        ImageData::new(image.name.clone(), image.image_type, image.width, image.height, data)
+    }
+}
@@ -1,5 +1,6 @@
-use crate::open::{ColumnCacheReader, ColumnCacheWriter, ColumnValue};
-use alloc::{vec, vec::Vec};
+use crate::open::{ColumnCacheWriter, ColumnLookup, ColumnValue, OColumnName};
+use alloc::{format, string::String, vec, vec::Vec};
+use core::fmt;
 use s2json::{
     PrimitiveShape, PrimitiveShapeType, PrimitiveValue, Shape, ShapePrimitive, ShapeType, Value,
     ValuePrimitive, ValuePrimitiveType, ValueType,
@@ -28,14 +29,13 @@ impl PrimitiveShapeToStore for PrimitiveShape {
     }
 
     fn decode(shape_store: &mut Vec<usize>) -> Self {
-        let shape_pair = ShapePair::decode(shape_store.remove(0));
-        shape_pair.count_or_col.into()
+        primitive(ShapePair::decode(take(shape_store)).count_or_col)
     }
 }
 
 trait ShapePrimitiveTypeToStore {
     fn encode(&self, shape_store: &mut Vec<ColumnValue>, cache: &mut ColumnCacheWriter);
-    fn decode(store: &mut Vec<usize>, cache: &mut ColumnCacheReader) -> Self;
+    fn decode(store: &mut Vec<usize>, cache: &impl ColumnLookup) -> Self;
 }
 impl ShapePrimitiveTypeToStore for PrimitiveShapeType {
     fn encode(&self, shape_store: &mut Vec<ColumnValue>, cache: &mut ColumnCacheWriter) {
@@ -51,27 +51,32 @@ impl ShapePrimitiveTypeToStore for PrimitiveShapeType {
         }
     }
 
-    fn decode(store: &mut Vec<usize>, cache: &mut ColumnCacheReader) -> Self {
-        let shape_pair = ShapePair::decode(store.remove(0));
+    fn decode(store: &mut Vec<usize>, cache: &impl ColumnLookup) -> Self {
+        let shape_pair = ShapePair::decode(take(store));
         match shape_pair.p_type {
-            ShapeDefinition::Primitive => {
-                Self::Primitive(PrimitiveShape::from(shape_pair.count_or_col))
-            }
+            ShapeDefinition::Primitive => Self::Primitive(primitive(shape_pair.count_or_col)),
             ShapeDefinition::Object => {
                 let mut nested = ShapePrimitive::new();
                 for _ in 0..shape_pair.count_or_col {
-                    nested.insert(cache.get_string(store.remove(0)), PrimitiveShape::decode(store));
+                    if store.is_empty() {
+                        break;
+                    }
+                    nested.insert(
+                        cache.get_string(take(store)).into_owned(),
+                        PrimitiveShape::decode(store),
+                    );
                 }
                 Self::NestedPrimitive(nested)
             }
-            _ => panic!("Unknown shape definition: {:?}", shape_pair),
+            // arrays can not hold arrays
+            ShapeDefinition::Array => Self::Primitive(PrimitiveShape::Null),
         }
     }
 }
 
 trait ShapeTypeToStore {
     fn encode(&self, shape_store: &mut Vec<ColumnValue>, cache: &mut ColumnCacheWriter);
-    fn decode(store: &mut Vec<usize>, cache: &mut ColumnCacheReader) -> Self;
+    fn decode(store: &mut Vec<usize>, cache: &impl ColumnLookup) -> Self;
 }
 impl ShapeTypeToStore for ShapeType {
     fn encode(&self, shape_store: &mut Vec<ColumnValue>, cache: &mut ColumnCacheWriter) {
@@ -87,13 +92,11 @@ impl ShapeTypeToStore for ShapeType {
         }
     }
 
-    fn decode(store: &mut Vec<usize>, cache: &mut ColumnCacheReader) -> Self {
-        let code = store.remove(0);
+    fn decode(store: &mut Vec<usize>, cache: &impl ColumnLookup) -> Self {
+        let code = take(store);
         let shape_pair = ShapePair::decode(code);
         match shape_pair.p_type {
-            ShapeDefinition::Primitive => {
-                Self::Primitive(PrimitiveShape::from(shape_pair.count_or_col))
-            }
+            ShapeDefinition::Primitive => Self::Primitive(primitive(shape_pair.count_or_col)),
             ShapeDefinition::Array => Self::Array(vec![PrimitiveShapeType::decode(store, cache)]),
             ShapeDefinition::Object => {
                 // reinsert code because shape will check it again
@@ -109,7 +112,7 @@ pub trait ShapeToStore {
     /// Encode the shape
     fn encode(&self, shape_store: &mut Vec<ColumnValue>, cache: &mut ColumnCacheWriter);
     /// Decode the shape
-    fn decode(store: &mut Vec<usize>, cache: &mut ColumnCacheReader) -> Self;
+    fn decode(store: &mut Vec<usize>, cache: &impl ColumnLookup) -> Self;
 }
 impl ShapeToStore for Shape {
     fn encode(&self, shape_store: &mut Vec<ColumnValue>, cache: &mut ColumnCacheWriter) {
@@ -119,14 +122,17 @@ impl ShapeToStore for Shape {
             value.encode(shape_store, cache);
         }
     }
-    fn decode(store: &mut Vec<usize>, cache: &mut ColumnCacheReader) -> Self {
+    fn decode(store: &mut Vec<usize>, cache: &impl ColumnLookup) -> Self {
         let mut map = Shape::new();
-        let shape_pair = ShapePair::decode(store.remove(0));
+        let shape_pair = ShapePair::decode(take(store));
         if shape_pair.p_type != ShapeDefinition::Object {
-            panic!("expected object shape")
+            return map;
         }
         for _ in 0..shape_pair.count_or_col {
-            let key = cache.get_string(store.remove(0));
+            if store.is_empty() {
+                break;
+            }
+            let key = cache.get_string(take(store)).into_owned();
             let shape = ShapeType::decode(store, cache);
             map.insert(key, shape);
         }
@@ -150,11 +156,91 @@ pub fn encode_shape(shape: &Shape, cache: &mut ColumnCacheWriter) -> usize {
     cache.add_shapes(shape_store)
 }
 
-/// Decode shapes from the column cache using an index to find the shape encoding
-pub fn decode_shape(shape_index: usize, cache: &mut ColumnCacheReader) -> Shape {
+/// Decode shapes from the column cache using an index to find the shape encoding.
+/// A malformed shape decodes to an empty one, see [`try_decode_shape`]
+pub fn decode_shape(shape_index: usize, cache: &impl ColumnLookup) -> Shape {
+    try_decode_shape(shape_index, cache).unwrap_or_default()
+}
+
+/// Decode shapes from the column cache, returning None if the shape is malformed: its encoding
+/// is cut short, refers to a string that is not in the cache, holds an array of arrays or nests
+/// objects more than 64 deep
+pub fn try_decode_shape(shape_index: usize, cache: &impl ColumnLookup) -> Option<Shape> {
     let mut shape_store = cache.get_shapes(shape_index);
-    // duplicate the array to avoid modifying the original
-    Shape::decode(&mut shape_store, cache)
+    let mut check =
+        ShapeCheck { store: &shape_store, strings: cache.column_len(OColumnName::String) };
+    let pair = ShapePair::decode(check.take()?);
+    if pair.p_type != ShapeDefinition::Object {
+        return None;
+    }
+    check.object(pair.count_or_col, 0)?;
+    Some(Shape::decode(&mut shape_store, cache))
+}
+
+/// Objects nested deeper than this inside a shape are rejected
+const MAX_SHAPE_DEPTH: usize = 64;
+/// Arrays of null are not backed by any column, so a corrupt length is capped to this
+const MAX_NULL_ARRAY: usize = 1 << 16;
+
+/// Walks an encoded shape without decoding it
+struct ShapeCheck<'a> {
+    store: &'a [usize],
+    /// the number of strings in the cache
+    strings: usize,
+}
+impl ShapeCheck<'_> {
+    fn take(&mut self) -> Option<usize> {
+        let (first, rest) = self.store.split_first()?;
+        self.store = rest;
+        Some(*first)
+    }
+
+    fn key(&mut self) -> Option<()> {
+        (self.take()? < self.strings).then_some(())
+    }
+
+    fn primitive(&mut self) -> Option<()> {
+        (ShapePair::decode(self.take()?).count_or_col <= 6).then_some(())
+    }
+
+    fn object(&mut self, count: usize, depth: usize) -> Option<()> {
+        if depth > MAX_SHAPE_DEPTH {
+            return None;
+        }
+        for _ in 0..count {
+            self.key()?;
+            let pair = ShapePair::decode(self.take()?);
+            match pair.p_type {
+                ShapeDefinition::Primitive if pair.count_or_col <= 6 => {}
+                ShapeDefinition::Primitive => return None,
+                ShapeDefinition::Array => {
+                    let pair = ShapePair::decode(self.take()?);
+                    match pair.p_type {
+                        ShapeDefinition::Primitive if pair.count_or_col <= 6 => {}
+                        ShapeDefinition::Object => {
+                            for _ in 0..pair.count_or_col {
+                                self.key()?;
+                                self.primitive()?;
+                            }
+                        }
+                        _ => return None,
+                    }
+                }
+                ShapeDefinition::Object => self.object(pair.count_or_col, depth + 1)?,
+            }
+        }
+        Some(())
+    }
+}
+
+/// Take the next entry of a store, or 0 once it is exhausted
+fn take(store: &mut Vec<usize>) -> usize {
+    if store.is_empty() { 0 } else { store.remove(0) }
+}
+
+/// The primitive shape of a shape code, or null if the code is unknown
+fn primitive(code: usize) -> PrimitiveShape {
+    if code <= 6 { PrimitiveShape::from(code) } else { PrimitiveShape::Null }
 }
 
 /// A shape pair for stronger compression and decoding
@@ -208,12 +294,8 @@ trait PrimitiveValueToStore {
         shape: &PrimitiveShape,
         store: &mut Vec<ColumnValue>,
         cache: &mut ColumnCacheWriter,
-    );
-    fn decode(
-        shape: &PrimitiveShape,
-        store: &mut Vec<usize>,
-        cache: &mut ColumnCacheReader,
-    ) -> Self;
+    ) -> Result<(), ValueMismatch>;
+    fn decode(shape: &PrimitiveShape, store: &mut Vec<usize>, cache: &impl ColumnLookup) -> Self;
 }
 impl PrimitiveValueToStore for PrimitiveValue {
     fn encode(
@@ -221,7 +303,7 @@ impl PrimitiveValueToStore for PrimitiveValue {
         shape: &PrimitiveShape,
         store: &mut Vec<ColumnValue>,
         cache: &mut ColumnCacheWriter,
-    ) {
+    ) -> Result<(), ValueMismatch> {
         match (self, shape) {
             // string
             (PrimitiveValue::String(s), PrimitiveShape::String) => {
@@ -259,28 +341,29 @@ impl PrimitiveValueToStore for PrimitiveValue {
             }
             // null
             (PrimitiveValue::Null, PrimitiveShape::Null) => {}
-            _ => panic!("shape mismatch"),
+            _ => {
+                return Err(ValueMismatch::Primitive(String::new(), shape.clone(), self.clone()));
+            }
         }
+        Ok(())
     }
 
-    fn decode(
-        shape: &PrimitiveShape,
-        store: &mut Vec<usize>,
-        cache: &mut ColumnCacheReader,
-    ) -> Self {
-        let col_val = store.remove(0);
+    fn decode(shape: &PrimitiveShape, store: &mut Vec<usize>, cache: &impl ColumnLookup) -> Self {
+        // null does not need to be decoded so it takes no column
+        if *shape == PrimitiveShape::Null {
+            return PrimitiveValue::Null;
+        }
+        let col_val = take(store);
         match shape {
-            PrimitiveShape::String => PrimitiveValue::String(cache.get_string(col_val)),
+            PrimitiveShape::String => {
+                PrimitiveValue::String(cache.get_string(col_val).into_owned())
+            }
             PrimitiveShape::U64 => PrimitiveValue::U64(cache.get_unsigned(col_val)),
             PrimitiveShape::I64 => PrimitiveValue::I64(cache.get_signed(col_val)),
             PrimitiveShape::F32 => PrimitiveValue::F32(cache.get_float(col_val)),
             PrimitiveShape::F64 => PrimitiveValue::F64(cache.get_double(col_val)),
             PrimitiveShape::Bool => PrimitiveValue::Bool(cache.get_unsigned(col_val) == 1),
-            PrimitiveShape::Null => {
-                // put the column back because null does not need to be decoded
-                store.insert(0, col_val);
-                PrimitiveValue::Null
-            }
+            PrimitiveShape::Null => PrimitiveValue::Null,
         }
     }
 }
@@ -291,11 +374,11 @@ trait PrimitiveShapeTypeToStore {
         shape: &PrimitiveShapeType,
         store: &mut Vec<ColumnValue>,
         cache: &mut ColumnCacheWriter,
-    );
+    ) -> Result<(), ValueMismatch>;
     fn decode(
         shape: &PrimitiveShapeType,
         store: &mut Vec<usize>,
-        cache: &mut ColumnCacheReader,
+        cache: &impl ColumnLookup,
     ) -> Self;
 }
 impl PrimitiveShapeTypeToStore for ValuePrimitiveType {
@@ -304,30 +387,37 @@ impl PrimitiveShapeTypeToStore for ValuePrimitiveType {
         shape: &PrimitiveShapeType,
         store: &mut Vec<ColumnValue>,
         cache: &mut ColumnCacheWriter,
-    ) {
+    ) -> Result<(), ValueMismatch> {
         match (shape, self) {
             (
                 PrimitiveShapeType::Primitive(shape_prim),
                 ValuePrimitiveType::Primitive(value_prim),
-            ) => {
-                value_prim.encode(shape_prim, store, cache);
-            }
+            ) => value_prim.encode(shape_prim, store, cache),
             (
                 PrimitiveShapeType::NestedPrimitive(shape_nest),
                 ValuePrimitiveType::NestedPrimitive(value_nest),
             ) => {
                 for (key, prim_shape) in shape_nest.iter() {
-                    let val = value_nest.get(key).unwrap();
-                    val.encode(prim_shape, store, cache);
+                    match value_nest.get(key) {
+                        Some(val) => val.encode(prim_shape, store, cache),
+                        None => PrimitiveValue::default_from_shape(prim_shape)
+                            .encode(prim_shape, store, cache),
+                    }
+                    .map_err(|err| err.under(key))?;
                 }
+                Ok(())
             }
-            _ => panic!("shape and value do not match"),
+            _ => Err(ValueMismatch::Structure(
+                String::new(),
+                ShapeType::Array(vec![shape.clone()]),
+                ValueType::Array(vec![self.clone()]),
+            )),
         }
     }
     fn decode(
         shape: &PrimitiveShapeType,
         store: &mut Vec<usize>,
-        cache: &mut ColumnCacheReader,
+        cache: &impl ColumnLookup,
     ) -> Self {
         match shape {
             PrimitiveShapeType::Primitive(shape_prim) => {
@@ -346,51 +436,75 @@ impl PrimitiveShapeTypeToStore for ValuePrimitiveType {
 
 /// Encode/Decode a ValueType to the column cache with a Shape describing how to store
 pub trait ValueTypeToStore {
-    /// Encode the value type into the store
+    /// Encode the value type into the store, panicking if it does not match the shape
     fn encode(
         &self,
         shape: &ShapeType,
         store: &mut Vec<ColumnValue>,
         cache: &mut ColumnCacheWriter,
-    );
+    ) {
+        self.try_encode(shape, store, cache).unwrap_or_else(|err| panic!("{}", err))
+    }
+    /// Encode the value type into the store, returning where it does not match the shape
+    fn try_encode(
+        &self,
+        shape: &ShapeType,
+        store: &mut Vec<ColumnValue>,
+        cache: &mut ColumnCacheWriter,
+    ) -> Result<(), ValueMismatch>;
     /// Decode the value type from the store
-    fn decode(shape: &ShapeType, store: &mut Vec<usize>, cache: &mut ColumnCacheReader) -> Self;
+    fn decode(shape: &ShapeType, store: &mut Vec<usize>, cache: &impl ColumnLookup) -> Self;
 }
 impl ValueTypeToStore for ValueType {
-    fn encode(
+    fn try_encode(
         &self,
         shape: &ShapeType,
         store: &mut Vec<ColumnValue>,
         cache: &mut ColumnCacheWriter,
-    ) {
+    ) -> Result<(), ValueMismatch> {
+        let mismatch = || ValueMismatch::Structure(String::new(), shape.clone(), self.clone());
         match (self, shape) {
             (ValueType::Primitive(val), ShapeType::Primitive(shape)) => {
-                val.encode(shape, store, cache);
+                val.encode(shape, store, cache)
             }
             (ValueType::Array(vals), ShapeType::Array(shape)) => {
+                if vals.is_empty() {
+                    store.push(ColumnValue::Number(0));
+                    return Ok(());
+                }
+                let element = shape.first().ok_or_else(mismatch)?;
                 // encode length
                 store.push(ColumnValue::Number(vals.len()));
-                for val in vals {
-                    val.encode(&shape[0], store, cache);
+                for (i, val) in vals.iter().enumerate() {
+                    val.encode(element, store, cache)
+                        .map_err(|err| err.under(&format!("[{i}]")))?;
                 }
+                Ok(())
             }
             (ValueType::Nested(val), ShapeType::Nested(shape)) => {
-                val.encode(shape, store, cache);
+                val.try_encode(shape, store, cache)
             }
-            _ => panic!("shape and value do not match"),
+            _ => Err(mismatch()),
         }
     }
 
-    fn decode(shape: &ShapeType, store: &mut Vec<usize>, cache: &mut ColumnCacheReader) -> Self {
+    fn decode(shape: &ShapeType, store: &mut Vec<usize>, cache: &impl ColumnLookup) -> Self {
         match shape {
             ShapeType::Primitive(shape_prim) => {
                 ValueType::Primitive(PrimitiveValue::decode(shape_prim, store, cache))
             }
             ShapeType::Array(shape_arr) => {
                 let mut val = Vec::<ValuePrimitiveType>::new();
-                let len = store.remove(0);
+                let len = take(store);
+                let Some(element) = shape_arr.first() else { return ValueType::Array(val) };
+                // a corrupt length must not read past the store, or allocate without bound when
+                // the elements take no entries
+                let len = if element_len(element) == 0 { len.min(MAX_NULL_ARRAY) } else { len };
                 for _ in 0..len {
-                    val.push(ValuePrimitiveType::decode(&shape_arr[0], store, cache));
+                    if store.is_empty() && element_len(element) > 0 {
+                        break;
+                    }
+                    val.push(ValuePrimitiveType::decode(element, store, cache));
                 }
                 ValueType::Array(val)
             }
@@ -401,19 +515,41 @@ impl ValueTypeToStore for ValueType {
 
 /// Encode/Decode a Value to the column cache with a Shape describing how to store
 pub trait ValueToStore {
-    /// Encode the value into the store
-    fn encode(&self, shape: &Shape, store: &mut Vec<ColumnValue>, cache: &mut ColumnCacheWriter);
+    /// Encode the value into the store, panicking if it does not match the shape
+    fn encode(&self, shape: &Shape, store: &mut Vec<ColumnValue>, cache: &mut ColumnCacheWriter) {
+        self.try_encode(shape, store, cache).unwrap_or_else(|err| panic!("{}", err))
+    }
+    /// Encode the value into the store, returning where it does not match the shape. Keys missing
+    /// from the value, including keys of objects inside arrays, are encoded as the shape's
+    /// default.
+    fn try_encode(
+        &self,
+        shape: &Shape,
+        store: &mut Vec<ColumnValue>,
+        cache: &mut ColumnCacheWriter,
+    ) -> Result<(), ValueMismatch>;
     /// Decode the value from the store
-    fn decode(shape: &Shape, store: &mut Vec<usize>, cache: &mut ColumnCacheReader) -> Self;
+    fn decode(shape: &Shape, store: &mut Vec<usize>, cache: &impl ColumnLookup) -> Self;
 }
 impl ValueToStore for Value {
-    fn encode(&self, shape: &Shape, store: &mut Vec<ColumnValue>, cache: &mut ColumnCacheWriter) {
+    fn try_encode(
+        &self,
+        shape: &Shape,
+        store: &mut Vec<ColumnValue>,
+        cache: &mut ColumnCacheWriter,
+    ) -> Result<(), ValueMismatch> {
         for (key, shape_type) in shape.iter() {
-            let val = self.get(key).unwrap_or(&ValueType::default_from_shape(shape_type)).clone();
-            val.encode(shape_type, store, cache);
+            match self.get(key) {
+                Some(val) => val.try_encode(shape_type, store, cache),
+                None => {
+                    ValueType::default_from_shape(shape_type).try_encode(shape_type, store, cache)
+                }
+            }
+            .map_err(|err| err.under(key))?;
         }
+        Ok(())
     }
-    fn decode(shape: &Shape, store: &mut Vec<usize>, cache: &mut ColumnCacheReader) -> Self {
+    fn decode(shape: &Shape, store: &mut Vec<usize>, cache: &impl ColumnLookup) -> Self {
         let mut value = Self::new();
         for (key, shape_type) in shape.iter() {
             let val = ValueType::decode(shape_type, store, cache);
@@ -423,15 +559,116 @@ impl ValueToStore for Value {
     }
 }
 
-/// Encode a value to the column cache
+/// Encode a value to the column cache, panicking if it does not match the shape
 pub fn encode_value(value: &Value, shape: &Shape, cache: &mut ColumnCacheWriter) -> usize {
+    try_encode_value(value, shape, cache).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Encode a value to the column cache, returning where it does not match the shape. Missing keys
+/// are encoded as the shape's default, see [`ValueToStore::try_encode`].
+pub fn try_encode_value(
+    value: &Value,
+    shape: &Shape,
+    cache: &mut ColumnCacheWriter,
+) -> Result<usize, ValueMismatch> {
     let mut value_store: Vec<ColumnValue> = vec![];
-    value.encode(shape, &mut value_store, cache);
-    cache.add_shapes(value_store)
+    value.try_encode(shape, &mut value_store, cache)?;
+    Ok(cache.add_shapes(value_store))
 }
 
 /// Decode a value from the column cache
-pub fn decode_value(value_index: usize, shape: &Shape, cache: &mut ColumnCacheReader) -> Value {
+pub fn decode_value(value_index: usize, shape: &Shape, cache: &impl ColumnLookup) -> Value {
     let value_store = cache.get_shapes(value_index);
     Value::decode(shape, &mut value_store.clone(), cache)
 }
+
+/// Decode a single top level key of a value from the column cache. The columns of the keys
+/// before it are skipped without being looked up. Returns None if the shape has no such key
+pub fn decode_value_key(
+    value_index: usize,
+    shape: &Shape,
+    key: &str,
+    cache: &impl ColumnLookup,
+) -> Option<ValueType> {
+    let mut store = cache.get_shapes(value_index);
+    for (k, shape_type) in shape.iter() {
+        if k == key {
+            return Some(ValueType::decode(shape_type, &mut store, cache));
+        }
+        let len = value_type_len(shape_type, &store);
+        store.drain(..len.min(store.len()));
+    }
+    None
+}
+
+/// The number of columns the value of a shape type takes up at the start of `store`
+fn value_type_len(shape: &ShapeType, store: &[usize]) -> usize {
+    match shape {
+        ShapeType::Primitive(shape) => primitive_len(shape),
+        ShapeType::Array(shape) => {
+            let element_len = shape.first().map(element_len).unwrap_or(0);
+            store.first().copied().unwrap_or(0).saturating_mul(element_len).saturating_add(1)
+        }
+        ShapeType::Nested(shape) => {
+            let mut len = 0;
+            for shape_type in shape.values() {
+                len =
+                    value_type_len(shape_type, &store[len.min(store.len())..]).saturating_add(len);
+            }
+            len
+        }
+    }
+}
+
+/// The number of columns a primitive takes up, null takes none
+fn primitive_len(shape: &PrimitiveShape) -> usize {
+    usize::from(*shape != PrimitiveShape::Null)
+}
+
+/// The number of columns an array element takes up
+fn element_len(shape: &PrimitiveShapeType) -> usize {
+    match shape {
+        PrimitiveShapeType::Primitive(shape) => primitive_len(shape),
+        PrimitiveShapeType::NestedPrimitive(shape) => shape.values().map(primitive_len).sum(),
+    }
+}
+
+/// Where and why a value can not be encoded with a shape
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueMismatch {
+    /// path to the key, the primitive expected and the primitive found
+    Primitive(String, PrimitiveShape, PrimitiveValue),
+    /// path to the key, the shape expected and the value found
+    Structure(String, ShapeType, ValueType),
+}
+impl ValueMismatch {
+    /// the path to the key, e.g. `a.b[2]`
+    pub fn path(&self) -> &str {
+        match self {
+            ValueMismatch::Primitive(path, ..) | ValueMismatch::Structure(path, ..) => path,
+        }
+    }
+
+    /// Prefix the path with the key (or `[index]`) the mismatch was found under
+    pub(crate) fn under(mut self, key: &str) -> Self {
+        let (ValueMismatch::Primitive(path, ..) | ValueMismatch::Structure(path, ..)) = &mut self;
+        if !path.is_empty() && !path.starts_with('[') {
+            path.insert(0, '.');
+        }
+        path.insert_str(0, key);
+        self
+    }
+}
+impl fmt::Display for ValueMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueMismatch::Primitive(path, expected, actual) => {
+                write!(f, "property \"{}\" expected {:?} but found {:?}", path, expected, actual)
+            }
+            ValueMismatch::Structure(path, expected, actual) => {
+                write!(f, "property \"{}\" expected {:?} but found {:?}", path, expected, actual)
+            }
+        }
+    }
+}
+impl core::error::Error for ValueMismatch {}
@@ -1,15 +1,20 @@
 use super::decode_value;
+use crate::shared::{Lazy, Shared, shared};
 use crate::{
     Point, Point3D, VectorFeatureMethods, VectorGeometry, VectorLine3DWithOffset,
     VectorLineWithOffset, VectorLines3DWithOffset, VectorLinesWithOffset, VectorPoints,
     VectorPoints3D,
     base::{BaseVectorFeature, TessellationWrapper, decode_offset},
+    error::{DecodeError, ProtoMessage},
     mapbox::FeatureType as MapboxFeatureType,
-    open::{ColumnCacheReader, ColumnCacheWriter, encode_value},
+    open::{
+        ColumnCacheReader, ColumnCacheWriter, ColumnLookup, OColumnName, ValueMismatch,
+        try_encode_value,
+    },
+    reader::{Reader, Result},
     unweave_2d, unweave_3d, zagzig,
 };
-use alloc::{rc::Rc, vec, vec::Vec};
-use core::cell::RefCell;
+use alloc::{vec, vec::Vec};
 use pbf::{BitCast, Protobuf};
 use s2json::{BBOX, Properties, Shape};
 use serde::{Deserialize, Serialize};
@@ -107,7 +112,15 @@ impl BitCast for FeatureType {
             4 => FeatureType::Points3D,
             5 => FeatureType::Lines3D,
             6 => FeatureType::Polygons3D,
-            _ => panic!("unknown value: {}", value),
+            _ => panic!(
+                "{}",
+                DecodeError::InvalidEnum {
+                    message: ProtoMessage::OpenFeature,
+                    tag: None,
+                    value,
+                    offset: 0,
+                }
+            ),
         }
     }
 }
@@ -122,20 +135,24 @@ impl From<&MapboxFeatureType> for FeatureType {
     }
 }
 
-/// Open Vector Tile Feature specification
+/// Open Vector Tile Feature specification. The properties, geometry and M-Values are looked up
+/// in the tile's column cache `C` when they are first read
 #[derive(Debug)]
-pub struct OpenVectorFeature {
+pub struct OpenVectorFeature<C: ColumnLookup = ColumnCacheReader> {
     /// the id of the feature
     pub id: Option<u64>,
-    /// the properties of the feature
-    pub properties: Properties,
     /// the type of the feature
     pub r#type: FeatureType,
-    cache: Rc<RefCell<ColumnCacheReader>>,
-    m_shape: Shape,
+    cache: Shared<C>,
+    shape: Shared<Shape>,
+    m_shape: Shared<Shape>,
     extent: Extent,
-    geometry_indices: Vec<u32>,
-    geometry: Option<VectorGeometry>,
+    value_index: usize,
+    properties: Lazy<Properties>,
+    /// the index of the geometry in the indices column, or the point itself if `single`
+    geometry_index: u64,
+    geometry_indices: Lazy<Vec<u32>>,
+    geometry: Lazy<VectorGeometry>,
     single: bool,
     bbox_index: Option<usize>,
     has_offsets: bool,
@@ -143,13 +160,52 @@ pub struct OpenVectorFeature {
     indices_index: Option<usize>,
     tessellation_index: Option<usize>,
 }
-impl OpenVectorFeature {
-    fn _load_geometry_points(&mut self) -> VectorPoints {
-        let mut cache = self.cache.borrow_mut();
+impl<C: ColumnLookup> Clone for OpenVectorFeature<C> {
+    fn clone(&self) -> Self {
+        OpenVectorFeature {
+            id: self.id,
+            r#type: self.r#type,
+            cache: self.cache.clone(),
+            shape: self.shape.clone(),
+            m_shape: self.m_shape.clone(),
+            extent: self.extent,
+            value_index: self.value_index,
+            properties: self.properties.clone(),
+            geometry_index: self.geometry_index,
+            geometry_indices: self.geometry_indices.clone(),
+            geometry: self.geometry.clone(),
+            single: self.single,
+            bbox_index: self.bbox_index,
+            has_offsets: self.has_offsets,
+            has_m_values: self.has_m_values,
+            indices_index: self.indices_index,
+            tessellation_index: self.tessellation_index,
+        }
+    }
+}
+impl<C: ColumnLookup> OpenVectorFeature<C> {
+    /// The index of the feature's properties in the column cache
+    pub fn value_index(&self) -> usize {
+        self.value_index
+    }
+
+    /// The indices describing the geometry, read from the indices column on first use
+    fn geometry_indices(&self) -> &[u32] {
+        self.geometry_indices.get_or_init(|| {
+            let is_points = matches!(self.r#type, FeatureType::Points | FeatureType::Points3D);
+            if is_points && self.single {
+                vec![self.geometry_index as u32]
+            } else {
+                self.cache.get_indices(self.geometry_index as usize)
+            }
+        })
+    }
+
+    fn _load_geometry_points(&self) -> VectorPoints {
+        let cache = &self.cache;
 
-        let mut index_pos = 0;
-        let geometry_index = self.geometry_indices[index_pos];
-        index_pos += 1;
+        let mut indices = self.geometry_indices().iter().copied();
+        let Some(geometry_index) = indices.next() else { return vec![] };
         if self.single {
             let (a, b) = unweave_2d(geometry_index);
             vec![Point::new(zagzig(a as u32), zagzig(b as u32))]
@@ -157,11 +213,8 @@ impl OpenVectorFeature {
             let mut geometry = cache.get_points(geometry_index as usize);
 
             if self.has_m_values {
-                let length = geometry.len();
-                geometry.iter_mut().take(length).for_each(|p| {
-                    let value_index = self.geometry_indices[index_pos];
-                    p.m = Some(decode_value(value_index as usize, &self.m_shape, &mut cache));
-                    index_pos += 1;
+                geometry.iter_mut().for_each(|p| {
+                    p.m = indices.next().map(|i| decode_value(i as usize, &self.m_shape, &**cache));
                 });
             }
 
@@ -169,12 +222,11 @@ impl OpenVectorFeature {
         }
     }
 
-    fn _load_geometry_points_3d(&mut self) -> VectorPoints3D {
-        let mut cache = self.cache.borrow_mut();
+    fn _load_geometry_points_3d(&self) -> VectorPoints3D {
+        let cache = &self.cache;
 
-        let mut index_pos = 0;
-        let geometry_index = self.geometry_indices[index_pos];
-        index_pos += 1;
+        let mut indices = self.geometry_indices().iter().copied();
+        let Some(geometry_index) = indices.next() else { return vec![] };
         if self.single {
             let (a, b, c) = unweave_3d(geometry_index as u64);
             vec![Point3D::new(zagzig(a), zagzig(b), zagzig(c))]
@@ -182,11 +234,8 @@ impl OpenVectorFeature {
             let mut geometry = cache.get_points_3d(geometry_index as usize);
 
             if self.has_m_values {
-                let length = geometry.len();
-                geometry.iter_mut().take(length).for_each(|p| {
-                    let value_index = self.geometry_indices[index_pos];
-                    p.m = Some(decode_value(value_index as usize, &self.m_shape, &mut cache));
-                    index_pos += 1;
+                geometry.iter_mut().for_each(|p| {
+                    p.m = indices.next().map(|i| decode_value(i as usize, &self.m_shape, &**cache));
                 });
             }
 
@@ -194,34 +243,35 @@ impl OpenVectorFeature {
         }
     }
 
-    fn _load_geometry_lines(&mut self) -> VectorLinesWithOffset {
-        let mut cache = self.cache.borrow_mut();
+    fn _load_geometry_lines(&self) -> VectorLinesWithOffset {
+        let mut indices = self.geometry_indices().iter().copied();
+        let line_count = if self.single { 1 } else { indices.next().unwrap_or(0) };
+        self._load_lines(&mut indices, line_count)
+    }
 
-        let mut res: VectorLinesWithOffset = vec![];
+    /// Read `line_count` lines, stopping early if the geometry indices run out
+    fn _load_lines(
+        &self,
+        indices: &mut impl Iterator<Item = u32>,
+        line_count: u32,
+    ) -> VectorLinesWithOffset {
+        let cache = &self.cache;
 
-        let mut index_pos = 0;
-        let mut line_count = 1;
-        if !self.single {
-            line_count = self.geometry_indices[index_pos];
-            index_pos += 1;
-        };
+        let mut res: VectorLinesWithOffset = vec![];
         for _ in 0..line_count {
             // get offset if it exists
             let mut offset = 0.0;
             if self.has_offsets {
-                offset = decode_offset(self.geometry_indices[index_pos]);
-                index_pos += 1;
+                let Some(encoded) = indices.next() else { break };
+                offset = decode_offset(encoded);
             }
             // get geometry
-            let mut geometry = cache.get_points(self.geometry_indices[index_pos] as usize);
-            index_pos += 1;
+            let Some(index) = indices.next() else { break };
+            let mut geometry = cache.get_points(index as usize);
             // inject m values if they exist
             if self.has_m_values {
-                let length = geometry.len();
-                geometry.iter_mut().take(length).for_each(|p| {
-                    let value_index = self.geometry_indices[index_pos];
-                    p.m = Some(decode_value(value_index as usize, &self.m_shape, &mut cache));
-                    index_pos += 1;
+                geometry.iter_mut().for_each(|p| {
+                    p.m = indices.next().map(|i| decode_value(i as usize, &self.m_shape, &**cache));
                 });
             }
             res.push(VectorLineWithOffset::new(offset, geometry));
@@ -230,34 +280,35 @@ impl OpenVectorFeature {
         res
     }
 
-    fn _load_geometry_lines_3d(&mut self) -> VectorLines3DWithOffset {
-        let mut cache = self.cache.borrow_mut();
+    fn _load_geometry_lines_3d(&self) -> VectorLines3DWithOffset {
+        let mut indices = self.geometry_indices().iter().copied();
+        let line_count = if self.single { 1 } else { indices.next().unwrap_or(0) };
+        self._load_lines_3d(&mut indices, line_count)
+    }
 
-        let mut res: VectorLines3DWithOffset = vec![];
+    /// Read `line_count` 3D lines, stopping early if the geometry indices run out
+    fn _load_lines_3d(
+        &self,
+        indices: &mut impl Iterator<Item = u32>,
+        line_count: u32,
+    ) -> VectorLines3DWithOffset {
+        let cache = &self.cache;
 
-        let mut index_pos = 0;
-        let mut line_count = 1;
-        if !self.single {
-            line_count = self.geometry_indices[index_pos];
-            index_pos += 1;
-        };
+        let mut res: VectorLines3DWithOffset = vec![];
         for _ in 0..line_count {
             // get offset if it exists
             let mut offset = 0.0;
             if self.has_offsets {
-                offset = decode_offset(self.geometry_indices[index_pos]);
-                index_pos += 1;
+                let Some(encoded) = indices.next() else { break };
+                offset = decode_offset(encoded);
             }
             // get geometry
-            let mut geometry = cache.get_points_3d(self.geometry_indices[index_pos] as usize);
-            index_pos += 1;
+            let Some(index) = indices.next() else { break };
+            let mut geometry = cache.get_points_3d(index as usize);
             // inject m values if they exist
             if self.has_m_values {
-                let length = geometry.len();
-                geometry.iter_mut().take(length).for_each(|p| {
-                    let value_index = self.geometry_indices[index_pos];
-                    p.m = Some(decode_value(value_index as usize, &self.m_shape, &mut cache));
-                    index_pos += 1;
+                geometry.iter_mut().for_each(|p| {
+                    p.m = indices.next().map(|i| decode_value(i as usize, &self.m_shape, &**cache));
                 });
             }
             res.push(VectorLine3DWithOffset::new(offset, geometry));
@@ -266,91 +317,33 @@ impl OpenVectorFeature {
         res
     }
 
-    fn _load_geometry_polys(&mut self) -> Vec<VectorLinesWithOffset> {
-        let mut cache = self.cache.borrow_mut();
-
+    fn _load_geometry_polys(&self) -> Vec<VectorLinesWithOffset> {
         let mut res: Vec<VectorLinesWithOffset> = vec![];
 
-        let mut index_pos = 0;
-        let mut poly_count = 1;
-        if !self.single {
-            poly_count = self.geometry_indices[index_pos];
-            index_pos += 1;
-        };
+        let mut indices = self.geometry_indices().iter().copied();
+        let poly_count = if self.single { 1 } else { indices.next().unwrap_or(0) };
         for _ in 0..poly_count {
-            let line_count = self.geometry_indices[index_pos];
-            index_pos += 1;
-            let mut lines: VectorLinesWithOffset = vec![];
-            for _ in 0..line_count {
-                // get offset if it exists
-                let mut offset = 0.0;
-                if self.has_offsets {
-                    offset = decode_offset(self.geometry_indices[index_pos]);
-                    index_pos += 1;
-                }
-                // get geometry
-                let mut geometry = cache.get_points(self.geometry_indices[index_pos] as usize);
-                index_pos += 1;
-                // inject m values if they exist
-                if self.has_m_values {
-                    let length = geometry.len();
-                    geometry.iter_mut().take(length).for_each(|p| {
-                        let value_index = self.geometry_indices[index_pos];
-                        p.m = Some(decode_value(value_index as usize, &self.m_shape, &mut cache));
-                        index_pos += 1;
-                    });
-                }
-                lines.push(VectorLineWithOffset::new(offset, geometry));
-            }
-            res.push(lines);
+            let Some(line_count) = indices.next() else { break };
+            res.push(self._load_lines(&mut indices, line_count));
         }
 
         res
     }
 
-    fn _load_geometry_polys_3d(&mut self) -> Vec<VectorLines3DWithOffset> {
-        let mut cache = self.cache.borrow_mut();
-
+    fn _load_geometry_polys_3d(&self) -> Vec<VectorLines3DWithOffset> {
         let mut res: Vec<VectorLines3DWithOffset> = vec![];
 
-        let mut index_pos = 0;
-        let mut poly_count = 1;
-        if !self.single {
-            poly_count = self.geometry_indices[index_pos];
-            index_pos += 1;
-        };
+        let mut indices = self.geometry_indices().iter().copied();
+        let poly_count = if self.single { 1 } else { indices.next().unwrap_or(0) };
         for _ in 0..poly_count {
-            let line_count = self.geometry_indices[index_pos];
-            index_pos += 1;
-            let mut lines: VectorLines3DWithOffset = vec![];
-            for _ in 0..line_count {
-                // get offset if it exists
-                let mut offset = 0.0;
-                if self.has_offsets {
-                    offset = decode_offset(self.geometry_indices[index_pos]);
-                    index_pos += 1;
-                }
-                // get geometry
-                let mut geometry = cache.get_points_3d(self.geometry_indices[index_pos] as usize);
-                index_pos += 1;
-                // inject m values if they exist
-                if self.has_m_values {
-                    let length = geometry.len();
-                    geometry.iter_mut().take(length).for_each(|p| {
-                        let value_index = self.geometry_indices[index_pos];
-                        p.m = Some(decode_value(value_index as usize, &self.m_shape, &mut cache));
-                        index_pos += 1;
-                    });
-                }
-                lines.push(VectorLine3DWithOffset::new(offset, geometry));
-            }
-            res.push(lines);
+            let Some(line_count) = indices.next() else { break };
+            res.push(self._load_lines_3d(&mut indices, line_count));
         }
 
         res
     }
 }
-impl VectorFeatureMethods for OpenVectorFeature {
+impl<C: ColumnLookup> VectorFeatureMethods for &OpenVectorFeature<C> {
     /// get the id of the feature
     fn id(&self) -> Option<u64> {
         self.id
@@ -367,7 +360,9 @@ impl VectorFeatureMethods for OpenVectorFeature {
     }
 
     fn properties(&self) -> Properties {
-        self.properties.clone()
+        self.properties
+            .get_or_init(|| decode_value(self.value_index, &self.shape, &*self.cache))
+            .clone()
     }
 
     /// Create a new OpenVectorFeature
@@ -378,7 +373,7 @@ impl VectorFeatureMethods for OpenVectorFeature {
     /// get the bbox of the feature
     fn bbox(&self) -> Option<BBOX> {
         if let Some(index) = self.bbox_index {
-            let mut cache = self.cache.borrow_mut();
+            let cache = &self.cache;
             Some(cache.get_bbox(index))
         } else {
             None
@@ -429,7 +424,9 @@ impl VectorFeatureMethods for OpenVectorFeature {
             }
             VectorGeometry::VectorPolys(polys) => polys
                 .iter()
-                .flat_map(|p| p.iter().flat_map(|p| p.geometry[..p.geometry.len() - 1].to_vec()))
+                .flat_map(|p| {
+                    p.iter().flat_map(|p| p.geometry[..p.geometry.len().saturating_sub(1)].to_vec())
+                })
                 .collect(),
             _ => {
                 panic!("unexpected geometry type")
@@ -446,7 +443,9 @@ impl VectorFeatureMethods for OpenVectorFeature {
             }
             VectorGeometry::VectorPolys3D(polys) => polys
                 .iter()
-                .flat_map(|p| p.iter().flat_map(|p| p.geometry[..p.geometry.len() - 1].to_vec()))
+                .flat_map(|p| {
+                    p.iter().flat_map(|p| p.geometry[..p.geometry.len().saturating_sub(1)].to_vec())
+                })
                 .collect(),
             _ => {
                 panic!("unexpected geometry type")
@@ -501,7 +500,7 @@ impl VectorFeatureMethods for OpenVectorFeature {
         if self.indices_index.is_none() {
             return vec![];
         }
-        let mut cache = self.cache.borrow_mut();
+        let cache = &self.cache;
         cache.get_indices(self.indices_index.unwrap())
     }
 
@@ -510,7 +509,7 @@ impl VectorFeatureMethods for OpenVectorFeature {
         let Some(tessellation_index) = self.tessellation_index else {
             return;
         };
-        let mut cache = self.cache.borrow_mut();
+        let cache = &self.cache;
         let data = cache.get_points(tessellation_index);
         for point in data {
             geometry.push(point.x as f64 * multiplier);
@@ -523,7 +522,7 @@ impl VectorFeatureMethods for OpenVectorFeature {
         let Some(tessellation_index) = self.tessellation_index else {
             return;
         };
-        let mut cache = self.cache.borrow_mut();
+        let cache = &self.cache;
         let data = cache.get_points_3d(tessellation_index);
         for point in data {
             geometry.push(point.x as f64 * multiplier);
@@ -578,100 +577,277 @@ impl VectorFeatureMethods for OpenVectorFeature {
 
     /// load the geometry
     fn load_geometry(&mut self) -> VectorGeometry {
-        if let Some(geometry) = &self.geometry {
-            return geometry.clone();
-        }
+        self.geometry
+            .get_or_init(|| match self.r#type {
+                FeatureType::Points => VectorGeometry::VectorPoints(self._load_geometry_points()),
+                FeatureType::Points3D => {
+                    VectorGeometry::VectorPoints3D(self._load_geometry_points_3d())
+                }
+                FeatureType::Lines => VectorGeometry::VectorLines(self._load_geometry_lines()),
+                FeatureType::Lines3D => {
+                    VectorGeometry::VectorLines3D(self._load_geometry_lines_3d())
+                }
+                FeatureType::Polygons => VectorGeometry::VectorPolys(self._load_geometry_polys()),
+                FeatureType::Polygons3D => {
+                    VectorGeometry::VectorPolys3D(self._load_geometry_polys_3d())
+                }
+            })
+            .clone()
+    }
+}
+// features are decoded and cached through `&self`, so the owned feature borrows itself
+impl<C: ColumnLookup> VectorFeatureMethods for OpenVectorFeature<C> {
+    fn id(&self) -> Option<u64> {
+        <&Self>::id(&self)
+    }
 
-        self.geometry = Some(match self.r#type {
-            FeatureType::Points => VectorGeometry::VectorPoints(self._load_geometry_points()),
-            FeatureType::Points3D => {
-                VectorGeometry::VectorPoints3D(self._load_geometry_points_3d())
-            }
-            FeatureType::Lines => VectorGeometry::VectorLines(self._load_geometry_lines()),
-            FeatureType::Lines3D => VectorGeometry::VectorLines3D(self._load_geometry_lines_3d()),
-            FeatureType::Polygons => VectorGeometry::VectorPolys(self._load_geometry_polys()),
-            FeatureType::Polygons3D => {
-                VectorGeometry::VectorPolys3D(self._load_geometry_polys_3d())
-            }
-        });
+    fn version(&self) -> u16 {
+        <&Self>::version(&self)
+    }
+
+    fn properties(&self) -> Properties {
+        <&Self>::properties(&self)
+    }
+
+    fn extent(&self) -> usize {
+        <&Self>::extent(&self)
+    }
+
+    fn get_type(&self) -> FeatureType {
+        <&Self>::get_type(&self)
+    }
+
+    fn bbox(&self) -> Option<BBOX> {
+        <&Self>::bbox(&self)
+    }
+
+    fn has_m_values(&self) -> bool {
+        <&Self>::has_m_values(&self)
+    }
 
-        self.load_geometry()
+    fn is_points(&self) -> bool {
+        <&Self>::is_points(&self)
+    }
+
+    fn is_lines(&self) -> bool {
+        <&Self>::is_lines(&self)
+    }
+
+    fn is_polygons(&self) -> bool {
+        <&Self>::is_polygons(&self)
+    }
+
+    fn is_points_3d(&self) -> bool {
+        <&Self>::is_points_3d(&self)
+    }
+
+    fn is_lines_3d(&self) -> bool {
+        <&Self>::is_lines_3d(&self)
+    }
+
+    fn is_polygons_3d(&self) -> bool {
+        <&Self>::is_polygons_3d(&self)
+    }
+
+    fn load_points(&mut self) -> VectorPoints {
+        <&Self>::load_points(&mut &*self)
+    }
+
+    fn load_points_3d(&mut self) -> VectorPoints3D {
+        <&Self>::load_points_3d(&mut &*self)
+    }
+
+    fn load_lines(&mut self) -> VectorLinesWithOffset {
+        <&Self>::load_lines(&mut &*self)
+    }
+
+    fn load_lines_3d(&mut self) -> VectorLines3DWithOffset {
+        <&Self>::load_lines_3d(&mut &*self)
+    }
+
+    fn load_polys(&mut self) -> Vec<VectorLinesWithOffset> {
+        <&Self>::load_polys(&mut &*self)
+    }
+
+    fn load_polys_3d(&mut self) -> Vec<VectorLines3DWithOffset> {
+        <&Self>::load_polys_3d(&mut &*self)
+    }
+
+    fn load_geometry_flat(&mut self) -> (Vec<f64>, Vec<u32>) {
+        <&Self>::load_geometry_flat(&mut &*self)
+    }
+
+    fn load_geometry(&mut self) -> VectorGeometry {
+        <&Self>::load_geometry(&mut &*self)
+    }
+
+    fn read_indices(&mut self) -> Vec<u32> {
+        <&Self>::read_indices(&mut &*self)
+    }
+
+    fn add_tessellation(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
+        <&Self>::add_tessellation(&mut &*self, geometry, multiplier)
+    }
+
+    fn add_tessellation_3d(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
+        <&Self>::add_tessellation_3d(&mut &*self, geometry, multiplier)
     }
 }
 
-/// Read a single feature given the encoded data
-pub fn read_feature(
-    data: Vec<u8>,
-    extent: Extent,
-    cache: Rc<RefCell<ColumnCacheReader>>,
-    shape: &Shape,
-    m_shape: Shape,
-) -> OpenVectorFeature {
-    let mut pbf = Protobuf::from_input(data);
+/// The header of an open feature message, read by [`read_feature_fields`]
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FeatureFields {
+    pub id: Option<u64>,
+    pub r#type: FeatureType,
+    pub single: bool,
+    pub has_offsets: bool,
+    pub has_m_values: bool,
+    /// the index of the properties in the shapes column
+    pub value_index: usize,
+    /// the index of the geometry in the indices column, or the point itself for a single point
+    pub geometry: u64,
+    pub indices_index: Option<usize>,
+    pub tessellation_index: Option<usize>,
+    pub bbox_index: Option<usize>,
+}
+
+/// Read the header of an open feature, checking that every column it refers to exists
+pub(crate) fn read_feature_fields(
+    mut pbf: Reader,
+    cache: &impl ColumnLookup,
+) -> Result<FeatureFields> {
+    let mut fields = FeatureFields::default();
+    let column = |pbf: &mut Reader, column: OColumnName| -> Result<usize> {
+        let start = pbf.pos();
+        let index = pbf.varint()?;
+        let len = cache.column_len(column);
+        if index >= len as u64 {
+            return Err(pbf.out_of_range(index, len, start));
+        }
+        Ok(index as usize)
+    };
     // pull in the type
-    let r#type: FeatureType = pbf.read_varint();
+    let start = pbf.pos();
+    let r#type = pbf.varint()?;
+    if !(1..=6).contains(&r#type) {
+        return Err(pbf.invalid_enum(r#type, start));
+    }
+    fields.r#type = FeatureType::from_u64(r#type);
+    let is_3d = r#type > 3;
     // next the flags
-    let flags: u8 = pbf.read_varint();
+    let flags: u8 = pbf.read_varint()?;
     // read the id if it exists
-    let id: Option<u64> = if flags & 1 > 0 { Some(pbf.read_varint()) } else { None };
+    if flags & 1 > 0 {
+        fields.id = Some(pbf.varint()?);
+    }
     let has_bbox = flags & (1 << 1) > 0;
-    let has_offsets = (flags & (1 << 2)) > 0;
+    fields.has_offsets = (flags & (1 << 2)) > 0;
     let has_indices = flags & (1 << 3) > 0;
     let has_tessellation = flags & (1 << 4) > 0;
-    let has_m_values = flags & (1 << 5) > 0;
-    let single = flags & (1 << 6) > 0;
-    // read the properties
-    let value_index: usize = pbf.read_varint();
-    let properties = decode_value(value_index, shape, &mut cache.borrow_mut());
-    // if type is 1 or 4, read geometry as a single index, otherwise as an array
-    let mut geometry_indices: Vec<u32> = vec![];
-    let mut indices_index: Option<usize> = None;
-    let mut tessellation_index: Option<usize> = None;
-    if r#type == FeatureType::Points || r#type == FeatureType::Points3D {
-        if single {
-            geometry_indices.push(pbf.read_varint())
-        } else {
-            geometry_indices = cache.borrow_mut().get_indices(pbf.read_varint());
-        }
+    fields.has_m_values = flags & (1 << 5) > 0;
+    fields.single = flags & (1 << 6) > 0;
+    // the properties
+    fields.value_index = column(&mut pbf, OColumnName::Shapes)?;
+    // a single point is stored in place, anything else is an index into the indices column
+    let is_points = matches!(fields.r#type, FeatureType::Points | FeatureType::Points3D);
+    fields.geometry = if is_points && fields.single {
+        pbf.varint()?
     } else {
-        geometry_indices = cache.borrow_mut().get_indices(pbf.read_varint());
-    }
-    // read indices and tessellation if they exist
-    if r#type == FeatureType::Polygons || r#type == FeatureType::Polygons3D {
+        column(&mut pbf, OColumnName::Indices)? as u64
+    };
+    // indices and tessellation are only stored for polygons
+    if matches!(fields.r#type, FeatureType::Polygons | FeatureType::Polygons3D) {
         if has_indices {
-            indices_index = Some(pbf.read_varint());
+            fields.indices_index = Some(column(&mut pbf, OColumnName::Indices)?);
         }
         if has_tessellation {
-            tessellation_index = Some(pbf.read_varint());
+            let points = if is_3d { OColumnName::Points3D } else { OColumnName::Points };
+            fields.tessellation_index = Some(column(&mut pbf, points)?);
         }
     }
-    let bbox_index = if has_bbox { Some(pbf.read_varint()) } else { None };
-
-    OpenVectorFeature {
-        id,
-        properties,
-        r#type,
-        cache,
-        m_shape,
-        extent,
-        geometry_indices,
-        geometry: None,
-        single,
-        bbox_index,
-        has_offsets,
-        has_m_values,
-        indices_index,
-        tessellation_index,
+    if has_bbox {
+        fields.bbox_index = Some(column(&mut pbf, OColumnName::BBox)?);
+    }
+
+    Ok(fields)
+}
+
+impl<C: ColumnLookup> OpenVectorFeature<C> {
+    /// Create a feature from its header. The properties and geometry indices are only decoded
+    /// once they are first read
+    pub(crate) fn from_fields(
+        fields: FeatureFields,
+        extent: Extent,
+        cache: Shared<C>,
+        shape: Shared<Shape>,
+        m_shape: Shared<Shape>,
+    ) -> Self {
+        OpenVectorFeature {
+            id: fields.id,
+            r#type: fields.r#type,
+            cache,
+            shape,
+            m_shape,
+            extent,
+            value_index: fields.value_index,
+            properties: Lazy::new(),
+            geometry_index: fields.geometry,
+            geometry_indices: Lazy::new(),
+            geometry: Lazy::new(),
+            single: fields.single,
+            bbox_index: fields.bbox_index,
+            has_offsets: fields.has_offsets,
+            has_m_values: fields.has_m_values,
+            indices_index: fields.indices_index,
+            tessellation_index: fields.tessellation_index,
+        }
     }
 }
 
-/// Write a single feature to the column cache and return the encoding indexes for lookup
+/// Read a single feature given the encoded data. A feature that is cut short or refers to
+/// columns missing from the cache reads as an empty feature
+pub fn read_feature<C: ColumnLookup>(
+    data: &[u8],
+    extent: Extent,
+    cache: Shared<C>,
+    shape: &Shape,
+    m_shape: Shared<Shape>,
+) -> OpenVectorFeature<C> {
+    let pbf = Reader::new(data, 0, data.len(), ProtoMessage::OpenFeature);
+    let fields = read_feature_fields(pbf, &*cache).unwrap_or_default();
+    OpenVectorFeature::from_fields(fields, extent, cache, shared(shape.clone()), m_shape)
+}
+
+/// Write a single feature to the column cache and return the encoding indexes for lookup.
+/// Panics if its properties or M-Values do not match the shapes, see [`try_write_feature`].
 pub fn write_feature(
     feature: &BaseVectorFeature,
     shape: &Shape,
     m_shape: Option<&Shape>,
     cache: &mut ColumnCacheWriter,
 ) -> Vec<u8> {
+    try_write_feature(feature, shape, m_shape, cache).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Fallible version of [`write_feature`], returning where the properties or M-Values do not
+/// match the shapes. M-Value paths are prefixed with `m_values`.
+pub fn try_write_feature(
+    feature: &BaseVectorFeature,
+    shape: &Shape,
+    m_shape: Option<&Shape>,
+    cache: &mut ColumnCacheWriter,
+) -> core::result::Result<Vec<u8>, ValueMismatch> {
+    encode_feature(feature, feature.bbox(), shape, m_shape, cache)
+}
+
+/// Write a feature like [`try_write_feature`], storing `bbox` in place of the feature's own
+pub(crate) fn encode_feature(
+    feature: &BaseVectorFeature,
+    bbox: Option<BBOX>,
+    shape: &Shape,
+    m_shape: Option<&Shape>,
+    cache: &mut ColumnCacheWriter,
+) -> core::result::Result<Vec<u8>, ValueMismatch> {
     // write id, type, properties, bbox, geometry, indices, tessellation, mValues
     let mut pbf = Protobuf::new();
     // type is just stored as a varint
@@ -684,7 +860,6 @@ pub fn write_feature(
     let tessellation = feature.tessellation();
     let has_tessellation = tessellation.is_some() && !tessellation.as_ref().unwrap().is_empty();
     let has_offsets = feature.has_offsets();
-    let bbox = feature.bbox();
     let has_bbox = bbox.is_some();
     let has_m_values = feature.has_m_values();
     let single = feature.single();
@@ -716,10 +891,11 @@ pub fn write_feature(
         pbf.write_varint(id.unwrap());
     }
     // index to values column
-    let value_index = encode_value(feature.properties(), shape, cache);
+    let value_index = try_encode_value(feature.properties(), shape, cache)?;
     pbf.write_varint(value_index);
     // geometry
-    let stored_geo = feature.encode_to_cache(cache, m_shape);
+    let stored_geo =
+        feature.try_encode_to_cache(cache, m_shape).map_err(|err| err.under("m_values"))?;
     pbf.write_varint(stored_geo);
     // indices
     if has_indices {
@@ -737,5 +913,5 @@ pub fn write_feature(
         pbf.write_varint(cache.add_bbox(bbox.unwrap()));
     }
 
-    pbf.take()
+    Ok(pbf.take())
 }
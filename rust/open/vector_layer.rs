@@ -1,14 +1,21 @@
 use crate::{
-    VectorFeature, VectorLayerMethods,
-    base::BaseVectorLayer,
+    Point, VectorFeature, VectorFeatureMethods, VectorFeatureRef, VectorLayerFeatures,
+    VectorLayerMethods, WriteOptions,
+    base::{BaseVectorFeature, BaseVectorLayer},
+    error::{DecodeError, EncodeError, ProtoMessage},
+    filter::Filter,
     open::{
-        ColumnCacheReader, ColumnCacheWriter, Extent, OpenVectorFeature, decode_shape,
-        encode_shape, read_feature, write_feature,
+        ColumnCacheReader, ColumnCacheWriter, ColumnLookup, Extent, FeatureFields, OColumnChunk,
+        OColumnName, OpenVectorFeature, ValueMismatch, decode_value_key, encode_feature,
+        encode_shape, read_feature_fields, try_decode_shape,
     },
+    query::{intersects_bbox, intersects_point},
+    reader::{BYTES, Reader, Result, VARINT},
+    shared::Shared,
+    spatial_index::{SpatialIndex, read_spatial_index},
 };
-use alloc::{rc::Rc, string::String, vec::Vec};
-use core::cell::RefCell;
-use pbf::{ProtoRead, Protobuf};
+use alloc::{string::String, vec::Vec};
+use pbf::Protobuf;
 use s2json::Shape;
 
 /// The Open Vector Layer class represents a layer in an Open Vector Tile.
@@ -25,12 +32,15 @@ pub struct OpenVectorLayer {
     /// the features in the layer
     pub features: Vec<OpenVectorFeature>,
     shape: Option<Shape>,
-    m_shape: Option<Shape>,
-    cache: Rc<RefCell<ColumnCacheReader>>,
+    m_shape: Option<Shared<Shape>>,
+    cache: Shared<ColumnCacheReader>,
+    index: Option<SpatialIndex>,
+    /// true if the index was read from the tile rather than built on first use
+    stored_index: bool,
 }
 impl OpenVectorLayer {
     /// Create a new OpenVectorLayer
-    pub fn new(cache: Rc<RefCell<ColumnCacheReader>>) -> OpenVectorLayer {
+    pub fn new(cache: Shared<ColumnCacheReader>) -> OpenVectorLayer {
         OpenVectorLayer {
             version: 1,
             name: String::new(),
@@ -39,8 +49,52 @@ impl OpenVectorLayer {
             m_shape: None,
             features: Vec::new(),
             cache,
+            index: None,
+            stored_index: false,
         }
     }
+
+    /// The shape used to describe the features properties in the layer
+    pub fn shape(&self) -> Option<&Shape> {
+        self.shape.as_ref()
+    }
+
+    /// The shape of each feature's M-Values
+    pub fn m_shape(&self) -> Option<&Shape> {
+        self.m_shape.as_deref()
+    }
+
+    /// The spatial index of the features. Read from the tile if it was stored with the layer,
+    /// otherwise built from the features' geometry on first use
+    pub fn spatial_index(&mut self) -> &SpatialIndex {
+        let features = &mut self.features;
+        self.index.get_or_insert_with(|| {
+            SpatialIndex::from_geometries(features.iter_mut().map(|f| f.load_geometry()))
+        })
+    }
+
+    /// true if the layer was written with a spatial index, see
+    /// [`BaseVectorLayer::spatial_index`]
+    pub fn has_spatial_index(&self) -> bool {
+        self.stored_index
+    }
+
+    /// Iterate the features that match the filter. Properties are looked up in the column cache
+    /// by key, so only the keys the filter uses are decoded and no geometry is loaded
+    pub fn filter<'a>(
+        &'a mut self,
+        filter: &'a Filter,
+    ) -> impl Iterator<Item = &'a mut OpenVectorFeature> + 'a {
+        let shape = self.shape.clone().unwrap_or_default();
+        let cache = self.cache.clone();
+        self.features.iter_mut().filter(move |feature| {
+            let value_index = feature.value_index();
+            filter.eval(
+                &mut |key| decode_value_key(value_index, &shape, key, &*cache),
+                feature.r#type,
+            )
+        })
+    }
 }
 impl VectorLayerMethods for OpenVectorLayer {
     fn version(&self) -> u16 {
@@ -61,63 +115,214 @@ impl VectorLayerMethods for OpenVectorLayer {
     fn feature(&mut self, i: usize) -> Option<VectorFeature<'_>> {
         self.features.get_mut(i).map(Into::into)
     }
+    fn query_point(&mut self, x: f64, y: f64, tolerance: f64) -> Vec<usize> {
+        let candidates = self.spatial_index().search_point(x, y, tolerance);
+        candidates
+            .into_iter()
+            .filter(|&i| intersects_point(&self.features[i].load_geometry(), x, y, tolerance))
+            .collect()
+    }
+    fn query_bbox(&mut self, min: Point, max: Point) -> Vec<usize> {
+        let candidates = self.spatial_index().search(min.x, min.y, max.x, max.y);
+        candidates
+            .into_iter()
+            .filter(|&i| intersects_bbox(&self.features[i].load_geometry(), &min, &max))
+            .collect()
+    }
+}
+impl VectorLayerFeatures for OpenVectorLayer {
+    fn get_feature(&self, i: usize) -> Option<VectorFeatureRef<'_>> {
+        self.features.get(i).map(Into::into)
+    }
+}
+/// The fields of an open layer message, read by [`read_layer`]
+#[derive(Debug, Default)]
+pub(crate) struct LayerFields {
+    pub version: u16,
+    /// the index of the name in the string column
+    pub name: usize,
+    pub extent: Extent,
+    pub shape: Option<Shape>,
+    pub m_shape: Option<Shape>,
+    pub index: Option<SpatialIndex>,
 }
-impl ProtoRead for OpenVectorLayer {
-    fn read(&mut self, tag: u64, pb: &mut Protobuf) {
+
+/// Read an open layer message, checking every column it refers to exists. Each feature is
+/// handed to `add_feature` along with its position, to be read again with
+/// [`Reader::at`]
+pub(crate) fn read_layer(
+    mut layer: Reader,
+    cache: &impl ColumnLookup,
+    mut add_feature: impl FnMut(usize, FeatureFields),
+) -> Result<LayerFields> {
+    let mut fields = LayerFields::default();
+    let mut features = 0;
+    let mut index_start = 0;
+    while !layer.done() {
+        let (tag, wire_type) = layer.field()?;
+        let start = layer.pos();
+        match tag {
+            1 | 2 | 3 | 5 | 6 => layer.expect(wire_type, VARINT)?,
+            4 | 7 => layer.expect(wire_type, BYTES)?,
+            _ => return Err(layer.unknown_tag(start)),
+        }
         match tag {
-            1 => self.version = pb.read_varint::<u16>(),
+            1 => fields.version = layer.read_varint()?,
             2 => {
-                self.name = {
-                    let mut cache = self.cache.borrow_mut();
-                    cache.get_string(pb.read_varint())
+                let name = layer.varint()?;
+                let len = cache.column_len(OColumnName::String);
+                if name >= len as u64 {
+                    return Err(layer.out_of_range(name, len, start));
                 }
+                fields.name = name as usize;
             }
-            3 => self.extent = pb.read_varint::<Extent>(),
-            4 => self.features.push(read_feature(
-                pb.read_bytes(),
-                self.extent,
-                self.cache.clone(),
-                &self.shape.clone().unwrap_or_default(),
-                self.m_shape.clone().unwrap_or_default(),
-            )),
-            5 => {
-                self.shape = {
-                    let mut cache = self.cache.borrow_mut();
-                    Some(decode_shape(pb.read_varint(), &mut cache))
-                }
+            3 => fields.extent = layer.read_varint()?,
+            4 => {
+                let feature = layer.message(ProtoMessage::OpenFeature)?;
+                add_feature(start, read_feature_fields(feature, cache)?);
+                features += 1;
             }
-            6 => {
-                self.m_shape = {
-                    let mut cache: core::cell::RefMut<ColumnCacheReader> = self.cache.borrow_mut();
-                    Some(decode_shape(pb.read_varint(), &mut cache))
+            5 | 6 => {
+                let index = layer.varint()?;
+                let len = cache.column_len(OColumnName::Shapes);
+                if index >= len as u64 {
+                    return Err(layer.out_of_range(index, len, start));
+                }
+                let shape = try_decode_shape(index as usize, cache)
+                    .ok_or_else(|| layer.malformed(start, "invalid shape"))?;
+                if tag == 5 {
+                    fields.shape = Some(shape);
+                } else {
+                    fields.m_shape = Some(shape);
                 }
             }
-            _ => panic!("unknown tag: {}", tag),
+            _ => {
+                index_start = start;
+                fields.index =
+                    Some(read_spatial_index(layer.message(ProtoMessage::SpatialIndex)?)?);
+            }
         }
     }
+    // the index may only refer to features of the layer
+    if let Some(max) = fields.index.as_ref().and_then(SpatialIndex::max_item)
+        && max >= features
+    {
+        return Err(DecodeError::OutOfRange {
+            message: ProtoMessage::OpenLayer,
+            tag: Some(7),
+            index: max as u64,
+            len: features,
+            offset: index_start,
+        });
+    }
+
+    Ok(fields)
 }
 
-/// Write the layer to a protobuf
+impl OpenVectorLayer {
+    /// Read the layer message covered by `layer`, using the tile's column cache
+    pub(crate) fn read(layer: Reader, cache: Shared<ColumnCacheReader>) -> Result<Self> {
+        let mut features = Vec::new();
+        let fields = read_layer(layer, &*cache, |_, feature| features.push(feature))?;
+        let shape: Shared<Shape> = fields.shape.clone().unwrap_or_default().into();
+        let m_shape: Shared<Shape> = fields.m_shape.clone().unwrap_or_default().into();
+        let features = features
+            .into_iter()
+            .map(|feature| {
+                OpenVectorFeature::from_fields(
+                    feature,
+                    fields.extent,
+                    cache.clone(),
+                    shape.clone(),
+                    m_shape.clone(),
+                )
+            })
+            .collect();
+
+        Ok(OpenVectorLayer {
+            version: fields.version,
+            name: cache.get_string(fields.name),
+            extent: fields.extent,
+            features,
+            shape: fields.shape,
+            m_shape: fields.m_shape.map(|_| m_shape),
+            stored_index: fields.index.is_some(),
+            index: fields.index,
+            cache,
+        })
+    }
+}
+
+/// Write the layer to a protobuf. The name is left out as its string index is only final once
+/// the cache is sorted, see [`write_layer_name`].
+///
+/// With [`BaseVectorLayer::spatial_index`] the layer's [`SpatialIndex`] is written as field 7
+/// (section 4.3.7 of the specification). Decoders older than that field reject it, so it is
+/// only written when asked for.
 pub fn write_layer(layer: &mut BaseVectorLayer, cache: &mut ColumnCacheWriter) -> Vec<u8> {
+    try_write_layer(layer, cache, &WriteOptions::default()).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Fallible version of [`write_layer`], returning an error naming the first feature whose
+/// properties or M-Values do not match the layer's shapes. The [`WriteOptions`] of the tile
+/// apply to every feature of the layer
+pub fn try_write_layer(
+    layer: &mut BaseVectorLayer,
+    cache: &mut ColumnCacheWriter,
+    options: &WriteOptions,
+) -> core::result::Result<Vec<u8>, EncodeError> {
     let mut pbf = Protobuf::new();
 
     pbf.write_varint_field(1, layer.version);
-    pbf.write_varint_field(2, cache.add_string(layer.name.clone()));
     pbf.write_varint_field(3, layer.extent);
     pbf.write_varint_field(5, encode_shape(&layer.shape, cache));
     if let Some(ref m_shape) = layer.m_shape {
         pbf.write_varint_field(6, encode_shape(m_shape, cache));
     }
 
-    // sort by feature type
+    if layer.tessellate {
+        layer.features.iter_mut().for_each(BaseVectorFeature::tessellate);
+    }
+    // sort by feature type, keeping the order the features were added in for errors
+    let mut order: Vec<usize> = (0..layer.features.len()).collect();
+    order.sort_by_key(|&i| layer.features[i].get_type());
     layer.features.sort_by_key(|a| a.get_type());
+    // the index refers to features by their position, so it's built after sorting
+    if layer.spatial_index {
+        let index = SpatialIndex::from_geometries(layer.features.iter().map(|f| f.load_geometry()));
+        pbf.write_message(7, &index);
+    }
 
-    for feature in &layer.features {
-        pbf.write_bytes_field(
-            4,
-            &write_feature(feature, &layer.shape, layer.m_shape.as_ref(), cache),
-        );
+    for (feature, i) in layer.features.iter().zip(order) {
+        let bbox = feature.bbox().or_else(|| {
+            let (zoom, x, y) = options.compute_bbox?;
+            feature.compute_bbox(layer.extent, zoom, x, y)
+        });
+        let data = encode_feature(feature, bbox, &layer.shape, layer.m_shape.as_ref(), cache)
+            .map_err(|err| encode_error(&layer.name, i, err))?;
+        pbf.write_bytes_field(4, &data);
     }
 
+    Ok(pbf.take())
+}
+
+/// Write the layer's name field given its string chunk. Call after [`ColumnCacheWriter::sort`]
+/// and prepend the result to the output of [`write_layer`].
+pub fn write_layer_name(name: &OColumnChunk) -> Vec<u8> {
+    let mut pbf = Protobuf::new();
+    pbf.write_varint_field(2, name.borrow().index);
     pbf.take()
 }
+
+/// Name the layer and feature a value mismatch was found in
+fn encode_error(layer: &str, feature: usize, mismatch: ValueMismatch) -> EncodeError {
+    let layer = layer.into();
+    match mismatch {
+        ValueMismatch::Primitive(key, expected, actual) => {
+            EncodeError::ShapeMismatch { layer, feature, key, expected, actual }
+        }
+        ValueMismatch::Structure(key, expected, actual) => {
+            EncodeError::StructureMismatch { layer, feature, key, expected, actual }
+        }
+    }
+}
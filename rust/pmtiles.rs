@@ -0,0 +1,665 @@
+pub use crate::tile_id::{tile_id_to_zxy, zxy_to_tile_id};
+
+use crate::{
+    DecodeError, VectorTile,
+    compression::{self, Codec, CompressionError},
+};
+use alloc::{string::ToString, vec, vec::Vec};
+use core::fmt;
+use libm::{atan, sinh};
+use pbf::Protobuf;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+/// The size of an encoded header
+pub const HEADER_SIZE: usize = 127;
+/// The header and root directory must fit in the first 16 KiB of an archive
+const ROOT_SIZE: usize = 16_384;
+/// Directories are followed at most this deep before giving up
+const MAX_DEPTH: usize = 3;
+
+/// An error reading or writing a PMTiles archive
+#[derive(Debug)]
+pub enum PMTilesError {
+    /// The archive could not be read or written
+    Io(io::Error),
+    /// The archive does not start with the `PMTiles` magic number
+    InvalidMagic,
+    /// Only version 3 archives are supported
+    UnsupportedVersion(u8),
+    /// The archive (or a tile) uses a compression that is not supported
+    UnsupportedCompression(Compression),
+    /// Directories, metadata or a tile could not be compressed or decompressed
+    Compression(CompressionError),
+    /// A directory is truncated or malformed
+    InvalidDirectory,
+    /// The metadata is not valid JSON
+    InvalidMetadata,
+    /// A tile could not be decoded
+    Decode(DecodeError),
+    /// A tile is past zoom 31 or its x or y is outside of its zoom
+    InvalidTile(u8, u32, u32),
+}
+impl fmt::Display for PMTilesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PMTilesError::Io(err) => write!(f, "pmtiles: {}", err),
+            PMTilesError::InvalidMagic => write!(f, "pmtiles: not a PMTiles archive"),
+            PMTilesError::UnsupportedVersion(version) => {
+                write!(f, "pmtiles: unsupported version {}", version)
+            }
+            PMTilesError::UnsupportedCompression(compression) => {
+                write!(f, "pmtiles: unsupported compression {:?}", compression)
+            }
+            PMTilesError::Compression(err) => write!(f, "pmtiles: {}", err),
+            PMTilesError::InvalidDirectory => write!(f, "pmtiles: invalid directory"),
+            PMTilesError::InvalidMetadata => write!(f, "pmtiles: invalid metadata"),
+            PMTilesError::Decode(err) => write!(f, "pmtiles: {}", err),
+            PMTilesError::InvalidTile(zoom, x, y) => {
+                write!(f, "pmtiles: invalid tile {}/{}/{}", zoom, x, y)
+            }
+        }
+    }
+}
+impl core::error::Error for PMTilesError {}
+impl From<io::Error> for PMTilesError {
+    fn from(err: io::Error) -> Self {
+        PMTilesError::Io(err)
+    }
+}
+impl From<CompressionError> for PMTilesError {
+    fn from(err: CompressionError) -> Self {
+        PMTilesError::Compression(err)
+    }
+}
+impl From<DecodeError> for PMTilesError {
+    fn from(err: DecodeError) -> Self {
+        PMTilesError::Decode(err)
+    }
+}
+
+/// The compression of the directories, metadata or tiles of an archive
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Unknown compression
+    Unknown = 0,
+    /// Not compressed
+    #[default]
+    None = 1,
+    /// Gzip
+    Gzip = 2,
+    /// Brotli
+    Brotli = 3,
+    /// Zstandard
+    Zstd = 4,
+}
+impl From<u8> for Compression {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Compression::None,
+            2 => Compression::Gzip,
+            3 => Compression::Brotli,
+            4 => Compression::Zstd,
+            _ => Compression::Unknown,
+        }
+    }
+}
+
+/// The type of tile stored in an archive
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TileType {
+    /// Unknown, which includes Open Vector Tiles
+    #[default]
+    Unknown = 0,
+    /// Mapbox Vector Tiles
+    Mvt = 1,
+    /// PNG images
+    Png = 2,
+    /// JPEG images
+    Jpeg = 3,
+    /// WebP images
+    Webp = 4,
+    /// AVIF images
+    Avif = 5,
+}
+impl From<u8> for TileType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => TileType::Mvt,
+            2 => TileType::Png,
+            3 => TileType::Jpeg,
+            4 => TileType::Webp,
+            5 => TileType::Avif,
+            _ => TileType::Unknown,
+        }
+    }
+}
+
+/// The fixed size header at the start of every archive
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Header {
+    /// offset of the root directory
+    pub root_offset: u64,
+    /// length of the root directory
+    pub root_length: u64,
+    /// offset of the JSON metadata
+    pub metadata_offset: u64,
+    /// length of the JSON metadata
+    pub metadata_length: u64,
+    /// offset of the leaf directories
+    pub leaf_dirs_offset: u64,
+    /// length of the leaf directories
+    pub leaf_dirs_length: u64,
+    /// offset of the tile data
+    pub tile_data_offset: u64,
+    /// length of the tile data
+    pub tile_data_length: u64,
+    /// number of tiles addressed by the directories
+    pub addressed_tiles: u64,
+    /// number of directory entries pointing to tiles
+    pub tile_entries: u64,
+    /// number of distinct tile contents
+    pub tile_contents: u64,
+    /// true if tile data is ordered by tile ID
+    pub clustered: bool,
+    /// compression of the directories and metadata
+    pub internal_compression: Compression,
+    /// compression of the tiles
+    pub tile_compression: Compression,
+    /// the type of tile stored
+    pub tile_type: TileType,
+    /// minimum zoom of the tiles
+    pub min_zoom: u8,
+    /// maximum zoom of the tiles
+    pub max_zoom: u8,
+    /// minimum longitude of the bounds
+    pub min_lon: f64,
+    /// minimum latitude of the bounds
+    pub min_lat: f64,
+    /// maximum longitude of the bounds
+    pub max_lon: f64,
+    /// maximum latitude of the bounds
+    pub max_lat: f64,
+    /// zoom to display first
+    pub center_zoom: u8,
+    /// longitude to display first
+    pub center_lon: f64,
+    /// latitude to display first
+    pub center_lat: f64,
+}
+impl Header {
+    /// Decode a header
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, PMTilesError> {
+        if buf.len() < HEADER_SIZE || &buf[0..7] != b"PMTiles" {
+            return Err(PMTilesError::InvalidMagic);
+        }
+        if buf[7] != 3 {
+            return Err(PMTilesError::UnsupportedVersion(buf[7]));
+        }
+        let u64_at = |pos: usize| u64::from_le_bytes(buf[pos..pos + 8].try_into().unwrap());
+        let coord_at =
+            |pos: usize| i32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap()) as f64 / 1e7;
+        Ok(Header {
+            root_offset: u64_at(8),
+            root_length: u64_at(16),
+            metadata_offset: u64_at(24),
+            metadata_length: u64_at(32),
+            leaf_dirs_offset: u64_at(40),
+            leaf_dirs_length: u64_at(48),
+            tile_data_offset: u64_at(56),
+            tile_data_length: u64_at(64),
+            addressed_tiles: u64_at(72),
+            tile_entries: u64_at(80),
+            tile_contents: u64_at(88),
+            clustered: buf[96] == 1,
+            internal_compression: buf[97].into(),
+            tile_compression: buf[98].into(),
+            tile_type: buf[99].into(),
+            min_zoom: buf[100],
+            max_zoom: buf[101],
+            min_lon: coord_at(102),
+            min_lat: coord_at(106),
+            max_lon: coord_at(110),
+            max_lat: coord_at(114),
+            center_zoom: buf[118],
+            center_lon: coord_at(119),
+            center_lat: coord_at(123),
+        })
+    }
+
+    /// Encode the header
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(HEADER_SIZE);
+        buf.extend_from_slice(b"PMTiles");
+        buf.push(3);
+        for value in [
+            self.root_offset,
+            self.root_length,
+            self.metadata_offset,
+            self.metadata_length,
+            self.leaf_dirs_offset,
+            self.leaf_dirs_length,
+            self.tile_data_offset,
+            self.tile_data_length,
+            self.addressed_tiles,
+            self.tile_entries,
+            self.tile_contents,
+        ] {
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+        buf.push(self.clustered as u8);
+        buf.push(self.internal_compression as u8);
+        buf.push(self.tile_compression as u8);
+        buf.push(self.tile_type as u8);
+        buf.push(self.min_zoom);
+        buf.push(self.max_zoom);
+        let coord = |value: f64| ((value * 1e7) as i32).to_le_bytes();
+        buf.extend_from_slice(&coord(self.min_lon));
+        buf.extend_from_slice(&coord(self.min_lat));
+        buf.extend_from_slice(&coord(self.max_lon));
+        buf.extend_from_slice(&coord(self.max_lat));
+        buf.push(self.center_zoom);
+        buf.extend_from_slice(&coord(self.center_lon));
+        buf.extend_from_slice(&coord(self.center_lat));
+        buf
+    }
+}
+
+/// A directory entry. A `run_length` of 0 points to a leaf directory, otherwise the entry
+/// addresses `run_length` consecutive tile IDs sharing the same data
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    /// the first tile ID of the entry
+    pub tile_id: u64,
+    /// offset of the data, relative to the tile data or leaf directories
+    pub offset: u64,
+    /// length of the data
+    pub length: u32,
+    /// number of tiles addressed, 0 for leaf directories
+    pub run_length: u32,
+}
+
+/// Find the entry addressing `tile_id`, or the leaf directory that may hold it
+fn find_entry(entries: &[Entry], tile_id: u64) -> Option<&Entry> {
+    let index = entries.partition_point(|entry| entry.tile_id <= tile_id);
+    let entry = entries.get(index.checked_sub(1)?)?;
+    if entry.run_length == 0 || tile_id - entry.tile_id < entry.run_length as u64 {
+        Some(entry)
+    } else {
+        None
+    }
+}
+
+/// Decode a (decompressed) directory
+fn read_directory(buf: &[u8]) -> Result<Vec<Entry>, PMTilesError> {
+    let mut pos = 0;
+    let mut varint = || -> Result<u64, PMTilesError> {
+        let mut val = 0;
+        for shift in (0..64).step_by(7) {
+            let b = *buf.get(pos).ok_or(PMTilesError::InvalidDirectory)?;
+            pos += 1;
+            val |= ((b & 0x7f) as u64) << shift;
+            if b < 0x80 {
+                return Ok(val);
+            }
+        }
+        Err(PMTilesError::InvalidDirectory)
+    };
+    let len = varint()? as usize;
+    // every entry takes at least 4 bytes
+    if len > buf.len() / 4 {
+        return Err(PMTilesError::InvalidDirectory);
+    }
+    let mut entries = vec![Entry::default(); len];
+    let mut tile_id = 0_u64;
+    for entry in entries.iter_mut() {
+        tile_id = tile_id.checked_add(varint()?).ok_or(PMTilesError::InvalidDirectory)?;
+        entry.tile_id = tile_id;
+    }
+    for entry in entries.iter_mut() {
+        entry.run_length = varint()? as u32;
+    }
+    for entry in entries.iter_mut() {
+        entry.length = varint()? as u32;
+    }
+    for i in 0..len {
+        let offset = varint()?;
+        entries[i].offset = if offset == 0 && i > 0 {
+            let prev = &entries[i - 1];
+            prev.offset.checked_add(prev.length as u64).ok_or(PMTilesError::InvalidDirectory)?
+        } else {
+            offset.checked_sub(1).ok_or(PMTilesError::InvalidDirectory)?
+        };
+    }
+    Ok(entries)
+}
+
+/// Encode a directory (before compression)
+fn write_directory(entries: &[Entry]) -> Vec<u8> {
+    let mut pb = Protobuf::new();
+    pb.write_varint(entries.len() as u64);
+    let mut last_id = 0;
+    for entry in entries {
+        pb.write_varint(entry.tile_id - last_id);
+        last_id = entry.tile_id;
+    }
+    entries.iter().for_each(|entry| pb.write_varint(entry.run_length));
+    entries.iter().for_each(|entry| pb.write_varint(entry.length));
+    for (i, entry) in entries.iter().enumerate() {
+        if i > 0 && entry.offset == entries[i - 1].offset + entries[i - 1].length as u64 {
+            pb.write_varint(0);
+        } else {
+            pb.write_varint(entry.offset + 1);
+        }
+    }
+    pb.take()
+}
+
+/// Decompress directories, metadata or tiles
+fn decompress(data: Vec<u8>, compression: Compression) -> Result<Vec<u8>, PMTilesError> {
+    match codec(compression)? {
+        None => Ok(data),
+        Some(codec) => {
+            compression::decompress_with(&data, codec).map_err(|err| unsupported(err, compression))
+        }
+    }
+}
+
+/// Compress directories, metadata or tiles
+fn compress(data: &[u8], compression: Compression) -> Result<Vec<u8>, PMTilesError> {
+    match codec(compression)? {
+        None => Ok(data.to_vec()),
+        Some(codec) => {
+            compression::compress(data, codec).map_err(|err| unsupported(err, compression))
+        }
+    }
+}
+
+/// The codec of a compression, None if the data is not compressed
+fn codec(compression: Compression) -> Result<Option<Codec>, PMTilesError> {
+    match compression {
+        Compression::None => Ok(None),
+        Compression::Gzip => Ok(Some(Codec::Gzip)),
+        Compression::Brotli => Ok(Some(Codec::Brotli)),
+        Compression::Zstd => Ok(Some(Codec::Zstd)),
+        Compression::Unknown => Err(PMTilesError::UnsupportedCompression(compression)),
+    }
+}
+
+/// Codecs whose cargo feature is not enabled are reported as an unsupported compression
+fn unsupported(err: CompressionError, compression: Compression) -> PMTilesError {
+    match err {
+        CompressionError::Unsupported(_) => PMTilesError::UnsupportedCompression(compression),
+        err => PMTilesError::Compression(err),
+    }
+}
+
+/// # PMTiles
+///
+/// ## Description
+/// A reader for [PMTiles v3](https://github.com/protomaps/PMTiles/blob/main/spec/v3/spec.md)
+/// archives: a single file holding a header, a root directory, JSON metadata, leaf directories
+/// and tile data. Tiles are addressed by their zoom/x/y and found by following the directories
+/// with the tile's Hilbert tile ID.
+///
+/// ## Usage
+/// ```rust,ignore
+/// use open_vector_tile::pmtiles::PMTiles;
+///
+/// let mut archive = PMTiles::open("tiles.pmtiles")?;
+/// let max_zoom = archive.header().max_zoom;
+/// let tile = archive.tile(0, 0, 0)?;
+/// ```
+#[derive(Debug)]
+pub struct PMTiles<R: Read + Seek> {
+    reader: R,
+    header: Header,
+    root: Vec<Entry>,
+}
+impl PMTiles<File> {
+    /// Open an archive on disk
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, PMTilesError> {
+        PMTiles::new(File::open(path)?)
+    }
+}
+impl<R: Read + Seek> PMTiles<R> {
+    /// Read the header and root directory of an archive
+    pub fn new(mut reader: R) -> Result<Self, PMTilesError> {
+        let mut buf = [0; HEADER_SIZE];
+        reader.read_exact(&mut buf).map_err(|_| PMTilesError::InvalidMagic)?;
+        let header = Header::from_bytes(&buf)?;
+        let mut archive = PMTiles { reader, header, root: vec![] };
+        archive.root = archive.directory(header.root_offset, header.root_length)?;
+        Ok(archive)
+    }
+
+    /// The archive's header
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// The entries of the root directory
+    pub fn root(&self) -> &[Entry] {
+        &self.root
+    }
+
+    /// The archive's JSON metadata
+    pub fn metadata(&mut self) -> Result<Value, PMTilesError> {
+        let data = self.read_at(self.header.metadata_offset, self.header.metadata_length)?;
+        let data = decompress(data, self.header.internal_compression)?;
+        if data.is_empty() {
+            return Ok(Value::Object(Default::default()));
+        }
+        serde_json::from_slice(&data).map_err(|_| PMTilesError::InvalidMetadata)
+    }
+
+    /// The (decompressed) bytes of a tile, None if the archive does not have it
+    pub fn tile_data(&mut self, zoom: u8, x: u32, y: u32) -> Result<Option<Vec<u8>>, PMTilesError> {
+        if zoom > 31 || x >= 1 << zoom || y >= 1 << zoom {
+            return Ok(None);
+        }
+        let tile_id = zxy_to_tile_id(zoom, x, y);
+        let mut entry = find_entry(&self.root, tile_id).copied();
+        for _ in 0..MAX_DEPTH {
+            let Some(found) = entry else { return Ok(None) };
+            if found.run_length > 0 {
+                let offset = self
+                    .header
+                    .tile_data_offset
+                    .checked_add(found.offset)
+                    .ok_or(PMTilesError::InvalidDirectory)?;
+                let data = self.read_at(offset, found.length as u64)?;
+                return decompress(data, self.header.tile_compression).map(Some);
+            }
+            let offset = self
+                .header
+                .leaf_dirs_offset
+                .checked_add(found.offset)
+                .ok_or(PMTilesError::InvalidDirectory)?;
+            let leaf = self.directory(offset, found.length as u64)?;
+            entry = find_entry(&leaf, tile_id).copied();
+        }
+        Err(PMTilesError::InvalidDirectory)
+    }
+
+    /// Decode a tile, None if the archive does not have it
+    pub fn tile(&mut self, zoom: u8, x: u32, y: u32) -> Result<Option<VectorTile>, PMTilesError> {
+        match self.tile_data(zoom, x, y)? {
+            Some(data) => Ok(Some(VectorTile::try_new(data, None)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Read and decode a directory
+    fn directory(&mut self, offset: u64, length: u64) -> Result<Vec<Entry>, PMTilesError> {
+        let data = self.read_at(offset, length)?;
+        read_directory(&decompress(data, self.header.internal_compression)?)
+    }
+
+    /// Read `length` bytes starting at `offset`
+    fn read_at(&mut self, offset: u64, length: u64) -> Result<Vec<u8>, PMTilesError> {
+        let end = self.reader.seek(SeekFrom::End(0))?;
+        if offset.checked_add(length).is_none_or(|stop| stop > end) {
+            return Err(PMTilesError::InvalidDirectory);
+        }
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let mut buf = vec![0; length as usize];
+        self.reader.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// Options for [`write_archive`]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ArchiveOptions {
+    /// the type of tile stored, [`TileType::Unknown`] for Open Vector Tiles
+    pub tile_type: TileType,
+    /// compress the tiles with [`Compression::None`] or [`Compression::Gzip`]
+    pub tile_compression: Compression,
+    /// JSON metadata to store alongside the tiles
+    pub metadata: Value,
+}
+
+/// Build a clustered archive from encoded tiles (e.g. the output of [`crate::write_tile`]).
+///
+/// Tiles are ordered by tile ID, identical tiles are stored once and consecutive identical tiles
+/// share a single entry. Directories and metadata are gzip compressed and split into leaf
+/// directories when the root directory would not fit in the first 16 KiB. Returns the header
+/// that was written, or [`PMTilesError::InvalidTile`] if a tile is past zoom 31 or outside of
+/// its zoom.
+pub fn write_archive<W: Write>(
+    mut writer: W,
+    tiles: impl IntoIterator<Item = (u8, u32, u32, Vec<u8>)>,
+    options: &ArchiveOptions,
+) -> Result<Header, PMTilesError> {
+    let mut tiles: Vec<(u64, u8, u32, u32, Vec<u8>)> = tiles
+        .into_iter()
+        .map(|(zoom, x, y, data)| {
+            if zoom > 31 || x >= 1 << zoom || y >= 1 << zoom {
+                return Err(PMTilesError::InvalidTile(zoom, x, y));
+            }
+            Ok((zxy_to_tile_id(zoom, x, y), zoom, x, y, data))
+        })
+        .collect::<Result<_, _>>()?;
+    tiles.sort_by_key(|tile| tile.0);
+    tiles.dedup_by_key(|tile| tile.0);
+
+    let mut header = Header {
+        clustered: true,
+        internal_compression: Compression::Gzip,
+        tile_compression: options.tile_compression,
+        tile_type: options.tile_type,
+        addressed_tiles: tiles.len() as u64,
+        min_zoom: u8::MAX,
+        min_lon: 180.,
+        min_lat: 85.0511287,
+        max_lon: -180.,
+        max_lat: -85.0511287,
+        ..Default::default()
+    };
+    if tiles.is_empty() {
+        header.min_zoom = 0;
+        header.min_lon = -180.;
+        header.min_lat = -85.0511287;
+        header.max_lon = 180.;
+        header.max_lat = 85.0511287;
+    }
+
+    // tile data and its entries
+    let mut tile_data = Vec::new();
+    let mut entries: Vec<Entry> = vec![];
+    let mut contents: HashMap<Vec<u8>, (u64, u32)> = HashMap::new();
+    for (tile_id, zoom, x, y, data) in tiles {
+        header.min_zoom = header.min_zoom.min(zoom);
+        header.max_zoom = header.max_zoom.max(zoom);
+        let (min_lon, min_lat, max_lon, max_lat) = tile_bounds(zoom, x, y);
+        header.min_lon = header.min_lon.min(min_lon);
+        header.min_lat = header.min_lat.min(min_lat);
+        header.max_lon = header.max_lon.max(max_lon);
+        header.max_lat = header.max_lat.max(max_lat);
+
+        let data = compress(&data, options.tile_compression)?;
+        let (offset, length) = *contents.entry(data).or_insert_with_key(|data| {
+            let offset = tile_data.len() as u64;
+            tile_data.extend_from_slice(data);
+            (offset, data.len() as u32)
+        });
+        match entries.last_mut() {
+            Some(last)
+                if last.offset == offset && last.tile_id + last.run_length as u64 == tile_id =>
+            {
+                last.run_length += 1;
+            }
+            _ => entries.push(Entry { tile_id, offset, length, run_length: 1 }),
+        }
+    }
+    header.tile_entries = entries.len() as u64;
+    header.tile_contents = contents.len() as u64;
+    header.center_zoom = header.min_zoom;
+    header.center_lon = (header.min_lon + header.max_lon) / 2.;
+    header.center_lat = (header.min_lat + header.max_lat) / 2.;
+
+    let (root, leaves) = build_directories(&entries)?;
+    let metadata = compress(options.metadata.to_string().as_bytes(), Compression::Gzip)?;
+
+    header.root_offset = HEADER_SIZE as u64;
+    header.root_length = root.len() as u64;
+    header.metadata_offset = header.root_offset + header.root_length;
+    header.metadata_length = metadata.len() as u64;
+    header.leaf_dirs_offset = header.metadata_offset + header.metadata_length;
+    header.leaf_dirs_length = leaves.len() as u64;
+    header.tile_data_offset = header.leaf_dirs_offset + header.leaf_dirs_length;
+    header.tile_data_length = tile_data.len() as u64;
+
+    let header_bytes = header.to_bytes();
+    writer.write_all(&header_bytes)?;
+    writer.write_all(&root)?;
+    writer.write_all(&metadata)?;
+    writer.write_all(&leaves)?;
+    writer.write_all(&tile_data)?;
+    writer.flush()?;
+    // bounds are stored with 7 decimals
+    Header::from_bytes(&header_bytes)
+}
+
+/// Compress the entries into a root directory, splitting them into leaf directories (growing
+/// the number of entries per leaf) until the root fits in the first 16 KiB
+fn build_directories(entries: &[Entry]) -> Result<(Vec<u8>, Vec<u8>), PMTilesError> {
+    let root = compress(&write_directory(entries), Compression::Gzip)?;
+    if root.len() <= ROOT_SIZE - HEADER_SIZE {
+        return Ok((root, vec![]));
+    }
+    let mut leaf_size = 4096;
+    loop {
+        let mut leaves = vec![];
+        let mut root_entries = vec![];
+        for chunk in entries.chunks(leaf_size) {
+            let leaf = compress(&write_directory(chunk), Compression::Gzip)?;
+            root_entries.push(Entry {
+                tile_id: chunk[0].tile_id,
+                offset: leaves.len() as u64,
+                length: leaf.len() as u32,
+                run_length: 0,
+            });
+            leaves.extend(leaf);
+        }
+        let root = compress(&write_directory(&root_entries), Compression::Gzip)?;
+        if root.len() <= ROOT_SIZE - HEADER_SIZE {
+            return Ok((root, leaves));
+        }
+        leaf_size += leaf_size / 5;
+    }
+}
+
+/// The longitude/latitude bounds of a Web Mercator tile
+fn tile_bounds(zoom: u8, x: u32, y: u32) -> (f64, f64, f64, f64) {
+    let n = (1_u64 << zoom) as f64;
+    let lon = |x: f64| x / n * 360. - 180.;
+    let lat = |y: f64| atan(sinh(core::f64::consts::PI * (1. - 2. * y / n))).to_degrees();
+    (lon(x as f64), lat(y as f64 + 1.), lon(x as f64 + 1.), lat(y as f64))
+}
@@ -0,0 +1,197 @@
+use crate::{Extent, Point, Point3D};
+use core::f64::consts::PI;
+use libm::{atan, atan2, cos, exp, fabs, floor, log, round, sin, sinh, sqrt, tan};
+use s2json::Face;
+
+/// The equatorial radius of the earth in meters (WGS84)
+pub const EARTH_RADIUS: f64 = 6_378_137.0;
+/// The maximum latitude that Web Mercator can represent
+pub const MAX_LAT: f64 = 85.051_128_779_806_59;
+
+// Web Mercator
+
+/// Convert lon/lat (degrees) to Web Mercator meters (EPSG:3857)
+pub fn lon_lat_to_meters(lon: f64, lat: f64) -> (f64, f64) {
+    let lat = lat.clamp(-MAX_LAT, MAX_LAT);
+    let x = EARTH_RADIUS * lon.to_radians();
+    let y = EARTH_RADIUS * log(tan(PI / 4.0 + lat.to_radians() / 2.0));
+    (x, y)
+}
+
+/// Convert Web Mercator meters (EPSG:3857) to lon/lat (degrees)
+pub fn meters_to_lon_lat(x: f64, y: f64) -> (f64, f64) {
+    let lon = (x / EARTH_RADIUS).to_degrees();
+    let lat = (2.0 * atan(exp(y / EARTH_RADIUS)) - PI / 2.0).to_degrees();
+    (lon, lat)
+}
+
+/// Convert lon/lat (degrees) to the 0->1 space of the whole Web Mercator world, y pointing down
+pub fn lon_lat_to_world(lon: f64, lat: f64) -> (f64, f64) {
+    let lat = lat.clamp(-MAX_LAT, MAX_LAT);
+    let x = (lon + 180.0) / 360.0;
+    let sin_lat = sin(lat.to_radians());
+    let y = 0.5 - log((1.0 + sin_lat) / (1.0 - sin_lat)) / (4.0 * PI);
+    (x, y)
+}
+
+/// Convert the 0->1 space of the whole Web Mercator world to lon/lat (degrees)
+pub fn world_to_lon_lat(x: f64, y: f64) -> (f64, f64) {
+    let lon = x * 360.0 - 180.0;
+    let lat = atan(sinh(PI * (1.0 - 2.0 * y))).to_degrees();
+    (lon, lat)
+}
+
+/// The Web Mercator tile at `zoom` that contains lon/lat
+pub fn lon_lat_to_tile(lon: f64, lat: f64, zoom: u8) -> (u32, u32) {
+    let size = zoom_size(zoom);
+    let (x, y) = lon_lat_to_world(lon, lat);
+    let max = size - 1.0;
+    ((floor(x * size).clamp(0.0, max)) as u32, (floor(y * size).clamp(0.0, max)) as u32)
+}
+
+/// Convert lon/lat (degrees) to the 0->1 space of the Web Mercator tile `zoom`/`x`/`y`.
+/// Positions outside of the tile fall outside of 0->1.
+pub fn lon_lat_to_tile_xy(lon: f64, lat: f64, zoom: u8, x: u32, y: u32) -> (f64, f64) {
+    let size = zoom_size(zoom);
+    let (wx, wy) = lon_lat_to_world(lon, lat);
+    (wx * size - x as f64, wy * size - y as f64)
+}
+
+/// Convert a position in the 0->1 space of the Web Mercator tile `zoom`/`x`/`y` to lon/lat
+pub fn tile_xy_to_lon_lat(px: f64, py: f64, zoom: u8, x: u32, y: u32) -> (f64, f64) {
+    let size = zoom_size(zoom);
+    world_to_lon_lat((x as f64 + px) / size, (y as f64 + py) / size)
+}
+
+// S2
+
+/// Convert lon/lat (degrees) to a point on the unit sphere
+pub fn lon_lat_to_xyz(lon: f64, lat: f64) -> (f64, f64, f64) {
+    let (lon, lat) = (lon.to_radians(), lat.to_radians());
+    (cos(lat) * cos(lon), cos(lat) * sin(lon), sin(lat))
+}
+
+/// Convert a point on the sphere to lon/lat (degrees). The point does not need to be normalized
+pub fn xyz_to_lon_lat(x: f64, y: f64, z: f64) -> (f64, f64) {
+    (atan2(y, x).to_degrees(), atan2(z, sqrt(x * x + y * y)).to_degrees())
+}
+
+/// Convert a point on the sphere to the cube face it projects onto and its face UV (-1->1)
+pub fn xyz_to_face_uv(x: f64, y: f64, z: f64) -> (Face, f64, f64) {
+    let (ax, ay, az) = (fabs(x), fabs(y), fabs(z));
+    let mut face = if ax > ay {
+        if ax > az { 0 } else { 2 }
+    } else if ay > az {
+        1
+    } else {
+        2
+    };
+    if [x, y, z][face] < 0.0 {
+        face += 3;
+    }
+    let face: Face = (face as u8).into();
+    let (u, v) = xyz_to_uv(face, x, y, z);
+    (face, u, v)
+}
+
+/// Project a point on the sphere onto the plane of `face`, returning its face UV. Points that
+/// belong to a neighbouring face land outside of -1->1, points on the far side of the sphere are
+/// meaningless. Panics if `face` is [`Face::WM`], which is not a cube face.
+pub fn xyz_to_uv(face: Face, x: f64, y: f64, z: f64) -> (f64, f64) {
+    match face {
+        Face::Face0 => (y / x, z / x),
+        Face::Face1 => (-x / y, z / y),
+        Face::Face2 => (-x / z, -y / z),
+        Face::Face3 => (z / x, y / x),
+        Face::Face4 => (z / y, -x / y),
+        Face::Face5 => (-y / z, -x / z),
+        Face::WM => panic!("the Web Mercator face is not a cube face"),
+    }
+}
+
+/// Convert a face UV (-1->1) to a point on the sphere (not normalized). Panics if `face` is
+/// [`Face::WM`], which is not a cube face.
+pub fn face_uv_to_xyz(face: Face, u: f64, v: f64) -> (f64, f64, f64) {
+    match face {
+        Face::Face0 => (1.0, u, v),
+        Face::Face1 => (-u, 1.0, v),
+        Face::Face2 => (-u, -v, 1.0),
+        Face::Face3 => (-1.0, -v, -u),
+        Face::Face4 => (v, -1.0, -u),
+        Face::Face5 => (v, u, -1.0),
+        Face::WM => panic!("the Web Mercator face is not a cube face"),
+    }
+}
+
+/// Convert a face U or V (-1->1) to S or T (0->1) using the quadratic projection
+pub fn uv_to_st(u: f64) -> f64 {
+    if u >= 0.0 { 0.5 * sqrt(1.0 + 3.0 * u) } else { 1.0 - 0.5 * sqrt(1.0 - 3.0 * u) }
+}
+
+/// Convert a face S or T (0->1) to U or V (-1->1) using the quadratic projection
+pub fn st_to_uv(s: f64) -> f64 {
+    if s >= 0.5 {
+        (1.0 / 3.0) * (4.0 * s * s - 1.0)
+    } else {
+        (1.0 / 3.0) * (1.0 - 4.0 * (1.0 - s) * (1.0 - s))
+    }
+}
+
+/// Convert lon/lat (degrees) to the cube face and face ST (0->1) it projects onto
+pub fn lon_lat_to_face_st(lon: f64, lat: f64) -> (Face, f64, f64) {
+    let (x, y, z) = lon_lat_to_xyz(lon, lat);
+    let (face, u, v) = xyz_to_face_uv(x, y, z);
+    (face, uv_to_st(u), uv_to_st(v))
+}
+
+/// Convert a cube face and face ST (0->1) to lon/lat (degrees). Panics if `face` is
+/// [`Face::WM`].
+pub fn face_st_to_lon_lat(face: Face, s: f64, t: f64) -> (f64, f64) {
+    let (x, y, z) = face_uv_to_xyz(face, st_to_uv(s), st_to_uv(t));
+    xyz_to_lon_lat(x, y, z)
+}
+
+/// Convert a face ST (0->1) to the 0->1 space of the S2 tile `zoom`/`i`/`j` on the same face.
+/// Positions outside of the tile fall outside of 0->1.
+pub fn face_st_to_tile_xy(s: f64, t: f64, zoom: u8, i: u32, j: u32) -> (f64, f64) {
+    let size = zoom_size(zoom);
+    (s * size - i as f64, t * size - j as f64)
+}
+
+/// Convert a position in the 0->1 space of the S2 tile `zoom`/`i`/`j` to a face ST (0->1)
+pub fn tile_xy_to_face_st(px: f64, py: f64, zoom: u8, i: u32, j: u32) -> (f64, f64) {
+    let size = zoom_size(zoom);
+    ((i as f64 + px) / size, (j as f64 + py) / size)
+}
+
+// Tile coordinates
+
+/// Convert a position in a tile's 0->1 space to a tile `Point` given the extent
+pub fn tile_xy_to_point(x: f64, y: f64, extent: Extent) -> Point {
+    let extent: f64 = extent.into();
+    Point::new(round(x * extent) as i32, round(y * extent) as i32)
+}
+
+/// Convert a position in a tile's 0->1 space to a tile `Point3D` given the extent.
+/// The z value is scaled by the extent as well.
+pub fn tile_xyz_to_point_3d(x: f64, y: f64, z: f64, extent: Extent) -> Point3D {
+    let extent: f64 = extent.into();
+    Point3D::new(round(x * extent) as i32, round(y * extent) as i32, round(z * extent) as i32)
+}
+
+/// Convert a tile `Point` to the tile's 0->1 space given the extent
+pub fn point_to_tile_xy(point: &Point, extent: Extent) -> (f64, f64) {
+    let extent: f64 = extent.into();
+    (point.x as f64 / extent, point.y as f64 / extent)
+}
+
+/// Convert a tile `Point3D` to the tile's 0->1 space given the extent
+pub fn point_3d_to_tile_xyz(point: &Point3D, extent: Extent) -> (f64, f64, f64) {
+    let extent: f64 = extent.into();
+    (point.x as f64 / extent, point.y as f64 / extent, point.z as f64 / extent)
+}
+
+/// The number of tiles across at `zoom`
+fn zoom_size(zoom: u8) -> f64 {
+    (1_u64 << zoom) as f64
+}
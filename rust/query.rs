@@ -0,0 +1,137 @@
+use crate::{Point, Point3D, VectorGeometry};
+use alloc::vec::Vec;
+
+/// Check if a geometry is within `tolerance` of the point `x`/`y`. Polygons also match if they
+/// contain the point. Coordinates are in extent units, 3D geometry is tested on the x/y plane
+pub fn intersects_point(geometry: &VectorGeometry, x: f64, y: f64, tolerance: f64) -> bool {
+    let sq_tolerance = tolerance * tolerance;
+    let near = |line: &[(f64, f64)]| sq_line_dist(line, (x, y)) <= sq_tolerance;
+    let poly_near = |rings: Vec<Vec<(f64, f64)>>| {
+        rings_contain(&rings, (x, y)) || rings.iter().any(|ring| near(ring))
+    };
+    match geometry {
+        VectorGeometry::VectorPoints(points) => points.iter().any(|p| near(&[p.xy()])),
+        VectorGeometry::VectorPoints3D(points) => points.iter().any(|p| near(&[p.xy()])),
+        VectorGeometry::VectorLines(lines) => lines.iter().any(|l| near(&xy(&l.geometry))),
+        VectorGeometry::VectorLines3D(lines) => lines.iter().any(|l| near(&xy(&l.geometry))),
+        VectorGeometry::VectorPolys(polys) => {
+            polys.iter().any(|p| poly_near(p.iter().map(|l| xy(&l.geometry)).collect()))
+        }
+        VectorGeometry::VectorPolys3D(polys) => {
+            polys.iter().any(|p| poly_near(p.iter().map(|l| xy(&l.geometry)).collect()))
+        }
+    }
+}
+
+/// Check if a geometry intersects the box `min`->`max`. Coordinates are in extent units, 3D
+/// geometry is tested on the x/y plane
+pub fn intersects_bbox(geometry: &VectorGeometry, min: &Point, max: &Point) -> bool {
+    let (min, max) = ((min.x as f64, min.y as f64), (max.x as f64, max.y as f64));
+    let line_in = |line: &[(f64, f64)]| match line {
+        [p] => inside(*p, min, max),
+        _ => line.windows(2).any(|s| clip_segment(s[0], s[1], min, max).is_some()),
+    };
+    // the polygon crosses the box, or the box is entirely inside of the polygon
+    let poly_in = |rings: Vec<Vec<(f64, f64)>>| {
+        rings.iter().any(|ring| line_in(ring)) || rings_contain(&rings, min)
+    };
+    match geometry {
+        VectorGeometry::VectorPoints(points) => points.iter().any(|p| inside(p.xy(), min, max)),
+        VectorGeometry::VectorPoints3D(points) => points.iter().any(|p| inside(p.xy(), min, max)),
+        VectorGeometry::VectorLines(lines) => lines.iter().any(|l| line_in(&xy(&l.geometry))),
+        VectorGeometry::VectorLines3D(lines) => lines.iter().any(|l| line_in(&xy(&l.geometry))),
+        VectorGeometry::VectorPolys(polys) => {
+            polys.iter().any(|p| poly_in(p.iter().map(|l| xy(&l.geometry)).collect()))
+        }
+        VectorGeometry::VectorPolys3D(polys) => {
+            polys.iter().any(|p| poly_in(p.iter().map(|l| xy(&l.geometry)).collect()))
+        }
+    }
+}
+
+/// A point's position on the x/y plane
+trait XY {
+    fn xy(&self) -> (f64, f64);
+}
+impl XY for Point {
+    fn xy(&self) -> (f64, f64) {
+        (self.x as f64, self.y as f64)
+    }
+}
+impl XY for Point3D {
+    fn xy(&self) -> (f64, f64) {
+        (self.x as f64, self.y as f64)
+    }
+}
+
+fn xy<P: XY>(points: &[P]) -> Vec<(f64, f64)> {
+    points.iter().map(XY::xy).collect()
+}
+
+fn inside(p: (f64, f64), min: (f64, f64), max: (f64, f64)) -> bool {
+    p.0 >= min.0 && p.0 <= max.0 && p.1 >= min.1 && p.1 <= max.1
+}
+
+/// Even-odd test of the point against all rings of a polygon, so holes are excluded
+fn rings_contain(rings: &[Vec<(f64, f64)>], (x, y): (f64, f64)) -> bool {
+    let mut contains = false;
+    for ring in rings {
+        let Some(mut j) = ring.len().checked_sub(1) else { continue };
+        for i in 0..ring.len() {
+            let ((xi, yi), (xj, yj)) = (ring[i], ring[j]);
+            if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+                contains = !contains;
+            }
+            j = i;
+        }
+    }
+    contains
+}
+
+/// Squared distance from `p` to the closest point of a line (or a single point)
+fn sq_line_dist(line: &[(f64, f64)], p: (f64, f64)) -> f64 {
+    match line {
+        [] => f64::INFINITY,
+        [a] => sq_dist(*a, p),
+        _ => line.windows(2).map(|s| sq_seg_dist(p, s[0], s[1])).fold(f64::INFINITY, f64::min),
+    }
+}
+
+fn sq_dist(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0) * (a.0 - b.0) + (a.1 - b.1) * (a.1 - b.1)
+}
+
+/// Squared distance from `p` to the segment `a`->`b`
+pub(crate) fn sq_seg_dist(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len = dx * dx + dy * dy;
+    if len == 0.0 {
+        return sq_dist(p, a);
+    }
+    let t = (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len).clamp(0.0, 1.0);
+    sq_dist(p, (a.0 + dx * t, a.1 + dy * t))
+}
+
+/// Liang-Barsky clipping of the segment `a`->`b`, returning the portion inside of the box as a
+/// range of 0->1 along the segment
+pub(crate) fn clip_segment(
+    a: (f64, f64),
+    b: (f64, f64),
+    min: (f64, f64),
+    max: (f64, f64),
+) -> Option<(f64, f64)> {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let (mut t0, mut t1): (f64, f64) = (0.0, 1.0);
+    for (p, q) in [(-dx, a.0 - min.0), (dx, max.0 - a.0), (-dy, a.1 - min.1), (dy, max.1 - a.1)] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    (t0 <= t1).then_some((t0, t1))
+}
@@ -0,0 +1,239 @@
+use crate::error::{DecodeError, ProtoMessage};
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use pbf::{BitCast, zagzig};
+
+/// The wire type of varint fields
+pub(crate) const VARINT: u8 = 0;
+/// The wire type of length delimited fields (bytes, strings, messages and packed arrays)
+pub(crate) const BYTES: u8 = 2;
+
+/// The result of reading encoded tile data
+pub(crate) type Result<T> = core::result::Result<T, DecodeError>;
+
+/// A bounds checked protobuf reader over borrowed bytes, shared by the owned and borrowed tiles.
+///
+/// A reader covers `buf[pos..end]` and every position it hands out is an absolute position in
+/// `buf`, so a field found while reading a tile can be read again later from the tile data.
+/// Reads past the end of the message return a [`DecodeError`] naming the message and field.
+#[derive(Debug, Clone)]
+pub(crate) struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    end: usize,
+    message: ProtoMessage,
+    tag: Option<u64>,
+    /// the position of the current field's key
+    key: usize,
+}
+impl<'a> Reader<'a> {
+    /// Create a reader over `buf[pos..end]`. `end` is capped to the length of the buffer
+    pub fn new(buf: &'a [u8], pos: usize, end: usize, message: ProtoMessage) -> Self {
+        Reader { buf, pos, end: end.min(buf.len()), message, tag: None, key: pos }
+    }
+
+    /// Create a reader for the length prefixed message found at `pos` in `buf`, as stored by an
+    /// earlier read of the message holding it. `tag` is the field the message was stored in
+    pub fn at(buf: &'a [u8], pos: usize, tag: u64, message: ProtoMessage) -> Result<Self> {
+        let mut reader = Reader::new(buf, pos, buf.len(), message);
+        reader.tag = Some(tag);
+        let mut message = reader.message(message)?;
+        message.tag = Some(tag);
+        Ok(message)
+    }
+
+    /// The data being read
+    pub fn buf(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    /// The current position
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// The tag of the field being read
+    pub fn tag(&self) -> u64 {
+        self.tag.unwrap_or_default()
+    }
+
+    /// true if the whole message has been read
+    pub fn done(&self) -> bool {
+        self.pos >= self.end
+    }
+
+    /// An error describing an out of range reference found at `offset`
+    pub fn out_of_range(&self, index: u64, len: usize, offset: usize) -> DecodeError {
+        DecodeError::OutOfRange { message: self.message, tag: self.tag, index, len, offset }
+    }
+
+    /// An error describing an unrecognized enumerated value found at `offset`
+    pub fn invalid_enum(&self, value: u64, offset: usize) -> DecodeError {
+        DecodeError::InvalidEnum { message: self.message, tag: self.tag, value, offset }
+    }
+
+    /// An error describing a malformed value found at `offset`
+    pub fn malformed(&self, offset: usize, reason: &'static str) -> DecodeError {
+        DecodeError::Malformed { message: self.message, tag: self.tag, offset, reason }
+    }
+
+    /// The error for a field that is not part of the message
+    pub fn unknown_tag(&self, offset: usize) -> DecodeError {
+        DecodeError::UnknownTag { message: self.message, tag: self.tag(), offset }
+    }
+
+    /// Read a varint
+    pub fn varint(&mut self) -> Result<u64> {
+        let start = self.pos;
+        let mut val: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            if self.pos >= self.end {
+                return Err(DecodeError::UnexpectedEof {
+                    message: self.message,
+                    tag: self.tag,
+                    offset: start,
+                });
+            }
+            let b = self.buf[self.pos];
+            self.pos += 1;
+            val |= ((b & 0x7f) as u64) << shift;
+            if b < 0x80 {
+                return Ok(val);
+            }
+        }
+        Err(self.malformed(start, "varint is longer than 10 bytes"))
+    }
+
+    /// Read a varint cast to `T`
+    pub fn read_varint<T: BitCast>(&mut self) -> Result<T> {
+        Ok(T::from_u64(self.varint()?))
+    }
+
+    /// Read a varint as a usize
+    pub fn usize(&mut self) -> Result<usize> {
+        Ok(self.varint()? as usize)
+    }
+
+    /// Read a zigzag encoded varint
+    pub fn s_varint(&mut self) -> Result<i64> {
+        Ok(zagzig(self.varint()?))
+    }
+
+    /// Read a zigzag encoded varint that has to fit in 32 bits
+    pub fn s_varint_i32(&mut self) -> Result<i32> {
+        let start = self.pos;
+        let val = self.s_varint()?;
+        i32::try_from(val).map_err(|_| self.malformed(start, "value does not fit in 32 bits"))
+    }
+
+    /// Read a field key, returning the tag and wire type
+    pub fn field(&mut self) -> Result<(u64, u8)> {
+        let start = self.pos;
+        let key = self.varint()?;
+        let tag = key >> 3;
+        let wire_type = (key & 0x7) as u8;
+        self.tag = Some(tag);
+        self.key = start;
+        match wire_type {
+            0 | 1 | 2 | 5 | 7 => Ok((tag, wire_type)),
+            _ => Err(DecodeError::InvalidWireType {
+                message: self.message,
+                tag,
+                wire_type,
+                offset: start,
+            }),
+        }
+    }
+
+    /// Check that the current field has the wire type it is read as. A varint stored where bytes
+    /// are expected would otherwise be read as a length (and the other way around)
+    pub fn expect(&self, wire_type: u8, expected: u8) -> Result<()> {
+        if wire_type == expected {
+            return Ok(());
+        }
+        Err(DecodeError::InvalidWireType {
+            message: self.message,
+            tag: self.tag(),
+            wire_type,
+            offset: self.key,
+        })
+    }
+
+    /// Skip the value of a field
+    pub fn skip(&mut self, wire_type: u8) -> Result<()> {
+        match wire_type {
+            0 => _ = self.varint()?,
+            1 => self.advance(8)?,
+            2 => _ = self.bytes()?,
+            5 => self.advance(4)?,
+            _ => {}
+        };
+        Ok(())
+    }
+
+    /// Move forward `len` bytes
+    fn advance(&mut self, len: usize) -> Result<()> {
+        match self.pos.checked_add(len) {
+            Some(end) if end <= self.end => {
+                self.pos = end;
+                Ok(())
+            }
+            _ => Err(DecodeError::UnexpectedEof {
+                message: self.message,
+                tag: self.tag,
+                offset: self.pos,
+            }),
+        }
+    }
+
+    /// Read a length prefixed block, returning its start and end
+    pub fn range(&mut self) -> Result<(usize, usize)> {
+        let len = self.usize()?;
+        let start = self.pos;
+        self.advance(len)?;
+        Ok((start, self.pos))
+    }
+
+    /// Read a length prefixed block of bytes without copying them
+    pub fn bytes(&mut self) -> Result<&'a [u8]> {
+        let (start, end) = self.range()?;
+        Ok(&self.buf[start..end])
+    }
+
+    /// Read a string, only copying it if it is not valid UTF-8
+    pub fn string(&mut self) -> Result<Cow<'a, str>> {
+        Ok(String::from_utf8_lossy(self.bytes()?))
+    }
+
+    /// Read a length prefixed message
+    pub fn message(&mut self, message: ProtoMessage) -> Result<Reader<'a>> {
+        let (start, end) = self.range()?;
+        Ok(Reader::new(self.buf, start, end, message))
+    }
+
+    /// Read a packed array of varints without decoding it. The returned reader covers the array
+    pub fn packed(&mut self) -> Result<Reader<'a>> {
+        let (start, end) = self.range()?;
+        Ok(Reader { pos: start, end, ..self.clone() })
+    }
+
+    /// Count the varints in a packed array, checking each one is complete
+    pub fn packed_len(&mut self) -> Result<usize> {
+        let mut packed = self.packed()?;
+        let mut len = 0;
+        while !packed.done() {
+            packed.varint()?;
+            len += 1;
+        }
+        Ok(len)
+    }
+
+    /// Read a packed array of varints
+    pub fn read_packed<T: BitCast>(&mut self) -> Result<Vec<T>> {
+        let mut packed = self.packed()?;
+        let mut res = Vec::new();
+        while !packed.done() {
+            res.push(packed.read_varint()?);
+        }
+        Ok(res)
+    }
+}
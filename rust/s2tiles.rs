@@ -0,0 +1,298 @@
+use crate::{DecodeError, VectorTile, tile_id::S2TileId};
+use alloc::{string::ToString, vec, vec::Vec};
+use core::fmt;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+/// The size of an encoded header
+pub const HEADER_SIZE: usize = 48;
+/// The size of an encoded directory entry
+const ENTRY_SIZE: usize = 20;
+
+/// An error reading or writing an S2 tile archive
+#[derive(Debug)]
+pub enum S2TilesError {
+    /// The archive could not be read or written
+    Io(io::Error),
+    /// The archive does not start with the `S2Tiles` magic number
+    InvalidMagic,
+    /// Only version 1 archives are supported
+    UnsupportedVersion(u8),
+    /// The directory is truncated or points outside of the archive
+    InvalidDirectory,
+    /// The metadata is not valid JSON
+    InvalidMetadata,
+    /// A tile ID is past the deepest zoom or outside of its face
+    InvalidTileId(S2TileId),
+    /// A tile could not be decoded
+    Decode(DecodeError),
+}
+impl fmt::Display for S2TilesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            S2TilesError::Io(err) => write!(f, "s2tiles: {}", err),
+            S2TilesError::InvalidMagic => write!(f, "s2tiles: not an S2 tile archive"),
+            S2TilesError::UnsupportedVersion(version) => {
+                write!(f, "s2tiles: unsupported version {}", version)
+            }
+            S2TilesError::InvalidDirectory => write!(f, "s2tiles: invalid directory"),
+            S2TilesError::InvalidMetadata => write!(f, "s2tiles: invalid metadata"),
+            S2TilesError::InvalidTileId(id) => write!(f, "s2tiles: invalid tile {}", id),
+            S2TilesError::Decode(err) => write!(f, "s2tiles: {}", err),
+        }
+    }
+}
+impl core::error::Error for S2TilesError {}
+impl From<io::Error> for S2TilesError {
+    fn from(err: io::Error) -> Self {
+        S2TilesError::Io(err)
+    }
+}
+impl From<DecodeError> for S2TilesError {
+    fn from(err: DecodeError) -> Self {
+        S2TilesError::Decode(err)
+    }
+}
+
+/// The fixed size header at the start of every archive
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    /// minimum zoom of the tiles
+    pub min_zoom: u8,
+    /// maximum zoom of the tiles
+    pub max_zoom: u8,
+    /// bit `n` is set if face `n` has tiles
+    pub faces: u8,
+    /// offset of the directory
+    pub directory_offset: u64,
+    /// number of directory entries
+    pub tiles: u64,
+    /// offset of the JSON metadata
+    pub metadata_offset: u64,
+    /// length of the JSON metadata
+    pub metadata_length: u64,
+}
+impl Header {
+    /// Decode a header
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, S2TilesError> {
+        if buf.len() < HEADER_SIZE || &buf[0..7] != b"S2Tiles" {
+            return Err(S2TilesError::InvalidMagic);
+        }
+        if buf[7] != 1 {
+            return Err(S2TilesError::UnsupportedVersion(buf[7]));
+        }
+        let u64_at = |pos: usize| u64::from_le_bytes(buf[pos..pos + 8].try_into().unwrap());
+        Ok(Header {
+            min_zoom: buf[8],
+            max_zoom: buf[9],
+            faces: buf[10],
+            directory_offset: u64_at(16),
+            tiles: u64_at(24),
+            metadata_offset: u64_at(32),
+            metadata_length: u64_at(40),
+        })
+    }
+
+    /// Encode the header
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(HEADER_SIZE);
+        buf.extend_from_slice(b"S2Tiles");
+        buf.push(1);
+        buf.extend_from_slice(&[self.min_zoom, self.max_zoom, self.faces, 0, 0, 0, 0, 0]);
+        for value in [self.directory_offset, self.tiles, self.metadata_offset, self.metadata_length]
+        {
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+        buf
+    }
+}
+
+/// A directory entry: the S2 cell ID of a tile and where its data is stored
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    id: u64,
+    offset: u64,
+    length: u32,
+}
+
+/// # S2 Tiles
+///
+/// ## Description
+/// A reader for a simple archive of S2 tiles covering any of the six cube faces. The archive is
+/// laid out as:
+///
+/// - a 48 byte header: the `S2Tiles` magic number, version `1`, the zoom range, a bit mask of the
+///   faces with tiles, and the offsets of the directory and metadata
+/// - the tile data (usually Open Vector Tiles)
+/// - JSON metadata
+/// - a directory of 20 byte entries sorted by S2 cell ID: the ID (u64), the offset of the tile
+///   data (u64) and its length (u32)
+///
+/// All integers are little endian. Since S2 cell IDs of a tile's descendants are contiguous, the
+/// tiles of a face or any subtree are stored next to each other.
+///
+/// ## Usage
+/// ```rust,ignore
+/// use open_vector_tile::{s2tiles::S2Tiles, tile_id::S2TileId};
+///
+/// let mut archive = S2Tiles::open("tiles.s2tiles")?;
+/// let tile = archive.tile(S2TileId::new(Face::Face2, 0, 0, 0))?;
+/// ```
+#[derive(Debug)]
+pub struct S2Tiles<R: Read + Seek> {
+    reader: R,
+    header: Header,
+    entries: Vec<Entry>,
+}
+impl S2Tiles<File> {
+    /// Open an archive on disk
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, S2TilesError> {
+        S2Tiles::new(File::open(path)?)
+    }
+}
+impl<R: Read + Seek> S2Tiles<R> {
+    /// Read the header and directory of an archive
+    pub fn new(mut reader: R) -> Result<Self, S2TilesError> {
+        let mut buf = [0; HEADER_SIZE];
+        reader.read_exact(&mut buf).map_err(|_| S2TilesError::InvalidMagic)?;
+        let header = Header::from_bytes(&buf)?;
+        let mut archive = S2Tiles { reader, header, entries: vec![] };
+        let length = header.tiles.checked_mul(ENTRY_SIZE as u64);
+        let directory = archive.read_at(header.directory_offset, length)?;
+        archive.entries = directory
+            .as_chunks::<ENTRY_SIZE>()
+            .0
+            .iter()
+            .map(|entry| Entry {
+                id: u64::from_le_bytes(entry[0..8].try_into().unwrap()),
+                offset: u64::from_le_bytes(entry[8..16].try_into().unwrap()),
+                length: u32::from_le_bytes(entry[16..20].try_into().unwrap()),
+            })
+            .collect();
+        if archive.entries.windows(2).any(|pair| pair[0].id >= pair[1].id) {
+            return Err(S2TilesError::InvalidDirectory);
+        }
+        Ok(archive)
+    }
+
+    /// The archive's header
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// The archive's JSON metadata
+    pub fn metadata(&mut self) -> Result<Value, S2TilesError> {
+        let data = self.read_at(self.header.metadata_offset, Some(self.header.metadata_length))?;
+        if data.is_empty() {
+            return Ok(Value::Object(Default::default()));
+        }
+        serde_json::from_slice(&data).map_err(|_| S2TilesError::InvalidMetadata)
+    }
+
+    /// The IDs of every tile in the archive, in S2 cell ID order
+    pub fn tile_ids(&self) -> impl Iterator<Item = S2TileId> + '_ {
+        self.entries.iter().filter_map(|entry| S2TileId::from_id(entry.id))
+    }
+
+    /// The bytes of a tile, None if the archive does not have it
+    pub fn tile_data(&mut self, id: S2TileId) -> Result<Option<Vec<u8>>, S2TilesError> {
+        if !id.is_valid() {
+            return Ok(None);
+        }
+        let cell_id = id.id();
+        let Ok(index) = self.entries.binary_search_by_key(&cell_id, |entry| entry.id) else {
+            return Ok(None);
+        };
+        let Entry { offset, length, .. } = self.entries[index];
+        self.read_at(offset, Some(length as u64)).map(Some)
+    }
+
+    /// Decode a tile, None if the archive does not have it
+    pub fn tile(&mut self, id: S2TileId) -> Result<Option<VectorTile>, S2TilesError> {
+        match self.tile_data(id)? {
+            Some(data) => Ok(Some(VectorTile::try_new(data, None)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Read `length` bytes starting at `offset`
+    fn read_at(&mut self, offset: u64, length: Option<u64>) -> Result<Vec<u8>, S2TilesError> {
+        let end = self.reader.seek(SeekFrom::End(0))?;
+        let Some(length) =
+            length.filter(|length| offset.checked_add(*length).is_some_and(|stop| stop <= end))
+        else {
+            return Err(S2TilesError::InvalidDirectory);
+        };
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let mut buf = vec![0; length as usize];
+        self.reader.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// Build an archive from encoded tiles (e.g. the output of [`crate::write_tile`]) on any of the
+/// six faces. Identical tiles are stored once and a tile given twice keeps its first data.
+/// Returns the header that was written.
+pub fn write_archive<W: Write>(
+    mut writer: W,
+    tiles: impl IntoIterator<Item = (S2TileId, Vec<u8>)>,
+    metadata: &Value,
+) -> Result<Header, S2TilesError> {
+    let mut tiles: Vec<(u64, Vec<u8>)> =
+        tiles
+            .into_iter()
+            .map(|(id, data)| {
+                if id.is_valid() {
+                    Ok((id.id(), data))
+                } else {
+                    Err(S2TilesError::InvalidTileId(id))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+    tiles.sort_by_key(|tile| tile.0);
+    tiles.dedup_by_key(|tile| tile.0);
+
+    let mut header = Header { min_zoom: u8::MAX, tiles: tiles.len() as u64, ..Default::default() };
+    // tile data and its entries
+    let mut offset = HEADER_SIZE as u64;
+    let mut entries = Vec::with_capacity(tiles.len());
+    let mut contents: HashMap<&[u8], u64> = HashMap::new();
+    let mut tile_data: Vec<&[u8]> = vec![];
+    for (id, data) in &tiles {
+        let tile = S2TileId::from_id(*id).unwrap();
+        header.min_zoom = header.min_zoom.min(tile.zoom);
+        header.max_zoom = header.max_zoom.max(tile.zoom);
+        header.faces |= 1 << tile.face as u8;
+        let tile_offset = *contents.entry(data).or_insert_with(|| {
+            tile_data.push(data);
+            offset += data.len() as u64;
+            offset - data.len() as u64
+        });
+        entries.push(Entry { id: *id, offset: tile_offset, length: data.len() as u32 });
+    }
+    if entries.is_empty() {
+        header.min_zoom = 0;
+    }
+    let metadata = metadata.to_string();
+    header.metadata_offset = offset;
+    header.metadata_length = metadata.len() as u64;
+    header.directory_offset = offset + header.metadata_length;
+
+    writer.write_all(&header.to_bytes())?;
+    for data in tile_data {
+        writer.write_all(data)?;
+    }
+    writer.write_all(metadata.as_bytes())?;
+    for entry in entries {
+        writer.write_all(&entry.id.to_le_bytes())?;
+        writer.write_all(&entry.offset.to_le_bytes())?;
+        writer.write_all(&entry.length.to_le_bytes())?;
+    }
+    writer.flush()?;
+    Ok(header)
+}
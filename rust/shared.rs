@@ -0,0 +1,31 @@
+#[cfg(not(feature = "sync"))]
+use alloc::rc::Rc;
+#[cfg(feature = "sync")]
+use alloc::sync::Arc;
+#[cfg(not(feature = "sync"))]
+use core::cell::OnceCell;
+#[cfg(feature = "sync")]
+use std::sync::OnceLock;
+
+/// A handle to data shared between a tile, its layers and their features. Tile data is never
+/// mutated once it is read, so every reader creates its own cursor over it.
+/// Single threaded by default, enable the `sync` feature to share decoded tiles across threads.
+#[cfg(not(feature = "sync"))]
+pub type Shared<T> = Rc<T>;
+/// A handle to data shared between a tile, its layers and their features.
+/// Thread safe as the `sync` feature is enabled, so decoded tiles are `Send + Sync`.
+#[cfg(feature = "sync")]
+pub type Shared<T> = Arc<T>;
+
+/// A value decoded on first access through a shared reference
+#[cfg(not(feature = "sync"))]
+pub type Lazy<T> = OnceCell<T>;
+/// A value decoded on first access through a shared reference. With the `sync` feature the
+/// first thread to access it decodes it while the others wait.
+#[cfg(feature = "sync")]
+pub type Lazy<T> = OnceLock<T>;
+
+/// Wrap the value in a [`Shared`] handle
+pub fn shared<T>(value: T) -> Shared<T> {
+    Shared::new(value)
+}
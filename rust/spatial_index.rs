@@ -0,0 +1,293 @@
+use crate::{
+    VectorGeometry,
+    reader::{BYTES, Reader, Result, VARINT},
+};
+use alloc::{vec, vec::Vec};
+use libm::{ceil, floor};
+use pbf::{ProtoRead, ProtoWrite, Protobuf};
+
+/// The default number of children of each node
+pub const DEFAULT_NODE_SIZE: usize = 16;
+
+/// # Spatial Index
+///
+/// ## Description
+/// A static packed R-tree over the bounds of a layer's features. Items are sorted along a
+/// Hilbert curve and packed `node_size` to a node, so the tree is built once in O(n log n) and
+/// each search only visits the nodes that intersect it. Bounds are in extent units.
+///
+/// The index is a protobuf message, so it can be stored with its layer in an OVT tile (see
+/// [`crate::base::BaseVectorLayer::spatial_index`]) instead of being rebuilt by every client.
+///
+/// ## Usage
+/// ```rust
+/// use open_vector_tile::spatial_index::SpatialIndex;
+///
+/// let index = SpatialIndex::new(vec![(0, [0, 0, 10, 10]), (1, [20, 20, 30, 30])], 16);
+/// assert_eq!(index.search(5, 5, 25, 25), vec![0, 1]);
+/// assert_eq!(index.search(11, 11, 19, 19), Vec::<usize>::new());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SpatialIndex {
+    node_size: usize,
+    num_items: usize,
+    /// the bounds of every node, items first and the root last
+    boxes: Vec<[i32; 4]>,
+    /// the item index of leaves and the position of the first child of other nodes
+    indices: Vec<u32>,
+}
+impl SpatialIndex {
+    /// Build an index from `(item index, [min_x, min_y, max_x, max_y])` pairs
+    pub fn new(mut items: Vec<(usize, [i32; 4])>, node_size: usize) -> Self {
+        let node_size = node_size.clamp(2, u16::MAX as usize);
+        let num_items = items.len();
+        if num_items == 0 {
+            return Self { node_size, ..Default::default() };
+        }
+
+        // sort the items along a hilbert curve through the center of their bounds
+        let [min_x, min_y, max_x, max_y] = union(items.iter().map(|(_, b)| b));
+        let width = (max_x as f64 - min_x as f64).max(1.0);
+        let height = (max_y as f64 - min_y as f64).max(1.0);
+        let hilbert_max = ((1 << 16) - 1) as f64;
+        let value = |b: &[i32; 4]| {
+            let cx = (b[0] as f64 + b[2] as f64) / 2.0 - min_x as f64;
+            let cy = (b[1] as f64 + b[3] as f64) / 2.0 - min_y as f64;
+            hilbert((hilbert_max * cx / width) as u32, (hilbert_max * cy / height) as u32)
+        };
+        items.sort_by_cached_key(|(i, b)| (value(b), *i));
+
+        let mut boxes: Vec<[i32; 4]> = items.iter().map(|(_, b)| *b).collect();
+        let mut indices: Vec<u32> = items.iter().map(|(i, _)| *i as u32).collect();
+        // pack each level into the one above it
+        let mut pos = 0;
+        let level_bounds = level_bounds(num_items, node_size);
+        // the root level has nothing above it
+        for &end in &level_bounds[..level_bounds.len() - 1] {
+            while pos < end {
+                let node_end = (pos + node_size).min(end);
+                boxes.push(union(boxes[pos..node_end].iter()));
+                indices.push(pos as u32);
+                pos = node_end;
+            }
+        }
+
+        Self { node_size, num_items, boxes, indices }
+    }
+
+    /// Build an index over geometries, the item index of each is its position. Empty geometries
+    /// are left out
+    pub fn from_geometries(geometries: impl Iterator<Item = VectorGeometry>) -> Self {
+        let items = geometries.enumerate().filter_map(|(i, g)| Some((i, geometry_bounds(&g)?)));
+        Self::new(items.collect(), DEFAULT_NODE_SIZE)
+    }
+
+    /// The number of items in the index
+    pub fn len(&self) -> usize {
+        self.num_items
+    }
+
+    /// True if the index has no items
+    pub fn is_empty(&self) -> bool {
+        self.num_items == 0
+    }
+
+    /// The largest item index in the index
+    pub(crate) fn max_item(&self) -> Option<usize> {
+        self.indices.iter().take(self.num_items).max().map(|i| *i as usize)
+    }
+
+    /// The (sorted) indices of the items whose bounds intersect the box
+    pub fn search(&self, min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Vec<usize> {
+        let mut res = vec![];
+        if self.boxes.is_empty() {
+            return res;
+        }
+        let level_bounds = level_bounds(self.num_items, self.node_size);
+        let mut queue = vec![self.boxes.len() - 1];
+        while let Some(node) = queue.pop() {
+            // the end of the node is capped by the end of its level
+            let level_end = level_bounds.iter().find(|end| **end > node).copied().unwrap_or(0);
+            let end = (node + self.node_size).min(level_end).min(self.boxes.len());
+            for pos in node..end {
+                let [x0, y0, x1, y1] = self.boxes[pos];
+                if max_x < x0 || max_y < y0 || min_x > x1 || min_y > y1 {
+                    continue;
+                }
+                let index = self.indices[pos] as usize;
+                if pos < self.num_items {
+                    res.push(index);
+                } else {
+                    queue.push(index);
+                }
+            }
+        }
+        res.sort_unstable();
+        res
+    }
+
+    /// The (sorted) indices of the items whose bounds are within `tolerance` of the point
+    pub fn search_point(&self, x: f64, y: f64, tolerance: f64) -> Vec<usize> {
+        self.search(
+            floor(x - tolerance) as i32,
+            floor(y - tolerance) as i32,
+            ceil(x + tolerance) as i32,
+            ceil(y + tolerance) as i32,
+        )
+    }
+}
+impl ProtoWrite for SpatialIndex {
+    fn write(&self, pb: &mut Protobuf) {
+        pb.write_varint_field(1, self.node_size);
+        pb.write_varint_field(2, self.num_items);
+        let boxes: Vec<i32> = self.boxes.iter().flatten().copied().collect();
+        pb.write_packed_s_varint(3, &boxes);
+        pb.write_packed_varint(4, &self.indices);
+    }
+}
+impl ProtoRead for SpatialIndex {
+    fn read(&mut self, tag: u64, pb: &mut Protobuf) {
+        match tag {
+            1 => self.node_size = pb.read_varint(),
+            2 => self.num_items = pb.read_varint(),
+            3 => {
+                let boxes = pb.read_s_packed::<i32>();
+                self.boxes = boxes.as_chunks::<4>().0.to_vec();
+            }
+            4 => self.indices = pb.read_packed(),
+            _ => {}
+        }
+    }
+}
+
+/// Read a layer's spatial index, checking the tree is well formed so searching it can not fail
+pub(crate) fn read_spatial_index(mut pb: Reader) -> Result<SpatialIndex> {
+    let begin = pb.pos();
+    let mut index = SpatialIndex::default();
+    let mut boxes: Vec<i32> = Vec::new();
+    while !pb.done() {
+        let (tag, wire_type) = pb.field()?;
+        let start = pb.pos();
+        match tag {
+            1 | 2 => pb.expect(wire_type, VARINT)?,
+            3 | 4 => pb.expect(wire_type, BYTES)?,
+            _ => return Err(pb.unknown_tag(start)),
+        }
+        match tag {
+            1 => index.node_size = pb.usize()?,
+            2 => index.num_items = pb.usize()?,
+            3 => {
+                let mut packed = pb.packed()?;
+                while !packed.done() {
+                    boxes.push(packed.s_varint()? as i32);
+                }
+            }
+            _ => index.indices = pb.read_packed()?,
+        }
+    }
+    index.boxes = boxes.as_chunks::<4>().0.to_vec();
+
+    let SpatialIndex { node_size, num_items, boxes: ref nodes, ref indices } = index;
+    let invalid = Err(pb.malformed(begin, "invalid spatial index"));
+    if !(2..=u16::MAX as usize).contains(&node_size) || num_items > indices.len() {
+        return invalid;
+    }
+    let num_nodes = if num_items == 0 {
+        0
+    } else {
+        level_bounds(num_items, node_size).last().copied().unwrap_or_default()
+    };
+    if indices.len() != num_nodes || nodes.len() != num_nodes || boxes.len() != num_nodes * 4 {
+        return invalid;
+    }
+    // each node's children come before it
+    if indices.iter().enumerate().skip(num_items).any(|(pos, child)| *child as usize >= pos) {
+        return invalid;
+    }
+
+    Ok(index)
+}
+
+/// The bounds of a geometry as `[min_x, min_y, max_x, max_y]`, None if it has no points
+pub fn geometry_bounds(geometry: &VectorGeometry) -> Option<[i32; 4]> {
+    let points: Vec<(i32, i32)> = match geometry {
+        VectorGeometry::VectorPoints(p) => p.iter().map(|p| (p.x, p.y)).collect(),
+        VectorGeometry::VectorPoints3D(p) => p.iter().map(|p| (p.x, p.y)).collect(),
+        VectorGeometry::VectorLines(l) => {
+            l.iter().flat_map(|l| &l.geometry).map(|p| (p.x, p.y)).collect()
+        }
+        VectorGeometry::VectorLines3D(l) => {
+            l.iter().flat_map(|l| &l.geometry).map(|p| (p.x, p.y)).collect()
+        }
+        VectorGeometry::VectorPolys(p) => {
+            p.iter().flatten().flat_map(|l| &l.geometry).map(|p| (p.x, p.y)).collect()
+        }
+        VectorGeometry::VectorPolys3D(p) => {
+            p.iter().flatten().flat_map(|l| &l.geometry).map(|p| (p.x, p.y)).collect()
+        }
+    };
+    let (first, rest) = points.split_first()?;
+    Some(rest.iter().fold([first.0, first.1, first.0, first.1], |[x0, y0, x1, y1], (x, y)| {
+        [x0.min(*x), y0.min(*y), x1.max(*x), y1.max(*y)]
+    }))
+}
+
+/// The number of nodes up to the end of each level of the tree, leaves first
+pub(crate) fn level_bounds(num_items: usize, node_size: usize) -> Vec<usize> {
+    let mut n = num_items;
+    let mut num_nodes = n;
+    let mut bounds = vec![n];
+    loop {
+        n = n.div_ceil(node_size);
+        num_nodes += n;
+        bounds.push(num_nodes);
+        if n <= 1 {
+            break;
+        }
+    }
+    bounds
+}
+
+fn union<'a>(mut boxes: impl Iterator<Item = &'a [i32; 4]>) -> [i32; 4] {
+    let first = *boxes.next().unwrap_or(&[0; 4]);
+    boxes
+        .fold(first, |[x0, y0, x1, y1], b| [x0.min(b[0]), y0.min(b[1]), x1.max(b[2]), y1.max(b[3])])
+}
+
+/// Position of `x`/`y` (0->65535) along a hilbert curve
+fn hilbert(x: u32, y: u32) -> u32 {
+    let mut a = x ^ y;
+    let mut b = 0xFFFF ^ a;
+    let mut c = 0xFFFF ^ (x | y);
+    let mut d = x & (y ^ 0xFFFF);
+
+    let mut aa = a | (b >> 1);
+    let mut bb = (a >> 1) ^ a;
+    let mut cc = ((c >> 1) ^ (b & (d >> 1))) ^ c;
+    let mut dd = ((a & (c >> 1)) ^ (d >> 1)) ^ d;
+
+    for shift in [2, 4] {
+        (a, b, c, d) = (aa, bb, cc, dd);
+        aa = (a & (a >> shift)) ^ (b & (b >> shift));
+        bb = (a & (b >> shift)) ^ (b & ((a ^ b) >> shift));
+        cc ^= (a & (c >> shift)) ^ (b & (d >> shift));
+        dd ^= (b & (c >> shift)) ^ ((a ^ b) & (d >> shift));
+    }
+
+    (a, b, c, d) = (aa, bb, cc, dd);
+    cc ^= (a & (c >> 8)) ^ (b & (d >> 8));
+    dd ^= (b & (c >> 8)) ^ ((a ^ b) & (d >> 8));
+
+    a = cc ^ (cc >> 1);
+    b = dd ^ (dd >> 1);
+
+    let spread = |mut i: u32| {
+        i = (i | (i << 8)) & 0x00FF_00FF;
+        i = (i | (i << 4)) & 0x0F0F_0F0F;
+        i = (i | (i << 2)) & 0x3333_3333;
+        (i | (i << 1)) & 0x5555_5555
+    };
+    let i0 = x ^ y;
+    let i1 = b | (0xFFFF ^ (i0 | a));
+    (spread(i1) << 1) | spread(i0)
+}
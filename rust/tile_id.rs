@@ -0,0 +1,338 @@
+use crate::projection::{face_uv_to_xyz, lon_lat_to_face_st, lon_lat_to_tile, xyz_to_face_uv};
+use alloc::vec::Vec;
+use core::fmt;
+use libm::floor;
+use s2json::Face;
+
+/// The deepest zoom a tile ID can address
+pub const MAX_ZOOM: u8 = 30;
+/// The number of leaf tiles along the edge of a face (or the Web Mercator world) at [`MAX_ZOOM`]
+const LEAF_SIZE: i64 = 1 << MAX_ZOOM;
+
+/// S2 Hilbert curve orientation flags
+const SWAP_MASK: u8 = 1;
+const INVERT_MASK: u8 = 2;
+/// The Hilbert curve position of each (i, j) quadrant, for each orientation
+const IJ_TO_POS: [[u8; 4]; 4] = [[0, 1, 3, 2], [0, 3, 1, 2], [2, 3, 1, 0], [2, 1, 3, 0]];
+/// The (i, j) quadrant of each Hilbert curve position, for each orientation
+const POS_TO_IJ: [[u8; 4]; 4] = [[0, 1, 3, 2], [0, 2, 3, 1], [3, 2, 0, 1], [3, 1, 0, 2]];
+/// The change in orientation when descending into each Hilbert curve position
+const POS_TO_ORIENTATION: [u8; 4] = [SWAP_MASK, 0, 0, INVERT_MASK | SWAP_MASK];
+
+/// # Web Mercator Tile ID
+///
+/// ## Description
+/// A Web Mercator tile addressed by `zoom`/`x`/`y`, with `y` pointing down (the XYZ scheme).
+///
+/// [`WMTileId::id`] packs the tile into the same 64 bit tile ID PMTiles uses: the number of
+/// tiles in all lower zooms plus the tile's position along its zoom's Hilbert curve.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WMTileId {
+    /// the zoom of the tile
+    pub zoom: u8,
+    /// the column of the tile
+    pub x: u32,
+    /// the row of the tile
+    pub y: u32,
+}
+impl WMTileId {
+    /// Create a new tile ID
+    pub fn new(zoom: u8, x: u32, y: u32) -> Self {
+        WMTileId { zoom, x, y }
+    }
+
+    /// The tile at `zoom` that contains lon/lat (degrees)
+    pub fn from_lon_lat(lon: f64, lat: f64, zoom: u8) -> Self {
+        let (x, y) = lon_lat_to_tile(lon, lat, zoom);
+        WMTileId { zoom, x, y }
+    }
+
+    /// Decode a tile ID created by [`WMTileId::id`]
+    pub fn from_id(id: u64) -> Self {
+        let (zoom, x, y) = tile_id_to_zxy(id);
+        WMTileId { zoom, x, y }
+    }
+
+    /// The 64 bit tile ID
+    pub fn id(&self) -> u64 {
+        zxy_to_tile_id(self.zoom, self.x, self.y)
+    }
+
+    /// true if the zoom is at most [`MAX_ZOOM`] and x/y are inside the world
+    pub fn is_valid(&self) -> bool {
+        self.zoom <= MAX_ZOOM
+            && (self.x as u64) < 1 << self.zoom
+            && (self.y as u64) < 1 << self.zoom
+    }
+
+    /// The tile one zoom up that contains this tile, None at zoom 0
+    pub fn parent(&self) -> Option<Self> {
+        let zoom = self.zoom.checked_sub(1)?;
+        Some(WMTileId { zoom, x: self.x >> 1, y: self.y >> 1 })
+    }
+
+    /// The four tiles one zoom down: top left, top right, bottom left, bottom right. None at
+    /// [`MAX_ZOOM`] or deeper.
+    pub fn children(&self) -> Option<[Self; 4]> {
+        if self.zoom >= MAX_ZOOM {
+            return None;
+        }
+        let (zoom, x, y) = (self.zoom + 1, self.x << 1, self.y << 1);
+        Some([
+            WMTileId { zoom, x, y },
+            WMTileId { zoom, x: x + 1, y },
+            WMTileId { zoom, x, y: y + 1 },
+            WMTileId { zoom, x: x + 1, y: y + 1 },
+        ])
+    }
+
+    /// true if `other` is this tile or one of its descendants
+    pub fn contains(&self, other: &Self) -> bool {
+        let Some(shift) = other.zoom.checked_sub(self.zoom) else { return false };
+        other.x.checked_shr(shift as u32) == Some(self.x)
+            && other.y.checked_shr(shift as u32) == Some(self.y)
+    }
+
+    /// The tiles sharing an edge with this tile: above, right, below and left. Columns wrap
+    /// around the antimeridian, there are no tiles past the top or bottom of the world. Empty
+    /// past [`MAX_ZOOM`].
+    pub fn neighbors(&self) -> Vec<Self> {
+        if self.zoom > MAX_ZOOM {
+            return Vec::new();
+        }
+        let size = 1_i64 << self.zoom;
+        let (x, y) = (self.x as i64, self.y as i64);
+        let mut res: Vec<Self> = Vec::with_capacity(4);
+        for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+            let ny = y + dy;
+            if ny < 0 || ny >= size {
+                continue;
+            }
+            let neighbor =
+                WMTileId { zoom: self.zoom, x: (x + dx).rem_euclid(size) as u32, y: ny as u32 };
+            if neighbor != *self && !res.contains(&neighbor) {
+                res.push(neighbor);
+            }
+        }
+        res
+    }
+}
+impl fmt::Display for WMTileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.zoom, self.x, self.y)
+    }
+}
+
+/// # S2 Tile ID
+///
+/// ## Description
+/// An S2 tile addressed by `face`/`zoom`/`i`/`j`, where `i` and `j` follow the face's S and T
+/// axes. An S2 tile at a zoom is the S2 cell at the same level.
+///
+/// [`S2TileId::id`] packs the tile into its 64 bit S2 cell ID: 3 bits of face, 2 bits per zoom
+/// of position along the face's Hilbert curve, then a trailing 1 bit marking the zoom. IDs of
+/// tiles inside a tile fall between the IDs of its first and last descendants.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct S2TileId {
+    /// the cube face of the tile
+    pub face: Face,
+    /// the zoom of the tile
+    pub zoom: u8,
+    /// the position of the tile along the face's S axis
+    pub i: u32,
+    /// the position of the tile along the face's T axis
+    pub j: u32,
+}
+impl S2TileId {
+    /// Create a new tile ID
+    pub fn new(face: Face, zoom: u8, i: u32, j: u32) -> Self {
+        S2TileId { face, zoom, i, j }
+    }
+
+    /// The tile at `zoom` that contains lon/lat (degrees)
+    pub fn from_lon_lat(lon: f64, lat: f64, zoom: u8) -> Self {
+        let (face, s, t) = lon_lat_to_face_st(lon, lat);
+        let (i, j) = (st_to_ij(s), st_to_ij(t));
+        let shift = MAX_ZOOM - zoom;
+        S2TileId { face, zoom, i: (i >> shift) as u32, j: (j >> shift) as u32 }
+    }
+
+    /// Decode an S2 cell ID, None if it is not a valid cell ID
+    pub fn from_id(id: u64) -> Option<Self> {
+        let face = (id >> 61) as u8;
+        if face > 5 || id == 0 || !id.trailing_zeros().is_multiple_of(2) {
+            return None;
+        }
+        let zoom = MAX_ZOOM - (id.trailing_zeros() / 2) as u8;
+        let (mut i, mut j) = (0_u32, 0_u32);
+        let mut orientation = face & SWAP_MASK;
+        for k in (MAX_ZOOM - zoom..MAX_ZOOM).rev() {
+            let pos = ((id >> (2 * k + 1)) & 3) as usize;
+            let ij = POS_TO_IJ[orientation as usize][pos] as u32;
+            let bit = k - (MAX_ZOOM - zoom);
+            i |= (ij >> 1) << bit;
+            j |= (ij & 1) << bit;
+            orientation ^= POS_TO_ORIENTATION[pos];
+        }
+        Some(S2TileId { face: face.into(), zoom, i, j })
+    }
+
+    /// The 64 bit S2 cell ID
+    pub fn id(&self) -> u64 {
+        let mut id = (self.face as u64) << 61;
+        let mut orientation = self.face as u8 & SWAP_MASK;
+        let shift = MAX_ZOOM - self.zoom;
+        for k in (shift..MAX_ZOOM).rev() {
+            let bit = k - shift;
+            let ij = ((self.i >> bit) & 1) << 1 | ((self.j >> bit) & 1);
+            let pos = IJ_TO_POS[orientation as usize][ij as usize];
+            id |= (pos as u64) << (2 * k + 1);
+            orientation ^= POS_TO_ORIENTATION[pos as usize];
+        }
+        id | 1 << (2 * shift)
+    }
+
+    /// true if the zoom is at most [`MAX_ZOOM`] and i/j are inside the face
+    pub fn is_valid(&self) -> bool {
+        self.zoom <= MAX_ZOOM
+            && (self.i as u64) < 1 << self.zoom
+            && (self.j as u64) < 1 << self.zoom
+    }
+
+    /// The tile one zoom up that contains this tile, None at zoom 0
+    pub fn parent(&self) -> Option<Self> {
+        let zoom = self.zoom.checked_sub(1)?;
+        Some(S2TileId { face: self.face, zoom, i: self.i >> 1, j: self.j >> 1 })
+    }
+
+    /// The four tiles one zoom down, in the order of their IDs. None at [`MAX_ZOOM`] or deeper.
+    pub fn children(&self) -> Option<[Self; 4]> {
+        if self.zoom >= MAX_ZOOM {
+            return None;
+        }
+        let orientation = self.orientation();
+        let (zoom, i, j) = (self.zoom + 1, self.i << 1, self.j << 1);
+        Some(POS_TO_IJ[orientation as usize].map(|ij| S2TileId {
+            face: self.face,
+            zoom,
+            i: i + (ij as u32 >> 1),
+            j: j + (ij as u32 & 1),
+        }))
+    }
+
+    /// true if `other` is this tile or one of its descendants
+    pub fn contains(&self, other: &Self) -> bool {
+        let Some(shift) = other.zoom.checked_sub(self.zoom) else { return false };
+        other.face == self.face
+            && other.i.checked_shr(shift as u32) == Some(self.i)
+            && other.j.checked_shr(shift as u32) == Some(self.j)
+    }
+
+    /// The tiles sharing an edge with this tile: below, right, above and left (in face ST).
+    /// Tiles on the edge of a face have neighbors on the adjacent faces. None past
+    /// [`MAX_ZOOM`].
+    pub fn neighbors(&self) -> Option<[Self; 4]> {
+        let size = 1_i64 << MAX_ZOOM.checked_sub(self.zoom)?;
+        let (i, j) = (self.i as i64 * size, self.j as i64 * size);
+        Some(
+            [(i, j - 1), (i + size, j), (i, j + size), (i - 1, j)]
+                .map(|(i, j)| from_face_ij_wrap(self.face, i, j, self.zoom)),
+        )
+    }
+
+    /// The orientation of the Hilbert curve inside this tile
+    fn orientation(&self) -> u8 {
+        let mut orientation = self.face as u8 & SWAP_MASK;
+        for bit in (0..self.zoom).rev() {
+            let ij = ((self.i >> bit) & 1) << 1 | ((self.j >> bit) & 1);
+            let pos = IJ_TO_POS[orientation as usize][ij as usize];
+            orientation ^= POS_TO_ORIENTATION[pos as usize];
+        }
+        orientation
+    }
+}
+impl fmt::Display for S2TileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}/{}", self.face as u8, self.zoom, self.i, self.j)
+    }
+}
+
+/// Convert a face S or T (0->1) to a leaf position along the face
+fn st_to_ij(s: f64) -> i64 {
+    (floor(s * LEAF_SIZE as f64) as i64).clamp(0, LEAF_SIZE - 1)
+}
+
+/// The tile at `zoom` containing the leaf position `i`/`j`, which may lie just past the edge of
+/// `face`, in which case it is wrapped onto the adjacent face
+fn from_face_ij_wrap(face: Face, i: i64, j: i64, zoom: u8) -> S2TileId {
+    let shift = MAX_ZOOM - zoom;
+    if (0..LEAF_SIZE).contains(&i) && (0..LEAF_SIZE).contains(&j) {
+        return S2TileId { face, zoom, i: (i >> shift) as u32, j: (j >> shift) as u32 };
+    }
+    // the center of the leaf just past the edge, projected linearly so it lands on the edge of
+    // the adjacent face
+    let limit = 1.0 + f64::EPSILON;
+    let to_uv = |p: i64| {
+        let p = p.clamp(-1, LEAF_SIZE);
+        ((2 * (p - LEAF_SIZE / 2) + 1) as f64 / LEAF_SIZE as f64).clamp(-limit, limit)
+    };
+    let (x, y, z) = face_uv_to_xyz(face, to_uv(i), to_uv(j));
+    let (face, u, v) = xyz_to_face_uv(x, y, z);
+    let (i, j) = (st_to_ij(0.5 * (u + 1.0)), st_to_ij(0.5 * (v + 1.0)));
+    S2TileId { face, zoom, i: (i >> shift) as u32, j: (j >> shift) as u32 }
+}
+
+/// Convert a Web Mercator zoom/x/y into its tile ID: the number of tiles in all zooms below
+/// `zoom` plus the position of the tile along the zoom's Hilbert curve
+pub fn zxy_to_tile_id(zoom: u8, x: u32, y: u32) -> u64 {
+    let n = 1_u64 << zoom;
+    let (mut x, mut y) = (x as u64, y as u64);
+    let mut d = 0;
+    let mut s = n >> 1;
+    while s > 0 {
+        let rx = ((x & s) > 0) as u64;
+        let ry = ((y & s) > 0) as u64;
+        d += s * s * ((3 * rx) ^ ry);
+        rotate(n, &mut x, &mut y, rx, ry);
+        s >>= 1;
+    }
+    zoom_offset(zoom) + d
+}
+
+/// Convert a Web Mercator tile ID back into its zoom/x/y
+pub fn tile_id_to_zxy(tile_id: u64) -> (u8, u32, u32) {
+    let mut zoom = 0;
+    while zoom < 31 && zoom_offset(zoom + 1) <= tile_id {
+        zoom += 1;
+    }
+    let n = 1_u64 << zoom;
+    let mut t = tile_id - zoom_offset(zoom);
+    let (mut x, mut y) = (0, 0);
+    let mut s = 1;
+    while s < n {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+        rotate(s, &mut x, &mut y, rx, ry);
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s <<= 1;
+    }
+    (zoom, x as u32, y as u32)
+}
+
+/// The number of tiles in all zooms below `zoom`
+fn zoom_offset(zoom: u8) -> u64 {
+    ((1_u64 << (2 * zoom as u64)) - 1) / 3
+}
+
+/// Rotate a quadrant of the Hilbert curve
+fn rotate(n: u64, x: &mut u64, y: &mut u64, rx: u64, ry: u64) {
+    if ry == 0 {
+        if rx == 1 {
+            *x = n - 1 - *x;
+            *y = n - 1 - *y;
+        }
+        core::mem::swap(x, y);
+    }
+}
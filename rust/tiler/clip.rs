@@ -0,0 +1,118 @@
+use crate::query::clip_segment;
+use alloc::vec::Vec;
+use libm::hypot;
+use s2json::VectorPoint;
+
+/// Keep the points that are inside of `min`->`max` on both axes
+pub fn clip_points(points: &[VectorPoint], min: f64, max: f64) -> Vec<VectorPoint> {
+    points.iter().filter(|p| inside(p, min, max)).cloned().collect()
+}
+
+/// Clip a line to `min`->`max` on both axes. A line that leaves and re-enters the box is split
+/// into multiple lines.
+pub fn clip_line(line: &[VectorPoint], min: f64, max: f64) -> Vec<Vec<VectorPoint>> {
+    clip_line_with_offset(line, min, max).into_iter().map(|(_, l)| l).collect()
+}
+
+/// Clip a line like [`clip_line`], also returning the offset of each clipped line: the length of
+/// the original line (on the x/y plane) that comes before it. Used to keep dashed lines
+/// continuous across tiles.
+pub fn clip_line_with_offset(
+    line: &[VectorPoint],
+    min: f64,
+    max: f64,
+) -> Vec<(f64, Vec<VectorPoint>)> {
+    let mut lines = Vec::new();
+    let mut current: Vec<VectorPoint> = Vec::new();
+    let mut offset = 0.0;
+    // the length of the line up to the start of the current segment
+    let mut length = 0.0;
+    for segment in line.windows(2) {
+        let (a, b) = (&segment[0], &segment[1]);
+        let segment_length = hypot(b.x - a.x, b.y - a.y);
+        match clip_segment((a.x, a.y), (b.x, b.y), (min, min), (max, max)) {
+            Some((t0, t1)) => {
+                if current.is_empty() {
+                    offset = length + segment_length * t0;
+                    current.push(interpolate(a, b, t0));
+                }
+                current.push(interpolate(a, b, t1));
+                // the segment leaves the box
+                if t1 < 1.0 {
+                    lines.push((offset, core::mem::take(&mut current)));
+                }
+            }
+            None => {
+                if !current.is_empty() {
+                    lines.push((offset, core::mem::take(&mut current)));
+                }
+            }
+        }
+        length += segment_length;
+    }
+    if !current.is_empty() {
+        lines.push((offset, current));
+    }
+    lines.retain(|(_, l)| l.len() >= 2);
+    lines
+}
+
+/// Clip a closed polygon ring to `min`->`max` on both axes. The result is closed, or empty if
+/// the ring is outside of the box.
+pub fn clip_ring(ring: &[VectorPoint], min: f64, max: f64) -> Vec<VectorPoint> {
+    // work on the open ring
+    let mut ring: Vec<VectorPoint> = match ring.split_last() {
+        Some((last, rest)) if rest.first().is_some_and(|f| f.x == last.x && f.y == last.y) => {
+            rest.to_vec()
+        }
+        _ => ring.to_vec(),
+    };
+    for (axis, k, keep_above) in [(0, min, true), (0, max, false), (1, min, true), (1, max, false)]
+    {
+        ring = clip_ring_edge(&ring, axis, k, keep_above);
+        if ring.is_empty() {
+            return ring;
+        }
+    }
+    ring.push(ring[0].clone());
+    ring
+}
+
+/// One Sutherland-Hodgman pass of a ring against a single edge
+fn clip_ring_edge(ring: &[VectorPoint], axis: usize, k: f64, keep_above: bool) -> Vec<VectorPoint> {
+    let coord = |p: &VectorPoint| if axis == 0 { p.x } else { p.y };
+    let is_in = |p: &VectorPoint| if keep_above { coord(p) >= k } else { coord(p) <= k };
+    let mut res = Vec::with_capacity(ring.len());
+    for (i, b) in ring.iter().enumerate() {
+        let a = &ring[(i + ring.len() - 1) % ring.len()];
+        match (is_in(a), is_in(b)) {
+            (true, true) => res.push(b.clone()),
+            (true, false) => res.push(interpolate(a, b, (k - coord(a)) / (coord(b) - coord(a)))),
+            (false, true) => {
+                res.push(interpolate(a, b, (k - coord(a)) / (coord(b) - coord(a))));
+                res.push(b.clone());
+            }
+            (false, false) => {}
+        }
+    }
+    res
+}
+
+/// The point `t` (0->1) of the way from `a` to `b`. The M-Values of `a` are kept.
+fn interpolate(a: &VectorPoint, b: &VectorPoint, t: f64) -> VectorPoint {
+    if t <= 0.0 {
+        return a.clone();
+    }
+    if t >= 1.0 {
+        return b.clone();
+    }
+    let z = match (a.z, b.z) {
+        (Some(az), Some(bz)) => Some(az + (bz - az) * t),
+        (z, _) => z,
+    };
+    VectorPoint::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t, z, a.m.clone())
+}
+
+fn inside(p: &VectorPoint, min: f64, max: f64) -> bool {
+    p.x >= min && p.x <= max && p.y >= min && p.y <= max
+}
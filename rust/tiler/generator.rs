@@ -0,0 +1,438 @@
+use crate::{
+    base::{BaseVectorLayer, BaseVectorTile, s2json_to_base},
+    export::{from_lines, from_points, from_polys},
+    open::Extent,
+    projection::{
+        face_st_to_lon_lat, lon_lat_to_world, lon_lat_to_xyz, uv_to_st, xyz_to_face_uv, xyz_to_uv,
+    },
+    tiler::{clip_line_with_offset, clip_points, clip_ring, simplify},
+};
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+use libm::round;
+use s2json::{
+    BBox3D, Face, Feature, Features, Geometry, MValue, Point as GeoPoint, Point3D as GeoPoint3D,
+    Properties, VectorFeature, VectorFeatureType, VectorGeometry, VectorPoint,
+};
+
+/// The projection tiles are generated in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TileProjection {
+    /// Web Mercator tiles, addressed by zoom/x/y
+    #[default]
+    WM,
+    /// S2 tiles, addressed by face/zoom/i/j
+    S2,
+}
+
+/// Options for the [`Tiler`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TilerOptions {
+    /// The projection of the generated tiles
+    pub projection: TileProjection,
+    /// The extent features are quantized to
+    pub extent: Extent,
+    /// The buffer kept around each tile, in extent units
+    pub buffer: f64,
+    /// The Douglas-Peucker tolerance, in extent units. 0 disables simplification
+    pub tolerance: f64,
+    /// Features are not simplified at or beyond this zoom
+    pub max_zoom: u8,
+    /// Triangulate polygons with earcut when the generated layers are written
+    pub tessellate: bool,
+}
+impl Default for TilerOptions {
+    fn default() -> Self {
+        Self {
+            projection: TileProjection::WM,
+            extent: Extent::Extent4096,
+            buffer: 64.0,
+            tolerance: 3.0,
+            max_zoom: 14,
+            tessellate: false,
+        }
+    }
+}
+
+/// # Tiler
+///
+/// ## Description
+/// Builds [`BaseVectorTile`]s from `s2json` features. Features are added in lon/lat (GeoJSON or
+/// WM vector features) or S2 (S2 vector features with face S/T coordinates) and are projected
+/// once. Each requested tile clips the features to the tile and its buffer, simplifies them for
+/// the zoom and quantizes them to the extent.
+///
+/// Polygon rings are rewound to the Mapbox convention (outer rings clockwise and holes
+/// counter-clockwise with y pointing down). S2 tiles use the face's S/T axes without flipping.
+///
+/// ## Usage
+/// ```rust,ignore
+/// use open_vector_tile::{tiler::{Tiler, TilerOptions}, write_tile};
+///
+/// let mut tiler = Tiler::new(TilerOptions::default());
+/// tiler.add_feature("roads", &feature);
+///
+/// let mut tile = tiler.get_tile(0.into(), 12, 1143, 1497);
+/// let data = write_tile(Some(&mut tile), None, None);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Tiler {
+    /// The options used to build tiles
+    pub options: TilerOptions,
+    /// the projected features of each layer
+    layers: BTreeMap<String, Vec<TilerFeature>>,
+}
+impl Tiler {
+    /// Create a new tiler
+    pub fn new(options: TilerOptions) -> Self {
+        Self { options, layers: BTreeMap::new() }
+    }
+
+    /// Add a GeoJSON feature with lon/lat coordinates to a layer
+    pub fn add_feature<M>(&mut self, layer: &str, feature: &Feature<M>) {
+        let (geometry, bbox) = geojson_geometry(&feature.geometry);
+        self.add(layer, feature.id, &feature.properties, geometry, bbox, None);
+    }
+
+    /// Add a vector feature to a layer. WM features, and S2 features on the [`Face::WM`] face,
+    /// have lon/lat coordinates. Other S2 features have S/T coordinates on their face
+    pub fn add_vector_feature<M>(&mut self, layer: &str, feature: &VectorFeature<M>) {
+        let (geometry, bbox) = vector_geometry(&feature.geometry);
+        let is_s2 = feature._type == VectorFeatureType::S2Feature && feature.face != Face::WM;
+        let face = is_s2.then_some(feature.face);
+        self.add(layer, feature.id, &feature.properties, geometry, bbox, face);
+    }
+
+    /// Add every feature of a collection to a layer
+    pub fn add_features<M>(&mut self, layer: &str, features: &[Features<M>]) {
+        for feature in features {
+            match feature {
+                Features::Feature(feature) => self.add_feature(layer, feature),
+                Features::VectorFeature(feature) => self.add_vector_feature(layer, feature),
+            }
+        }
+    }
+
+    /// Build the tile at `face`/`zoom`/`x`/`y`. Web Mercator tiles only use `Face0`. Layers with
+    /// no features in the tile are left out.
+    pub fn get_tile(&self, face: Face, zoom: u8, x: u32, y: u32) -> BaseVectorTile {
+        let TilerOptions { extent, buffer, tolerance, max_zoom, tessellate, .. } = self.options;
+        let extent_size: f64 = extent.into();
+        let (min, max) = (-buffer / extent_size, 1.0 + buffer / extent_size);
+        let tolerance = if zoom < max_zoom { tolerance / extent_size } else { 0.0 };
+        let size = (1_u64 << zoom) as f64;
+        let to_tile = |p: &VectorPoint| {
+            VectorPoint::new(p.x * size - x as f64, p.y * size - y as f64, p.z, p.m.clone())
+        };
+
+        let mut tile = BaseVectorTile::default();
+        for (name, features) in &self.layers {
+            let mut layer = BaseVectorLayer::new(name.clone(), extent, vec![], None, None);
+            layer.tessellate = tessellate;
+            for feature in features.iter().filter(|f| f.face == face) {
+                let [min_x, min_y, max_x, max_y] = feature.bounds;
+                if min_x * size - x as f64 > max
+                    || max_x * size - (x as f64) < min
+                    || min_y * size - y as f64 > max
+                    || max_y * size - (y as f64) < min
+                {
+                    continue;
+                }
+                let geometry = match &feature.geometry {
+                    TilerGeometry::Points(points) => {
+                        let points: Vec<_> = points.iter().map(to_tile).collect();
+                        let points = clip_points(&points, min, max);
+                        if points.is_empty() {
+                            continue;
+                        }
+                        from_points(points, feature.bbox)
+                    }
+                    TilerGeometry::Lines(lines) => {
+                        let lines: Vec<_> = lines
+                            .iter()
+                            .flat_map(|l| {
+                                let l = l.iter().map(to_tile).collect::<Vec<_>>();
+                                clip_line_with_offset(&l, min, max)
+                            })
+                            // the offset is kept in extent units
+                            .map(|(offset, l)| {
+                                (offset * extent_size, dedupe(simplify(&l, tolerance), extent_size))
+                            })
+                            .filter(|(_, l)| l.len() >= 2)
+                            .collect();
+                        if lines.is_empty() {
+                            continue;
+                        }
+                        from_lines(lines, feature.bbox)
+                    }
+                    TilerGeometry::Polys(polys) => {
+                        let polys: Vec<Vec<_>> = polys
+                            .iter()
+                            .filter_map(|poly| {
+                                let mut rings = vec![];
+                                for (i, ring) in poly.iter().enumerate() {
+                                    let ring = ring.iter().map(to_tile).collect::<Vec<_>>();
+                                    let ring = clip_ring(&ring, min, max);
+                                    let ring = dedupe(simplify(&ring, tolerance), extent_size);
+                                    if ring.len() >= 4 {
+                                        rings.push((0.0, rewind(ring, i == 0)));
+                                    } else if i == 0 {
+                                        // without an outer ring the holes are meaningless
+                                        return None;
+                                    }
+                                }
+                                Some(rings)
+                            })
+                            .collect();
+                        if polys.is_empty() {
+                            continue;
+                        }
+                        from_polys(polys, feature.bbox)
+                    }
+                };
+                let vf: VectorFeature =
+                    VectorFeature::new_wm(feature.id, feature.properties.clone(), geometry, None);
+                layer.add_feature(s2json_to_base(&vf, extent));
+            }
+            if !layer.is_empty() {
+                tile.add_layer(layer);
+            }
+        }
+
+        tile
+    }
+
+    /// Project a feature and store it on each face it touches
+    fn add(
+        &mut self,
+        layer: &str,
+        id: Option<u64>,
+        properties: &Properties,
+        geometry: TilerGeometry,
+        bbox: Option<BBox3D>,
+        face: Option<Face>,
+    ) {
+        let mut projected = vec![];
+        match (self.options.projection, face) {
+            (TileProjection::WM, face) => projected.push((
+                Face::Face0,
+                geometry.map(|p| {
+                    let (lon, lat) = match face {
+                        Some(face) => face_st_to_lon_lat(face, p.x, p.y),
+                        None => (p.x, p.y),
+                    };
+                    let (x, y) = lon_lat_to_world(lon, lat);
+                    VectorPoint::new(x, y, p.z, p.m.clone())
+                }),
+            )),
+            (TileProjection::S2, Some(face)) => projected.push((face, geometry)),
+            (TileProjection::S2, None) => {
+                let mut faces = vec![];
+                geometry.map(|p| {
+                    let (x, y, z) = lon_lat_to_xyz(p.x, p.y);
+                    let (face, _, _) = xyz_to_face_uv(x, y, z);
+                    if !faces.contains(&face) {
+                        faces.push(face);
+                    }
+                    p.clone()
+                });
+                for face in faces {
+                    projected.push((
+                        face,
+                        geometry.map(|p| {
+                            let (x, y, z) = lon_lat_to_xyz(p.x, p.y);
+                            let (u, v) = xyz_to_uv(face, x, y, z);
+                            VectorPoint::new(uv_to_st(u), uv_to_st(v), p.z, p.m.clone())
+                        }),
+                    ));
+                }
+            }
+        }
+
+        let features = self.layers.entry(layer.into()).or_default();
+        for (face, geometry) in projected {
+            features.push(TilerFeature {
+                face,
+                id,
+                properties: properties.clone(),
+                bbox,
+                bounds: geometry.bounds(),
+                geometry,
+            });
+        }
+    }
+}
+
+/// A feature projected to the 0->1 space of a face
+#[derive(Debug, Clone)]
+struct TilerFeature {
+    face: Face,
+    id: Option<u64>,
+    properties: Properties,
+    bbox: Option<BBox3D>,
+    /// [min_x, min_y, max_x, max_y] of the projected geometry
+    bounds: [f64; 4],
+    geometry: TilerGeometry,
+}
+
+/// Geometry stored by the tiler
+#[derive(Debug, Clone)]
+enum TilerGeometry {
+    Points(Vec<VectorPoint>),
+    Lines(Vec<Vec<VectorPoint>>),
+    Polys(Vec<Vec<Vec<VectorPoint>>>),
+}
+impl TilerGeometry {
+    /// Apply `f` to every point
+    fn map(&self, mut f: impl FnMut(&VectorPoint) -> VectorPoint) -> TilerGeometry {
+        match self {
+            TilerGeometry::Points(points) => TilerGeometry::Points(points.iter().map(f).collect()),
+            TilerGeometry::Lines(lines) => {
+                TilerGeometry::Lines(lines.iter().map(|l| l.iter().map(&mut f).collect()).collect())
+            }
+            TilerGeometry::Polys(polys) => TilerGeometry::Polys(
+                polys
+                    .iter()
+                    .map(|p| p.iter().map(|l| l.iter().map(&mut f).collect()).collect())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// [min_x, min_y, max_x, max_y] of every point
+    fn bounds(&self) -> [f64; 4] {
+        let mut bounds = [f64::MAX, f64::MAX, f64::MIN, f64::MIN];
+        self.map(|p| {
+            bounds =
+                [bounds[0].min(p.x), bounds[1].min(p.y), bounds[2].max(p.x), bounds[3].max(p.y)];
+            p.clone()
+        });
+        bounds
+    }
+}
+
+/// Remove consecutive points that quantize to the same position
+fn dedupe(line: Vec<VectorPoint>, extent: f64) -> Vec<VectorPoint> {
+    let mut res: Vec<VectorPoint> = Vec::with_capacity(line.len());
+    for p in line {
+        if res.last().is_some_and(|last| {
+            round(last.x * extent) == round(p.x * extent)
+                && round(last.y * extent) == round(p.y * extent)
+        }) {
+            continue;
+        }
+        res.push(p);
+    }
+    res
+}
+
+/// Wind outer rings clockwise and holes counter-clockwise (with y pointing down)
+fn rewind(mut ring: Vec<VectorPoint>, outer: bool) -> Vec<VectorPoint> {
+    let mut area = 0.0;
+    for i in 0..ring.len() {
+        let (a, b) = (&ring[(i + ring.len() - 1) % ring.len()], &ring[i]);
+        area += (a.x - b.x) * (b.y + a.y);
+    }
+    if (area > 0.0) != outer {
+        ring.reverse();
+    }
+    ring
+}
+
+/// Convert a GeoJSON geometry to the tiler's geometry
+fn geojson_geometry(geometry: &Geometry) -> (TilerGeometry, Option<BBox3D>) {
+    match geometry {
+        Geometry::Point(g) => (
+            TilerGeometry::Points(vec![point(&g.coordinates, g.m_values.clone())]),
+            g.bbox.map(Into::into),
+        ),
+        Geometry::MultiPoint(g) => (
+            TilerGeometry::Points(line(&g.coordinates, g.m_values.as_ref())),
+            g.bbox.map(Into::into),
+        ),
+        Geometry::LineString(g) => (
+            TilerGeometry::Lines(vec![line(&g.coordinates, g.m_values.as_ref())]),
+            g.bbox.map(Into::into),
+        ),
+        Geometry::MultiLineString(g) => (
+            TilerGeometry::Lines(lines(&g.coordinates, g.m_values.as_ref())),
+            g.bbox.map(Into::into),
+        ),
+        Geometry::Polygon(g) => (
+            TilerGeometry::Polys(vec![lines(&g.coordinates, g.m_values.as_ref())]),
+            g.bbox.map(Into::into),
+        ),
+        Geometry::MultiPolygon(g) => (
+            TilerGeometry::Polys(
+                g.coordinates
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| lines(p, g.m_values.as_ref().and_then(|m| m.get(i))))
+                    .collect(),
+            ),
+            g.bbox.map(Into::into),
+        ),
+        Geometry::Point3D(g) => {
+            (TilerGeometry::Points(vec![point_3d(&g.coordinates, g.m_values.clone())]), g.bbox)
+        }
+        Geometry::MultiPoint3D(g) => {
+            (TilerGeometry::Points(line_3d(&g.coordinates, g.m_values.as_ref())), g.bbox)
+        }
+        Geometry::LineString3D(g) => {
+            (TilerGeometry::Lines(vec![line_3d(&g.coordinates, g.m_values.as_ref())]), g.bbox)
+        }
+        Geometry::MultiLineString3D(g) => {
+            (TilerGeometry::Lines(lines_3d(&g.coordinates, g.m_values.as_ref())), g.bbox)
+        }
+        Geometry::Polygon3D(g) => {
+            (TilerGeometry::Polys(vec![lines_3d(&g.coordinates, g.m_values.as_ref())]), g.bbox)
+        }
+        Geometry::MultiPolygon3D(g) => (
+            TilerGeometry::Polys(
+                g.coordinates
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| lines_3d(p, g.m_values.as_ref().and_then(|m| m.get(i))))
+                    .collect(),
+            ),
+            g.bbox,
+        ),
+    }
+}
+
+/// Convert a vector geometry to the tiler's geometry
+fn vector_geometry(geometry: &VectorGeometry) -> (TilerGeometry, Option<BBox3D>) {
+    match geometry {
+        VectorGeometry::Point(g) => (TilerGeometry::Points(vec![g.coordinates.clone()]), g.bbox),
+        VectorGeometry::MultiPoint(g) => (TilerGeometry::Points(g.coordinates.clone()), g.bbox),
+        VectorGeometry::LineString(g) => {
+            (TilerGeometry::Lines(vec![g.coordinates.clone()]), g.bbox)
+        }
+        VectorGeometry::MultiLineString(g) => (TilerGeometry::Lines(g.coordinates.clone()), g.bbox),
+        VectorGeometry::Polygon(g) => (TilerGeometry::Polys(vec![g.coordinates.clone()]), g.bbox),
+        VectorGeometry::MultiPolygon(g) => (TilerGeometry::Polys(g.coordinates.clone()), g.bbox),
+    }
+}
+
+fn point(p: &GeoPoint, m: Option<MValue>) -> VectorPoint {
+    VectorPoint::new_xy(p.0, p.1, m)
+}
+
+fn point_3d(p: &GeoPoint3D, m: Option<MValue>) -> VectorPoint {
+    VectorPoint::new_xyz(p.0, p.1, p.2, m)
+}
+
+fn line(points: &[GeoPoint], m: Option<&Vec<MValue>>) -> Vec<VectorPoint> {
+    points.iter().enumerate().map(|(i, p)| point(p, m.and_then(|m| m.get(i)).cloned())).collect()
+}
+
+fn line_3d(points: &[GeoPoint3D], m: Option<&Vec<MValue>>) -> Vec<VectorPoint> {
+    points.iter().enumerate().map(|(i, p)| point_3d(p, m.and_then(|m| m.get(i)).cloned())).collect()
+}
+
+fn lines(lines: &[Vec<GeoPoint>], m: Option<&Vec<Vec<MValue>>>) -> Vec<Vec<VectorPoint>> {
+    lines.iter().enumerate().map(|(i, l)| line(l, m.and_then(|m| m.get(i)))).collect()
+}
+
+fn lines_3d(lines: &[Vec<GeoPoint3D>], m: Option<&Vec<Vec<MValue>>>) -> Vec<Vec<VectorPoint>> {
+    lines.iter().enumerate().map(|(i, l)| line_3d(l, m.and_then(|m| m.get(i)))).collect()
+}
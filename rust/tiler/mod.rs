@@ -0,0 +1,10 @@
+/// Clip points, lines and polygon rings to a tile and its buffer
+pub mod clip;
+/// Store features and generate tiles from them
+pub mod generator;
+/// Douglas-Peucker simplification
+pub mod simplify;
+
+pub use clip::*;
+pub use generator::*;
+pub use simplify::*;
@@ -0,0 +1,39 @@
+use crate::query::sq_seg_dist;
+use alloc::{vec, vec::Vec};
+use s2json::VectorPoint;
+
+/// Simplify a line (or closed ring) with the Douglas-Peucker algorithm. Points closer than
+/// `tolerance` to the simplified line are removed, the first and last points are always kept.
+pub fn simplify(line: &[VectorPoint], tolerance: f64) -> Vec<VectorPoint> {
+    if line.len() <= 2 || tolerance <= 0.0 {
+        return line.to_vec();
+    }
+    let sq_tolerance = tolerance * tolerance;
+    let mut keep = vec![false; line.len()];
+    keep[0] = true;
+    keep[line.len() - 1] = true;
+
+    let mut stack = vec![(0, line.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let mut max_sq_dist = 0.0;
+        let mut index = first;
+        for (i, p) in line.iter().enumerate().take(last).skip(first + 1) {
+            let sq_dist = sq_seg_dist(
+                (p.x, p.y),
+                (line[first].x, line[first].y),
+                (line[last].x, line[last].y),
+            );
+            if sq_dist > max_sq_dist {
+                index = i;
+                max_sq_dist = sq_dist;
+            }
+        }
+        if max_sq_dist > sq_tolerance {
+            keep[index] = true;
+            stack.push((first, index));
+            stack.push((index, last));
+        }
+    }
+
+    line.iter().zip(keep).filter(|(_, keep)| *keep).map(|(p, _)| p.clone()).collect()
+}
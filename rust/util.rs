@@ -205,8 +205,8 @@ pub fn unweave_and_delta_decode_array(array: &[u64]) -> Vec<Point> {
 
     for &encoded_num in array {
         let (a, b) = unweave_2d(encoded_num as u32);
-        let x = zagzig(a as u32) + prev_x;
-        let y = zagzig(b as u32) + prev_y;
+        let x = zagzig(a as u32).wrapping_add(prev_x);
+        let y = zagzig(b as u32).wrapping_add(prev_y);
         res.push(Point::new(x, y));
         prev_x = x;
         prev_y = y;
@@ -244,9 +244,9 @@ pub fn unweave_and_delta_decode_3d_array(array: &[u64]) -> Vec<Point3D> {
 
     for &encoded_num in array {
         let (a, b, c) = unweave_3d(encoded_num);
-        let x = zagzig(a) + offset_x;
-        let y = zagzig(b) + offset_y;
-        let z = zagzig(c) + offset_z;
+        let x = zagzig(a).wrapping_add(offset_x);
+        let y = zagzig(b).wrapping_add(offset_y);
+        let z = zagzig(c).wrapping_add(offset_z);
         res.push(Point3D::new(x, y, z));
         offset_x = x;
         offset_y = y;
@@ -277,7 +277,7 @@ pub fn delta_decode_array(array: &[u32]) -> Vec<u32> {
     let mut offset = 0;
 
     for &encoded_num in array {
-        let num = zagzig(encoded_num) + offset;
+        let num = zagzig(encoded_num).wrapping_add(offset);
         res.push(num as u32);
         offset = num;
     }
@@ -305,7 +305,8 @@ pub fn delta_decode_sorted_array(array: &[u32]) -> Vec<i32> {
     let mut offset = 0;
 
     for &encoded_num in array {
-        let num = encoded_num as i32 + offset; // Casting to i32; since encoded as non-negative delta
+        // Casting to i32; since encoded as non-negative delta
+        let num = (encoded_num as i32).wrapping_add(offset);
         res.push(num);
         offset = num;
     }
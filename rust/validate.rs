@@ -0,0 +1,367 @@
+use crate::{
+    DecodeError, VectorFeatureMethods, VectorGeometry, VectorLayer, VectorLayerFeatures,
+    VectorLayerMethods, VectorTile, open::FeatureType,
+};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+use pbf::Protobuf;
+
+/// The extents an Open Vector layer may use
+pub const EXTENTS: [usize; 6] = [512, 1_024, 2_048, 4_096, 8_192, 16_384];
+
+/// How serious a [`Violation`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// A SHOULD of the specification is not followed. The tile is still readable
+    Warning,
+    /// A MUST of the specification is broken
+    Error,
+}
+
+/// Where in a tile a [`Violation`] was found
+#[derive(Debug, Clone, PartialEq)]
+pub enum Location {
+    /// The tile itself
+    Tile,
+    /// A vector layer, by name
+    Layer(String),
+    /// A feature, by the name of its layer and its index in the layer
+    Feature {
+        /// the name of the layer
+        layer: String,
+        /// the index of the feature in the layer
+        index: usize,
+        /// the id of the feature if it has one
+        id: Option<u64>,
+    },
+    /// A grid layer, by name
+    Grid(String),
+    /// An image layer, by name
+    Image(String),
+}
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Tile => write!(f, "tile"),
+            Location::Layer(name) => write!(f, "layer \"{}\"", name),
+            Location::Feature { layer, index, id: Some(id) } => {
+                write!(f, "layer \"{}\" feature {} (id {})", layer, index, id)
+            }
+            Location::Feature { layer, index, id: None } => {
+                write!(f, "layer \"{}\" feature {}", layer, index)
+            }
+            Location::Grid(name) => write!(f, "grid \"{}\"", name),
+            Location::Image(name) => write!(f, "image \"{}\"", name),
+        }
+    }
+}
+
+/// The rule of the specification that was broken
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    /// The tile could not be decoded. This includes column cache references that are out of range
+    Decode(DecodeError),
+    /// The tile has Open Vector layers but no column cache, so they can not be read
+    MissingColumnCache,
+    /// More than one layer uses the same name, so only the last one is readable
+    DuplicateLayerName,
+    /// The layer, grid or image has no name
+    MissingName,
+    /// The layer's extent is not one of [`EXTENTS`]
+    InvalidExtent(usize),
+    /// The layer's version is not one this crate knows how to read
+    UnknownVersion(u16),
+    /// The layer has no features
+    EmptyLayer,
+    /// The feature's geometry does not match its type
+    GeometryMismatch(FeatureType),
+    /// The feature has no geometry
+    EmptyGeometry,
+    /// A line has fewer than 2 points
+    ShortLine(usize),
+    /// A polygon ring has fewer than 4 points
+    ShortRing(usize),
+    /// A polygon ring does not end on its first point
+    UnclosedRing,
+    /// The polygon's triangle indices are not a multiple of 3 or refer past its vertices
+    InvalidIndices {
+        /// the number of indices
+        len: usize,
+        /// the largest index
+        max: u32,
+        /// the number of vertices including tessellation
+        vertices: usize,
+    },
+    /// The image width or height is not a power of 2
+    ImageSize {
+        /// the image width
+        width: u32,
+        /// the image height
+        height: u32,
+    },
+    /// The image type is not one the specification lists
+    UnknownImageType,
+    /// The image or grid has no data
+    EmptyData,
+    /// The grid's min is greater than its max
+    GridRange {
+        /// the grid min
+        min: f64,
+        /// the grid max
+        max: f64,
+    },
+    /// The grid does not hold `size * size` values
+    GridSize {
+        /// the grid size
+        size: f64,
+        /// the number of values
+        len: usize,
+    },
+}
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Decode(err) => write!(f, "{}", err),
+            Rule::MissingColumnCache => write!(f, "open layers without a column cache"),
+            Rule::DuplicateLayerName => write!(f, "more than one layer shares a name"),
+            Rule::MissingName => write!(f, "missing name"),
+            Rule::InvalidExtent(extent) => write!(f, "invalid extent {}", extent),
+            Rule::UnknownVersion(version) => write!(f, "unknown version {}", version),
+            Rule::EmptyLayer => write!(f, "no features"),
+            Rule::GeometryMismatch(r#type) => {
+                write!(f, "geometry does not match type {:?}", r#type)
+            }
+            Rule::EmptyGeometry => write!(f, "empty geometry"),
+            Rule::ShortLine(len) => write!(f, "line with {} points", len),
+            Rule::ShortRing(len) => write!(f, "polygon ring with {} points", len),
+            Rule::UnclosedRing => write!(f, "polygon ring is not closed"),
+            Rule::InvalidIndices { len, max, vertices } => {
+                write!(f, "{} indices up to {} for {} vertices", len, max, vertices)
+            }
+            Rule::ImageSize { width, height } => {
+                write!(f, "image size {}x{} is not a power of 2", width, height)
+            }
+            Rule::UnknownImageType => write!(f, "unknown image type"),
+            Rule::EmptyData => write!(f, "no data"),
+            Rule::GridRange { min, max } => {
+                write!(f, "grid min {} is greater than max {}", min, max)
+            }
+            Rule::GridSize { size, len } => write!(f, "{} values for a grid of size {}", len, size),
+        }
+    }
+}
+
+/// A rule of the specification broken by a tile
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// how serious it is
+    pub severity: Severity,
+    /// where it was found
+    pub location: Location,
+    /// the rule that was broken
+    pub rule: Rule,
+}
+impl Violation {
+    fn error(location: Location, rule: Rule) -> Self {
+        Violation { severity: Severity::Error, location, rule }
+    }
+
+    fn warning(location: Location, rule: Rule) -> Self {
+        Violation { severity: Severity::Warning, location, rule }
+    }
+}
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}: {}", severity, self.location, self.rule)
+    }
+}
+
+/// Validate encoded tile bytes. Besides the checks of [`validate_tile`], this finds what is lost
+/// when the tile is decoded: bytes that can not be read, open layers without a column cache and
+/// layers that share a name.
+pub fn validate_bytes(data: Vec<u8>) -> Vec<Violation> {
+    let (mut layers, mut columns, mut open_layers) = (0, false, false);
+    let mut pb = Protobuf::from_input(data.clone());
+    let mut tile = match VectorTile::try_new(data, None) {
+        Ok(tile) => tile,
+        Err(err) => return vec![Violation::error(Location::Tile, Rule::Decode(err))],
+    };
+    // the tile was checked when it was read, so its fields can be walked safely
+    while pb.get_pos() < pb.len() {
+        let field = pb.read_field();
+        match field.tag {
+            1 | 3 => layers += 1,
+            4 => (layers, open_layers) = (layers + 1, true),
+            5 => columns = true,
+            _ => {}
+        }
+        pb.skip(field.r#type);
+    }
+
+    let mut res = vec![];
+    if open_layers && !columns {
+        res.push(Violation::error(Location::Tile, Rule::MissingColumnCache));
+    } else if layers > tile.layers.len() {
+        res.push(Violation::error(Location::Tile, Rule::DuplicateLayerName));
+    }
+    res.extend(validate_tile(&mut tile));
+    res
+}
+
+/// Validate a decoded tile against the Open Vector Tile specification, returning every rule that
+/// is broken. Mapbox layers are held to the Mapbox Vector Tile rules, so their extent may be any
+/// non-zero value.
+pub fn validate_tile(tile: &mut VectorTile) -> Vec<Violation> {
+    let mut res = vec![];
+    for (name, layer) in tile.layers.iter_mut() {
+        validate_layer(name, layer, &mut res);
+    }
+    for (name, grid) in tile.grids.iter_mut() {
+        let location = || Location::Grid(name.clone());
+        if name.is_empty() {
+            res.push(Violation::error(location(), Rule::MissingName));
+        }
+        let (size, min, max) = (grid.size, grid.min, grid.max);
+        if min > max {
+            res.push(Violation::error(location(), Rule::GridRange { min, max }));
+        }
+        let len = grid.data().len();
+        if len == 0 {
+            res.push(Violation::error(location(), Rule::EmptyData));
+        } else if size * size != len as f64 {
+            res.push(Violation::error(location(), Rule::GridSize { size, len }));
+        }
+    }
+    for (name, image) in tile.images.iter_mut() {
+        let location = || Location::Image(name.clone());
+        if name.is_empty() {
+            res.push(Violation::error(location(), Rule::MissingName));
+        }
+        let (width, height) = (image.width, image.height);
+        if !width.is_power_of_two() || !height.is_power_of_two() {
+            res.push(Violation::error(location(), Rule::ImageSize { width, height }));
+        }
+        if image.image_type == crate::open::ImageType::UNKNOWN {
+            res.push(Violation::warning(location(), Rule::UnknownImageType));
+        }
+        if image.image().is_empty() {
+            res.push(Violation::error(location(), Rule::EmptyData));
+        }
+    }
+    res
+}
+
+fn validate_layer(name: &str, layer: &mut VectorLayer, res: &mut Vec<Violation>) {
+    let location = || Location::Layer(name.into());
+    if name.is_empty() {
+        res.push(Violation::error(location(), Rule::MissingName));
+    }
+    let extent = layer.extent();
+    match layer {
+        VectorLayer::Open(open) => {
+            if !EXTENTS.contains(&extent) {
+                res.push(Violation::error(location(), Rule::InvalidExtent(extent)));
+            }
+            if open.version != 1 {
+                res.push(Violation::warning(location(), Rule::UnknownVersion(open.version)));
+            }
+        }
+        VectorLayer::Mapbox(_) if extent == 0 => {
+            res.push(Violation::error(location(), Rule::InvalidExtent(extent)));
+        }
+        VectorLayer::Mapbox(_) => {}
+    }
+    if layer.is_empty() {
+        res.push(Violation::warning(location(), Rule::EmptyLayer));
+    }
+
+    for (index, mut feature) in layer.features().enumerate() {
+        let location = Location::Feature { layer: name.to_string(), index, id: feature.id() };
+        for rule in validate_feature(&mut feature) {
+            res.push(Violation::error(location.clone(), rule));
+        }
+    }
+}
+
+/// The x, y and z of each point of each line (or ring) of a geometry
+type Lines = Vec<Vec<(i32, i32, i32)>>;
+
+/// The rules broken by a feature's geometry, all of which are errors
+fn validate_feature<F: VectorFeatureMethods + ?Sized>(feature: &mut F) -> Vec<Rule> {
+    let r#type = feature.get_type();
+    let (lines, polys): (Lines, bool) = match (r#type, feature.load_geometry()) {
+        (FeatureType::Points, VectorGeometry::VectorPoints(points)) => {
+            (vec![points.iter().map(|p| (p.x, p.y, 0)).collect()], false)
+        }
+        (FeatureType::Points3D, VectorGeometry::VectorPoints3D(points)) => {
+            (vec![points.iter().map(|p| (p.x, p.y, p.z)).collect()], false)
+        }
+        (FeatureType::Lines, VectorGeometry::VectorLines(lines)) => (
+            lines.iter().map(|l| l.geometry.iter().map(|p| (p.x, p.y, 0)).collect()).collect(),
+            false,
+        ),
+        (FeatureType::Lines3D, VectorGeometry::VectorLines3D(lines)) => (
+            lines.iter().map(|l| l.geometry.iter().map(|p| (p.x, p.y, p.z)).collect()).collect(),
+            false,
+        ),
+        (FeatureType::Polygons, VectorGeometry::VectorPolys(polys)) => (
+            polys
+                .iter()
+                .flatten()
+                .map(|l| l.geometry.iter().map(|p| (p.x, p.y, 0)).collect())
+                .collect(),
+            true,
+        ),
+        (FeatureType::Polygons3D, VectorGeometry::VectorPolys3D(polys)) => (
+            polys
+                .iter()
+                .flatten()
+                .map(|l| l.geometry.iter().map(|p| (p.x, p.y, p.z)).collect())
+                .collect(),
+            true,
+        ),
+        _ => return vec![Rule::GeometryMismatch(r#type)],
+    };
+
+    let mut res = vec![];
+    if lines.iter().all(|l| l.is_empty()) {
+        res.push(Rule::EmptyGeometry);
+        return res;
+    }
+    let is_points = matches!(r#type, FeatureType::Points | FeatureType::Points3D);
+    for line in lines.iter().filter(|_| !is_points) {
+        if polys && line.len() < 4 {
+            res.push(Rule::ShortRing(line.len()));
+        } else if polys && line.first() != line.last() {
+            res.push(Rule::UnclosedRing);
+        } else if line.len() < 2 {
+            res.push(Rule::ShortLine(line.len()));
+        }
+    }
+
+    let indices = if polys { feature.read_indices() } else { vec![] };
+    if !indices.is_empty() {
+        let mut tessellation = vec![];
+        let vertices = lines.iter().map(Vec::len).sum::<usize>()
+            + if r#type == FeatureType::Polygons3D {
+                feature.add_tessellation_3d(&mut tessellation, 1.0);
+                tessellation.len() / 3
+            } else {
+                feature.add_tessellation(&mut tessellation, 1.0);
+                tessellation.len() / 2
+            };
+        let max = indices.iter().copied().max().unwrap_or(0);
+        if indices.len() % 3 != 0 || max as usize >= vertices {
+            res.push(Rule::InvalidIndices { len: indices.len(), max, vertices });
+        }
+    }
+    res
+}
@@ -1,8 +1,10 @@
 use crate::{
-    FeatureType, OpenVectorFeature, VectorGeometry, VectorLines3DWithOffset, VectorLinesWithOffset,
-    VectorPoints, VectorPoints3D, mapbox::MapboxVectorFeature,
+    FeatureType, OpenVectorFeature, VectorGeometry, VectorLayerFeatures, VectorLines3DWithOffset,
+    VectorLinesWithOffset, VectorPoints, VectorPoints3D, export::feature_to_s2json,
+    mapbox::MapboxVectorFeature, mlt::MltVectorFeature,
 };
 use alloc::vec::Vec;
+use core::ops::Range;
 use s2json::{BBOX, Properties};
 
 /// Methods that all vector features should have
@@ -55,15 +57,23 @@ pub trait VectorFeatureMethods {
     fn add_tessellation(&mut self, geometry: &mut Vec<f64>, multiplier: f64);
     /// Add 3D tessellation data to the geometry
     fn add_tessellation_3d(&mut self, geometry: &mut Vec<f64>, multiplier: f64);
+    /// Convert to an S2JSON vector feature with coordinates divided by `extent`
+    /// (see [`crate::export::feature_to_s2json`])
+    fn to_s2json(&mut self, extent: usize) -> s2json::VectorFeature {
+        feature_to_s2json(self, extent)
+    }
 }
 
-/// Either a mapbox or open vector feature. Implements the [`VectorFeatureMethods`] trait
+/// Either a mapbox, open or MapLibre Tile vector feature. Implements the
+/// [`VectorFeatureMethods`] trait
 #[derive(Debug)]
 pub enum VectorFeature<'a> {
     /// Mapbox Vector Feature
     Mapbox(&'a mut MapboxVectorFeature),
     /// Open Vector Feature
     Open(&'a mut OpenVectorFeature),
+    /// MapLibre Tile Feature
+    Mlt(&'a mut MltVectorFeature),
 }
 impl<'a> From<&'a mut MapboxVectorFeature> for VectorFeature<'a> {
     fn from(value: &'a mut MapboxVectorFeature) -> Self {
@@ -75,149 +85,401 @@ impl<'a> From<&'a mut OpenVectorFeature> for VectorFeature<'a> {
         VectorFeature::Open(value)
     }
 }
+impl<'a> From<&'a mut MltVectorFeature> for VectorFeature<'a> {
+    fn from(value: &'a mut MltVectorFeature) -> Self {
+        VectorFeature::Mlt(value)
+    }
+}
+/// An iterator over the features of a layer, returned by
+/// [`crate::VectorLayerFeatures::features`]
+#[derive(Debug)]
+pub struct VectorFeatures<'a, L: ?Sized> {
+    layer: &'a L,
+    indices: Range<usize>,
+}
+impl<'a, L: VectorLayerFeatures + ?Sized> VectorFeatures<'a, L> {
+    /// Iterate over every feature of the layer, in order
+    pub fn new(layer: &'a L) -> Self {
+        VectorFeatures { layer, indices: 0..layer.len() }
+    }
+}
+impl<'a, L: VectorLayerFeatures + ?Sized> Iterator for VectorFeatures<'a, L> {
+    type Item = VectorFeatureRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.layer.get_feature(self.indices.next()?)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+impl<L: VectorLayerFeatures + ?Sized> ExactSizeIterator for VectorFeatures<'_, L> {}
 impl VectorFeatureMethods for VectorFeature<'_> {
     fn id(&self) -> Option<u64> {
         match self {
             VectorFeature::Mapbox(feature) => feature.id(),
             VectorFeature::Open(feature) => feature.id(),
+            VectorFeature::Mlt(feature) => feature.id(),
         }
     }
     fn version(&self) -> u16 {
         match self {
             VectorFeature::Mapbox(feature) => feature.version(),
             VectorFeature::Open(feature) => feature.version(),
+            VectorFeature::Mlt(feature) => feature.version(),
         }
     }
     fn properties(&self) -> Properties {
         match self {
             VectorFeature::Mapbox(feature) => feature.properties(),
             VectorFeature::Open(feature) => feature.properties(),
+            VectorFeature::Mlt(feature) => feature.properties(),
         }
     }
     fn extent(&self) -> usize {
         match self {
             VectorFeature::Mapbox(feature) => feature.extent(),
             VectorFeature::Open(feature) => feature.extent(),
+            VectorFeature::Mlt(feature) => feature.extent(),
         }
     }
     fn get_type(&self) -> FeatureType {
         match self {
             VectorFeature::Mapbox(feature) => feature.get_type(),
             VectorFeature::Open(feature) => feature.get_type(),
+            VectorFeature::Mlt(feature) => feature.get_type(),
         }
     }
     fn bbox(&self) -> Option<BBOX> {
         match self {
             VectorFeature::Mapbox(feature) => feature.bbox(),
             VectorFeature::Open(feature) => feature.bbox(),
+            VectorFeature::Mlt(feature) => feature.bbox(),
         }
     }
     fn has_m_values(&self) -> bool {
         match self {
             VectorFeature::Mapbox(feature) => feature.has_m_values(),
             VectorFeature::Open(feature) => feature.has_m_values(),
+            VectorFeature::Mlt(feature) => feature.has_m_values(),
         }
     }
     fn is_points(&self) -> bool {
         match self {
             VectorFeature::Mapbox(feature) => feature.is_points(),
             VectorFeature::Open(feature) => feature.is_points(),
+            VectorFeature::Mlt(feature) => feature.is_points(),
         }
     }
     fn is_lines(&self) -> bool {
         match self {
             VectorFeature::Mapbox(feature) => feature.is_lines(),
             VectorFeature::Open(feature) => feature.is_lines(),
+            VectorFeature::Mlt(feature) => feature.is_lines(),
         }
     }
     fn is_polygons(&self) -> bool {
         match self {
             VectorFeature::Mapbox(feature) => feature.is_polygons(),
             VectorFeature::Open(feature) => feature.is_polygons(),
+            VectorFeature::Mlt(feature) => feature.is_polygons(),
         }
     }
     fn is_points_3d(&self) -> bool {
         match self {
             VectorFeature::Mapbox(feature) => feature.is_points_3d(),
             VectorFeature::Open(feature) => feature.is_points_3d(),
+            VectorFeature::Mlt(feature) => feature.is_points_3d(),
         }
     }
     fn is_lines_3d(&self) -> bool {
         match self {
             VectorFeature::Mapbox(feature) => feature.is_lines_3d(),
             VectorFeature::Open(feature) => feature.is_lines_3d(),
+            VectorFeature::Mlt(feature) => feature.is_lines_3d(),
         }
     }
     fn is_polygons_3d(&self) -> bool {
         match self {
             VectorFeature::Mapbox(feature) => feature.is_polygons_3d(),
             VectorFeature::Open(feature) => feature.is_polygons_3d(),
+            VectorFeature::Mlt(feature) => feature.is_polygons_3d(),
         }
     }
     fn load_points(&mut self) -> VectorPoints {
         match self {
             VectorFeature::Mapbox(feature) => feature.load_points(),
             VectorFeature::Open(feature) => feature.load_points(),
+            VectorFeature::Mlt(feature) => feature.load_points(),
         }
     }
     fn load_points_3d(&mut self) -> VectorPoints3D {
         match self {
             VectorFeature::Mapbox(feature) => feature.load_points_3d(),
             VectorFeature::Open(feature) => feature.load_points_3d(),
+            VectorFeature::Mlt(feature) => feature.load_points_3d(),
         }
     }
     fn load_lines(&mut self) -> VectorLinesWithOffset {
         match self {
             VectorFeature::Mapbox(feature) => feature.load_lines(),
             VectorFeature::Open(feature) => feature.load_lines(),
+            VectorFeature::Mlt(feature) => feature.load_lines(),
         }
     }
     fn load_lines_3d(&mut self) -> VectorLines3DWithOffset {
         match self {
             VectorFeature::Mapbox(feature) => feature.load_lines_3d(),
             VectorFeature::Open(feature) => feature.load_lines_3d(),
+            VectorFeature::Mlt(feature) => feature.load_lines_3d(),
         }
     }
     fn load_polys(&mut self) -> Vec<VectorLinesWithOffset> {
         match self {
             VectorFeature::Mapbox(feature) => feature.load_polys(),
             VectorFeature::Open(feature) => feature.load_polys(),
+            VectorFeature::Mlt(feature) => feature.load_polys(),
         }
     }
     fn load_polys_3d(&mut self) -> Vec<VectorLines3DWithOffset> {
         match self {
             VectorFeature::Mapbox(feature) => feature.load_polys_3d(),
             VectorFeature::Open(feature) => feature.load_polys_3d(),
+            VectorFeature::Mlt(feature) => feature.load_polys_3d(),
         }
     }
     fn load_geometry_flat(&mut self) -> (Vec<f64>, Vec<u32>) {
         match self {
             VectorFeature::Mapbox(feature) => feature.load_geometry_flat(),
             VectorFeature::Open(feature) => feature.load_geometry_flat(),
+            VectorFeature::Mlt(feature) => feature.load_geometry_flat(),
         }
     }
     fn load_geometry(&mut self) -> VectorGeometry {
         match self {
             VectorFeature::Mapbox(feature) => feature.load_geometry(),
             VectorFeature::Open(feature) => feature.load_geometry(),
+            VectorFeature::Mlt(feature) => feature.load_geometry(),
         }
     }
     fn read_indices(&mut self) -> Vec<u32> {
         match self {
             VectorFeature::Mapbox(feature) => feature.read_indices(),
             VectorFeature::Open(feature) => feature.read_indices(),
+            VectorFeature::Mlt(feature) => feature.read_indices(),
         }
     }
     fn add_tessellation(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
         match self {
             VectorFeature::Mapbox(feature) => feature.add_tessellation(geometry, multiplier),
             VectorFeature::Open(feature) => feature.add_tessellation(geometry, multiplier),
+            VectorFeature::Mlt(feature) => feature.add_tessellation(geometry, multiplier),
         }
     }
     fn add_tessellation_3d(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
         match self {
             VectorFeature::Mapbox(feature) => feature.add_tessellation_3d(geometry, multiplier),
             VectorFeature::Open(feature) => feature.add_tessellation_3d(geometry, multiplier),
+            VectorFeature::Mlt(feature) => feature.add_tessellation_3d(geometry, multiplier),
+        }
+    }
+}
+
+/// A mapbox, open or MapLibre Tile vector feature borrowed from its layer, returned by
+/// [`crate::VectorLayerFeatures::get_feature`]. Geometry loaded through it is cached in the layer
+/// like [`crate::VectorLayerMethods::feature`]. Implements the [`VectorFeatureMethods`] trait
+#[derive(Debug, Clone, Copy)]
+pub enum VectorFeatureRef<'a> {
+    /// Mapbox Vector Feature
+    Mapbox(&'a MapboxVectorFeature),
+    /// Open Vector Feature
+    Open(&'a OpenVectorFeature),
+    /// MapLibre Tile Feature
+    Mlt(&'a MltVectorFeature),
+}
+impl<'a> From<&'a MapboxVectorFeature> for VectorFeatureRef<'a> {
+    fn from(value: &'a MapboxVectorFeature) -> Self {
+        VectorFeatureRef::Mapbox(value)
+    }
+}
+impl<'a> From<&'a OpenVectorFeature> for VectorFeatureRef<'a> {
+    fn from(value: &'a OpenVectorFeature) -> Self {
+        VectorFeatureRef::Open(value)
+    }
+}
+impl<'a> From<&'a MltVectorFeature> for VectorFeatureRef<'a> {
+    fn from(value: &'a MltVectorFeature) -> Self {
+        VectorFeatureRef::Mlt(value)
+    }
+}
+impl VectorFeatureMethods for VectorFeatureRef<'_> {
+    fn id(&self) -> Option<u64> {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.id(),
+            VectorFeatureRef::Open(feature) => feature.id(),
+            VectorFeatureRef::Mlt(feature) => feature.id(),
+        }
+    }
+    fn version(&self) -> u16 {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.version(),
+            VectorFeatureRef::Open(feature) => feature.version(),
+            VectorFeatureRef::Mlt(feature) => feature.version(),
+        }
+    }
+    fn properties(&self) -> Properties {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.properties(),
+            VectorFeatureRef::Open(feature) => feature.properties(),
+            VectorFeatureRef::Mlt(feature) => feature.properties(),
+        }
+    }
+    fn extent(&self) -> usize {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.extent(),
+            VectorFeatureRef::Open(feature) => feature.extent(),
+            VectorFeatureRef::Mlt(feature) => feature.extent(),
+        }
+    }
+    fn get_type(&self) -> FeatureType {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.get_type(),
+            VectorFeatureRef::Open(feature) => feature.get_type(),
+            VectorFeatureRef::Mlt(feature) => feature.get_type(),
+        }
+    }
+    fn bbox(&self) -> Option<BBOX> {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.bbox(),
+            VectorFeatureRef::Open(feature) => feature.bbox(),
+            VectorFeatureRef::Mlt(feature) => feature.bbox(),
+        }
+    }
+    fn has_m_values(&self) -> bool {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.has_m_values(),
+            VectorFeatureRef::Open(feature) => feature.has_m_values(),
+            VectorFeatureRef::Mlt(feature) => feature.has_m_values(),
+        }
+    }
+    fn is_points(&self) -> bool {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.is_points(),
+            VectorFeatureRef::Open(feature) => feature.is_points(),
+            VectorFeatureRef::Mlt(feature) => feature.is_points(),
+        }
+    }
+    fn is_lines(&self) -> bool {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.is_lines(),
+            VectorFeatureRef::Open(feature) => feature.is_lines(),
+            VectorFeatureRef::Mlt(feature) => feature.is_lines(),
+        }
+    }
+    fn is_polygons(&self) -> bool {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.is_polygons(),
+            VectorFeatureRef::Open(feature) => feature.is_polygons(),
+            VectorFeatureRef::Mlt(feature) => feature.is_polygons(),
+        }
+    }
+    fn is_points_3d(&self) -> bool {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.is_points_3d(),
+            VectorFeatureRef::Open(feature) => feature.is_points_3d(),
+            VectorFeatureRef::Mlt(feature) => feature.is_points_3d(),
+        }
+    }
+    fn is_lines_3d(&self) -> bool {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.is_lines_3d(),
+            VectorFeatureRef::Open(feature) => feature.is_lines_3d(),
+            VectorFeatureRef::Mlt(feature) => feature.is_lines_3d(),
+        }
+    }
+    fn is_polygons_3d(&self) -> bool {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.is_polygons_3d(),
+            VectorFeatureRef::Open(feature) => feature.is_polygons_3d(),
+            VectorFeatureRef::Mlt(feature) => feature.is_polygons_3d(),
+        }
+    }
+    fn load_points(&mut self) -> VectorPoints {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.load_points(),
+            VectorFeatureRef::Open(feature) => feature.load_points(),
+            VectorFeatureRef::Mlt(feature) => feature.load_points(),
+        }
+    }
+    fn load_points_3d(&mut self) -> VectorPoints3D {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.load_points_3d(),
+            VectorFeatureRef::Open(feature) => feature.load_points_3d(),
+            VectorFeatureRef::Mlt(feature) => feature.load_points_3d(),
+        }
+    }
+    fn load_lines(&mut self) -> VectorLinesWithOffset {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.load_lines(),
+            VectorFeatureRef::Open(feature) => feature.load_lines(),
+            VectorFeatureRef::Mlt(feature) => feature.load_lines(),
+        }
+    }
+    fn load_lines_3d(&mut self) -> VectorLines3DWithOffset {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.load_lines_3d(),
+            VectorFeatureRef::Open(feature) => feature.load_lines_3d(),
+            VectorFeatureRef::Mlt(feature) => feature.load_lines_3d(),
+        }
+    }
+    fn load_polys(&mut self) -> Vec<VectorLinesWithOffset> {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.load_polys(),
+            VectorFeatureRef::Open(feature) => feature.load_polys(),
+            VectorFeatureRef::Mlt(feature) => feature.load_polys(),
+        }
+    }
+    fn load_polys_3d(&mut self) -> Vec<VectorLines3DWithOffset> {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.load_polys_3d(),
+            VectorFeatureRef::Open(feature) => feature.load_polys_3d(),
+            VectorFeatureRef::Mlt(feature) => feature.load_polys_3d(),
+        }
+    }
+    fn load_geometry_flat(&mut self) -> (Vec<f64>, Vec<u32>) {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.load_geometry_flat(),
+            VectorFeatureRef::Open(feature) => feature.load_geometry_flat(),
+            VectorFeatureRef::Mlt(feature) => feature.load_geometry_flat(),
+        }
+    }
+    fn load_geometry(&mut self) -> VectorGeometry {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.load_geometry(),
+            VectorFeatureRef::Open(feature) => feature.load_geometry(),
+            VectorFeatureRef::Mlt(feature) => feature.load_geometry(),
+        }
+    }
+    fn read_indices(&mut self) -> Vec<u32> {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.read_indices(),
+            VectorFeatureRef::Open(feature) => feature.read_indices(),
+            VectorFeatureRef::Mlt(feature) => feature.read_indices(),
+        }
+    }
+    fn add_tessellation(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.add_tessellation(geometry, multiplier),
+            VectorFeatureRef::Open(feature) => feature.add_tessellation(geometry, multiplier),
+            VectorFeatureRef::Mlt(feature) => feature.add_tessellation(geometry, multiplier),
+        }
+    }
+    fn add_tessellation_3d(&mut self, geometry: &mut Vec<f64>, multiplier: f64) {
+        match self {
+            VectorFeatureRef::Mapbox(feature) => feature.add_tessellation_3d(geometry, multiplier),
+            VectorFeatureRef::Open(feature) => feature.add_tessellation_3d(geometry, multiplier),
+            VectorFeatureRef::Mlt(feature) => feature.add_tessellation_3d(geometry, multiplier),
         }
     }
 }
@@ -1,14 +1,20 @@
 use crate::{
-    VectorFeature,
+    Point, VectorFeature, VectorFeatureMethods, VectorFeatureRef, VectorFeatures,
     base::BaseVectorTile,
+    error::{DecodeError, EncodeError, ProtoMessage},
+    export::{ExportMetadata, feature_to_s2json, s2json_to_geojson},
     mapbox::MapboxVectorLayer,
     open::{
-        ColumnCacheReader, ColumnCacheWriter, GridData, ImageData, OpenVectorLayer, write_layer,
+        ColumnCacheReader, ColumnCacheWriter, GridData, GridLayer, ImageData, ImageLayer,
+        OpenVectorLayer, try_write_layer, write_layer_name,
     },
+    query::{intersects_bbox, intersects_point},
+    reader::{BYTES, Reader, Result},
+    shared::{Shared, shared},
 };
-use alloc::{collections::BTreeMap, rc::Rc, string::String, vec::Vec};
-use core::cell::RefCell;
-use pbf::{ProtoRead, Protobuf};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use pbf::Protobuf;
+use s2json::{FeatureCollection, Features};
 
 /// Methods that all vector layers should have
 pub trait VectorLayerMethods {
@@ -19,12 +25,50 @@ pub trait VectorLayerMethods {
     /// the extent of the vector tile (only **512**, **1_024**, **2_048**, **4_096**, and **8_192**
     /// are supported for the open spec)
     fn extent(&self) -> usize;
-    /// grab a feature from the layer
+    /// grab a feature from the layer. The feature is cached so its geometry is only decoded once
     fn feature(&mut self, i: usize) -> Option<VectorFeature<'_>>;
     /// length (layer count)
     fn len(&self) -> usize;
     /// empty (layer count is 0)
     fn is_empty(&self) -> bool;
+    /// indices of the features within `tolerance` of the point `x`/`y`, in extent units.
+    /// Polygons also match if they contain the point. Useful for hit testing a click.
+    ///
+    /// By default every feature's geometry is loaded and tested. Feature bboxes are not used to
+    /// skip features: they are in lon/lat and a layer does not know which tile it is in, so they
+    /// can not be projected to extent units. Open layers narrow the search with their
+    /// [`crate::spatial_index::SpatialIndex`] instead
+    fn query_point(&mut self, x: f64, y: f64, tolerance: f64) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&i| {
+                self.feature(i)
+                    .is_some_and(|mut f| intersects_point(&f.load_geometry(), x, y, tolerance))
+            })
+            .collect()
+    }
+    /// indices of the features that intersect the box `min`->`max`, in extent units. Like
+    /// [`VectorLayerMethods::query_point`], every feature is tested by default
+    fn query_bbox(&mut self, min: Point, max: Point) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&i| {
+                self.feature(i).is_some_and(|mut f| intersects_bbox(&f.load_geometry(), &min, &max))
+            })
+            .collect()
+    }
+}
+
+/// Reading many or shared features from a layer. Kept apart from [`VectorLayerMethods`] so
+/// layers implemented outside of this crate do not have to provide them
+pub trait VectorLayerFeatures: VectorLayerMethods {
+    /// borrow a feature from the layer. Only needs `&self`, so with the `sync` feature a decoded
+    /// tile can be shared across threads. The feature and its geometry are decoded on first use
+    /// and cached in the layer, shared with [`VectorLayerMethods::feature`]
+    fn get_feature(&self, i: usize) -> Option<VectorFeatureRef<'_>>;
+    /// iterate over every feature in the layer, in order, borrowing each like
+    /// [`VectorLayerFeatures::get_feature`]
+    fn features(&self) -> VectorFeatures<'_, Self> {
+        VectorFeatures::new(self)
+    }
 }
 
 /// Layer container supporting both mapbox and open vector layers
@@ -77,6 +121,28 @@ impl VectorLayerMethods for VectorLayer {
             VectorLayer::Open(layer) => layer.is_empty(),
         }
     }
+
+    fn query_point(&mut self, x: f64, y: f64, tolerance: f64) -> Vec<usize> {
+        match self {
+            VectorLayer::Mapbox(layer) => layer.query_point(x, y, tolerance),
+            VectorLayer::Open(layer) => layer.query_point(x, y, tolerance),
+        }
+    }
+
+    fn query_bbox(&mut self, min: Point, max: Point) -> Vec<usize> {
+        match self {
+            VectorLayer::Mapbox(layer) => layer.query_bbox(min, max),
+            VectorLayer::Open(layer) => layer.query_bbox(min, max),
+        }
+    }
+}
+impl VectorLayerFeatures for VectorLayer {
+    fn get_feature(&self, i: usize) -> Option<VectorFeatureRef<'_>> {
+        match self {
+            VectorLayer::Mapbox(layer) => layer.get_feature(i),
+            VectorLayer::Open(layer) => layer.get_feature(i),
+        }
+    }
 }
 
 /// # Open Vector Tile
@@ -116,15 +182,15 @@ impl VectorLayerMethods for VectorLayer {
 ///
 /// // IMAGE API
 ///
-/// let satellite = tile.images.get("satellite").unwrap();
+/// let satellite = tile.images.get_mut("satellite").unwrap();
 /// // grab the image data
-/// let data = &satellite.image;
+/// let data = satellite.image();
 ///
 /// // GRID API
 ///
-/// let elevation = tile.grids.get("elevation").unwrap();
+/// let elevation = tile.grids.get_mut("elevation").unwrap();
 /// // grab the grid data
-/// let data = &elevation.data;
+/// let data = elevation.data();
 /// ```
 #[derive(Debug)]
 pub struct VectorTile {
@@ -133,104 +199,230 @@ pub struct VectorTile {
     /// indexes to track the layers. Needed for the open spec because we need the cache before we can
     /// parse layers and features
     layer_indexes: Vec<usize>,
-    /// the protobuf for the vector tile
-    pbf: Rc<RefCell<Protobuf>>,
+    /// the tile data the layers read their features from
+    data: Shared<[u8]>,
     /// the column cache
-    columns: Option<Rc<RefCell<ColumnCacheReader>>>,
-    /// Gridded data
-    pub grids: BTreeMap<String, GridData>,
-    /// Image data
-    pub images: BTreeMap<String, ImageData>,
+    columns: Option<Shared<ColumnCacheReader>>,
+    /// Gridded data, decoded on first access
+    pub grids: BTreeMap<String, GridLayer>,
+    /// Image data, read on first access
+    pub images: BTreeMap<String, ImageLayer>,
 }
 impl VectorTile {
     /// Create a new vector tile
+    ///
+    /// # Panics
+    /// If the data is not a valid tile. Use [`VectorTile::try_new`] for untrusted input.
     pub fn new(data: Vec<u8>, end: Option<usize>) -> Self {
-        let pbf = Rc::new(RefCell::new(data.into()));
+        Self::try_new(data, end).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create a new vector tile, returning an error if the data is truncated or malformed.
+    ///
+    /// The whole tile (including the lazily read feature geometry) is checked up front, so a tile
+    /// returned here will not panic while it is being read.
+    pub fn try_new(data: Vec<u8>, end: Option<usize>) -> core::result::Result<Self, DecodeError> {
+        let data: Shared<[u8]> = data.into();
+        let tile = read_tile(&data, end, true)?;
+
         let mut vt = VectorTile {
-            pbf: pbf.clone(),
+            data: data.clone(),
             columns: None,
-            layer_indexes: Vec::new(),
+            layer_indexes: tile.open,
             layers: BTreeMap::new(),
             grids: BTreeMap::new(),
             images: BTreeMap::new(),
         };
-
-        pbf.borrow_mut().read_fields(&mut vt, end);
-
-        if !vt.layer_indexes.is_empty() {
-            vt.read_layers();
+        for (layer, is_s2) in tile.mapbox {
+            let layer = MapboxVectorLayer::read(&data, layer, is_s2)?;
+            vt.layers.insert(layer.name.clone(), VectorLayer::Mapbox(layer));
         }
+        if let Some(columns) = tile.columns {
+            // geometry columns are only decoded once a feature asks for them
+            vt.columns = Some(shared(ColumnCacheReader::read(&data, columns)?));
+        }
+        for grid in tile.grids {
+            let grid = GridLayer::read(&data, grid)?;
+            vt.grids.insert(grid.name.clone(), grid);
+        }
+        for image in tile.images {
+            let image = ImageLayer::read(&data, image)?;
+            vt.images.insert(image.name.clone(), image);
+        }
+        vt.try_read_layers()?;
 
-        vt
+        Ok(vt)
     }
 
     /// Read the layers
     pub fn read_layers(&mut self) -> Option<()> {
-        let layer_indexes = self.layer_indexes.clone();
-        let mut tmp_pbf = self.pbf.borrow_mut();
-        let cache = self.columns.as_ref()?.clone();
-
-        for pos in layer_indexes {
-            tmp_pbf.set_pos(pos);
-            let mut layer = OpenVectorLayer::new(cache.clone());
-            tmp_pbf.read_message(&mut layer);
+        self.columns.as_ref()?;
+        self.try_read_layers().ok()
+    }
+
+    /// Read the open layers once the column cache is known. Without one they are never decoded
+    fn try_read_layers(&mut self) -> Result<()> {
+        let Some(cache) = self.columns.clone() else { return Ok(()) };
+        let data = self.data.clone();
+        for &pos in &self.layer_indexes {
+            let layer = Reader::at(&data, pos, 4, ProtoMessage::OpenLayer)?;
+            let layer = OpenVectorLayer::read(layer, cache.clone())?;
             self.layers.insert(layer.name.clone(), VectorLayer::Open(layer));
         }
-
-        Some(())
+        Ok(())
     }
 
     /// Get a layer given the name
     pub fn layer(&mut self, name: &str) -> Option<&mut VectorLayer> {
         self.layers.get_mut(name)
     }
+
+    /// Iterate over the layers, sorted by name
+    pub fn iter_layers(&mut self) -> impl Iterator<Item = &mut VectorLayer> {
+        self.layers.values_mut()
+    }
+
+    /// Iterate over every feature in the tile as `(layer_name, feature)` pairs. Layers are visited
+    /// in name order and features in the order they are stored in the layer
+    pub fn iter_features(&self) -> impl Iterator<Item = (&str, VectorFeatureRef<'_>)> {
+        self.layers
+            .iter()
+            .flat_map(|(name, layer)| layer.features().map(move |feature| (name.as_str(), feature)))
+    }
+
+    /// Convert every feature in the tile to GeoJSON, projecting the coordinates to lon/lat given
+    /// the tile's Web Mercator `zoom`/`x`/`y`. Each feature's metadata records its layer name
+    pub fn to_feature_collection(
+        &self,
+        zoom: u8,
+        x: u32,
+        y: u32,
+    ) -> FeatureCollection<ExportMetadata> {
+        let mut collection = FeatureCollection::new(None);
+        for (name, layer) in self.layers.iter() {
+            let extent = layer.extent();
+            for mut feature in layer.features() {
+                let mut vector_feature = feature_to_s2json(&mut feature, extent);
+                vector_feature.metadata = Some(ExportMetadata { layer: name.clone() });
+                collection.features.push(Features::Feature(s2json_to_geojson(
+                    vector_feature,
+                    zoom,
+                    x,
+                    y,
+                )));
+            }
+        }
+        collection
+    }
+}
+/// The messages of a tile, read by [`read_tile`]. Open layers are stored by their position as
+/// they can only be read once the column cache is known
+#[derive(Debug, Default)]
+pub(crate) struct TileMessages<'a> {
+    /// Mapbox layers and whether they use the S2 spec
+    pub mapbox: Vec<(Reader<'a>, bool)>,
+    pub open: Vec<usize>,
+    pub columns: Option<Reader<'a>>,
+    pub grids: Vec<Reader<'a>>,
+    pub images: Vec<Reader<'a>>,
 }
-impl ProtoRead for VectorTile {
-    fn read(&mut self, tag: u64, pb: &mut Protobuf) {
+
+/// Split a tile into its messages, reading `data[..end]`. Only Mapbox layers are accepted unless
+/// `open` is set
+pub(crate) fn read_tile(data: &[u8], end: Option<usize>, open: bool) -> Result<TileMessages<'_>> {
+    let end = end.unwrap_or(data.len());
+    if end > data.len() {
+        return Err(DecodeError::UnexpectedEof {
+            message: ProtoMessage::Tile,
+            tag: None,
+            offset: data.len(),
+        });
+    }
+    let mut pb = Reader::new(data, 0, end, ProtoMessage::Tile);
+    let mut messages = TileMessages::default();
+    while !pb.done() {
+        let (tag, wire_type) = pb.field()?;
+        let start = pb.pos();
         match tag {
-            1 | 3 => {
-                let mut layer = MapboxVectorLayer::new(self.pbf.clone(), tag == 1);
-                pb.read_message(&mut layer);
-                self.layers.insert(layer.name.clone(), VectorLayer::Mapbox(layer));
-            }
+            1 | 3 => pb.expect(wire_type, BYTES)?,
+            4..=7 if open => pb.expect(wire_type, BYTES)?,
+            _ => return Err(pb.unknown_tag(start)),
+        }
+        match tag {
+            1 | 3 => messages.mapbox.push((pb.message(ProtoMessage::MapboxLayer)?, tag == 1)),
             4 => {
-                // store the position of each layer for later retrieval.
-                // Columns must be prepped before reading the layer.
-                self.layer_indexes.push(pb.get_pos());
-            }
-            5 => {
-                let mut column_reader = ColumnCacheReader::new();
-                pb.read_message(&mut column_reader);
-                self.columns = Some(Rc::new(RefCell::new(column_reader)));
-            }
-            6 => {
-                let mut grid = GridData::default();
-                pb.read_message(&mut grid);
-                self.grids.insert(grid.name.clone(), grid);
+                messages.open.push(start);
+                pb.skip(wire_type)?;
             }
-            7 => {
-                let mut image = ImageData::default();
-                pb.read_message(&mut image);
-                self.images.insert(image.name.clone(), image);
-            }
-            _ => panic!("unknown tag: {}", tag),
+            5 => messages.columns = Some(pb.message(ProtoMessage::ColumnCache)?),
+            6 => messages.grids.push(pb.message(ProtoMessage::GridData)?),
+            _ => messages.images.push(pb.message(ProtoMessage::ImageData)?),
         }
     }
+    Ok(messages)
+}
+
+/// Options for [`write_tile_with`] and [`try_write_tile`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// if set to the Web Mercator zoom/x/y of the tile, features without a bbox are written with
+    /// one computed from their geometry, see [`crate::base::BaseVectorFeature::compute_bbox`].
+    /// The features themselves are left untouched
+    pub compute_bbox: Option<(u8, u32, u32)>,
 }
 
-/// writer for converting a BaseVectorTile to encoded bytes of the Open Vector Tile format
+/// writer for converting a BaseVectorTile to encoded bytes of the Open Vector Tile format.
+/// Panics if a feature's properties or M-Values do not match its layer's shapes, see
+/// [`try_write_tile`].
 pub fn write_tile(
     tile: Option<&mut BaseVectorTile>,
     images: Option<Vec<&ImageData>>,
     grids: Option<Vec<&GridData>>,
 ) -> Vec<u8> {
+    write_tile_with(tile, images, grids, &WriteOptions::default())
+}
+
+/// Version of [`write_tile`] taking [`WriteOptions`]
+pub fn write_tile_with(
+    tile: Option<&mut BaseVectorTile>,
+    images: Option<Vec<&ImageData>>,
+    grids: Option<Vec<&GridData>>,
+    options: &WriteOptions,
+) -> Vec<u8> {
+    try_write_tile(tile, images, grids, options).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Fallible version of [`write_tile_with`]. Returns an error naming the first feature whose
+/// properties or M-Values do not match its layer's shape and M-Value shape instead of panicking.
+/// Keys missing from a feature's properties, including keys of objects inside arrays, are written
+/// as the shape's default.
+pub fn try_write_tile(
+    tile: Option<&mut BaseVectorTile>,
+    images: Option<Vec<&ImageData>>,
+    grids: Option<Vec<&GridData>>,
+    options: &WriteOptions,
+) -> core::result::Result<Vec<u8>, EncodeError> {
     let mut pbf = Protobuf::new();
     let mut cache = ColumnCacheWriter::default();
 
     // first write layers
     if let Some(tile) = tile {
-        for layer in tile.layers.values_mut() {
-            pbf.write_bytes_field(4, &write_layer(layer, &mut cache));
+        let layers = tile
+            .layers
+            .values_mut()
+            .map(|layer| {
+                Ok((
+                    cache.add_string(layer.name.clone()),
+                    try_write_layer(layer, &mut cache, options)?,
+                ))
+            })
+            .collect::<core::result::Result<Vec<_>, EncodeError>>()?;
+        // string indices are final once the cache is sorted
+        cache.sort();
+        for (name, layer) in layers {
+            let mut data = write_layer_name(&name);
+            data.extend(layer);
+            pbf.write_bytes_field(4, &data);
         }
         // now we can write columns
         pbf.write_message(5, &cache);
@@ -248,5 +440,5 @@ pub fn write_tile(
         }
     }
 
-    pbf.take()
+    Ok(pbf.take())
 }
@@ -129,7 +129,7 @@ mod tests {
 
         // encode_to_cache
         assert_eq!(feature_base.encode_to_cache(&mut col, None), 0);
-        assert_eq!(feature_base_2.encode_to_cache(&mut col, Some(&example_shape)), 1);
+        assert_eq!(feature_base_2.encode_to_cache(&mut col, Some(&example_shape)), 0);
     }
 
     #[test]
@@ -264,7 +264,7 @@ mod tests {
 
         // encode_to_cache
         assert_eq!(feature_base.encode_to_cache(&mut col, None), 0);
-        assert_eq!(feature_base_2.encode_to_cache(&mut col, Some(&example_shape)), 1);
+        assert_eq!(feature_base_2.encode_to_cache(&mut col, Some(&example_shape)), 0);
     }
 
     #[test]
@@ -413,7 +413,7 @@ mod tests {
 
         // encode_to_cache
         assert_eq!(feature_base.encode_to_cache(&mut col, None), 0);
-        assert_eq!(feature_base_2.encode_to_cache(&mut col, Some(&example_shape)), 2);
+        assert_eq!(feature_base_2.encode_to_cache(&mut col, Some(&example_shape)), 1);
     }
 
     #[test]
@@ -587,7 +587,7 @@ mod tests {
 
         // encode_to_cache
         assert_eq!(feature_base.encode_to_cache(&mut col, None), 0);
-        assert_eq!(feature_base_2.encode_to_cache(&mut col, Some(&example_shape)), 2);
+        assert_eq!(feature_base_2.encode_to_cache(&mut col, Some(&example_shape)), 1);
     }
 
     #[test]
@@ -772,7 +772,7 @@ mod tests {
 
         // encode_to_cache
         assert_eq!(feature_base.encode_to_cache(&mut col, None), 0);
-        assert_eq!(feature_base_2.encode_to_cache(&mut col, Some(&example_shape)), 2);
+        assert_eq!(feature_base_2.encode_to_cache(&mut col, Some(&example_shape)), 1);
     }
 
     #[test]
@@ -1007,7 +1007,7 @@ mod tests {
 
         // encode_to_cache
         assert_eq!(feature_base.encode_to_cache(&mut col, None), 0);
-        assert_eq!(feature_base_2.encode_to_cache(&mut col, Some(&example_shape)), 2);
+        assert_eq!(feature_base_2.encode_to_cache(&mut col, Some(&example_shape)), 1);
     }
 
     #[test]
@@ -0,0 +1,81 @@
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    extern crate alloc;
+
+    use open_vector_tile::{VectorLayer, VectorLayerMethods, VectorTile};
+    use std::{env, fs, path::PathBuf, process::Command};
+
+    fn ovt(args: &[&str]) -> (bool, String) {
+        let output = Command::new(env!("CARGO_BIN_EXE_ovt")).args(args).output().unwrap();
+        (output.status.success(), String::from_utf8(output.stdout).unwrap())
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("ovt-cli-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_cli_info_and_dump() {
+        let (ok, out) = ovt(&["info", "./tests/fixtures/12-1143-1497.vector.pbf"]);
+        assert!(ok);
+        assert!(out.contains("layer road: mvt v1, extent 4096, 247 features"));
+
+        let (ok, out) = ovt(&["dump", "./tests/fixtures/multi-point.pbf"]);
+        assert!(ok);
+        assert_eq!(out.lines().count(), 1);
+        assert!(out.starts_with(r#"{"type":"VectorFeature","id":1"#));
+        assert!(out.contains(r#""metadata":{"layer":"geojson"}"#));
+
+        let (ok, out) = ovt(&["dump", "./tests/fixtures/multi-point.pbf", "--tile", "0/0/0"]);
+        assert!(ok);
+        assert!(out.starts_with(r#"{"type":"FeatureCollection""#));
+
+        assert!(!ovt(&["dump", "./tests/fixtures/multi-point.pbf", "--tile", "0/0"]).0);
+        assert!(!ovt(&["unknown"]).0);
+    }
+
+    #[test]
+    fn test_cli_convert_and_validate() {
+        let dir = temp_dir("convert");
+        let input = dir.join("mvt");
+        fs::create_dir_all(input.join("12/1143")).unwrap();
+        fs::copy("./tests/fixtures/12-1143-1497.vector.pbf", input.join("12/1143/1497.mvt"))
+            .unwrap();
+        fs::copy("./tests/fixtures/multi-line.pbf", input.join("multi-line.mvt")).unwrap();
+
+        // directories are converted into the same layout
+        let output = dir.join("ovt");
+        let (ok, _) =
+            ovt(&["convert", input.to_str().unwrap(), output.to_str().unwrap(), "--to", "ovt"]);
+        assert!(ok);
+        let data = fs::read(output.join("12/1143/1497.ovt")).unwrap();
+        let mut tile = VectorTile::new(data, None);
+        let Some(VectorLayer::Open(road)) = tile.layer("road") else { panic!("expected ovt") };
+        assert_eq!(road.len(), 247);
+        assert!(output.join("multi-line.ovt").exists());
+
+        let (ok, out) = ovt(&["validate", output.to_str().unwrap()]);
+        assert!(ok);
+        assert_eq!(out.lines().count(), 2);
+
+        // and back again to a single file
+        let ofvt = dir.join("1497.ofvt");
+        let ovt_file = output.join("12/1143/1497.ovt");
+        assert!(
+            ovt(&["convert", ovt_file.to_str().unwrap(), ofvt.to_str().unwrap(), "--to", "ofvt"]).0
+        );
+        let mut tile = VectorTile::new(fs::read(&ofvt).unwrap(), None);
+        let Some(VectorLayer::Mapbox(road)) = tile.layer("road") else { panic!("expected ofvt") };
+        assert!(road.is_s2());
+        assert_eq!(road.len(), 247);
+
+        // invalid tiles fail validation
+        fs::write(dir.join("bad.ovt"), [0x22, 0x10, 0x01]).unwrap();
+        assert!(!ovt(&["validate", dir.join("bad.ovt").to_str().unwrap()]).0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use crate::point_tile;
+    use open_vector_tile::{
+        VectorTile,
+        base::BaseVectorTile,
+        compression::{Codec, CompressionError, decompress, detect},
+        write_tile,
+    };
+
+    fn tile() -> BaseVectorTile {
+        point_tile("points", 7)
+    }
+
+    #[cfg(any(feature = "gzip", feature = "zlib", feature = "zstd", feature = "brotli"))]
+    fn roundtrip(codec: Codec) {
+        use open_vector_tile::{
+            VectorFeatureMethods, VectorLayerMethods,
+            compression::{compress, decompress_with_limit, write_tile_compressed},
+        };
+
+        let data = write_tile_compressed(Some(&mut tile()), None, None, codec).unwrap();
+        assert_ne!(data, write_tile(Some(&mut tile()), None, None));
+        let mut vector_tile = VectorTile::from_compressed(data).unwrap();
+        let points = vector_tile.layer("points").unwrap();
+        assert_eq!(points.feature(0).unwrap().id(), Some(7));
+
+        // decompressing stops at the limit
+        let zeros = vec![0; 1 << 20];
+        let data = compress(&zeros, codec).unwrap();
+        assert!(data.len() < 4096);
+        assert_eq!(decompress_with_limit(&data, codec, 1 << 20).unwrap(), zeros);
+        assert_eq!(
+            decompress_with_limit(&data, codec, (1 << 20) - 1),
+            Err(CompressionError::TooLarge(codec))
+        );
+        assert_eq!(decompress_with_limit(&data, codec, 0), Err(CompressionError::TooLarge(codec)));
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect(&[0x1f, 0x8b, 8, 0]), Some(Codec::Gzip));
+        assert_eq!(detect(&[0x28, 0xb5, 0x2f, 0xfd, 0]), Some(Codec::Zstd));
+        assert_eq!(detect(&[0x78, 0x9c]), Some(Codec::Zlib));
+        assert_eq!(detect(&[0x78, 0x9d]), None);
+        assert_eq!(detect(&[]), None);
+
+        // uncompressed tiles are never detected and are returned as is
+        let data = write_tile(Some(&mut tile()), None, None);
+        assert_eq!(detect(&data), None);
+        assert_eq!(decompress(data.clone()).unwrap(), data);
+        let mut vector_tile = VectorTile::from_compressed(data).unwrap();
+        assert!(vector_tile.layer("points").is_some());
+        let fixture = std::fs::read("./tests/fixtures/multi-point.pbf").unwrap();
+        assert_eq!(detect(&fixture), None);
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn test_gzip() {
+        roundtrip(Codec::Gzip);
+        // a header with a file name, as written by the gzip command line tool
+        let mut data =
+            open_vector_tile::compression::write_tile_compressed(None, None, None, Codec::Gzip)
+                .unwrap();
+        data[3] = 8;
+        data.splice(10..10, *b"tile.pbf\0");
+        assert_eq!(decompress(data.clone()).unwrap(), write_tile(None, None, None));
+        // a bad checksum
+        let len = data.len();
+        data[len - 5] ^= 1;
+        assert_eq!(decompress(data), Err(CompressionError::Corrupt(Codec::Gzip)));
+    }
+
+    #[test]
+    #[cfg(feature = "zlib")]
+    fn test_zlib() {
+        roundtrip(Codec::Zlib);
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_zstd() {
+        roundtrip(Codec::Zstd);
+        assert_eq!(
+            decompress(vec![0x28, 0xb5, 0x2f, 0xfd, 0xff]),
+            Err(CompressionError::Corrupt(Codec::Zstd))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "brotli")]
+    fn test_brotli() {
+        use open_vector_tile::compression::{compress, decompress_limited};
+
+        roundtrip(Codec::Brotli);
+        // undetected brotli past the limit is an error rather than returned as is
+        let data = compress(&[0; 4096], Codec::Brotli).unwrap();
+        assert_eq!(decompress_limited(data.clone(), 4096).unwrap(), vec![0; 4096]);
+        assert_eq!(decompress_limited(data, 4095), Err(CompressionError::TooLarge(Codec::Brotli)));
+    }
+
+    #[test]
+    #[cfg(not(feature = "zstd"))]
+    fn test_unsupported() {
+        let err = VectorTile::from_compressed(vec![0x28, 0xb5, 0x2f, 0xfd, 0]).unwrap_err();
+        assert_eq!(err, CompressionError::Unsupported(Codec::Zstd));
+        assert_eq!(err.to_string(), "zstd compressed data requires the \"zstd\" feature");
+    }
+}
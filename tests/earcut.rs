@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use open_vector_tile::{
+        Point, Point3D, VectorFeatureMethods, VectorLayerMethods, VectorLine3DWithOffset,
+        VectorLineWithOffset, VectorTile,
+        base::{
+            BaseVectorFeature, BaseVectorLayer, BaseVectorPolys3DFeature, BaseVectorPolysFeature,
+            BaseVectorTile,
+        },
+        earcut::earcut,
+        open::Extent,
+        write_tile,
+    };
+    use s2json::Properties;
+
+    /// Sum of the triangle areas
+    fn triangles_area(data: &[f64], indices: &[u32]) -> f64 {
+        indices
+            .chunks(3)
+            .map(|t| {
+                let (a, b, c) = (t[0] as usize * 2, t[1] as usize * 2, t[2] as usize * 2);
+                ((data[b] - data[a]) * (data[c + 1] - data[a + 1])
+                    - (data[c] - data[a]) * (data[b + 1] - data[a + 1]))
+                    .abs()
+                    / 2.0
+            })
+            .sum()
+    }
+
+    /// Area of a closed ring
+    fn ring_area(ring: &[(f64, f64)]) -> f64 {
+        ring.windows(2).map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1).sum::<f64>().abs() / 2.0
+    }
+
+    #[test]
+    fn test_earcut() {
+        // a square, open and closed
+        let square = [0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 0.0, 10.0];
+        let indices = earcut(&square, &[], 2);
+        assert_eq!(indices.len(), 6);
+        assert_eq!(triangles_area(&square, &indices), 100.0);
+        let closed = [0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 0.0, 10.0, 0.0, 0.0];
+        assert_eq!(triangles_area(&closed, &earcut(&closed, &[], 2)), 100.0);
+
+        // a square with a square hole, both wound the same way
+        let data = [
+            0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 0.0, 10.0, // outer
+            2.0, 2.0, 8.0, 2.0, 8.0, 8.0, 2.0, 8.0, // hole
+        ];
+        let indices = earcut(&data, &[4], 2);
+        assert_eq!(indices.len(), 8 * 3);
+        assert_eq!(triangles_area(&data, &indices), 100.0 - 36.0);
+
+        // a concave polygon with extra dimensions
+        let data = [0.0, 0.0, 1.0, 4.0, 0.0, 1.0, 4.0, 4.0, 1.0, 2.0, 1.0, 1.0, 0.0, 4.0, 1.0];
+        let indices = earcut(&data, &[], 3);
+        assert_eq!(indices.len(), 9);
+        let xy: Vec<f64> = data.chunks(3).flat_map(|p| [p[0], p[1]]).collect();
+        assert_eq!(triangles_area(&xy, &indices), 16.0 - 6.0);
+
+        // degenerate input
+        assert!(earcut(&[], &[], 2).is_empty());
+        assert!(earcut(&[0.0, 0.0, 1.0, 1.0], &[], 2).is_empty());
+    }
+
+    #[test]
+    fn test_tessellate_on_write() {
+        let outer = vec![
+            Point::new(0, 0),
+            Point::new(100, 0),
+            Point::new(100, 100),
+            Point::new(50, 40),
+            Point::new(0, 100),
+            Point::new(0, 0),
+        ];
+        let hole = vec![
+            Point::new(40, 10),
+            Point::new(60, 10),
+            Point::new(60, 20),
+            Point::new(40, 20),
+            Point::new(40, 10),
+        ];
+        let polys = BaseVectorPolysFeature::new(
+            Some(1),
+            vec![
+                vec![VectorLineWithOffset::new(0.0, outer), VectorLineWithOffset::new(0.0, hole)],
+                vec![VectorLineWithOffset::new(
+                    0.0,
+                    vec![
+                        Point::new(200, 200),
+                        Point::new(300, 200),
+                        Point::new(300, 300),
+                        Point::new(200, 200),
+                    ],
+                )],
+            ],
+            Properties::default(),
+            None,
+            vec![],
+            vec![],
+        );
+        let polys_3d = BaseVectorPolys3DFeature::new(
+            Some(2),
+            vec![vec![VectorLine3DWithOffset::new(
+                0.0,
+                vec![
+                    Point3D::new(0, 0, 5),
+                    Point3D::new(100, 0, 5),
+                    Point3D::new(100, 100, 10),
+                    Point3D::new(0, 100, 10),
+                    Point3D::new(0, 0, 5),
+                ],
+            )]],
+            Properties::default(),
+            None,
+            vec![],
+            vec![],
+        );
+        let mut tile = BaseVectorTile::default();
+        for (name, tessellate) in [("tessellated", true), ("plain", false)] {
+            let mut layer = BaseVectorLayer::new(
+                name.into(),
+                Extent::Extent4096,
+                vec![
+                    BaseVectorFeature::BaseVectorPolysFeature(polys.clone()),
+                    BaseVectorFeature::BaseVectorPolys3DFeature(polys_3d.clone()),
+                ],
+                None,
+                None,
+            );
+            layer.tessellate = tessellate;
+            tile.add_layer(layer);
+        }
+
+        let data = write_tile(Some(&mut tile), None, None);
+        let mut tile = VectorTile::new(data, None);
+
+        let layer = tile.layer("tessellated").unwrap();
+        let (geometry, indices) = layer.feature(0).unwrap().load_geometry_flat();
+        let area =
+            ring_area(&[(0., 0.), (100., 0.), (100., 100.), (50., 40.), (0., 100.), (0., 0.)])
+                - 200.0
+                + 5000.0;
+        let extent_sq = 4096.0 * 4096.0;
+        assert!(!indices.is_empty());
+        assert!((triangles_area(&geometry, &indices) * extent_sq - area).abs() < 1e-6);
+        let (geometry, indices) = layer.feature(1).unwrap().load_geometry_flat();
+        assert_eq!(indices.len(), 6);
+        assert!((triangles_area(&geometry, &indices) * extent_sq - 10_000.0).abs() < 1e-6);
+
+        // layers that don't opt in are left as is
+        let layer = tile.layer("plain").unwrap();
+        assert!(layer.feature(0).unwrap().load_geometry_flat().1.is_empty());
+        assert!(layer.feature(1).unwrap().load_geometry_flat().1.is_empty());
+
+        // existing indices are kept
+        let mut feature = BaseVectorFeature::BaseVectorPolysFeature(BaseVectorPolysFeature {
+            indices: vec![0, 1, 2],
+            ..polys
+        });
+        feature.tessellate();
+        let BaseVectorFeature::BaseVectorPolysFeature(feature) = feature else { unreachable!() };
+        assert_eq!(feature.indices, vec![0, 1, 2]);
+    }
+}
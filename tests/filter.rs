@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use open_vector_tile::{
+        Point, VectorFeatureMethods, VectorLayer, VectorLineWithOffset, VectorTile,
+        base::{
+            BaseVectorFeature, BaseVectorLayer, BaseVectorLinesFeature, BaseVectorPointsFeature,
+            BaseVectorTile,
+        },
+        filter::Filter,
+        open::{Extent, FeatureType, OpenVectorLayer},
+        write_tile,
+    };
+    use s2json::{PrimitiveValue, Properties, ValuePrimitiveType, ValueType};
+
+    fn properties(class: &str, rank: i64) -> Properties {
+        Properties::from([
+            // sorted before the keys being filtered on, so they are skipped in the column cache
+            ("a_list".into(), ValueType::Array(vec![ValuePrimitiveType::Primitive(1_u64.into())])),
+            ("a_nested".into(), ValueType::Nested(Properties::from([("x".into(), 2.5.into())]))),
+            ("a_null".into(), ValueType::Primitive(PrimitiveValue::Null)),
+            ("class".into(), class.into()),
+            ("open".into(), (rank % 2 == 0).into()),
+            ("rank".into(), rank.into()),
+        ])
+    }
+
+    #[test]
+    fn test_filter() {
+        let props = properties("park", 4);
+        let matches = |filter: Filter| filter.matches(&props, FeatureType::Polygons);
+
+        assert!(matches(Filter::Eq("class".into(), "park".into())));
+        assert!(!matches(Filter::Eq("class".into(), "road".into())));
+        assert!(matches(Filter::NotEq("class".into(), "road".into())));
+        assert!(matches(Filter::NotEq("missing".into(), "road".into())));
+        // numbers compare by value regardless of their type
+        assert!(matches(Filter::Eq("rank".into(), 4_u64.into())));
+        assert!(matches(Filter::Eq("rank".into(), 4.0.into())));
+        assert!(matches(Filter::Lt("rank".into(), 5_u64.into())));
+        assert!(matches(Filter::Lte("rank".into(), 4_i64.into())));
+        assert!(!matches(Filter::Gt("rank".into(), 4_i64.into())));
+        assert!(matches(Filter::Gte("rank".into(), 3.5.into())));
+        assert!(matches(Filter::Lt("class".into(), "parks".into())));
+        // different types never match
+        assert!(!matches(Filter::Eq("rank".into(), "4".into())));
+        assert!(!matches(Filter::Lt("open".into(), true.into())));
+        assert!(matches(Filter::Eq("open".into(), true.into())));
+        assert!(!matches(Filter::Eq("a_list".into(), 1_u64.into())));
+
+        assert!(matches(Filter::In("class".into(), vec!["road".into(), "park".into()])));
+        assert!(!matches(Filter::In("class".into(), vec![])));
+        assert!(matches(Filter::Has("a_null".into())));
+        assert!(!matches(Filter::Has("missing".into())));
+
+        assert!(matches(Filter::All(vec![])));
+        assert!(!matches(Filter::Any(vec![])));
+        assert!(matches(Filter::Any(vec![
+            Filter::Has("missing".into()),
+            Filter::All(vec![Filter::Type(FeatureType::Polygons), Filter::Has("class".into())]),
+        ])));
+        assert!(matches(Filter::Not(Box::new(Filter::Type(FeatureType::Polygons3D)))));
+    }
+
+    #[test]
+    fn test_filter_open_layer() {
+        let features = vec![
+            BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                Some(1),
+                vec![Point::new(0, 0)],
+                properties("park", 1),
+                None,
+            )),
+            BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                Some(2),
+                vec![Point::new(1, 1)],
+                properties("school", 2),
+                None,
+            )),
+            BaseVectorFeature::BaseVectorLinesFeature(BaseVectorLinesFeature::new(
+                Some(3),
+                vec![VectorLineWithOffset::new(0.0, vec![Point::new(0, 0), Point::new(5, 5)])],
+                properties("park", 3),
+                None,
+            )),
+        ];
+        let mut layer = BaseVectorLayer::new("pois".into(), Extent::Extent4096, vec![], None, None);
+        features.into_iter().for_each(|f| layer.add_feature(f));
+        let mut tile = BaseVectorTile::default();
+        tile.add_layer(layer);
+        let data = write_tile(Some(&mut tile), None, None);
+        let mut tile = VectorTile::new(data, None);
+        let Some(VectorLayer::Open(layer)) = tile.layers.get_mut("pois") else {
+            panic!("expected an open layer")
+        };
+
+        let ids = |layer: &mut OpenVectorLayer, filter: &Filter| {
+            layer.filter(filter).map(|f| f.id().unwrap()).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(layer, &Filter::Eq("class".into(), "park".into())), vec![1, 3]);
+        assert_eq!(
+            ids(
+                layer,
+                &Filter::All(vec![
+                    Filter::Eq("class".into(), "park".into()),
+                    Filter::Type(FeatureType::Points),
+                ])
+            ),
+            vec![1]
+        );
+        assert_eq!(ids(layer, &Filter::Gte("rank".into(), 2_u64.into())), vec![2, 3]);
+        assert_eq!(ids(layer, &Filter::Eq("open".into(), true.into())), vec![2]);
+        assert_eq!(ids(layer, &Filter::Has("missing".into())), Vec::<u64>::new());
+
+        // the same results as filtering the decoded properties
+        for filter in [
+            Filter::NotEq("class".into(), "school".into()),
+            Filter::In("rank".into(), vec![1_u64.into(), 2_u64.into()]),
+            Filter::Lt("rank".into(), 3_u64.into()),
+        ] {
+            let expected: Vec<u64> = layer
+                .features
+                .iter()
+                .filter(|f| filter.matches_feature(*f))
+                .map(|f| f.id().unwrap())
+                .collect();
+            assert_eq!(ids(layer, &filter), expected);
+        }
+    }
+}
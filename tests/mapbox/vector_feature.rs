@@ -1,12 +1,12 @@
 #[cfg(test)]
 mod tests {
     extern crate alloc;
-    use alloc::{collections::BTreeMap, rc::Rc, string::String};
-    use core::cell::RefCell;
+    use alloc::{collections::BTreeMap, string::String};
     use open_vector_tile::{
-        Point,
+        Point, VectorFeatureMethods,
         base::{BaseVectorFeature, BaseVectorPointsFeature},
         mapbox::{MapboxVectorFeature, write_feature},
+        shared::{Shared, shared},
     };
     use pbf::Protobuf;
     use s2json::{PrimitiveValue, Value};
@@ -40,23 +40,44 @@ mod tests {
         pbf_write
             .write_bytes_field(3, &write_feature(&base_feature, &mut keys, &mut values, false));
         let data = pbf_write.take();
-        let pbf = Rc::new(RefCell::new(data.into()));
+        let tile_data: Shared<[u8]> = data.as_slice().into();
 
         let mut mapbox_feature = MapboxVectorFeature::new(
-            pbf.clone(),
+            tile_data,
             true,
             4096,
             1,
-            Rc::new(RefCell::new(keys.into_keys().collect())),
-            Rc::new(RefCell::new(values.into_keys().collect())),
+            shared(keys.into_keys().collect()),
+            shared(values.into_keys().collect()),
         );
-        let mut pbf_mut = pbf.borrow_mut();
+        let mut pbf_mut = Protobuf::from(data);
         pbf_mut.read_field();
         pbf_mut.read_message(&mut mapbox_feature);
 
         assert!(mapbox_feature.is_s2);
     }
 
+    #[test]
+    fn test_mapbox_vector_feature_deltas_wrap() {
+        // a point feature moving to i32::MAX and then one step past it, read without the checks
+        // `MapboxVectorTile::try_new` runs first
+        let data: Vec<u8> = vec![
+            0x12, 0x0D, 0x18, 0x01, 0x22, 0x09, 0x11, 0xFE, 0xFF, 0xFF, 0xFF, 0x0F, 0x00, 0x02,
+            0x00,
+        ];
+        let tile_data: Shared<[u8]> = data.as_slice().into();
+        let mut mapbox_feature =
+            MapboxVectorFeature::new(tile_data, false, 4096, 2, shared(vec![]), shared(vec![]));
+        let mut pbf = Protobuf::from(data);
+        pbf.read_field();
+        pbf.read_message(&mut mapbox_feature);
+
+        assert_eq!(
+            mapbox_feature.load_points(),
+            vec![Point::new(i32::MAX, 0), Point::new(i32::MIN, 0)]
+        );
+    }
+
     #[test]
     fn test_value() {
         let mut pb = Protobuf::new();
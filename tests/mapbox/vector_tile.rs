@@ -2,13 +2,14 @@
 mod tests {
     extern crate alloc;
     use open_vector_tile::{
-        Point, VectorFeatureMethods, VectorGeometry, VectorLayerMethods, VectorLineWithOffset,
-        VectorTile,
+        DecodeError, EncodeError, Point, Point3D, ProtoMessage, VectorFeatureMethods,
+        VectorGeometry, VectorLayerMethods, VectorLineWithOffset, VectorTile,
         base::{
             BaseVectorFeature, BaseVectorFeatureMethods, BaseVectorLayer, BaseVectorLinesFeature,
-            BaseVectorPointsFeature, BaseVectorPolysFeature, BaseVectorTile,
+            BaseVectorPoints3DFeature, BaseVectorPointsFeature, BaseVectorPolysFeature,
+            BaseVectorTile,
         },
-        mapbox::vector_tile::{MapboxVectorTile, write_tile},
+        mapbox::vector_tile::{MapboxVectorTile, try_write_tile, write_tile},
         open::{Extent, FeatureType},
     };
     use s2json::Value;
@@ -871,4 +872,342 @@ mod tests {
             _ => panic!("expected BaseVectorPolysFeature"),
         }
     }
+
+    #[test]
+    fn test_unread_features_to_base_vector_tile() {
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let mut tile = MapboxVectorTile::new(data.clone(), None);
+        // no features have been read yet
+        let base_tile = BaseVectorTile::from(&mut tile);
+
+        // read every feature up front
+        let mut tile = MapboxVectorTile::new(data, None);
+        for layer in tile.layers.values_mut() {
+            for i in 0..layer.len() {
+                layer.feature(i);
+            }
+        }
+        let loaded_tile = BaseVectorTile::from(&mut tile);
+
+        assert_eq!(base_tile.layers.len(), tile.layers.len());
+        for (name, layer) in tile.layers.iter() {
+            let base_layer = base_tile.layers.get(name).unwrap();
+            assert_eq!(base_layer.len(), layer.len());
+            assert_eq!(base_layer.features, loaded_tile.layers.get(name).unwrap().features);
+        }
+    }
+
+    #[test]
+    fn test_try_new_fixtures() {
+        for name in [
+            "1-1-0.vector.pbf",
+            "12-1143-1497.vector.pbf",
+            "lots-of-tags.vector.pbf",
+            "multi-line.pbf",
+            "multi-point.pbf",
+            "multi-polygon.pbf",
+            "multipolygon-with-closepath.pbf",
+            "polygon-with-inner.pbf",
+            "singleton-multi-line.pbf",
+            "singleton-multi-point.pbf",
+            "singleton-multi-polygon.pbf",
+            "stacked-multipolygon.pbf",
+            "zero-line.pbf",
+            "zero-point.pbf",
+            "zero-polygon.pbf",
+        ] {
+            let data = fs::read(format!("./tests/fixtures/{name}")).unwrap();
+            if let Err(err) = MapboxVectorTile::try_new(data.clone(), None) {
+                panic!("{name}: {err}")
+            }
+            assert!(VectorTile::try_new(data, None).is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_try_new_errors() {
+        // unknown tile tag
+        let err = MapboxVectorTile::try_new(vec![0x20, 0x00], None).unwrap_err();
+        assert_eq!(err, DecodeError::UnknownTag { message: ProtoMessage::Tile, tag: 4, offset: 1 });
+        assert_eq!(err.to_string(), "unknown tag in tile message (tag 4) at byte 1");
+
+        // end past the data
+        assert!(matches!(
+            MapboxVectorTile::try_new(vec![], Some(4)),
+            Err(DecodeError::UnexpectedEof { message: ProtoMessage::Tile, .. })
+        ));
+
+        // a layer named "a" with a single feature. `feature` is spliced in at byte 7
+        let tile = |feature: &[u8]| {
+            let mut layer = vec![0x0A, 0x01, b'a', 0x12, feature.len() as u8];
+            layer.extend_from_slice(feature);
+            let mut tile = vec![0x1A, layer.len() as u8];
+            tile.extend(layer);
+            tile
+        };
+        // valid
+        assert!(
+            MapboxVectorTile::try_new(tile(&[0x18, 0x01, 0x22, 0x03, 0x09, 0x00, 0x00]), None)
+                .is_ok()
+        );
+        // unknown feature type
+        assert_eq!(
+            MapboxVectorTile::try_new(tile(&[0x18, 0x09, 0x22, 0x00]), None).unwrap_err(),
+            DecodeError::InvalidEnum {
+                message: ProtoMessage::MapboxFeature,
+                tag: Some(3),
+                value: 9,
+                offset: 8,
+            }
+        );
+        // property key that is not in the key store
+        assert_eq!(
+            MapboxVectorTile::try_new(tile(&[0x12, 0x02, 0x00, 0x00, 0x22, 0x00]), None)
+                .unwrap_err(),
+            DecodeError::OutOfRange {
+                message: ProtoMessage::MapboxFeature,
+                tag: Some(2),
+                index: 0,
+                len: 0,
+                offset: 9,
+            }
+        );
+        // missing geometry
+        assert!(matches!(
+            MapboxVectorTile::try_new(tile(&[0x18, 0x01]), None),
+            Err(DecodeError::Malformed { message: ProtoMessage::MapboxFeature, .. })
+        ));
+        // unknown geometry command
+        assert_eq!(
+            MapboxVectorTile::try_new(tile(&[0x22, 0x01, 0x0B]), None).unwrap_err(),
+            DecodeError::UnknownCommand {
+                message: ProtoMessage::MapboxFeature,
+                tag: Some(4),
+                command: 3,
+                offset: 9,
+            }
+        );
+        // geometry that runs past the end of the feature
+        assert!(matches!(
+            MapboxVectorTile::try_new(tile(&[0x22, 0x02, 0x09, 0x00]), None),
+            Err(DecodeError::UnexpectedEof { message: ProtoMessage::MapboxFeature, .. })
+        ));
+        // geometry stored as a varint would be read as the length of the command stream
+        assert_eq!(
+            MapboxVectorTile::try_new(tile(&[0x20, 0x01]), None).unwrap_err(),
+            DecodeError::InvalidWireType {
+                message: ProtoMessage::MapboxFeature,
+                tag: 4,
+                wire_type: 0,
+                offset: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn test_try_new_close_count() {
+        // a triangle closed by a close path command with a count of `close`
+        let tile = |close: u8| {
+            let feature =
+                [0x18, 0x03, 0x22, 0x09, 0x09, 0x00, 0x00, 0x12, 0x02, 0x00, 0x00, 0x02, close];
+            let mut layer = vec![0x0A, 0x01, b'a', 0x12, feature.len() as u8];
+            layer.extend_from_slice(&feature);
+            let mut tile = vec![0x1A, layer.len() as u8];
+            tile.extend(layer);
+            MapboxVectorTile::try_new(tile, None).unwrap()
+        };
+        let mut once = tile((1 << 3) | 7);
+        let mut twice = tile((2 << 3) | 7);
+        assert_eq!(
+            twice.layer("a").unwrap().feature(0).unwrap().load_polys(),
+            once.layer("a").unwrap().feature(0).unwrap().load_polys()
+        );
+    }
+
+    #[test]
+    fn test_try_new_corrupted() {
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        // truncating the tile must never panic
+        for len in (0..data.len()).step_by(7) {
+            if let Ok(mut tile) = MapboxVectorTile::try_new(data[..len].to_vec(), None) {
+                load_all(&mut tile);
+            }
+        }
+        // neither can flipping bytes
+        let mut seed: u32 = 7;
+        for _ in 0..1_000 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let mut corrupted = data.clone();
+            let index = (seed >> 8) as usize % corrupted.len();
+            corrupted[index] ^= (seed >> 24) as u8 | 1;
+            if let Ok(mut tile) = MapboxVectorTile::try_new(corrupted, None) {
+                load_all(&mut tile);
+            }
+        }
+    }
+
+    fn load_all(tile: &mut MapboxVectorTile) {
+        let names: Vec<String> = tile.layers.keys().cloned().collect();
+        for name in names {
+            let layer = tile.layer(&name).unwrap();
+            for i in 0..layer.len() {
+                let mut feature = layer.feature(i).unwrap();
+                feature.properties();
+                feature.load_geometry();
+                feature.read_indices();
+                if feature.is_polygons() {
+                    feature.load_geometry_flat();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_area_ring() {
+        let mut tile = BaseVectorTile::default();
+        let mut layer = BaseVectorLayer::new("polys".to_string(), 4_096.into(), vec![], None, None);
+        let ring = vec![Point::new(0, 0), Point::new(4, 0), Point::new(4, 4), Point::new(0, 0)];
+        layer.add_feature(BaseVectorFeature::BaseVectorPolysFeature(BaseVectorPolysFeature::new(
+            None,
+            vec![vec![
+                VectorLineWithOffset::new(
+                    0.0,
+                    vec![Point::new(1, 1), Point::new(2, 2), Point::new(1, 1)],
+                ),
+                VectorLineWithOffset::new(0.0, ring.clone()),
+            ]],
+            Value::default(),
+            None,
+            vec![],
+            vec![],
+        )));
+        tile.add_layer(layer);
+
+        let mut mapbox_tile = MapboxVectorTile::try_new(write_tile(&mut tile, true), None).unwrap();
+        let layer = mapbox_tile.layer("polys").unwrap();
+        let mut feature = layer.feature(0).unwrap();
+        assert_eq!(feature.load_polys(), vec![vec![VectorLineWithOffset::new(0.0, ring)]]);
+    }
+
+    /// Write a single polygon feature made of `rings` to a Mapbox tile and read its polygons back
+    fn read_polys(rings: Vec<Vec<Point>>) -> Vec<Vec<VectorLineWithOffset>> {
+        let mut tile = BaseVectorTile::default();
+        let mut layer = BaseVectorLayer::new("polys".to_string(), 4_096.into(), vec![], None, None);
+        layer.add_feature(BaseVectorFeature::BaseVectorPolysFeature(BaseVectorPolysFeature::new(
+            None,
+            vec![rings.into_iter().map(|ring| VectorLineWithOffset::new(0.0, ring)).collect()],
+            Value::default(),
+            None,
+            vec![],
+            vec![],
+        )));
+        tile.add_layer(layer);
+
+        let mut mapbox_tile = MapboxVectorTile::try_new(write_tile(&mut tile, true), None).unwrap();
+        let layer = mapbox_tile.layer("polys").unwrap();
+        layer.feature(0).unwrap().load_polys()
+    }
+
+    #[test]
+    fn test_zero_area_rings_only() {
+        // every ring is skipped, so the feature has no polygons rather than looping forever
+        let line = vec![Point::new(1, 1), Point::new(2, 2), Point::new(1, 1)];
+        let point = vec![Point::new(3, 3), Point::new(3, 3)];
+        assert_eq!(read_polys(vec![line, point]), Vec::<Vec<VectorLineWithOffset>>::new());
+    }
+
+    #[test]
+    fn test_zero_area_ring_between_outer_and_hole() {
+        let outer = vec![Point::new(0, 0), Point::new(8, 0), Point::new(8, 8), Point::new(0, 0)];
+        let line = vec![Point::new(1, 1), Point::new(2, 2), Point::new(1, 1)];
+        let hole = vec![Point::new(2, 1), Point::new(6, 5), Point::new(6, 1), Point::new(2, 1)];
+        assert_eq!(
+            read_polys(vec![outer.clone(), line, hole.clone()]),
+            vec![vec![VectorLineWithOffset::new(0.0, outer), VectorLineWithOffset::new(0.0, hole)]]
+        );
+    }
+
+    #[test]
+    fn test_ring_area_past_i32() {
+        // twice the area of the outer ring is 2 * 10^10, which does not fit in an i32
+        let outer = vec![
+            Point::new(0, 0),
+            Point::new(100_000, 0),
+            Point::new(100_000, 100_000),
+            Point::new(0, 100_000),
+            Point::new(0, 0),
+        ];
+        let hole = vec![
+            Point::new(10, 10),
+            Point::new(10, 90_000),
+            Point::new(90_000, 90_000),
+            Point::new(90_000, 10),
+            Point::new(10, 10),
+        ];
+        assert_eq!(
+            read_polys(vec![outer.clone(), hole.clone(), outer.clone()]),
+            vec![
+                vec![
+                    VectorLineWithOffset::new(0.0, outer.clone()),
+                    VectorLineWithOffset::new(0.0, hole)
+                ],
+                vec![VectorLineWithOffset::new(0.0, outer)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_try_new_coordinate_overflow() {
+        // a point feature moving to i32::MAX and then one step past it
+        let feature =
+            [0x18, 0x01, 0x22, 0x09, 0x11, 0xFE, 0xFF, 0xFF, 0xFF, 0x0F, 0x00, 0x02, 0x00];
+        let mut layer = vec![0x0A, 0x01, b'a', 0x12, feature.len() as u8];
+        layer.extend_from_slice(&feature);
+        let mut data = vec![0x1A, layer.len() as u8];
+        data.extend(layer);
+        assert_eq!(
+            MapboxVectorTile::try_new(data, None).unwrap_err(),
+            DecodeError::Malformed {
+                message: ProtoMessage::MapboxFeature,
+                tag: Some(4),
+                offset: 18,
+                reason: "coordinate overflow",
+            }
+        );
+    }
+
+    #[test]
+    fn test_try_write_tile() {
+        let mut tile = BaseVectorTile::default();
+        let mut layer =
+            BaseVectorLayer::new("points".to_string(), 4_096.into(), vec![], None, None);
+        layer.add_feature(BaseVectorFeature::BaseVectorPointsFeature(
+            BaseVectorPointsFeature::new(None, vec![Point::new(1, 2)], Value::default(), None),
+        ));
+        tile.add_layer(layer);
+        let bytes = try_write_tile(&mut tile, true).unwrap();
+        assert_eq!(bytes, write_tile(&mut tile, true));
+
+        tile.layers.get_mut("points").unwrap().add_feature(
+            BaseVectorFeature::BaseVectorPoints3DFeature(BaseVectorPoints3DFeature::new(
+                None,
+                vec![Point3D::new(1, 2, 3)],
+                Value::default(),
+                None,
+            )),
+        );
+        let err = try_write_tile(&mut tile, true).unwrap_err();
+        assert_eq!(
+            err,
+            EncodeError::UnsupportedGeometry {
+                layer: "points".to_string(),
+                feature: 1,
+                feature_type: FeatureType::Points3D,
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "unsupported geometry type Points3D in feature 1 of layer \"points\""
+        );
+    }
 }
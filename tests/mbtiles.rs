@@ -0,0 +1,130 @@
+#[cfg(all(test, feature = "mbtiles"))]
+mod tests {
+    extern crate alloc;
+
+    use crate::{point_tile as tile, temp_file};
+    use open_vector_tile::{
+        VectorFeatureMethods, VectorLayer, VectorLayerMethods, VectorTile,
+        base::BaseVectorTile,
+        mbtiles::{MBTiles, MBTilesError, TileFormat},
+    };
+    use std::fs;
+
+    #[test]
+    fn test_mbtiles_ovt() {
+        let path = temp_file("ovt.mbtiles");
+        let mut archive = MBTiles::create(&path).unwrap();
+        archive.set_metadata("name", "test").unwrap();
+        archive
+            .write_tiles(
+                vec![(0, 0, 0, tile("water", 1)), (2, 1, 0, tile("roads", 2))],
+                TileFormat::Ovt,
+            )
+            .unwrap();
+        drop(archive);
+
+        let archive = MBTiles::open(&path).unwrap();
+        let metadata = archive.metadata().unwrap();
+        assert_eq!(metadata.get("name").unwrap(), "test");
+        assert_eq!(metadata.get("format").unwrap(), "ovt");
+        assert_eq!(metadata.get("minzoom").unwrap(), "0");
+        assert_eq!(metadata.get("maxzoom").unwrap(), "2");
+        let json: serde_json::Value = serde_json::from_str(metadata.get("json").unwrap()).unwrap();
+        assert_eq!(json["vector_layers"][0]["id"], "roads");
+        assert_eq!(json["vector_layers"][0]["minzoom"], 2);
+        assert_eq!(json["vector_layers"][1]["id"], "water");
+
+        // the archive stores TMS rows and gzip compressed tiles
+        let (row, data): (u32, Vec<u8>) = archive
+            .connection()
+            .query_row("SELECT tile_row, tile_data FROM tiles WHERE zoom_level = 2", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(row, 3);
+        assert_eq!(data[..2], [0x1f, 0x8b]);
+
+        let mut vector_tile = archive.tile(2, 1, 0).unwrap().unwrap();
+        let Some(VectorLayer::Open(roads)) = vector_tile.layer("roads") else {
+            panic!("expected an open layer")
+        };
+        assert_eq!(roads.feature(0).unwrap().id(), Some(2));
+        assert!(archive.tile(2, 1, 3).unwrap().is_none());
+        assert!(archive.tile_data(0, 0, 0).unwrap().is_some());
+
+        // tiles past zoom 30 or outside of their zoom are rejected instead of overflowing
+        for (zoom, x, y) in [(31, 0, 0), (255, 0, 0), (2, 4, 0), (2, 0, 4), (30, 0, 1 << 30)] {
+            let invalid = |res: Result<(), MBTilesError>| {
+                res.unwrap_err().to_string() == format!("mbtiles: invalid tile {zoom}/{x}/{y}")
+            };
+            assert!(invalid(archive.tile_data(zoom, x, y).map(|_| ())));
+            assert!(invalid(archive.put_tile_data(zoom, x, y, &[])));
+        }
+        let mut archive = MBTiles::open(&path).unwrap();
+        let res = archive.write_tiles(vec![(1, 0, 2, tile("water", 1))], TileFormat::Ovt);
+        assert!(matches!(res, Err(MBTilesError::InvalidTile(1, 0, 2))));
+        let max = (1 << 30) - 1;
+        archive.put_tile_data(30, max, max, &[1, 2, 3]).unwrap();
+        assert_eq!(archive.tile_data(30, max, max).unwrap(), Some(vec![1, 2, 3]));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_mbtiles_pbf() {
+        let path = temp_file("pbf.mbtiles");
+        let mut archive = MBTiles::create(&path).unwrap();
+        archive.write_tiles(vec![(1, 1, 0, tile("water", 7))], TileFormat::Pbf).unwrap();
+        assert_eq!(archive.metadata().unwrap().get("format").unwrap(), "pbf");
+        let mut vector_tile = archive.tile(1, 1, 0).unwrap().unwrap();
+        let Some(VectorLayer::Mapbox(water)) = vector_tile.layer("water") else {
+            panic!("expected a mapbox layer")
+        };
+        assert!(!water.is_s2());
+        assert_eq!(water.feature(0).unwrap().id(), Some(7));
+
+        // uncompressed tiles are read as they are
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        archive.put_tile_data(12, 1143, 1497, &data).unwrap();
+        assert_eq!(archive.tile_data(12, 1143, 1497).unwrap().unwrap(), data);
+        let mut vector_tile = archive.tile(12, 1143, 1497).unwrap().unwrap();
+        assert_eq!(vector_tile.layer("road").unwrap().len(), 247);
+
+        // tiles that do not decode are reported
+        archive.put_tile_data(0, 0, 0, &[0x22, 0x10, 0x01]).unwrap();
+        assert!(archive.tile(0, 0, 0).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_mbtiles_fixture_roundtrip() {
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let mut original = VectorTile::new(data, None);
+        let base = BaseVectorTile::from(&mut original);
+
+        let path = temp_file("fixture.mbtiles");
+        let mut archive = MBTiles::create(&path).unwrap();
+        archive.write_tiles(vec![(12, 1143, 1497, base)], TileFormat::Ovt).unwrap();
+        let json = archive.metadata().unwrap().get("json").unwrap().clone();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["vector_layers"].as_array().unwrap().len(), original.layers.len());
+
+        let mut tile = archive.tile(12, 1143, 1497).unwrap().unwrap();
+        assert_eq!(tile.layers.len(), original.layers.len());
+        for (name, layer) in original.layers.iter_mut() {
+            let read = tile.layer(name).unwrap();
+            assert_eq!(read.len(), layer.len(), "{name}");
+            for i in 0..layer.len() {
+                let mut expected = layer.feature(i).unwrap();
+                let mut feature = read.feature(i).unwrap();
+                assert_eq!(feature.id(), expected.id(), "{name} {i}");
+                assert_eq!(feature.get_type(), expected.get_type(), "{name} {i}");
+                assert_eq!(feature.properties(), expected.properties(), "{name} {i}");
+                assert_eq!(feature.load_geometry(), expected.load_geometry(), "{name} {i}");
+            }
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,374 @@
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use open_vector_tile::{
+        Point, VectorFeatureMethods, VectorGeometry, VectorLayerFeatures, VectorLayerMethods,
+        VectorLineWithOffset,
+        base::{
+            BaseVectorFeature, BaseVectorLayer, BaseVectorLinesFeature, BaseVectorPointsFeature,
+            BaseVectorPolysFeature, BaseVectorTile,
+        },
+        error::EncodeError,
+        mlt::{
+            ColumnType, GeometryType, MltError, MltVectorTile, ScalarType, TileSetMetadata,
+            write_tile,
+        },
+        open::{Extent, FeatureType},
+    };
+    use s2json::{MapboxProperties, PrimitiveValue, Properties, ValueType};
+    use std::fs;
+
+    fn fixture(name: &str) -> (Vec<u8>, TileSetMetadata) {
+        let path = format!("./benchmarks/data/bing/mlt/{}.mlt", name);
+        let metadata = fs::read(format!("{}.meta.pbf", path)).unwrap();
+        (fs::read(path).unwrap(), TileSetMetadata::from_bytes(&metadata).unwrap())
+    }
+
+    fn line(points: &[(i32, i32)]) -> VectorLineWithOffset {
+        let points: Vec<Point> = points.iter().map(|(x, y)| Point::new(*x, *y)).collect();
+        (&points[..]).into()
+    }
+
+    fn props(pairs: &[(&str, ValueType)]) -> Properties {
+        let mut properties = Properties::default();
+        for (key, value) in pairs {
+            properties.insert((*key).into(), value.clone());
+        }
+        properties
+    }
+
+    fn base_tile() -> BaseVectorTile {
+        let features = vec![
+            BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                Some(1),
+                vec![Point::new(10, 20)],
+                props(&[("name", "a".into()), ("rank", ValueType::from(1_u64))]),
+                None,
+            )),
+            BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                None,
+                vec![Point::new(1, 2), Point::new(3, 4)],
+                props(&[("name", "b".into()), ("rank", ValueType::from(-2_i64))]),
+                None,
+            )),
+            BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                Some(u32::MAX as u64 + 1),
+                vec![Point::new(-5, 4_100)],
+                props(&[("name", "a".into()), ("flag", true.into()), ("size", 1.5_f64.into())]),
+                None,
+            )),
+        ];
+        let mut tile = BaseVectorTile::default();
+        tile.add_layer(BaseVectorLayer::new(
+            "points".into(),
+            Extent::Extent4096,
+            features,
+            None,
+            None,
+        ));
+
+        let features = vec![
+            BaseVectorFeature::BaseVectorLinesFeature(BaseVectorLinesFeature::new(
+                None,
+                vec![line(&[(0, 0), (10, 10), (20, 0)])],
+                props(&[("kind", "road".into())]),
+                None,
+            )),
+            BaseVectorFeature::BaseVectorLinesFeature(BaseVectorLinesFeature::new(
+                None,
+                vec![line(&[(5, 5), (6, 6)]), line(&[(7, 7), (8, 8), (9, 9)])],
+                props(&[]),
+                None,
+            )),
+        ];
+        tile.add_layer(BaseVectorLayer::new(
+            "lines".into(),
+            Extent::Extent4096,
+            features,
+            None,
+            None,
+        ));
+
+        let features = vec![
+            BaseVectorFeature::BaseVectorPolysFeature(BaseVectorPolysFeature::new(
+                Some(3),
+                vec![vec![
+                    line(&[(0, 0), (100, 0), (100, 100), (0, 100), (0, 0)]),
+                    line(&[(10, 10), (10, 20), (20, 20), (20, 10), (10, 10)]),
+                ]],
+                props(&[("area", 1.25_f32.into())]),
+                None,
+                vec![],
+                vec![],
+            )),
+            BaseVectorFeature::BaseVectorPolysFeature(BaseVectorPolysFeature::new(
+                Some(4),
+                vec![
+                    vec![line(&[(200, 200), (300, 200), (300, 300), (200, 200)])],
+                    vec![line(&[(400, 400), (500, 400), (500, 500), (400, 400)])],
+                ],
+                props(&[("area", 2.5_f32.into())]),
+                None,
+                vec![],
+                vec![],
+            )),
+            // lines in a layer with polygons take their lengths from the rings
+            BaseVectorFeature::BaseVectorLinesFeature(BaseVectorLinesFeature::new(
+                Some(5),
+                vec![line(&[(1, 1), (2, 2)])],
+                props(&[]),
+                None,
+            )),
+        ];
+        tile.add_layer(BaseVectorLayer::new(
+            "polygons".into(),
+            Extent::Extent4096,
+            features,
+            None,
+            None,
+        ));
+        tile
+    }
+
+    #[test]
+    fn test_metadata() {
+        let (_, metadata) = fixture("4_8_5");
+        let island = metadata.feature_tables.iter().find(|t| t.name == "island").unwrap();
+        assert_eq!(island.columns[0].name, "id");
+        assert_eq!(island.columns[0].r#type, ColumnType::Scalar(ScalarType::UInt32));
+        assert!(!island.columns[0].nullable);
+        assert_eq!(island.columns[1].r#type, ColumnType::Geometry);
+        let name = island.columns.iter().find(|c| c.name == "name").unwrap();
+        assert_eq!(name.r#type, ColumnType::Scalar(ScalarType::String));
+        assert!(name.nullable);
+
+        // the metadata survives a roundtrip
+        assert_eq!(TileSetMetadata::from_bytes(&metadata.to_bytes()).unwrap(), metadata);
+    }
+
+    #[test]
+    fn test_decode_fixture() {
+        let (data, metadata) = fixture("4_8_5");
+        let mut tile = MltVectorTile::new(&data, &metadata);
+
+        let water = tile.layer("water_feature").unwrap();
+        assert_eq!(water.extent(), 4_096);
+        assert_eq!(water.len(), 9);
+        let types: Vec<GeometryType> = water.features.iter().map(|f| f.r#type).collect();
+        assert_eq!(types[..7], [GeometryType::MultiPolygon; 7]);
+        assert_eq!(types[7..], [GeometryType::Point; 2]);
+        let bkt: Vec<_> =
+            water.features[..4].iter().map(|f| f.properties.get("bkt").cloned()).collect();
+        assert_eq!(bkt, [1_222, 1_241, 2_030, 1_228].map(|v| Some(PrimitiveValue::I64(v))));
+
+        let island = tile.layer("island").unwrap();
+        assert_eq!(island.len(), 1);
+        let mut feature = island.feature(0).unwrap();
+        assert_eq!(feature.get_type(), FeatureType::Points);
+        assert_eq!(feature.load_points(), vec![Point::new(1_880, 132)]);
+        let properties = &island.features[0].properties;
+        assert_eq!(properties.get("name"), Some(&PrimitiveValue::String("Fyn".into())));
+        assert_eq!(properties.get("gt"), Some(&PrimitiveValue::String("pg".into())));
+        assert_eq!(properties.get("max-text-width"), Some(&PrimitiveValue::F32(100.0)));
+        assert_eq!(properties.get("bkt"), Some(&PrimitiveValue::I64(5)));
+        let Some(PrimitiveValue::F32(name_f)) = properties.get("name-f") else { panic!() };
+        assert!((name_f - 10.2667).abs() < 1e-3);
+
+        let pattern = tile.layer("water_pattern_area").unwrap();
+        let mut feature = pattern.feature(0).unwrap();
+        assert!(feature.is_polygons());
+        let polys = feature.load_polys();
+        assert_eq!(polys.len(), 1);
+        // the ring is closed when decoded
+        let ring = &polys[0][0].geometry;
+        assert_eq!(ring.len(), 5);
+        assert_eq!(ring[0], Point::new(3_711, 2_453));
+        assert_eq!(ring[0], ring[4]);
+        assert_eq!(
+            pattern.features[0].properties.get("st-fill-pattern"),
+            Some(&PrimitiveValue::String("non_perennial".into()))
+        );
+
+        // morton encoded vertices
+        let road = tile.layer("road").unwrap();
+        assert_eq!(road.len(), 155);
+        assert!(
+            road.features.iter().all(|f| matches!(
+                f.r#type,
+                GeometryType::LineString | GeometryType::MultiLineString
+            ))
+        );
+        assert!(road.features.iter().any(|f| {
+            f.properties.get("st-et")
+                == Some(&PrimitiveValue::String("controlledAccessHighway".into()))
+        }));
+
+        let countries = tile.layer("country_region").unwrap();
+        assert_eq!(countries.len(), 25);
+        let names: Vec<_> = countries
+            .features
+            .iter()
+            .filter_map(|f| match f.properties.get("name") {
+                Some(PrimitiveValue::String(name)) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(names.contains(&"United Kingdom"));
+        assert!(names.contains(&"Switzerland"));
+    }
+
+    #[test]
+    fn test_decode_all_fixtures() {
+        let mut layers = 0;
+        for entry in fs::read_dir("./benchmarks/data/bing/mlt").unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap();
+            let Some(name) = name.strip_suffix(".mlt") else { continue };
+            let (data, metadata) = fixture(name);
+            let mut tile = MltVectorTile::try_new(&data, &metadata).unwrap();
+            for layer in tile.layers.values_mut() {
+                for mut feature in layer.features() {
+                    // every vertex is inside the tile's buffer
+                    let geometry = feature.load_geometry();
+                    let points = match geometry {
+                        VectorGeometry::VectorPoints(points) => points,
+                        VectorGeometry::VectorLines(lines) => {
+                            lines.into_iter().flat_map(|l| l.geometry).collect()
+                        }
+                        VectorGeometry::VectorPolys(polys) => {
+                            polys.into_iter().flatten().flat_map(|l| l.geometry).collect()
+                        }
+                        _ => panic!("unexpected geometry type"),
+                    };
+                    assert!(!points.is_empty());
+                    assert!(points.iter().all(|p| p.x.abs() < 8_192 && p.y.abs() < 8_192));
+                }
+                layers += 1;
+            }
+        }
+        assert_eq!(layers, 159);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let tile = base_tile();
+        let (data, metadata) = write_tile(&tile).unwrap();
+        let metadata = TileSetMetadata::from_bytes(&metadata.to_bytes()).unwrap();
+        let mut mlt = MltVectorTile::new(&data, &metadata);
+        assert_eq!(mlt.layers.len(), 3);
+
+        // ids are stored as 64 bit values when any is too large for 32 bits
+        let schema = metadata.feature_tables.iter().find(|t| t.name == "points").unwrap();
+        assert_eq!(schema.columns[0].r#type, ColumnType::Scalar(ScalarType::UInt64));
+        assert!(schema.columns[0].nullable);
+        // unsigned and signed values share a signed column, flags are only on one feature
+        let rank = schema.columns.iter().find(|c| c.name == "rank").unwrap();
+        assert_eq!(rank.r#type, ColumnType::Scalar(ScalarType::Int64));
+        assert!(rank.nullable);
+        let lines = metadata.feature_tables.iter().find(|t| t.name == "lines").unwrap();
+        assert!(lines.columns.iter().all(|c| c.name != "id"));
+
+        let points = mlt.layer("points").unwrap();
+        let ids: Vec<_> = points.features.iter().map(|f| f.id).collect();
+        assert_eq!(ids, vec![Some(1), None, Some(u32::MAX as u64 + 1)]);
+        let types: Vec<_> = points.features.iter().map(|f| f.r#type).collect();
+        assert_eq!(types, vec![GeometryType::Point, GeometryType::MultiPoint, GeometryType::Point]);
+        assert_eq!(points.features[0].properties.get("rank"), Some(&PrimitiveValue::I64(1)));
+        assert_eq!(points.features[1].properties.get("rank"), Some(&PrimitiveValue::I64(-2)));
+        assert_eq!(points.features[2].properties.get("rank"), None);
+        assert_eq!(points.features[2].properties.get("flag"), Some(&PrimitiveValue::Bool(true)));
+        assert_eq!(points.features[2].properties.get("size"), Some(&PrimitiveValue::F64(1.5)));
+
+        // decoding and converting back gives the original layers
+        let base = BaseVectorTile::from(&mut mlt);
+        for (name, layer) in base.layers.iter() {
+            let original = &tile.layers[name];
+            assert_eq!(layer.extent, original.extent);
+            assert_eq!(layer.features.len(), original.features.len());
+            for (feature, expected) in layer.features.iter().zip(original.features.iter()) {
+                assert_eq!(feature.id(), expected.id());
+                assert_eq!(feature.load_geometry(), expected.load_geometry());
+                let mut properties: MapboxProperties = feature.properties().clone().into();
+                let mut expected: MapboxProperties = expected.properties().clone().into();
+                // signed columns widen unsigned values
+                for properties in [&mut properties, &mut expected] {
+                    if let Some(PrimitiveValue::U64(v)) = properties.get("rank").cloned() {
+                        properties.insert("rank".into(), PrimitiveValue::I64(v as i64));
+                    }
+                }
+                assert_eq!(properties, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixture_roundtrip() {
+        let (data, metadata) = fixture("4_8_5");
+        let mut tile = MltVectorTile::new(&data, &metadata);
+        let base = BaseVectorTile::from(&mut tile);
+        let (data, metadata) = write_tile(&base).unwrap();
+        let mut decoded = MltVectorTile::new(&data, &metadata);
+        assert_eq!(decoded.layers.len(), tile.layers.len());
+        for (name, layer) in tile.layers.iter_mut() {
+            let other = decoded.layer(name).unwrap();
+            assert_eq!(layer.len(), other.len());
+            for (a, b) in layer.features.iter_mut().zip(other.features.iter_mut()) {
+                assert_eq!(a.load_geometry(), b.load_geometry());
+                assert_eq!(a.properties, b.properties);
+                assert_eq!(a.id, b.id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let (data, metadata) = fixture("4_8_5");
+        // truncated data
+        let err = MltVectorTile::try_new(&data[..data.len() - 3], &metadata).unwrap_err();
+        assert!(matches!(err, MltError::UnexpectedEof { .. } | MltError::Malformed { .. }));
+        let err = MltVectorTile::try_new(&data[..3], &metadata).unwrap_err();
+        assert_eq!(err, MltError::UnexpectedEof { offset: 3 });
+        assert_eq!(err.to_string(), "mlt: unexpected end of data at byte 3");
+
+        // a layer referencing a feature table the metadata does not have
+        let err = MltVectorTile::try_new(&data, &TileSetMetadata::default()).unwrap_err();
+        assert!(matches!(err, MltError::UnknownFeatureTable(_)));
+        assert!(err.to_string().starts_with("mlt: unknown feature table"));
+
+        let err = MltVectorTile::try_new(&[2, 0, 0, 0, 0], &metadata).unwrap_err();
+        assert_eq!(err, MltError::Unsupported { offset: 0, reason: "layer version" });
+        assert_eq!(err.to_string(), "mlt: unsupported layer version at byte 0");
+
+        assert!(TileSetMetadata::from_bytes(&[0x12, 0x05, 0x0a]).is_err());
+    }
+
+    #[test]
+    fn test_write_errors() {
+        let mut tile = BaseVectorTile::default();
+        let features = vec![
+            BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                None,
+                vec![Point::new(0, 0)],
+                props(&[("kind", "a".into())]),
+                None,
+            )),
+            BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                None,
+                vec![Point::new(0, 0)],
+                props(&[("kind", ValueType::from(1_u64))]),
+                None,
+            )),
+        ];
+        tile.add_layer(BaseVectorLayer::new(
+            "mixed".into(),
+            Extent::Extent4096,
+            features,
+            None,
+            None,
+        ));
+        let err = write_tile(&tile).unwrap_err();
+        assert!(matches!(err, EncodeError::ShapeMismatch { feature: 1, .. }));
+        assert_eq!(err.layer(), "mixed");
+    }
+}
@@ -1,5 +1,91 @@
 pub mod base_vector_tile;
+pub mod cli;
+pub mod compression;
+pub mod earcut;
+pub mod filter;
 pub mod geometry;
 pub mod mapbox;
+pub mod mbtiles;
+pub mod mlt;
 pub mod open_vector_tile;
+pub mod pmtiles;
+pub mod projection;
+pub mod query;
+pub mod s2tiles;
+pub mod spatial_index;
+pub mod tile_id;
+pub mod tiler;
 pub mod util;
+pub mod validate;
+
+use ::open_vector_tile::{
+    Point,
+    base::{BaseVectorFeature, BaseVectorLayer, BaseVectorPointsFeature, BaseVectorTile},
+    open::Extent,
+};
+use s2json::Properties;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+/// The system allocator, counting the allocations made by each thread so a test can check what
+/// a call allocates without the tests running beside it interfering
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Run `f`, returning its result and the number of allocations it made on this thread
+pub fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let res = f();
+    (res, ALLOCATIONS.with(Cell::get) - before)
+}
+
+/// A tile with a single point feature with the `id` in the layer `name`
+pub fn point_tile(name: &str, id: u64) -> BaseVectorTile {
+    let feature = BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+        Some(id),
+        vec![Point::new(10, 20)],
+        Properties::default(),
+        None,
+    ));
+    let mut tile = BaseVectorTile::default();
+    tile.add_layer(BaseVectorLayer::new(
+        name.into(),
+        Extent::Extent4096,
+        vec![feature],
+        None,
+        None,
+    ));
+    tile
+}
+
+/// A path in the temp directory that is unique to the test run, removed if a previous run left it
+#[cfg(any(feature = "mbtiles", feature = "pmtiles", feature = "s2tiles"))]
+pub fn temp_file(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("ovt-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
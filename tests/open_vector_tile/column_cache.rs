@@ -1,7 +1,12 @@
 #[cfg(test)]
 mod tests {
     extern crate alloc;
-    use open_vector_tile::open::OColumnName;
+    use open_vector_tile::{
+        Point, Point3D,
+        open::{ColumnCacheReader, ColumnCacheWriter, ColumnValue, OColumnName},
+    };
+    use pbf::Protobuf;
+    use s2json::{BBOX, BBox};
 
     #[test]
     fn test_column_name() {
@@ -29,4 +34,88 @@ mod tests {
         assert_eq!(OColumnName::from(10), OColumnName::BBox);
         assert_eq!(OColumnName::from(11), OColumnName::String);
     }
+
+    #[test]
+    fn test_lazy_reader() {
+        let mut cache = ColumnCacheWriter::default();
+        let name = cache.add_string("name".to_string());
+        let u = cache.add_u64(22);
+        let points = cache.add_points(vec![Point::new(1, 2), Point::new(-3, 4)]);
+        let points_2 = cache.add_points(vec![Point::new(100, 200)]);
+        let points_3d = cache.add_points_3d(vec![Point3D::new(1, 2, 3), Point3D::new(4, 5, -6)]);
+        let indices = cache.add_indices(vec![0, 5, 2, 7]);
+        let shapes =
+            cache.add_shapes(vec![ColumnValue::Number(2), ColumnValue::Column(u), 3.into()]);
+        let bbox = cache.add_bbox(BBOX::BBox(BBox::new(0.0, 0.0, 1.0, 1.0)));
+
+        let mut pbf = Protobuf::new();
+        pbf.write_fields(&cache);
+        let data = pbf.take();
+
+        let mut eager = ColumnCacheReader::new();
+        Protobuf::from_input(data.clone()).read_fields(&mut eager, None);
+        let lazy = ColumnCacheReader::from_input(data);
+
+        for reader in [&eager, &lazy] {
+            assert_eq!(reader.get_string(name.borrow().index), "name");
+            assert_eq!(reader.get_unsigned(0), 22);
+            // read out of order and more than once
+            for _ in 0..2 {
+                assert_eq!(reader.get_points(points_2), vec![Point::new(100, 200)]);
+                assert_eq!(reader.get_points(points), vec![Point::new(1, 2), Point::new(-3, 4)]);
+                assert_eq!(
+                    reader.get_points_3d(points_3d),
+                    vec![Point3D::new(1, 2, 3), Point3D::new(4, 5, -6)]
+                );
+                assert_eq!(reader.get_indices(indices), vec![0, 5, 2, 7]);
+                assert_eq!(reader.get_shapes(shapes), vec![2, 0, 3]);
+            }
+        }
+        assert_eq!(lazy.get_bbox(bbox), eager.get_bbox(bbox));
+    }
+
+    #[test]
+    fn test_sorted_columns() {
+        let mut cache = ColumnCacheWriter::default();
+        let once = cache.add_u64(5);
+        let twice = cache.add_u64(900);
+        cache.add_u64(900);
+        let tie = cache.add_u64(3);
+        let rare = cache.add_string("rare".to_string());
+        let common = cache.add_string("common".to_string());
+        cache.add_string("common".to_string());
+        let shape = cache.add_shapes(vec![
+            ColumnValue::Column(once.clone()),
+            ColumnValue::Column(twice.clone()),
+            ColumnValue::Column(tie.clone()),
+            ColumnValue::Column(rare.clone()),
+            ColumnValue::Column(common.clone()),
+        ]);
+        // the same values reuse the shape
+        let values = vec![
+            cache.add_u64(5).into(),
+            cache.add_u64(900).into(),
+            cache.add_u64(3).into(),
+            rare.clone().into(),
+            common.clone().into(),
+        ];
+        let shape_2 = cache.add_shapes(values);
+        assert_eq!(shape, shape_2);
+
+        let mut pbf = Protobuf::new();
+        pbf.write_fields(&cache);
+        let reader = ColumnCacheReader::from_input(pbf.take());
+
+        // most reused first, ties settled by value
+        assert_eq!(twice.borrow().index, 0);
+        assert_eq!(tie.borrow().index, 1);
+        assert_eq!(once.borrow().index, 2);
+        assert_eq!(common.borrow().index, 0);
+        assert_eq!(rare.borrow().index, 1);
+        assert_eq!(reader.get_unsigned(0), 900);
+        assert_eq!(reader.get_unsigned(1), 3);
+        assert_eq!(reader.get_unsigned(2), 5);
+        assert_eq!(reader.get_string(0), "common");
+        assert_eq!(reader.get_shapes(shape), vec![2, 0, 1, 1, 0]);
+    }
 }
@@ -1,8 +1,9 @@
 #[cfg(test)]
 mod tests {
     extern crate alloc;
-    use open_vector_tile::open::{
-        Extent, GridData, convert_mapbox_elevation_data, convert_terrarium_elevation_data,
+    use open_vector_tile::{
+        DecodeError, ProtoMessage,
+        open::{Extent, GridData, convert_mapbox_elevation_data, convert_terrarium_elevation_data},
     };
     use pbf::Protobuf;
 
@@ -51,4 +52,38 @@ mod tests {
         assert_eq!(elevation_res.max, 4.0);
         assert_eq!(elevation_res.data, vec![-1.0, 1.9998779296875, 3.000244140625, 4.0]);
     }
+
+    #[test]
+    fn test_try_from() {
+        let grid = GridData::new(
+            "elevation".to_owned(),
+            Extent::Extent8192,
+            2.0,
+            0.0,
+            0.0,
+            vec![-1.0, 2.0, 3.0, 4.0],
+        );
+        let mut pb = Protobuf::new();
+        pb.write_fields(&grid);
+        let bytes = pb.take();
+
+        let decoded = GridData::try_from(&bytes[..]).unwrap();
+        assert_eq!(decoded.name, "elevation");
+        assert_eq!(decoded.data, vec![-1.0, 1.9998779296875, 3.000244140625, 4.0]);
+
+        // invalid wire type
+        assert_eq!(
+            GridData::try_from(&[0x0B][..]),
+            Err(DecodeError::InvalidWireType {
+                message: ProtoMessage::GridData,
+                tag: 1,
+                wire_type: 3,
+                offset: 0,
+            })
+        );
+        // every truncation is an error rather than a panic
+        for len in 0..bytes.len() {
+            let _ = GridData::try_from(&bytes[..len]);
+        }
+    }
 }
@@ -1,7 +1,10 @@
 #[cfg(test)]
 mod tests {
     extern crate alloc;
-    use open_vector_tile::open::{ImageData, ImageType};
+    use open_vector_tile::{
+        DecodeError, ProtoMessage,
+        open::{ImageData, ImageType},
+    };
     use pbf::Protobuf;
     use std::panic::{self, AssertUnwindSafe};
 
@@ -78,4 +81,35 @@ mod tests {
         let result = panic::catch_unwind(AssertUnwindSafe(|| pb.read_varint::<ImageType>()));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_try_from() {
+        let image =
+            ImageData::new("test".to_string(), ImageType::WEBP, 2, 3, Vec::from([1, 2, 3, 4]));
+        let mut pb = Protobuf::new();
+        pb.write_fields(&image);
+        let bytes = pb.take();
+
+        assert_eq!(ImageData::try_from(&bytes[..]), Ok(image));
+        // truncated
+        assert!(matches!(
+            ImageData::try_from(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEof { message: ProtoMessage::ImageData, tag: Some(5), .. })
+        ));
+        // bad image type
+        assert_eq!(
+            ImageData::try_from(&[0x08, 0x09][..]),
+            Err(DecodeError::InvalidEnum {
+                message: ProtoMessage::ImageData,
+                tag: Some(1),
+                value: 9,
+                offset: 1,
+            })
+        );
+        // unknown tag
+        assert_eq!(
+            ImageData::try_from(&[0x30, 0x01][..]),
+            Err(DecodeError::UnknownTag { message: ProtoMessage::ImageData, tag: 6, offset: 1 })
+        );
+    }
 }
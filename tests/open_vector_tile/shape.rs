@@ -4,7 +4,7 @@ mod tests {
 
     use open_vector_tile::open::{
         ColumnCacheReader, ColumnCacheWriter, ColumnValue, ShapeDefinition, ShapePair,
-        ShapeToStore, ValueToStore,
+        ShapeToStore, ValueMismatch, ValueToStore,
     };
     use pbf::Protobuf;
     use s2json::{
@@ -75,35 +75,10 @@ mod tests {
         shape.encode(&mut shape_store, &mut cache);
 
         assert_eq!(
-            shape_store,
+            shape_store.iter().map(ColumnValue::index).collect::<Vec<usize>>(),
             vec![
-                ColumnValue::Number(17),
-                ColumnValue::Number(0),
-                ColumnValue::Number(10),
-                ColumnValue::Number(1),
-                ColumnValue::Number(0),
-                ColumnValue::Number(2),
-                ColumnValue::Number(2),
-                ColumnValue::Number(17),
-                ColumnValue::Number(3),
-                ColumnValue::Number(18),
-                ColumnValue::Number(4),
-                ColumnValue::Number(22),
-                ColumnValue::Number(5),
-                ColumnValue::Number(26),
-                ColumnValue::Number(6),
-                ColumnValue::Number(9),
-                ColumnValue::Number(7),
-                ColumnValue::Number(10),
-                ColumnValue::Number(8),
-                ColumnValue::Number(2),
-                ColumnValue::Number(3),
-                ColumnValue::Number(0),
-                ColumnValue::Number(9),
-                ColumnValue::Number(9),
-                ColumnValue::Number(18),
-                ColumnValue::Number(10),
-                ColumnValue::Number(22)
+                17, 0, 10, 1, 0, 2, 2, 17, 3, 18, 4, 22, 5, 26, 6, 9, 7, 10, 8, 2, 3, 0, 9, 9, 18,
+                10, 22
             ]
         );
 
@@ -123,16 +98,17 @@ mod tests {
         pbf_read.read_message(&mut reader);
 
         let mut shape_read = reader.get_shapes(shape_index);
+        // "d" is used twice so it is sorted to the front of the string column
         assert_eq!(
             shape_read,
             vec![
-                17, 0, 10, 1, 0, 2, 2, 17, 3, 18, 4, 22, 5, 26, 6, 9, 7, 10, 8, 2, 3, 0, 9, 9, 18,
+                17, 1, 10, 2, 0, 2, 3, 17, 0, 18, 4, 22, 5, 26, 6, 9, 7, 10, 8, 2, 0, 0, 9, 9, 18,
                 10, 22
             ]
         );
 
         // decode shape
-        let shape_decoded = Shape::decode(&mut shape_read, &mut reader);
+        let shape_decoded = Shape::decode(&mut shape_read, &reader);
         assert_eq!(shape_decoded, shape);
     }
 
@@ -232,7 +208,7 @@ mod tests {
         pbf_read.read_message(&mut reader);
         let mut value_data = reader.get_shapes(encoded_value_index);
 
-        let decoded_value = Value::decode(&example_shape, &mut value_data, &mut reader);
+        let decoded_value = Value::decode(&example_shape, &mut value_data, &reader);
         assert_eq!(
             decoded_value,
             Value::from([
@@ -379,7 +355,7 @@ mod tests {
         let mut value_data = reader.get_shapes(encoded_value_index);
         let mut value_data_2 = reader.get_shapes(encoded_value_index_2);
 
-        let decoded_value = Value::decode(&example_shape, &mut value_data, &mut reader);
+        let decoded_value = Value::decode(&example_shape, &mut value_data, &reader);
         assert_eq!(
             decoded_value,
             Value::from([
@@ -413,7 +389,7 @@ mod tests {
             ])
         );
 
-        let decoded_value_2 = Value::decode(&example_shape, &mut value_data_2, &mut reader);
+        let decoded_value_2 = Value::decode(&example_shape, &mut value_data_2, &reader);
         assert_eq!(
             decoded_value_2,
             Value::from([
@@ -487,4 +463,43 @@ mod tests {
         // null
         assert_eq!(PrimitiveValue::from(&PrimitiveValue::Null), PrimitiveValue::Null);
     }
+
+    #[test]
+    fn try_encode_value_mismatch() {
+        let shape = serde_json::from_str::<Shape>(
+            r#"{ "a": { "b": [{ "c": "u64", "d": "bool" }] }, "e": ["string"] }"#,
+        )
+        .unwrap();
+        let try_encode = |value: &str| {
+            let value = serde_json::from_str::<Value>(value).unwrap();
+            let mut col = ColumnCacheWriter::default();
+            value.try_encode(&shape, &mut vec![], &mut col)
+        };
+
+        // missing keys, including keys of objects inside arrays, are encoded as the default
+        assert_eq!(try_encode(r#"{ "a": { "b": [{ "c": 1 }, {}] } }"#), Ok(()));
+        let err = try_encode(r#"{ "a": { "b": [{ "c": 1 }, { "d": 2 }] } }"#).unwrap_err();
+        assert_eq!(
+            err,
+            ValueMismatch::Primitive(
+                "a.b[1].d".to_string(),
+                PrimitiveShape::Bool,
+                PrimitiveValue::U64(2)
+            )
+        );
+        assert_eq!(err.path(), "a.b[1].d");
+        assert_eq!(err.to_string(), "property \"a.b[1].d\" expected Bool but found U64(2)");
+        let err = try_encode(r#"{ "e": ["x", { "y": 1 }] }"#).unwrap_err();
+        assert!(matches!(err, ValueMismatch::Structure(ref path, ..) if path == "e[1]"));
+        let err = try_encode(r#"{ "a": 1 }"#).unwrap_err();
+        assert!(matches!(err, ValueMismatch::Structure(ref path, ..) if path == "a"));
+
+        // the panicking encode names the same path
+        let value = serde_json::from_str::<Value>(r#"{ "e": [1] }"#).unwrap();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            value.encode(&shape, &mut vec![], &mut ColumnCacheWriter::default())
+        }));
+        let message = result.unwrap_err().downcast::<String>().unwrap();
+        assert_eq!(*message, "property \"e[0]\" expected String but found U64(1)");
+    }
 }
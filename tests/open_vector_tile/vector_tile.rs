@@ -2,19 +2,31 @@
 mod tests {
     extern crate alloc;
 
+    use crate::count_allocations;
     use open_vector_tile::{
-        Point, Point3D, VectorFeatureMethods, VectorGeometry, VectorLayerMethods,
-        VectorLine3DWithOffset, VectorLineWithOffset, VectorTile,
+        DecodeError, EncodeError, Point, Point3D, ProtoMessage, VectorFeatureMethods,
+        VectorFeatureRef, VectorGeometry, VectorLayerFeatures, VectorLayerMethods,
+        VectorLine3DWithOffset, VectorLineWithOffset, VectorTile, WriteOptions,
         base::{
             BaseVectorFeature, BaseVectorLayer, BaseVectorLines3DFeature, BaseVectorLinesFeature,
             BaseVectorPoints3DFeature, BaseVectorPointsFeature, BaseVectorPolys3DFeature,
-            BaseVectorPolysFeature, BaseVectorTile,
+            BaseVectorPolysFeature, BaseVectorTile, s2json_to_base,
         },
+        borrowed::BorrowedVectorTile,
+        export::ExportMetadata,
+        mapbox::MapboxVectorTile,
         open::{Extent, FeatureType, GridData, ImageData, ImageType},
-        write_tile,
+        try_write_tile, write_tile, write_tile_with,
+    };
+    use s2json::{
+        BBOX, BBox, BBox3D, Features, Geometry, PrimitiveShape, PrimitiveValue, Shape, Value,
+        ValuePrimitiveType, ValueType,
+    };
+    use std::{
+        fs,
+        panic::{self, AssertUnwindSafe},
+        ptr,
     };
-    use s2json::{BBOX, BBox, BBox3D, PrimitiveValue, Value, ValuePrimitiveType, ValueType};
-    use std::panic::{self, AssertUnwindSafe};
 
     #[test]
     fn test_open_vector_tile() {
@@ -1489,21 +1501,194 @@ mod tests {
         tile.add_layer(points_layer);
 
         let open_tile_bytes = write_tile(Some(&mut tile), None, None);
-        let mut _open_tile = VectorTile::new(open_tile_bytes, None);
-
-        // let base_tile = BaseVectorTile::from(&mut open_tile);
-
-        // assert_eq!(base_tile.layers.len(), 1);
-        // let points_layer = base_tile.layers.get("points").unwrap();
-        // assert_eq!(points_layer.features.len(), 2);
-        // assert_eq!(
-        //     points_layer.features[0],
-        //     BaseVectorFeature::BaseVectorPointsFeature(feature.clone())
-        // );
-        // assert_eq!(
-        //     points_layer.features[1],
-        //     BaseVectorFeature::BaseVectorPoints3DFeature(feature2.clone())
-        // );
+        let mut open_tile = VectorTile::new(open_tile_bytes, None);
+
+        let base_tile = BaseVectorTile::from(&mut open_tile);
+
+        assert_eq!(base_tile.layers.len(), 1);
+        let points_layer = base_tile.layers.get("points").unwrap();
+        assert_eq!(points_layer.features.len(), 2);
+        // a single point is woven into the geometry index, which has no room for its M-Value
+        assert_eq!(
+            points_layer.features[0],
+            BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                Some(1),
+                vec![Point::new(0, 0)],
+                example_value.clone(),
+                None,
+            ))
+        );
+        assert_eq!(
+            points_layer.features[1],
+            BaseVectorFeature::BaseVectorPoints3DFeature(BaseVectorPoints3DFeature::new(
+                Some(2),
+                vec![Point3D::new(0, 0, 0)],
+                example_value.clone(),
+                None,
+            ))
+        );
+    }
+
+    #[test]
+    fn test_vector_tile_round_trip() {
+        let mut tile = BaseVectorTile::default();
+
+        let properties = Value::from([
+            ("name".to_string(), ValueType::Primitive(PrimitiveValue::String("a".to_string()))),
+            ("rank".to_string(), ValueType::Primitive(PrimitiveValue::U64(2))),
+        ]);
+        let m_value =
+            Value::from([("speed".to_string(), ValueType::Primitive(PrimitiveValue::F64(1.5)))]);
+        let m_value2 =
+            Value::from([("speed".to_string(), ValueType::Primitive(PrimitiveValue::F64(-3.0)))]);
+
+        // 2D layer
+        let mut layer = BaseVectorLayer::new("2d".to_string(), 4_096.into(), vec![], None, None);
+        layer.add_feature(BaseVectorFeature::BaseVectorPointsFeature(
+            BaseVectorPointsFeature::new(
+                Some(1),
+                vec![
+                    Point::new_with_m(5, 6, m_value.clone()),
+                    Point::new_with_m(7, 8, m_value2.clone()),
+                ],
+                properties.clone(),
+                Some(BBox::new(5.0, 6.0, 7.0, 8.0)),
+            ),
+        ));
+        layer.add_feature(BaseVectorFeature::BaseVectorLinesFeature(BaseVectorLinesFeature::new(
+            Some(2),
+            vec![
+                VectorLineWithOffset::new(
+                    1.5,
+                    vec![
+                        Point::new_with_m(0, 0, m_value.clone()),
+                        Point::new_with_m(10, 10, m_value2.clone()),
+                    ],
+                ),
+                VectorLineWithOffset::new(
+                    0.0,
+                    vec![
+                        Point::new_with_m(20, 20, m_value2.clone()),
+                        Point::new_with_m(30, 40, m_value.clone()),
+                    ],
+                ),
+            ],
+            properties.clone(),
+            None,
+        )));
+        layer.add_feature(BaseVectorFeature::BaseVectorPolysFeature(BaseVectorPolysFeature::new(
+            None,
+            vec![vec![VectorLineWithOffset::new(
+                0.0,
+                vec![
+                    Point::new(0, 0),
+                    Point::new(10, 0),
+                    Point::new(10, 10),
+                    Point::new(0, 10),
+                    Point::new(0, 0),
+                ],
+            )]],
+            properties.clone(),
+            Some(BBox::new(0.0, 0.0, 10.0, 10.0)),
+            vec![0, 1, 2, 2, 3, 0, 1, 5, 4],
+            vec![Point::new(5, 5), Point::new(6, 6)],
+        )));
+        tile.add_layer(layer);
+
+        // 3D layer
+        let mut layer = BaseVectorLayer::new("3d".to_string(), 8_192.into(), vec![], None, None);
+        layer.add_feature(BaseVectorFeature::BaseVectorPoints3DFeature(
+            BaseVectorPoints3DFeature::new(
+                None,
+                vec![Point3D::new(1, 2, 3), Point3D::new(4, 5, 6)],
+                properties.clone(),
+                Some(BBox3D::new(1.0, 2.0, 4.0, 5.0, 3.0, 6.0)),
+            ),
+        ));
+        layer.add_feature(BaseVectorFeature::BaseVectorLines3DFeature(
+            BaseVectorLines3DFeature::new(
+                Some(3),
+                vec![VectorLine3DWithOffset::new(
+                    2.25,
+                    vec![Point3D::new(0, 0, 0), Point3D::new(5, 5, 5)],
+                )],
+                properties.clone(),
+                None,
+            ),
+        ));
+        layer.add_feature(BaseVectorFeature::BaseVectorPolys3DFeature(
+            BaseVectorPolys3DFeature::new(
+                Some(4),
+                vec![vec![VectorLine3DWithOffset::new(
+                    0.0,
+                    vec![
+                        Point3D::new(0, 0, 1),
+                        Point3D::new(10, 0, 1),
+                        Point3D::new(10, 10, 1),
+                        Point3D::new(0, 0, 1),
+                    ],
+                )]],
+                properties.clone(),
+                None,
+                vec![0, 1, 2],
+                vec![Point3D::new(3, 3, 1)],
+            ),
+        ));
+        tile.add_layer(layer);
+
+        let bytes = write_tile(Some(&mut tile), None, None);
+
+        // read back into base types
+        let mut open_tile = VectorTile::new(bytes.clone(), None);
+        let mut base_tile = BaseVectorTile::from(&mut open_tile);
+
+        for (name, layer) in tile.layers.iter() {
+            let base_layer = base_tile.layers.get(name).unwrap();
+            assert_eq!(base_layer.version, layer.version);
+            assert_eq!(base_layer.extent, layer.extent);
+            assert_eq!(base_layer.shape, layer.shape);
+            assert_eq!(base_layer.m_shape, layer.m_shape);
+            assert!(base_layer.shape_defined);
+            // features were sorted by type when written
+            assert_eq!(base_layer.len(), layer.len());
+            for (base_feature, feature) in base_layer.features.iter().zip(layer.features.iter()) {
+                // bboxes are quantized so only compare them approximately
+                assert_bbox_close(base_feature.bbox(), feature.bbox());
+                assert_eq!(without_bbox(base_feature), without_bbox(feature));
+            }
+        }
+
+        // writing it back out again is lossless
+        assert_eq!(write_tile(Some(&mut base_tile), None, None), bytes);
+
+        // edit and rewrite
+        let layer = base_tile.layers.get_mut("2d").unwrap();
+        layer.features.remove(0);
+        let bytes = write_tile(Some(&mut base_tile), None, None);
+        let mut open_tile = VectorTile::new(bytes, None);
+        let layer = open_tile.layer("2d").unwrap();
+        assert_eq!(layer.len(), 2);
+        let mut feature = layer.feature(0).unwrap();
+        assert_eq!(feature.id(), Some(2));
+        assert_eq!(
+            feature.load_lines(),
+            vec![
+                VectorLineWithOffset::new(
+                    1.5,
+                    vec![
+                        Point::new_with_m(0, 0, m_value.clone()),
+                        Point::new_with_m(10, 10, m_value2.clone()),
+                    ],
+                ),
+                VectorLineWithOffset::new(
+                    0.0,
+                    vec![
+                        Point::new_with_m(20, 20, m_value2.clone()),
+                        Point::new_with_m(30, 40, m_value.clone()),
+                    ],
+                ),
+            ]
+        );
     }
 
     #[test]
@@ -1512,10 +1697,16 @@ mod tests {
             ImageData::new("test".to_string(), ImageType::AVIF, 2, 3, Vec::from([1, 2, 3, 10]));
 
         let open_tile_bytes = write_tile(None, Some(vec![&image]), None);
-        let open_tile = VectorTile::new(open_tile_bytes, None);
-        let image_from_tile = open_tile.images.get("test").unwrap();
-
-        assert_eq!(*image_from_tile, image);
+        let mut open_tile = VectorTile::new(open_tile_bytes, None);
+        let image_from_tile = open_tile.images.get_mut("test").unwrap();
+
+        // metadata is available before the image is read
+        assert_eq!(image_from_tile.name, "test");
+        assert_eq!(image_from_tile.image_type, ImageType::AVIF);
+        assert_eq!(image_from_tile.width, 2);
+        assert_eq!(image_from_tile.height, 3);
+        assert_eq!(image_from_tile.image(), &[1, 2, 3, 10]);
+        assert_eq!(ImageData::from(image_from_tile), image);
     }
 
     #[test]
@@ -1530,11 +1721,18 @@ mod tests {
         );
         let bytes = write_tile(None, None, Some(vec![&elevation_data]));
 
-        let open_tile = VectorTile::new(bytes, None);
-        let grid_data = open_tile.grids.get("elevation").unwrap();
+        let mut open_tile = VectorTile::new(bytes, None);
+        let grid_data = open_tile.grids.get_mut("elevation").unwrap();
 
+        // metadata is available before the grid is decoded
+        assert_eq!(grid_data.name, "elevation");
+        assert_eq!(grid_data.extent, Extent::Extent8192);
+        assert_eq!(grid_data.size, 512.0);
+        assert_eq!(grid_data.min, -1.0);
+        assert_eq!(grid_data.max, 4.0);
+        assert_eq!(grid_data.data(), &[-1.0, 1.9998779296875, 3.000244140625, 4.0]);
         assert_eq!(
-            *grid_data,
+            GridData::from(grid_data),
             GridData {
                 name: "elevation".to_owned(),
                 extent: Extent::Extent8192,
@@ -1545,4 +1743,656 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_grid_values_skipped() {
+        // a grid named "g" whose packed values end with an incomplete varint
+        let grid = [0x08, 0x03, 0x2A, 0x02, 0x02, 0x80, 0x32, 0x01, b'g'];
+        let mut data = vec![0x32, grid.len() as u8];
+        data.extend_from_slice(&grid);
+
+        // the values are only skipped when the tile is read, and decoded up to the incomplete one
+        let mut tile = VectorTile::try_new(data.clone(), None).unwrap();
+        assert_eq!(tile.grids.get_mut("g").unwrap().data(), &[0.0]);
+        let borrowed = BorrowedVectorTile::try_new(&data, None).unwrap();
+        assert_eq!(borrowed.grids["g"].data(), vec![0.0]);
+    }
+
+    #[test]
+    fn test_try_new_errors() {
+        // unknown tile tag
+        assert_eq!(
+            VectorTile::try_new(vec![0x48, 0x00], None).unwrap_err(),
+            DecodeError::UnknownTag { message: ProtoMessage::Tile, tag: 9, offset: 1 }
+        );
+        // invalid wire type
+        assert_eq!(
+            VectorTile::try_new(vec![0x2B], None).unwrap_err(),
+            DecodeError::InvalidWireType {
+                message: ProtoMessage::Tile,
+                tag: 5,
+                wire_type: 3,
+                offset: 0
+            }
+        );
+
+        // a column cache holding the string "a", followed by an open layer at byte 7
+        let tile = |layer: &[u8]| {
+            let mut tile = vec![0x2A, 0x03, 0x0A, 0x01, b'a', 0x22, layer.len() as u8];
+            tile.extend_from_slice(layer);
+            tile
+        };
+        let mut open_tile = VectorTile::try_new(tile(&[0x10, 0x00]), None).unwrap();
+        assert_eq!(open_tile.layer("a").unwrap().len(), 0);
+        // layer name is not in the string column
+        assert_eq!(
+            VectorTile::try_new(tile(&[0x10, 0x05]), None).unwrap_err(),
+            DecodeError::OutOfRange {
+                message: ProtoMessage::OpenLayer,
+                tag: Some(2),
+                index: 5,
+                len: 1,
+                offset: 8,
+            }
+        );
+        // unknown feature type
+        assert_eq!(
+            VectorTile::try_new(tile(&[0x10, 0x00, 0x22, 0x01, 0x09]), None).unwrap_err(),
+            DecodeError::InvalidEnum {
+                message: ProtoMessage::OpenFeature,
+                tag: None,
+                value: 9,
+                offset: 11,
+            }
+        );
+        // properties that are not in the shapes column
+        assert!(matches!(
+            VectorTile::try_new(tile(&[0x10, 0x00, 0x22, 0x04, 0x01, 0x40, 0x00, 0x00]), None),
+            Err(DecodeError::OutOfRange { message: ProtoMessage::OpenFeature, index: 0, .. })
+        ));
+        // truncated layer
+        assert!(matches!(
+            VectorTile::try_new(tile(&[0x10]), None),
+            Err(DecodeError::UnexpectedEof { message: ProtoMessage::OpenLayer, .. })
+        ));
+        // without a column cache open layers are skipped
+        let open_tile = VectorTile::try_new(vec![0x22, 0x02, 0x10, 0x05], None).unwrap();
+        assert!(open_tile.layers.is_empty());
+        // but they must still be length delimited and fit in the tile
+        for data in [vec![0x20, 0x01], vec![0x27], [tile(&[]), vec![0x20, 0x01]].concat()] {
+            assert!(matches!(
+                VectorTile::try_new(data, None),
+                Err(DecodeError::InvalidWireType { message: ProtoMessage::Tile, tag: 4, .. })
+            ));
+        }
+        assert!(matches!(
+            VectorTile::try_new(vec![0x22, 0x05, 0x10], None),
+            Err(DecodeError::UnexpectedEof { message: ProtoMessage::Tile, tag: Some(4), .. })
+        ));
+    }
+
+    #[test]
+    fn test_try_new_corrupted() {
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let mut tile = BaseVectorTile::from(&mut MapboxVectorTile::new(data, None));
+        let mut layer =
+            BaseVectorLayer::new("lines3d".to_string(), 4_096.into(), vec![], None, None);
+        let m_value =
+            Value::from([("a".to_string(), ValueType::Primitive(PrimitiveValue::I64(-2)))]);
+        layer.add_feature(BaseVectorFeature::BaseVectorLines3DFeature(
+            BaseVectorLines3DFeature::new(
+                Some(3),
+                vec![VectorLine3DWithOffset::new(
+                    2.2,
+                    vec![
+                        Point3D::new_with_m(0, 1, 2, m_value.clone()),
+                        Point3D::new_with_m(3, 4, 5, m_value),
+                    ],
+                )],
+                Value::default(),
+                Some(BBox3D::new(-1.0, -2.0, 3.0, 4.0, 5.0, 6.0)),
+            ),
+        ));
+        tile.add_layer(layer);
+        let image = ImageData::new("image".to_string(), ImageType::PNG, 1, 1, vec![1, 2, 3]);
+        let grid = GridData::new("grid".to_string(), 4_096.into(), 2.0, 0.0, 0.0, vec![1.0, 2.0]);
+        let data = write_tile(Some(&mut tile), Some(vec![&image]), Some(vec![&grid]));
+        let mut open_tile = VectorTile::try_new(data.clone(), None).unwrap();
+        load_all(&mut open_tile);
+
+        // truncating the tile must never panic
+        for len in (0..data.len()).step_by(7) {
+            if let Ok(mut tile) = VectorTile::try_new(data[..len].to_vec(), None) {
+                load_all(&mut tile);
+            }
+        }
+        // neither can flipping bytes
+        let mut seed: u32 = 11;
+        for _ in 0..1_000 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let mut corrupted = data.clone();
+            let index = (seed >> 8) as usize % corrupted.len();
+            corrupted[index] ^= (seed >> 24) as u8 | 1;
+            if let Ok(mut tile) = VectorTile::try_new(corrupted, None) {
+                load_all(&mut tile);
+            }
+        }
+    }
+
+    fn load_all(tile: &mut VectorTile) {
+        let names: Vec<String> = tile.layers.keys().cloned().collect();
+        for name in names {
+            let layer = tile.layer(&name).unwrap();
+            for i in 0..layer.len() {
+                let mut feature = layer.feature(i).unwrap();
+                feature.properties();
+                feature.bbox();
+                feature.load_geometry();
+                feature.read_indices();
+                if feature.is_polygons() || feature.is_polygons_3d() {
+                    feature.load_geometry_flat();
+                }
+            }
+        }
+        for grid in tile.grids.values_mut() {
+            grid.data();
+        }
+        for image in tile.images.values_mut() {
+            image.image();
+        }
+    }
+
+    #[test]
+    fn test_try_write_tile() {
+        let shape = serde_json::from_str::<Shape>(
+            r#"{ "a": "i64", "b": { "c": ["f32"] }, "d": "string", "f": [{ "x": "u64", "y": "string" }] }"#,
+        )
+        .unwrap();
+        let m_shape = serde_json::from_str::<Shape>(r#"{ "e": "bool" }"#).unwrap();
+        let good = serde_json::from_str::<Value>(
+            r#"{ "a": 1, "b": { "c": [1, -2, 3.3] }, "f": [{ "x": 1 }] }"#,
+        )
+        .unwrap();
+        let bad_nested =
+            serde_json::from_str::<Value>(r#"{ "a": 1, "b": { "c": [1, "two"] } }"#).unwrap();
+        let bad_structure = serde_json::from_str::<Value>(r#"{ "d": ["x"] }"#).unwrap();
+        let bad_m_value = serde_json::from_str::<Value>(r#"{ "e": 1 }"#).unwrap();
+        let feature = |properties: &Value, m: Option<Value>| {
+            let point = match m {
+                Some(m) => Point::new_with_m(0, 0, m),
+                None => Point::new(0, 0),
+            };
+            BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                None,
+                vec![point, Point::new(1, 1)],
+                properties.clone(),
+                None,
+            ))
+        };
+        let tile_with = |features: Vec<BaseVectorFeature>| {
+            let mut tile = BaseVectorTile::default();
+            tile.add_layer(BaseVectorLayer::new(
+                "layer".to_string(),
+                4_096.into(),
+                features,
+                Some(shape.clone()),
+                Some(m_shape.clone()),
+            ));
+            tile
+        };
+
+        // a tile that matches its shapes encodes exactly like write_tile
+        let mut tile = tile_with(vec![feature(&good, None), feature(&Value::default(), None)]);
+        let bytes = try_write_tile(Some(&mut tile), None, None, &WriteOptions::default()).unwrap();
+        assert_eq!(bytes, write_tile(Some(&mut tile), None, None));
+        // missing keys inside an array of objects are encoded as the shape's default
+        let mut open_tile = VectorTile::new(bytes, None);
+        let properties = open_tile.layer("layer").unwrap().feature(0).unwrap().properties();
+        assert_eq!(
+            properties.get("f"),
+            Some(&ValueType::Array(vec![ValuePrimitiveType::NestedPrimitive(
+                serde_json::from_str(r#"{ "x": 1, "y": "" }"#).unwrap()
+            )]))
+        );
+
+        // mismatched primitive in an array of a nested object
+        let mut tile = tile_with(vec![feature(&good, None), feature(&bad_nested, None)]);
+        let err =
+            try_write_tile(Some(&mut tile), None, None, &WriteOptions::default()).unwrap_err();
+        assert_eq!(
+            err,
+            EncodeError::ShapeMismatch {
+                layer: "layer".to_string(),
+                feature: 1,
+                key: "b.c[1]".to_string(),
+                expected: PrimitiveShape::F32,
+                actual: PrimitiveValue::String("two".to_string()),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "property \"b.c[1]\" expected F32 but found String(\"two\") in feature 1 of layer \"layer\""
+        );
+
+        // an array where a primitive is expected
+        let mut tile = tile_with(vec![feature(&bad_structure, None)]);
+        let err =
+            try_write_tile(Some(&mut tile), None, None, &WriteOptions::default()).unwrap_err();
+        assert!(matches!(
+            err,
+            EncodeError::StructureMismatch { feature: 0, ref key, .. } if key == "d"
+        ));
+
+        // m-values are checked against the m-shape
+        let mut tile = tile_with(vec![feature(&good, Some(bad_m_value))]);
+        let err =
+            try_write_tile(Some(&mut tile), None, None, &WriteOptions::default()).unwrap_err();
+        assert_eq!(
+            err,
+            EncodeError::ShapeMismatch {
+                layer: "layer".to_string(),
+                feature: 0,
+                key: "m_values.e".to_string(),
+                expected: PrimitiveShape::Bool,
+                actual: PrimitiveValue::U64(1),
+            }
+        );
+
+        // features are named in the order they were added, before they are sorted by type
+        let line = BaseVectorFeature::BaseVectorLinesFeature(BaseVectorLinesFeature::new(
+            None,
+            vec![VectorLineWithOffset::new(0., vec![Point::new(0, 0), Point::new(1, 1)])],
+            good.clone(),
+            None,
+        ));
+        let mut tile = tile_with(vec![line, feature(&bad_nested, None)]);
+        let err =
+            try_write_tile(Some(&mut tile), None, None, &WriteOptions::default()).unwrap_err();
+        assert_eq!((err.feature(), err.to_string().contains("\"b.c[1]\"")), (1, true));
+    }
+
+    #[test]
+    fn test_get_feature() {
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let mut mvt = MapboxVectorTile::new(data.clone(), None);
+        let open_data = write_tile(Some(&mut BaseVectorTile::from(&mut mvt)), None, None);
+
+        for data in [data, open_data] {
+            let mut tile = VectorTile::new(data, None);
+            for layer in tile.layers.values_mut() {
+                assert!(layer.get_feature(layer.len()).is_none());
+                // features are borrowed from the layer rather than copied
+                let same = match (layer.get_feature(0).unwrap(), layer.get_feature(0).unwrap()) {
+                    (VectorFeatureRef::Mapbox(a), VectorFeatureRef::Mapbox(b)) => ptr::eq(a, b),
+                    (VectorFeatureRef::Open(a), VectorFeatureRef::Open(b)) => ptr::eq(a, b),
+                    _ => false,
+                };
+                assert!(same);
+                for i in 0..layer.len() {
+                    let mut borrowed = layer.get_feature(i).unwrap();
+                    let flat = borrowed.is_polygons().then(|| borrowed.load_geometry_flat());
+                    let expected =
+                        (borrowed.id(), borrowed.properties(), borrowed.load_geometry(), flat);
+                    let mut cached = layer.feature(i).unwrap();
+                    let flat = cached.is_polygons().then(|| cached.load_geometry_flat());
+                    assert_eq!(
+                        (cached.id(), cached.properties(), cached.load_geometry(), flat),
+                        expected
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_iter_features() {
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let mut mvt = MapboxVectorTile::new(data.clone(), None);
+        let open_data = write_tile(Some(&mut BaseVectorTile::from(&mut mvt)), None, None);
+
+        for data in [data, open_data] {
+            let mut tile = VectorTile::new(data.clone(), None);
+            let mut expected: Vec<(String, Option<u64>, VectorGeometry)> = vec![];
+            for layer in tile.iter_layers() {
+                for i in 0..layer.len() {
+                    let mut feature = layer.get_feature(i).unwrap();
+                    expected.push((layer.name(), feature.id(), feature.load_geometry()));
+                }
+            }
+            assert!(!expected.is_empty());
+
+            let mut features = vec![];
+            for (name, mut feature) in tile.iter_features() {
+                features.push((name.to_string(), feature.id(), feature.load_geometry()));
+            }
+            assert_eq!(features, expected);
+
+            // features are cached, so iterating again sees the same features
+            let mut tile = VectorTile::new(data, None);
+            let layer = tile.layers.values_mut().next().unwrap();
+            let len = layer.len();
+            layer.feature(len - 1).unwrap();
+            assert_eq!(layer.features().len(), len);
+            let ids: Vec<_> = layer.features().map(|f| f.id()).collect();
+            let expected_ids: Vec<_> =
+                (0..len).map(|i| layer.get_feature(i).unwrap().id()).collect();
+            assert_eq!(ids, expected_ids);
+        }
+    }
+
+    #[test]
+    fn test_to_s2json() {
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let mut mvt = MapboxVectorTile::new(data.clone(), None);
+        let open_data = write_tile(Some(&mut BaseVectorTile::from(&mut mvt)), None, None);
+
+        for data in [data, open_data] {
+            let mut tile = VectorTile::new(data, None);
+            let mut count = 0;
+            for layer in tile.iter_layers() {
+                let extent = layer.extent();
+                for i in 0..layer.len() {
+                    let mut feature = layer.feature(i).unwrap();
+                    let vector_feature = feature.to_s2json(extent);
+                    assert_eq!(vector_feature.id, feature.id());
+                    assert_eq!(vector_feature.properties, feature.properties());
+                    let bbox = stored_bbox(&vector_feature.geometry);
+                    assert_eq!(bbox.is_some(), feature.bbox().is_some());
+                    // converting back to the base types is lossless
+                    let base = s2json_to_base(&vector_feature, extent.into());
+                    let expected = match feature {
+                        open_vector_tile::VectorFeature::Mapbox(f) => BaseVectorFeature::from(f),
+                        open_vector_tile::VectorFeature::Open(f) => BaseVectorFeature::from(f),
+                        open_vector_tile::VectorFeature::Mlt(f) => BaseVectorFeature::from(f),
+                    };
+                    assert_eq!(base, expected);
+                    count += 1;
+                }
+            }
+            assert!(count > 0);
+        }
+    }
+
+    #[test]
+    fn test_to_feature_collection() {
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let tile = VectorTile::new(data, None);
+        let count = tile.iter_features().count();
+        let collection = tile.to_feature_collection(12, 1143, 1497);
+        assert_eq!(collection.features.len(), count);
+
+        // tile 12/1143/1497 covers lon -79.54 -> -79.45 and lat 43.45 -> 43.52, allowing for
+        // the features that spill into the neighbouring tiles
+        let in_tile =
+            |lon: f64, lat: f64| (-79.7..=-79.3).contains(&lon) && (43.3..=43.7).contains(&lat);
+        for feature in &collection.features {
+            let Features::Feature(feature) = feature else { panic!("expected a GeoJSON feature") };
+            assert!(
+                feature.metadata.as_ref().is_some_and(|m: &ExportMetadata| !m.layer.is_empty())
+            );
+            match &feature.geometry {
+                Geometry::Point(g) => assert!(in_tile(g.coordinates.0, g.coordinates.1)),
+                Geometry::MultiPoint(g) => {
+                    assert!(g.coordinates.iter().all(|p| in_tile(p.0, p.1)))
+                }
+                Geometry::LineString(g) => {
+                    assert!(g.coordinates.iter().all(|p| in_tile(p.0, p.1)))
+                }
+                Geometry::MultiLineString(g) => {
+                    assert!(g.coordinates.iter().flatten().all(|p| in_tile(p.0, p.1)))
+                }
+                Geometry::Polygon(g) => {
+                    assert!(g.coordinates.iter().flatten().all(|p| in_tile(p.0, p.1)))
+                }
+                Geometry::MultiPolygon(g) => {
+                    assert!(g.coordinates.iter().flatten().flatten().all(|p| in_tile(p.0, p.1)))
+                }
+                _ => panic!("expected a 2D geometry"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_borrowed_tile() {
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let mut mvt = MapboxVectorTile::new(data.clone(), None);
+        let image = ImageData::new("test".into(), ImageType::WEBP, 2, 3, vec![1, 2, 3, 10]);
+        let grid =
+            GridData::new("elevation".into(), 8_192.into(), 512.0, 0.0, 0.0, vec![-1.0, 2.0, 3.0]);
+        let open_data = write_tile(
+            Some(&mut BaseVectorTile::from(&mut mvt)),
+            Some(vec![&image]),
+            Some(vec![&grid]),
+        );
+
+        for data in [data, open_data] {
+            let borrowed = BorrowedVectorTile::new(&data, None);
+            let mut tile = VectorTile::new(data.clone(), None);
+            assert_eq!(
+                borrowed.layers.keys().map(|k| k.to_string()).collect::<Vec<_>>(),
+                tile.layers.keys().cloned().collect::<Vec<_>>()
+            );
+            for (name, layer) in tile.layers.iter_mut() {
+                let borrowed_layer = borrowed.layer(name).unwrap();
+                assert_eq!(borrowed_layer.name(), name);
+                assert_eq!(borrowed_layer.version(), layer.version());
+                assert_eq!(borrowed_layer.extent(), layer.extent());
+                assert_eq!(borrowed_layer.len(), layer.len());
+                assert!(borrowed_layer.feature(layer.len()).is_none());
+                for i in 0..layer.len() {
+                    let mut expected = layer.feature(i).unwrap();
+                    let mut feature = borrowed_layer.feature(i).unwrap();
+                    assert_eq!(feature.id(), expected.id());
+                    assert_eq!(feature.get_type(), expected.get_type());
+                    assert_eq!(feature.properties(), expected.properties());
+                    assert_eq!(feature.bbox(), expected.bbox());
+                    assert_eq!(feature.load_geometry(), expected.load_geometry());
+                    if feature.is_polygons() {
+                        assert_eq!(feature.load_geometry_flat(), expected.load_geometry_flat());
+                    }
+                }
+            }
+            for (name, image) in tile.images.iter_mut() {
+                let borrowed_image = &borrowed.images[name.as_str()];
+                assert_eq!(borrowed_image.image_type, image.image_type);
+                assert_eq!((borrowed_image.width, borrowed_image.height), (2, 3));
+                assert_eq!(borrowed_image.image, image.image());
+                // the image is a slice of the input rather than a copy
+                assert!(data.as_ptr_range().contains(&borrowed_image.image.as_ptr()));
+            }
+            for (name, grid) in tile.grids.iter_mut() {
+                assert_eq!(borrowed.grids[name.as_str()].data(), grid.data());
+            }
+        }
+    }
+
+    #[test]
+    fn test_borrowed_tile_allocations() {
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let mut mvt = MapboxVectorTile::new(data.clone(), None);
+        let open_data = write_tile(Some(&mut BaseVectorTile::from(&mut mvt)), None, None);
+
+        for data in [data, open_data] {
+            // the tile allocates for its layers and columns rather than for each feature
+            let (tile, count) =
+                count_allocations(|| BorrowedVectorTile::try_new(&data, None).unwrap());
+            let features: usize = tile.layers.values().map(|layer| layer.len()).sum();
+            assert_eq!(features, 256);
+            assert!(count < 64, "{count} allocations");
+            // and features only allocate once their geometry or properties are decoded
+            for layer in tile.layers.values() {
+                let (read, count) = count_allocations(|| layer.features().count());
+                assert_eq!(read, layer.len());
+                assert_eq!(count, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_borrowed_tile_errors() {
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let truncated = &data[..data.len() - 3];
+        assert!(BorrowedVectorTile::try_new(truncated, None).is_err());
+        assert!(BorrowedVectorTile::try_new(&data, Some(data.len() + 1)).is_err());
+        // open layers stored as a varint or with an invalid wire type
+        for data in [&[0x20, 0x01][..], &[0x27]] {
+            assert!(matches!(
+                BorrowedVectorTile::try_new(data, None),
+                Err(DecodeError::InvalidWireType { message: ProtoMessage::Tile, tag: 4, .. })
+            ));
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_share_tile_across_threads() {
+        use std::sync::Arc;
+
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let mut mvt = MapboxVectorTile::new(data.clone(), None);
+        let open_data = write_tile(Some(&mut BaseVectorTile::from(&mut mvt)), None, None);
+
+        let load = |tile: &VectorTile| -> Vec<VectorGeometry> {
+            tile.layers
+                .values()
+                .flat_map(|layer| {
+                    (0..layer.len()).map(|i| layer.get_feature(i).unwrap().load_geometry())
+                })
+                .collect()
+        };
+        // both the mapbox and the open read paths
+        for data in [data, open_data] {
+            let tile = Arc::new(VectorTile::new(data, None));
+            let expected = load(&tile);
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let tile = tile.clone();
+                    std::thread::spawn(move || load(&tile))
+                })
+                .collect();
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_bbox() {
+        let points = BaseVectorPointsFeature::new(
+            Some(1),
+            vec![Point::new(0, 4096), Point::new(2048, 0)],
+            Default::default(),
+            None,
+        );
+        let lines_3d = BaseVectorLines3DFeature::new(
+            Some(2),
+            vec![VectorLine3DWithOffset::new(
+                0.0,
+                vec![Point3D::new(0, 4096, 5), Point3D::new(4096, 2048, -3)],
+            )],
+            Default::default(),
+            None,
+        );
+        // features that already have a bbox keep it
+        let existing = BBox::new(-1.0, -2.0, 3.0, 4.0);
+        let lines = BaseVectorLinesFeature::new(
+            Some(3),
+            vec![VectorLineWithOffset::new(0.0, vec![Point::new(0, 0), Point::new(1, 1)])],
+            Default::default(),
+            Some(existing),
+        );
+        let mut tile = BaseVectorTile::default();
+        tile.add_layer(BaseVectorLayer::new(
+            "test".into(),
+            Extent::Extent4096,
+            vec![
+                BaseVectorFeature::BaseVectorPointsFeature(points.clone()),
+                BaseVectorFeature::BaseVectorLines3DFeature(lines_3d),
+                BaseVectorFeature::BaseVectorLinesFeature(lines.clone()),
+            ],
+            None,
+            None,
+        ));
+        let options = WriteOptions { compute_bbox: Some((1, 1, 0)) };
+        let data = write_tile_with(Some(&mut tile), None, None, &options);
+        // the tile being written is left untouched
+        let BaseVectorFeature::BaseVectorPointsFeature(written) = &tile.layers["test"].features[0]
+        else {
+            panic!("expected a points feature")
+        };
+        assert_eq!(written.bbox, None);
+        let mut tile = VectorTile::new(data, None);
+        let layer = tile.layer("test").unwrap();
+        let bbox = |id: u64| layer.features().find(|f| f.id() == Some(id)).and_then(|f| f.bbox());
+
+        // the tile 1/1/0 covers lon 0 -> 180 and lat 0 -> 85.05
+        assert_bbox_close(
+            bbox(1),
+            Some(BBOX::BBox(BBox::new(0.0, 0.0, 90.0, 85.051_128_779_806_59))),
+        );
+        assert_bbox_close(
+            bbox(2),
+            Some(BBOX::BBox3D(BBox3D::new(0.0, 0.0, 180.0, 66.513_260_443_111_85, -3.0, 5.0))),
+        );
+        assert_bbox_close(bbox(3), Some(BBOX::BBox(existing)));
+
+        // the feature level helper ignores the existing bbox and leaves it as is
+        let feature = BaseVectorFeature::BaseVectorPointsFeature(points);
+        assert_bbox_close(
+            feature.compute_bbox(Extent::Extent4096, 0, 0, 0),
+            Some(BBOX::BBox(BBox::new(-180.0, -85.051_128_779_806_59, 0.0, 85.051_128_779_806_59))),
+        );
+        let feature = BaseVectorFeature::BaseVectorLinesFeature(lines);
+        assert!(feature.compute_bbox(Extent::Extent4096, 0, 0, 0).is_some());
+        assert_bbox_close(feature.bbox(), Some(BBOX::BBox(existing)));
+        let empty = BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+            None,
+            vec![],
+            Default::default(),
+            None,
+        ));
+        assert_eq!(empty.compute_bbox(Extent::Extent4096, 0, 0, 0), None);
+    }
+
+    /// The bbox stored on an S2JSON geometry, without computing one from the coordinates
+    fn stored_bbox(geometry: &s2json::VectorGeometry) -> Option<BBox3D> {
+        match geometry {
+            s2json::VectorGeometry::Point(g) => g.bbox,
+            s2json::VectorGeometry::MultiPoint(g) => g.bbox,
+            s2json::VectorGeometry::LineString(g) => g.bbox,
+            s2json::VectorGeometry::MultiLineString(g) => g.bbox,
+            s2json::VectorGeometry::Polygon(g) => g.bbox,
+            s2json::VectorGeometry::MultiPolygon(g) => g.bbox,
+        }
+    }
+
+    fn assert_bbox_close(a: Option<BBOX>, b: Option<BBOX>) {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-4;
+        match (a, b) {
+            (None, None) => {}
+            (Some(BBOX::BBox(a)), Some(BBOX::BBox(b))) => {
+                assert!(close(a.left, b.left) && close(a.bottom, b.bottom), "{a:?} != {b:?}");
+                assert!(close(a.right, b.right) && close(a.top, b.top), "{a:?} != {b:?}");
+            }
+            (Some(BBOX::BBox3D(a)), Some(BBOX::BBox3D(b))) => {
+                assert!(close(a.left, b.left) && close(a.bottom, b.bottom), "{a:?} != {b:?}");
+                assert!(close(a.right, b.right) && close(a.top, b.top), "{a:?} != {b:?}");
+                assert!(close(a.near, b.near) && close(a.far, b.far), "{a:?} != {b:?}");
+            }
+            (a, b) => panic!("{a:?} != {b:?}"),
+        }
+    }
+
+    fn without_bbox(feature: &BaseVectorFeature) -> BaseVectorFeature {
+        let mut feature = feature.clone();
+        match &mut feature {
+            BaseVectorFeature::BaseVectorPointsFeature(f) => f.bbox = None,
+            BaseVectorFeature::BaseVectorLinesFeature(f) => f.bbox = None,
+            BaseVectorFeature::BaseVectorPolysFeature(f) => f.bbox = None,
+            BaseVectorFeature::BaseVectorPoints3DFeature(f) => f.bbox = None,
+            BaseVectorFeature::BaseVectorLines3DFeature(f) => f.bbox = None,
+            BaseVectorFeature::BaseVectorPolys3DFeature(f) => f.bbox = None,
+        }
+        feature
+    }
 }
@@ -0,0 +1,178 @@
+#[cfg(all(test, feature = "pmtiles"))]
+mod tests {
+    extern crate alloc;
+
+    use crate::{point_tile, temp_file};
+    use open_vector_tile::{
+        VectorFeatureMethods, VectorLayerMethods,
+        pmtiles::{
+            ArchiveOptions, Compression, PMTiles, PMTilesError, TileType, tile_id_to_zxy,
+            write_archive, zxy_to_tile_id,
+        },
+        write_tile,
+    };
+    use serde_json::json;
+    use std::{fs, io::Cursor};
+
+    fn tile(id: u64) -> Vec<u8> {
+        write_tile(Some(&mut point_tile("points", id)), None, None)
+    }
+
+    #[test]
+    fn test_tile_ids() {
+        assert_eq!(zxy_to_tile_id(0, 0, 0), 0);
+        assert_eq!(zxy_to_tile_id(1, 0, 0), 1);
+        assert_eq!(zxy_to_tile_id(1, 0, 1), 2);
+        assert_eq!(zxy_to_tile_id(1, 1, 1), 3);
+        assert_eq!(zxy_to_tile_id(1, 1, 0), 4);
+        assert_eq!(zxy_to_tile_id(2, 0, 0), 5);
+        assert_eq!(zxy_to_tile_id(12, 3423, 1763), 19078479);
+        assert_eq!(tile_id_to_zxy(19078479), (12, 3423, 1763));
+
+        // every tile of the first zooms maps to the next id along the curve
+        let mut ids = vec![];
+        for zoom in 0..6 {
+            for x in 0..1 << zoom {
+                for y in 0..1 << zoom {
+                    let id = zxy_to_tile_id(zoom, x, y);
+                    assert_eq!(tile_id_to_zxy(id), (zoom, x, y));
+                    ids.push(id);
+                }
+            }
+        }
+        ids.sort();
+        assert_eq!(ids, (0..ids.len() as u64).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_pmtiles() {
+        let path = temp_file("archive.pmtiles");
+        let tiles = vec![
+            (2, 1, 1, tile(2)),
+            (0, 0, 0, tile(1)),
+            // consecutive identical tiles share one entry
+            (1, 0, 0, tile(3)),
+            (1, 0, 1, tile(3)),
+            // identical tiles elsewhere share the data
+            (2, 3, 3, tile(3)),
+        ];
+        let options = ArchiveOptions {
+            metadata: json!({ "name": "test", "vector_layers": [{ "id": "points" }] }),
+            ..Default::default()
+        };
+        let header = write_archive(fs::File::create(&path).unwrap(), tiles, &options).unwrap();
+        assert_eq!(header.addressed_tiles, 5);
+        assert_eq!(header.tile_entries, 4);
+        assert_eq!(header.tile_contents, 3);
+
+        let mut archive = PMTiles::open(&path).unwrap();
+        assert_eq!(archive.header(), &header);
+        let header = archive.header();
+        assert!(header.clustered);
+        assert_eq!(header.internal_compression, Compression::Gzip);
+        assert_eq!(header.tile_compression, Compression::None);
+        assert_eq!(header.tile_type, TileType::Unknown);
+        assert_eq!((header.min_zoom, header.max_zoom), (0, 2));
+        assert_eq!((header.min_lon, header.max_lon), (-180., 180.));
+        assert_eq!(header.leaf_dirs_length, 0);
+        assert_eq!(archive.root().len(), 4);
+        assert_eq!(archive.metadata().unwrap()["name"], "test");
+
+        assert_eq!(archive.tile_data(1, 0, 1).unwrap().unwrap(), tile(3));
+        assert_eq!(archive.tile_data(2, 3, 3).unwrap().unwrap(), tile(3));
+        let mut vector_tile = archive.tile(2, 1, 1).unwrap().unwrap();
+        let points = vector_tile.layer("points").unwrap();
+        assert_eq!(points.feature(0).unwrap().id(), Some(2));
+        assert!(archive.tile_data(1, 1, 1).unwrap().is_none());
+        assert!(archive.tile_data(3, 0, 0).unwrap().is_none());
+        assert!(archive.tile_data(1, 2, 0).unwrap().is_none());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_pmtiles_gzip_tiles() {
+        let options = ArchiveOptions {
+            tile_type: TileType::Mvt,
+            tile_compression: Compression::Gzip,
+            ..Default::default()
+        };
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let mut buf = vec![];
+        write_archive(&mut buf, vec![(12, 1143, 1497, data.clone())], &options).unwrap();
+
+        let mut archive = PMTiles::new(Cursor::new(buf)).unwrap();
+        assert_eq!(archive.header().tile_type, TileType::Mvt);
+        assert_eq!(archive.header().tile_compression, Compression::Gzip);
+        assert!(archive.header().tile_data_length < data.len() as u64);
+        assert_eq!(archive.tile_data(12, 1143, 1497).unwrap().unwrap(), data);
+        let mut vector_tile = archive.tile(12, 1143, 1497).unwrap().unwrap();
+        assert_eq!(vector_tile.layer("road").unwrap().len(), 247);
+    }
+
+    #[test]
+    fn test_pmtiles_leaf_directories() {
+        // unique tiles with gaps between their ids keep the directories from compressing well
+        let mut seed = 7_u64;
+        let mut ids = vec![];
+        let mut id = zxy_to_tile_id(12, 0, 0);
+        for _ in 0..40_000 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            id += 1 + (seed >> 59);
+            ids.push(id);
+        }
+        let data = |id: u64| id.to_le_bytes()[..(3 + id % 6) as usize].to_vec();
+        let tiles = ids.iter().map(|&id| {
+            let (zoom, x, y) = tile_id_to_zxy(id);
+            (zoom, x, y, data(id))
+        });
+        let path = temp_file("leaves.pmtiles");
+        write_archive(fs::File::create(&path).unwrap(), tiles, &ArchiveOptions::default()).unwrap();
+
+        let mut archive = PMTiles::open(&path).unwrap();
+        assert!(archive.header().leaf_dirs_length > 0);
+        assert!(archive.header().root_offset + archive.header().root_length <= 16_384);
+        assert!(archive.root().iter().all(|entry| entry.run_length == 0));
+        for &id in ids.iter().step_by(997) {
+            let (zoom, x, y) = tile_id_to_zxy(id);
+            assert_eq!(archive.tile_data(zoom, x, y).unwrap(), Some(data(id)));
+            let (zoom, x, y) = tile_id_to_zxy(id + 1);
+            if !ids.contains(&(id + 1)) {
+                assert_eq!(archive.tile_data(zoom, x, y).unwrap(), None);
+            }
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_pmtiles() {
+        let data = fs::read("./tests/fixtures/multi-point.pbf").unwrap();
+        assert!(matches!(PMTiles::new(Cursor::new(data)), Err(PMTilesError::InvalidMagic)));
+
+        let mut buf = vec![];
+        write_archive(&mut buf, vec![(0, 0, 0, tile(1))], &ArchiveOptions::default()).unwrap();
+        let mut old = buf.clone();
+        old[7] = 2;
+        assert!(matches!(PMTiles::new(Cursor::new(old)), Err(PMTilesError::UnsupportedVersion(2))));
+        buf.truncate(140);
+        assert!(matches!(PMTiles::new(Cursor::new(buf)), Err(PMTilesError::InvalidDirectory)));
+
+        // offsets that overflow past the tile data section are rejected
+        let mut buf = vec![];
+        let tiles = vec![(0, 0, 0, tile(1)), (1, 0, 0, tile(2))];
+        write_archive(&mut buf, tiles, &ArchiveOptions::default()).unwrap();
+        buf[56..64].copy_from_slice(&u64::MAX.to_le_bytes());
+        let mut archive = PMTiles::new(Cursor::new(buf)).unwrap();
+        assert!(matches!(archive.tile_data(1, 0, 0), Err(PMTilesError::InvalidDirectory)));
+
+        // tiles past zoom 31 or outside of their zoom are rejected
+        for (zoom, x, y) in [(32, 0, 0), (255, 0, 0), (1, 2, 0), (1, 0, 2), (31, 0, 1 << 31)] {
+            let tiles = vec![(0, 0, 0, tile(1)), (zoom, x, y, tile(2))];
+            let err = write_archive(vec![], tiles, &ArchiveOptions::default()).unwrap_err();
+            assert!(matches!(err, PMTilesError::InvalidTile(z, i, j) if (z, i, j) == (zoom, x, y)));
+        }
+        let tiles = vec![(31, (1 << 31) - 1, (1 << 31) - 1, tile(1))];
+        write_archive(vec![], tiles, &ArchiveOptions::default()).unwrap();
+    }
+}
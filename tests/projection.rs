@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use open_vector_tile::{
+        Extent, Point, Point3D,
+        projection::{
+            face_st_to_lon_lat, face_st_to_tile_xy, face_uv_to_xyz, lon_lat_to_face_st,
+            lon_lat_to_meters, lon_lat_to_tile, lon_lat_to_tile_xy, lon_lat_to_world,
+            meters_to_lon_lat, point_3d_to_tile_xyz, point_to_tile_xy, st_to_uv,
+            tile_xy_to_face_st, tile_xy_to_lon_lat, tile_xy_to_point, tile_xyz_to_point_3d,
+            uv_to_st, world_to_lon_lat, xyz_to_face_uv,
+        },
+    };
+    use s2json::Face;
+
+    fn assert_close(a: (f64, f64), b: (f64, f64)) {
+        assert!((a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_web_mercator() {
+        assert_close(lon_lat_to_meters(0.0, 0.0), (0.0, 0.0));
+        assert_close(lon_lat_to_meters(180.0, 0.0), (20_037_508.342_789_244, 0.0));
+        let (x, y) = lon_lat_to_meters(-79.5, 43.5);
+        assert_close(meters_to_lon_lat(x, y), (-79.5, 43.5));
+        // latitudes past the poles are clamped
+        assert_close(lon_lat_to_meters(0.0, 90.0), lon_lat_to_meters(0.0, 85.051_128_779_806_59));
+
+        assert_close(lon_lat_to_world(-180.0, 85.051_128_779_806_59), (0.0, 0.0));
+        assert_close(lon_lat_to_world(0.0, 0.0), (0.5, 0.5));
+        assert_close(world_to_lon_lat(0.25, 0.75), (-90.0, -66.513_260_443_111_85));
+        assert_close(world_to_lon_lat(0.25, 0.75), {
+            let (x, y) = lon_lat_to_world(-90.0, -66.513_260_443_111_85);
+            world_to_lon_lat(x, y)
+        });
+    }
+
+    #[test]
+    fn test_web_mercator_tiles() {
+        assert_eq!(lon_lat_to_tile(-79.5, 43.5, 12), (1143, 1497));
+        assert_eq!(lon_lat_to_tile(0.0, 0.0, 0), (0, 0));
+        assert_eq!(lon_lat_to_tile(180.0, -90.0, 1), (1, 1));
+
+        assert_close(
+            tile_xy_to_lon_lat(0.0, 0.0, 12, 1143, 1497),
+            (-79.541_015_625, 43.516_688_535_029_07),
+        );
+        assert_close(
+            tile_xy_to_lon_lat(1.0, 1.0, 12, 1143, 1497),
+            (-79.453_125, 43.452_918_893_554_65),
+        );
+        let (x, y) = lon_lat_to_tile_xy(-79.5, 43.5, 12, 1143, 1497);
+        assert!((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y));
+        assert_close(tile_xy_to_lon_lat(x, y, 12, 1143, 1497), (-79.5, 43.5));
+        // positions in neighbouring tiles fall outside of 0->1
+        let (x, _) = lon_lat_to_tile_xy(-79.5, 43.5, 12, 1144, 1497);
+        assert!(x < 0.0);
+    }
+
+    #[test]
+    fn test_s2() {
+        assert_eq!(uv_to_st(0.0), 0.5);
+        assert_eq!(uv_to_st(1.0), 1.0);
+        assert_eq!(uv_to_st(-1.0), 0.0);
+        for s in [0.0, 0.1, 0.5, 0.75, 1.0] {
+            assert!((uv_to_st(st_to_uv(s)) - s).abs() < 1e-12);
+        }
+
+        assert_eq!(xyz_to_face_uv(1.0, 0.0, 0.0), (Face::Face0, 0.0, 0.0));
+        assert_eq!(xyz_to_face_uv(0.0, 1.0, 0.0).0, Face::Face1);
+        assert_eq!(xyz_to_face_uv(0.0, 0.0, 1.0).0, Face::Face2);
+        assert_eq!(xyz_to_face_uv(-1.0, 0.0, 0.0).0, Face::Face3);
+        assert_eq!(xyz_to_face_uv(0.0, -1.0, 0.0).0, Face::Face4);
+        assert_eq!(xyz_to_face_uv(0.0, 0.0, -1.0).0, Face::Face5);
+        for face in 0..6_u8 {
+            let face: Face = face.into();
+            let (x, y, z) = face_uv_to_xyz(face, 0.25, -0.5);
+            let (f, u, v) = xyz_to_face_uv(x, y, z);
+            assert_eq!(f, face);
+            assert_close((u, v), (0.25, -0.5));
+        }
+
+        assert_eq!(lon_lat_to_face_st(0.0, 0.0), (Face::Face0, 0.5, 0.5));
+        assert_eq!(lon_lat_to_face_st(90.0, 0.0).0, Face::Face1);
+        assert_eq!(lon_lat_to_face_st(0.0, 90.0).0, Face::Face2);
+        let (face, s, t) = lon_lat_to_face_st(-79.5, 43.5);
+        assert_eq!(face, Face::Face4);
+        assert_close(face_st_to_lon_lat(face, s, t), (-79.5, 43.5));
+
+        let (x, y) = face_st_to_tile_xy(s, t, 10, 0, 0);
+        let (i, j) = ((x as u32), (y as u32));
+        let (x, y) = face_st_to_tile_xy(s, t, 10, i, j);
+        assert!((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y));
+        assert_close(tile_xy_to_face_st(x, y, 10, i, j), (s, t));
+    }
+
+    #[test]
+    #[should_panic(expected = "the Web Mercator face is not a cube face")]
+    fn test_wm_face() {
+        face_st_to_lon_lat(Face::WM, 0.5, 0.5);
+    }
+
+    #[test]
+    fn test_tile_points() {
+        assert_eq!(tile_xy_to_point(0.5, 0.25, Extent::Extent4096), Point::new(2048, 1024));
+        assert_eq!(tile_xy_to_point(-0.1, 1.1, Extent::Extent512), Point::new(-51, 563));
+        assert_eq!(
+            tile_xyz_to_point_3d(0.5, 0.25, 1.0, Extent::Extent1024),
+            Point3D::new(512, 256, 1024)
+        );
+        assert_eq!(point_to_tile_xy(&Point::new(2048, 1024), Extent::Extent4096), (0.5, 0.25));
+        assert_eq!(
+            point_3d_to_tile_xyz(&Point3D::new(512, 256, 1024), Extent::Extent1024),
+            (0.5, 0.25, 1.0)
+        );
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use open_vector_tile::{
+        Point, Point3D, VectorFeatureMethods, VectorLayerMethods, VectorLine3DWithOffset,
+        VectorLineWithOffset, VectorTile,
+        base::{
+            BaseVectorFeature, BaseVectorLayer, BaseVectorLinesFeature, BaseVectorPointsFeature,
+            BaseVectorPolys3DFeature, BaseVectorPolysFeature, BaseVectorTile,
+        },
+        open::Extent,
+        write_tile,
+    };
+    use s2json::Properties;
+
+    fn ring(min: i32, max: i32) -> Vec<Point> {
+        vec![
+            Point::new(min, min),
+            Point::new(max, min),
+            Point::new(max, max),
+            Point::new(min, max),
+            Point::new(min, min),
+        ]
+    }
+
+    fn tile() -> VectorTile {
+        let features = vec![
+            BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                Some(1),
+                vec![Point::new(10, 10)],
+                Properties::default(),
+                None,
+            )),
+            BaseVectorFeature::BaseVectorLinesFeature(BaseVectorLinesFeature::new(
+                Some(2),
+                vec![VectorLineWithOffset::new(
+                    0.0,
+                    vec![Point::new(0, 100), Point::new(100, 100)],
+                )],
+                Properties::default(),
+                None,
+            )),
+            // a square with a square hole
+            BaseVectorFeature::BaseVectorPolysFeature(BaseVectorPolysFeature::new(
+                Some(3),
+                vec![vec![
+                    VectorLineWithOffset::new(0.0, ring(200, 400)),
+                    VectorLineWithOffset::new(0.0, ring(250, 350)),
+                ]],
+                Properties::default(),
+                None,
+                vec![],
+                vec![],
+            )),
+            BaseVectorFeature::BaseVectorPolys3DFeature(BaseVectorPolys3DFeature::new(
+                Some(4),
+                vec![vec![VectorLine3DWithOffset::new(
+                    0.0,
+                    ring(1_000, 1_100).into_iter().map(|p| Point3D::new(p.x, p.y, 7)).collect(),
+                )]],
+                Properties::default(),
+                None,
+                vec![],
+                vec![],
+            )),
+        ];
+        let mut tile = BaseVectorTile::default();
+        tile.add_layer(BaseVectorLayer::new(
+            "test".into(),
+            Extent::Extent4096,
+            features,
+            None,
+            None,
+        ));
+        VectorTile::new(write_tile(Some(&mut tile), None, None), None)
+    }
+
+    #[test]
+    fn test_query_point() {
+        let mut tile = tile();
+        let layer = tile.layer("test").unwrap();
+        let mut ids = |x: f64, y: f64, tolerance: f64| {
+            let indices = layer.query_point(x, y, tolerance);
+            indices.into_iter().map(|i| layer.feature(i).unwrap().id().unwrap()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(10.0, 11.0, 2.0), vec![1]);
+        assert_eq!(ids(10.0, 13.0, 2.0), Vec::<u64>::new());
+        assert_eq!(ids(50.0, 102.0, 3.0), vec![2]);
+        assert_eq!(ids(150.0, 100.0, 49.0), Vec::<u64>::new());
+        // inside of the polygon, its hole and near the hole's edge
+        assert_eq!(ids(220.0, 220.0, 0.0), vec![3]);
+        assert_eq!(ids(300.0, 300.0, 0.0), Vec::<u64>::new());
+        assert_eq!(ids(300.0, 349.0, 1.0), vec![3]);
+        // 3D geometry is tested on the x/y plane
+        assert_eq!(ids(1_050.0, 1_050.0, 0.0), vec![4]);
+        assert_eq!(ids(1_050.0, 1_101.0, 2.0), vec![4]);
+    }
+
+    #[test]
+    fn test_query_bbox() {
+        let mut tile = tile();
+        let layer = tile.layer("test").unwrap();
+        let mut ids = |min: (i32, i32), max: (i32, i32)| {
+            let indices = layer.query_bbox(Point::new(min.0, min.1), Point::new(max.0, max.1));
+            indices.into_iter().map(|i| layer.feature(i).unwrap().id().unwrap()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids((0, 0), (4_096, 4_096)), vec![1, 2, 3, 4]);
+        assert_eq!(ids((5, 5), (15, 15)), vec![1]);
+        // the line crosses the box without a vertex in it
+        assert_eq!(ids((40, 90), (60, 110)), vec![2]);
+        assert_eq!(ids((40, 101), (60, 110)), Vec::<u64>::new());
+        // the box is inside the polygon, inside its hole or across the hole's edge
+        assert_eq!(ids((205, 205), (210, 210)), vec![3]);
+        assert_eq!(ids((260, 260), (340, 340)), Vec::<u64>::new());
+        assert_eq!(ids((240, 240), (260, 260)), vec![3]);
+        assert_eq!(ids((1_050, 1_050), (2_000, 2_000)), vec![4]);
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(all(test, feature = "s2tiles"))]
+mod tests {
+    extern crate alloc;
+
+    use crate::{point_tile, temp_file};
+    use open_vector_tile::{
+        VectorFeatureMethods, VectorLayerMethods,
+        s2tiles::{S2Tiles, S2TilesError, write_archive},
+        tile_id::S2TileId,
+        write_tile,
+    };
+    use s2json::Face;
+    use serde_json::json;
+    use std::{fs, io::Cursor};
+
+    fn tile(id: u64) -> Vec<u8> {
+        write_tile(Some(&mut point_tile("points", id)), None, None)
+    }
+
+    #[test]
+    fn test_s2tiles() {
+        let faces = [Face::Face0, Face::Face1, Face::Face2, Face::Face3, Face::Face4, Face::Face5];
+        let mut tiles: Vec<(S2TileId, Vec<u8>)> =
+            faces.iter().map(|&face| (S2TileId::new(face, 0, 0, 0), tile(face as u64))).collect();
+        // the children of face 4, all with the same data
+        let parent = S2TileId::new(Face::Face4, 0, 0, 0);
+        tiles.extend(parent.children().unwrap().map(|child| (child, tile(100))));
+
+        let path = temp_file("faces.s2tiles");
+        let header =
+            write_archive(fs::File::create(&path).unwrap(), tiles, &json!({ "name": "faces" }))
+                .unwrap();
+        assert_eq!((header.min_zoom, header.max_zoom), (0, 1));
+        assert_eq!(header.faces, 0b111111);
+        assert_eq!(header.tiles, 10);
+
+        let mut archive = S2Tiles::open(&path).unwrap();
+        assert_eq!(archive.header(), &header);
+        assert_eq!(archive.metadata().unwrap()["name"], "faces");
+        let ids: Vec<S2TileId> = archive.tile_ids().collect();
+        assert_eq!(ids.len(), 10);
+        assert!(ids.windows(2).all(|pair| pair[0].id() < pair[1].id()));
+        // the descendants of a tile are stored next to it
+        assert_eq!(ids[4..9].iter().filter(|id| parent.contains(id)).count(), 5);
+
+        for face in faces {
+            let mut vector_tile = archive.tile(S2TileId::new(face, 0, 0, 0)).unwrap().unwrap();
+            let points = vector_tile.layer("points").unwrap();
+            assert_eq!(points.feature(0).unwrap().id(), Some(face as u64));
+        }
+        for child in parent.children().unwrap() {
+            assert_eq!(archive.tile_data(child).unwrap().unwrap(), tile(100));
+        }
+        assert!(archive.tile_data(S2TileId::new(Face::Face0, 1, 0, 0)).unwrap().is_none());
+        assert!(archive.tile_data(S2TileId::new(Face::Face0, 1, 2, 0)).unwrap().is_none());
+
+        // identical tiles are stored once
+        let size = fs::metadata(&path).unwrap().len() as usize;
+        assert!(size < 48 + 7 * tile(100).len() + 20 + 10 * 20);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_s2tiles() {
+        let invalid = S2TileId::new(Face::Face0, 1, 2, 0);
+        let err = write_archive(vec![], vec![(invalid, tile(1))], &json!({})).unwrap_err();
+        assert!(matches!(err, S2TilesError::InvalidTileId(id) if id == invalid));
+        assert_eq!(err.to_string(), "s2tiles: invalid tile 0/1/2/0");
+
+        let data = fs::read("./tests/fixtures/multi-point.pbf").unwrap();
+        assert!(matches!(S2Tiles::new(Cursor::new(data)), Err(S2TilesError::InvalidMagic)));
+
+        let mut buf = vec![];
+        let id = S2TileId::new(Face::Face5, 0, 0, 0);
+        write_archive(&mut buf, vec![(id, tile(1))], &json!({})).unwrap();
+        let mut archive = S2Tiles::new(Cursor::new(buf.clone())).unwrap();
+        assert_eq!(archive.tile_data(id).unwrap().unwrap(), tile(1));
+        buf.truncate(buf.len() - 1);
+        assert!(matches!(S2Tiles::new(Cursor::new(buf)), Err(S2TilesError::InvalidDirectory)));
+    }
+}
@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use open_vector_tile::{
+        DecodeError, Point, ProtoMessage, VectorFeatureMethods, VectorLayer, VectorLayerMethods,
+        VectorTile,
+        base::{BaseVectorFeature, BaseVectorLayer, BaseVectorPointsFeature, BaseVectorTile},
+        borrowed::{BorrowedVectorLayer, BorrowedVectorTile},
+        open::Extent,
+        query::{intersects_bbox, intersects_point},
+        spatial_index::{SpatialIndex, geometry_bounds},
+        write_tile,
+    };
+    use pbf::Protobuf;
+    use s2json::Properties;
+    use std::fs;
+
+    /// A small deterministic pseudo random generator
+    fn random(seed: &mut u64, max: i32) -> i32 {
+        *seed =
+            seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        ((*seed >> 33) % max as u64) as i32
+    }
+
+    fn brute_force(boxes: &[[i32; 4]], q: [i32; 4]) -> Vec<usize> {
+        (0..boxes.len())
+            .filter(|&i| {
+                let b = boxes[i];
+                b[0] <= q[2] && b[1] <= q[3] && b[2] >= q[0] && b[3] >= q[1]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_spatial_index() {
+        let mut seed = 7;
+        let boxes: Vec<[i32; 4]> = (0..1_000)
+            .map(|_| {
+                let (x, y) = (random(&mut seed, 4_096), random(&mut seed, 4_096));
+                [x, y, x + random(&mut seed, 50), y + random(&mut seed, 50)]
+            })
+            .collect();
+        for node_size in [2, 5, 16] {
+            let index = SpatialIndex::new(boxes.iter().copied().enumerate().collect(), node_size);
+            assert_eq!(index.len(), 1_000);
+            for _ in 0..50 {
+                let (x, y) = (random(&mut seed, 4_096), random(&mut seed, 4_096));
+                let query = [x, y, x + random(&mut seed, 500), y + random(&mut seed, 500)];
+                assert_eq!(
+                    index.search(query[0], query[1], query[2], query[3]),
+                    brute_force(&boxes, query)
+                );
+            }
+            assert_eq!(index.search(-10, -10, 5_000, 5_000).len(), 1_000);
+            assert!(index.search(5_000, 5_000, 6_000, 6_000).is_empty());
+        }
+
+        // small and empty indexes
+        let index = SpatialIndex::new(vec![(3, [1, 1, 2, 2])], 16);
+        assert_eq!(index.search(0, 0, 1, 1), vec![3]);
+        assert_eq!(index.search_point(4.5, 4.5, 1.0), Vec::<usize>::new());
+        assert_eq!(index.search_point(4.5, 4.5, 2.5), vec![3]);
+        let index = SpatialIndex::new(vec![], 16);
+        assert!(index.is_empty());
+        assert!(index.search(0, 0, 10, 10).is_empty());
+
+        // protobuf round trip
+        let index = SpatialIndex::new(boxes.iter().copied().enumerate().collect(), 16);
+        let mut pb = Protobuf::new();
+        pb.write_message(1, &index);
+        let mut pb = Protobuf::from(pb.take());
+        let _message_id = pb.read_field();
+        let mut index_res = SpatialIndex::default();
+        pb.read_message(&mut index_res);
+        assert_eq!(index_res, index);
+
+        assert_eq!(
+            geometry_bounds(&open_vector_tile::VectorGeometry::VectorPoints(vec![
+                Point::new(5, -2),
+                Point::new(-1, 8)
+            ])),
+            Some([-1, -2, 5, 8])
+        );
+    }
+
+    fn points_tile(spatial_index: bool) -> Vec<u8> {
+        let mut seed = 11;
+        let features = (0..2_000)
+            .map(|i| {
+                BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                    Some(i),
+                    vec![Point::new(random(&mut seed, 4_096), random(&mut seed, 4_096))],
+                    Properties::default(),
+                    None,
+                ))
+            })
+            .collect();
+        let mut layer =
+            BaseVectorLayer::new("pois".into(), Extent::Extent4096, features, None, None);
+        layer.spatial_index = spatial_index;
+        let mut tile = BaseVectorTile::default();
+        tile.add_layer(layer);
+        write_tile(Some(&mut tile), None, None)
+    }
+
+    #[test]
+    fn test_layer_spatial_index() {
+        let data = points_tile(true);
+        assert!(data.len() > points_tile(false).len());
+        let mut indexed = VectorTile::new(data, None);
+        let mut plain = VectorTile::new(points_tile(false), None);
+        let Some(VectorLayer::Open(indexed)) = indexed.layers.get_mut("pois") else {
+            panic!("expected an open layer")
+        };
+        let Some(VectorLayer::Open(plain)) = plain.layers.get_mut("pois") else {
+            panic!("expected an open layer")
+        };
+
+        // the stored index matches the one built on demand
+        assert_eq!(indexed.spatial_index().len(), 2_000);
+        assert_eq!(indexed.spatial_index().clone(), plain.spatial_index().clone());
+        assert!(indexed.has_spatial_index());
+        assert!(!plain.has_spatial_index());
+
+        // borrowed layers expose the stored index as is
+        let data = points_tile(true);
+        let borrowed = BorrowedVectorTile::new(&data, None);
+        let Some(BorrowedVectorLayer::Open(borrowed)) = borrowed.layer("pois") else {
+            panic!("expected an open layer")
+        };
+        assert_eq!(borrowed.spatial_index(), Some(plain.spatial_index()));
+        let data = points_tile(false);
+        let borrowed = BorrowedVectorTile::new(&data, None);
+        let Some(BorrowedVectorLayer::Open(borrowed)) = borrowed.layer("pois") else {
+            panic!("expected an open layer")
+        };
+        assert!(borrowed.spatial_index().is_none());
+
+        // converting the layer keeps the index
+        let mut tile = BaseVectorTile::default();
+        tile.add_layer(BaseVectorLayer::from(&mut *indexed));
+        assert!(tile.layers["pois"].spatial_index);
+        let mut rewritten = VectorTile::new(write_tile(Some(&mut tile), None, None), None);
+        let Some(VectorLayer::Open(rewritten)) = rewritten.layers.get_mut("pois") else {
+            panic!("expected an open layer")
+        };
+        assert!(rewritten.has_spatial_index());
+        assert_eq!(rewritten.spatial_index().len(), 2_000);
+
+        let (min, max) = (Point::new(1_000, 1_000), Point::new(1_400, 1_200));
+        let expected: Vec<usize> = (0..plain.len())
+            .filter(|&i| intersects_bbox(&plain.feature(i).unwrap().load_geometry(), &min, &max))
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(indexed.query_bbox(min.clone(), max.clone()), expected);
+        assert_eq!(plain.query_bbox(min, max), expected);
+
+        let expected: Vec<usize> = (0..plain.len())
+            .filter(|&i| {
+                intersects_point(
+                    &plain.feature(i).unwrap().load_geometry(),
+                    2_000.0,
+                    2_000.0,
+                    150.0,
+                )
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(indexed.query_point(2_000.0, 2_000.0, 150.0), expected);
+    }
+
+    #[test]
+    fn test_mapbox_layer_spatial_index() {
+        let data = fs::read("./tests/fixtures/12-1143-1497.vector.pbf").unwrap();
+        let mut tile = VectorTile::new(data, None);
+        let layer = tile.layer("road").unwrap();
+        let (min, max) = (Point::new(-4_096, -4_096), Point::new(-2_048, -2_048));
+        let expected: Vec<usize> = (0..layer.len())
+            .filter(|&i| intersects_bbox(&layer.feature(i).unwrap().load_geometry(), &min, &max))
+            .collect();
+        assert!(!expected.is_empty() && expected.len() < layer.len());
+        assert_eq!(layer.query_bbox(min, max), expected);
+    }
+
+    #[test]
+    fn test_invalid_spatial_index() {
+        let mut data = points_tile(true);
+        // the index is the last field of the layer and its node size is the first of the index
+        let pos = data.windows(2).rposition(|w| w == [0x08, 16]).unwrap();
+        data[pos + 1] = 1;
+        let err = VectorTile::try_new(data, None).unwrap_err();
+        assert!(matches!(err, DecodeError::Malformed { message: ProtoMessage::SpatialIndex, .. }));
+    }
+}
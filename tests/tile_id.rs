@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use open_vector_tile::tile_id::{S2TileId, WMTileId, tile_id_to_zxy, zxy_to_tile_id};
+    use s2json::Face;
+
+    const FACES: [Face; 6] =
+        [Face::Face0, Face::Face1, Face::Face2, Face::Face3, Face::Face4, Face::Face5];
+
+    #[test]
+    fn test_wm_tile_id() {
+        let tile = WMTileId::new(12, 3423, 1763);
+        assert_eq!(tile.id(), 19078479);
+        assert_eq!(tile.id(), zxy_to_tile_id(12, 3423, 1763));
+        assert_eq!(WMTileId::from_id(19078479), tile);
+        assert_eq!(tile_id_to_zxy(0), (0, 0, 0));
+        assert_eq!(tile.to_string(), "12/3423/1763");
+        assert!(tile.is_valid());
+        assert!(!WMTileId::new(1, 2, 0).is_valid());
+        assert_eq!(WMTileId::from_lon_lat(-0.5, 0.5, 1), WMTileId::new(1, 0, 0));
+        assert_eq!(WMTileId::from_lon_lat(179., -80., 2), WMTileId::new(2, 3, 3));
+
+        // parents and children
+        let parent = tile.parent().unwrap();
+        assert_eq!(parent, WMTileId::new(11, 1711, 881));
+        assert!(parent.children().unwrap().contains(&tile));
+        assert!(parent.contains(&tile) && !tile.contains(&parent));
+        assert!(WMTileId::new(0, 0, 0).contains(&tile));
+        assert_eq!(WMTileId::new(0, 0, 0).parent(), None);
+        assert_eq!(
+            WMTileId::new(1, 1, 0).children(),
+            Some([
+                WMTileId::new(2, 2, 0),
+                WMTileId::new(2, 3, 0),
+                WMTileId::new(2, 2, 1),
+                WMTileId::new(2, 3, 1)
+            ])
+        );
+
+        // the deepest zoom has no children, and shifts past it do not overflow
+        assert_eq!(WMTileId::new(30, 0, 0).children(), None);
+        assert_eq!(WMTileId::new(255, 0, 0).children(), None);
+        assert!(!WMTileId::new(0, 0, 0).contains(&WMTileId::new(40, 0, 0)));
+        assert!(!WMTileId::new(40, 0, 0).contains(&WMTileId::new(0, 0, 0)));
+        assert_eq!(WMTileId::new(30, 0, 0).neighbors().len(), 3);
+        assert_eq!(WMTileId::new(64, 0, 0).neighbors(), vec![]);
+
+        // neighbors wrap around the antimeridian but not the poles
+        assert_eq!(WMTileId::new(0, 0, 0).neighbors(), vec![]);
+        assert_eq!(
+            WMTileId::new(1, 0, 0).neighbors(),
+            vec![WMTileId::new(1, 1, 0), WMTileId::new(1, 0, 1)]
+        );
+        assert_eq!(
+            WMTileId::new(2, 0, 1).neighbors(),
+            vec![
+                WMTileId::new(2, 0, 0),
+                WMTileId::new(2, 1, 1),
+                WMTileId::new(2, 0, 2),
+                WMTileId::new(2, 3, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_s2_tile_id() {
+        // tile IDs are S2 cell IDs
+        for face in FACES {
+            let tile = S2TileId::new(face, 0, 0, 0);
+            assert_eq!(tile.id(), (2 * face as u64 + 1) << 60);
+            assert_eq!(S2TileId::from_id(tile.id()), Some(tile));
+        }
+        let center = S2TileId::from_lon_lat(0., 0., 30);
+        assert_eq!(center, S2TileId::new(Face::Face0, 30, 1 << 29, 1 << 29));
+        assert_eq!(center.id(), 0x1000000000000001);
+        assert_eq!(S2TileId::from_lon_lat(0., 90., 0).face, Face::Face2);
+        assert_eq!(S2TileId::from_lon_lat(-90., 0., 3), S2TileId::new(Face::Face4, 3, 4, 4));
+        assert_eq!(S2TileId::new(Face::Face4, 3, 4, 4).to_string(), "4/3/4/4");
+        assert_eq!(S2TileId::from_id(0), None);
+        assert_eq!(S2TileId::from_id(0x1000000000000002), None);
+        assert_eq!(S2TileId::from_id(7 << 61 | 1), None);
+        assert!(!S2TileId::new(Face::Face1, 2, 0, 4).is_valid());
+
+        // children are returned in ID order, and IDs of descendants fall inside their ancestors
+        let parent = S2TileId::new(Face::Face3, 4, 5, 9);
+        let children = parent.children().unwrap();
+        let ids: Vec<u64> = children.iter().map(|child| child.id()).collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        let lsb = parent.id() & parent.id().wrapping_neg();
+        for child in children {
+            assert_eq!(child.parent(), Some(parent));
+            assert!(parent.contains(&child));
+            assert!(child.id() > parent.id() - lsb && child.id() < parent.id() + lsb);
+            assert_eq!(S2TileId::from_id(child.id()), Some(child));
+        }
+        assert_eq!(S2TileId::new(Face::Face0, 1, 0, 0).id(), 0x0400000000000000);
+        assert!(!parent.contains(&S2TileId::new(Face::Face2, 5, 10, 18)));
+
+        // the deepest zoom has no children, and shifts past it do not overflow
+        assert_eq!(S2TileId::new(Face::Face0, 30, 0, 0).children(), None);
+        assert_eq!(S2TileId::new(Face::Face0, 255, 0, 0).children(), None);
+        assert!(!S2TileId::new(Face::Face0, 0, 0, 0).contains(&S2TileId::new(
+            Face::Face0,
+            40,
+            0,
+            0
+        )));
+        assert!(S2TileId::new(Face::Face0, 30, 0, 0).neighbors().is_some());
+        assert_eq!(S2TileId::new(Face::Face0, 31, 0, 0).neighbors(), None);
+    }
+
+    #[test]
+    fn test_s2_tile_id_neighbors() {
+        // each face borders the four faces that are not itself or its opposite
+        let faces = |tile: S2TileId| tile.neighbors().unwrap().map(|neighbor| neighbor.face as u8);
+        assert_eq!(faces(S2TileId::new(Face::Face0, 0, 0, 0)), [5, 1, 2, 4]);
+        assert_eq!(faces(S2TileId::new(Face::Face3, 0, 0, 0)), [1, 5, 4, 2]);
+
+        // inside a face
+        assert_eq!(
+            S2TileId::new(Face::Face1, 2, 1, 2).neighbors(),
+            Some([
+                S2TileId::new(Face::Face1, 2, 1, 1),
+                S2TileId::new(Face::Face1, 2, 2, 2),
+                S2TileId::new(Face::Face1, 2, 1, 3),
+                S2TileId::new(Face::Face1, 2, 0, 2),
+            ])
+        );
+
+        // neighbors are symmetric, including across faces
+        for face in FACES {
+            for zoom in 0..4 {
+                for i in 0..1 << zoom {
+                    for j in 0..1 << zoom {
+                        let tile = S2TileId::new(face, zoom, i, j);
+                        for neighbor in tile.neighbors().unwrap() {
+                            assert!(neighbor.is_valid());
+                            assert_ne!(neighbor, tile);
+                            assert!(
+                                neighbor.neighbors().unwrap().contains(&tile),
+                                "{tile} {neighbor}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
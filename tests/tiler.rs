@@ -0,0 +1,330 @@
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use open_vector_tile::{
+        Point, VectorFeatureMethods, VectorGeometry, VectorLayerMethods, VectorLineWithOffset,
+        VectorTile,
+        base::{BaseVectorFeature, BaseVectorLinesFeature, BaseVectorPointsFeature},
+        open::Extent,
+        projection::lon_lat_to_face_st,
+        tiler::{
+            TileProjection, Tiler, TilerOptions, clip_line, clip_line_with_offset, clip_points,
+            clip_ring, simplify,
+        },
+        write_tile,
+    };
+    use s2json::{
+        BaseGeometry, Face, Feature, Geometry, GeometryType, MValue, Point as GeoPoint,
+        Point3D as GeoPoint3D, Properties, VectorFeature, VectorGeometry as S2Geometry,
+        VectorPoint,
+    };
+
+    fn vp(x: f64, y: f64) -> VectorPoint {
+        VectorPoint::new_xy(x, y, None)
+    }
+
+    fn xy(points: &[VectorPoint]) -> Vec<(f64, f64)> {
+        points.iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    fn geojson(geometry: Geometry) -> Feature {
+        Feature::new(Some(1), Properties::from([("name".into(), "test".into())]), geometry, None)
+    }
+
+    #[test]
+    fn test_clip() {
+        let points = [vp(0.5, 0.5), vp(-0.5, 0.5), vp(1.0, 1.0)];
+        assert_eq!(xy(&clip_points(&points, 0.0, 1.0)), vec![(0.5, 0.5), (1.0, 1.0)]);
+
+        // leaves and re-enters the box
+        let line = [vp(-1.0, 0.5), vp(0.5, 0.5), vp(0.5, 2.0), vp(0.75, 2.0), vp(0.75, 0.75)];
+        let lines = clip_line(&line, 0.0, 1.0);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(xy(&lines[0]), vec![(0.0, 0.5), (0.5, 0.5), (0.5, 1.0)]);
+        assert_eq!(xy(&lines[1]), vec![(0.75, 1.0), (0.75, 0.75)]);
+        assert!(clip_line(&[vp(2.0, 2.0), vp(3.0, 3.0)], 0.0, 1.0).is_empty());
+        // each clipped line knows how much of the line came before it
+        let offsets: Vec<f64> =
+            clip_line_with_offset(&line, 0.0, 1.0).into_iter().map(|(o, _)| o).collect();
+        assert_eq!(offsets, vec![1.0, 1.5 + 1.5 + 0.25 + 1.0]);
+
+        // z and M-Values are carried through
+        let m = MValue::from([("a".into(), 1_u32.into())]);
+        let line = [
+            VectorPoint::new_xyz(-1.0, 0.5, 0.0, Some(m.clone())),
+            VectorPoint::new_xyz(1.0, 0.5, 10.0, None),
+        ];
+        let lines = clip_line(&line, 0.0, 1.0);
+        assert_eq!(lines[0][0].z, Some(5.0));
+        assert_eq!(lines[0][0].m, Some(m));
+
+        let ring = [vp(-1.0, -1.0), vp(2.0, -1.0), vp(2.0, 0.5), vp(-1.0, 0.5), vp(-1.0, -1.0)];
+        assert_eq!(
+            xy(&clip_ring(&ring, 0.0, 1.0)),
+            vec![(0.0, 0.0), (1.0, 0.0), (1.0, 0.5), (0.0, 0.5), (0.0, 0.0)]
+        );
+        let ring = [vp(2.0, 2.0), vp(3.0, 2.0), vp(3.0, 3.0), vp(2.0, 2.0)];
+        assert!(clip_ring(&ring, 0.0, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_simplify() {
+        let line = [vp(0.0, 0.0), vp(1.0, 0.1), vp(2.0, -0.1), vp(3.0, 5.0), vp(4.0, 6.0)];
+        assert_eq!(
+            xy(&simplify(&line, 0.5)),
+            vec![(0.0, 0.0), (2.0, -0.1), (3.0, 5.0), (4.0, 6.0)]
+        );
+        assert_eq!(xy(&simplify(&line, 10.0)), vec![(0.0, 0.0), (4.0, 6.0)]);
+        assert_eq!(simplify(&line, 0.0).len(), 5);
+    }
+
+    #[test]
+    fn test_tiler_web_mercator() {
+        let mut tiler =
+            Tiler::new(TilerOptions { extent: Extent::Extent512, ..Default::default() });
+        tiler.add_feature(
+            "points",
+            &geojson(Geometry::Point(BaseGeometry {
+                _type: GeometryType::Point,
+                coordinates: GeoPoint(0.0, 0.0),
+                ..Default::default()
+            })),
+        );
+        tiler.add_feature(
+            "lines",
+            &geojson(Geometry::LineString(BaseGeometry {
+                _type: GeometryType::LineString,
+                coordinates: vec![GeoPoint(-170.0, 0.0), GeoPoint(170.0, 0.0)],
+                ..Default::default()
+            })),
+        );
+        tiler.add_feature(
+            "polys",
+            &geojson(Geometry::Polygon(BaseGeometry {
+                _type: GeometryType::Polygon,
+                coordinates: vec![vec![
+                    GeoPoint(-90.0, -60.0),
+                    GeoPoint(90.0, -60.0),
+                    GeoPoint(90.0, 60.0),
+                    GeoPoint(-90.0, 60.0),
+                    GeoPoint(-90.0, -60.0),
+                ]],
+                ..Default::default()
+            })),
+        );
+
+        let tile = tiler.get_tile(Face::Face0, 0, 0, 0);
+        assert_eq!(tile.layers.len(), 3);
+        assert_eq!(
+            tile.layers["points"].features,
+            vec![BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                Some(1),
+                vec![Point::new(256, 256)],
+                Properties::from([("name".into(), "test".into())]),
+                None,
+            ))]
+        );
+        assert_eq!(
+            tile.layers["lines"].features,
+            vec![BaseVectorFeature::BaseVectorLinesFeature(BaseVectorLinesFeature::new(
+                Some(1),
+                vec![VectorLineWithOffset::new(
+                    0.0,
+                    vec![Point::new(14, 256), Point::new(498, 256)]
+                )],
+                Properties::from([("name".into(), "test".into())]),
+                None,
+            ))]
+        );
+
+        // zoom 1: the line is clipped to each tile plus its buffer
+        let tile = tiler.get_tile(Face::Face0, 1, 0, 0);
+        let BaseVectorFeature::BaseVectorLinesFeature(line) = &tile.layers["lines"].features[0]
+        else {
+            panic!("expected a lines feature")
+        };
+        assert_eq!(line.geometry[0].geometry, vec![Point::new(28, 512), Point::new(576, 512)]);
+        assert!(!tile.layers.contains_key("nothing"));
+        // far away tiles have nothing in them
+        assert!(tiler.get_tile(Face::Face0, 4, 0, 0).layers.is_empty());
+
+        // the generated tiles can be written and read back
+        let mut tile = tiler.get_tile(Face::Face0, 1, 1, 1);
+        let data = write_tile(Some(&mut tile), None, None);
+        let mut tile = VectorTile::new(data, None);
+        let polys = tile.layer("polys").unwrap();
+        assert_eq!(polys.extent(), 512);
+        let mut feature = polys.feature(0).unwrap();
+        assert_eq!(feature.id(), Some(1));
+        let VectorGeometry::VectorPolys(polys) = feature.load_geometry() else {
+            panic!("expected polygons")
+        };
+        let ring: Vec<(i32, i32)> = polys[0][0].geometry.iter().map(|p| (p.x, p.y)).collect();
+        // the tile covers lon 0 -> 180 and lat 0 -> -85, the outer ring is wound clockwise
+        assert_eq!(ring.len(), 5);
+        assert_eq!(ring[0], ring[4]);
+        for p in [(-64, -64), (256, -64), (256, 215), (-64, 215)] {
+            assert!(ring.contains(&p));
+        }
+        let area: i32 = ring.windows(2).map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1).sum();
+        assert!(area > 0);
+    }
+
+    #[test]
+    fn test_tiler_line_offsets() {
+        let mut tiler =
+            Tiler::new(TilerOptions { extent: Extent::Extent512, ..Default::default() });
+        tiler.add_feature(
+            "lines",
+            &geojson(Geometry::LineString(BaseGeometry {
+                _type: GeometryType::LineString,
+                coordinates: vec![GeoPoint(-170.0, 0.0), GeoPoint(170.0, 0.0)],
+                ..Default::default()
+            })),
+        );
+        tiler.add_feature(
+            "lines3d",
+            &geojson(Geometry::LineString3D(BaseGeometry {
+                _type: GeometryType::LineString3D,
+                coordinates: vec![GeoPoint3D(-170.0, 0.0, 1.0), GeoPoint3D(170.0, 0.0, 1.0)],
+                ..Default::default()
+            })),
+        );
+
+        // the line starts in the tile, so nothing comes before it
+        let tile = tiler.get_tile(Face::Face0, 1, 0, 0);
+        let BaseVectorFeature::BaseVectorLinesFeature(line) = &tile.layers["lines"].features[0]
+        else {
+            panic!("expected a lines feature")
+        };
+        assert_eq!(line.geometry[0].offset, 0.0);
+
+        // in the next tile the line starts at the buffer, the offset is the length of the line
+        // left of it in extent units
+        let expected = (-64.0 / 512.0 - ((10.0 / 360.0) * 2.0 - 1.0)) * 512.0;
+        let tile = tiler.get_tile(Face::Face0, 1, 1, 0);
+        let BaseVectorFeature::BaseVectorLinesFeature(line) = &tile.layers["lines"].features[0]
+        else {
+            panic!("expected a lines feature")
+        };
+        assert_eq!(line.geometry[0].geometry[0], Point::new(-64, 512));
+        assert!((line.geometry[0].offset - expected).abs() < 1e-9);
+        let BaseVectorFeature::BaseVectorLines3DFeature(line) = &tile.layers["lines3d"].features[0]
+        else {
+            panic!("expected a 3D lines feature")
+        };
+        assert!((line.geometry[0].offset - expected).abs() < 1e-9);
+
+        // and survives writing the tile
+        let mut tile = tile;
+        let data = write_tile(Some(&mut tile), None, None);
+        let mut tile = VectorTile::new(data, None);
+        let mut feature = tile.layer("lines").unwrap().feature(0).unwrap();
+        let VectorGeometry::VectorLines(lines) = feature.load_geometry() else {
+            panic!("expected lines")
+        };
+        assert!((lines[0].offset - expected).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_tiler_simplifies_below_max_zoom() {
+        let coordinates: Vec<GeoPoint> = (0..=100)
+            .map(|i| GeoPoint(i as f64 / 10.0, if i % 2 == 0 { 0.0 } else { 0.0001 }))
+            .collect();
+        let mut tiler = Tiler::new(TilerOptions { max_zoom: 10, ..Default::default() });
+        tiler.add_feature(
+            "lines",
+            &geojson(Geometry::LineString(BaseGeometry {
+                _type: GeometryType::LineString,
+                coordinates,
+                ..Default::default()
+            })),
+        );
+        let len = |zoom: u8, x: u32, y: u32| {
+            let tile = tiler.get_tile(Face::Face0, zoom, x, y);
+            let BaseVectorFeature::BaseVectorLinesFeature(line) = &tile.layers["lines"].features[0]
+            else {
+                panic!("expected a lines feature")
+            };
+            line.geometry[0].geometry.len()
+        };
+        assert_eq!(len(0, 0, 0), 2);
+        assert!(len(10, 512, 511) > 2);
+    }
+
+    #[test]
+    fn test_tiler_s2() {
+        let mut tiler = Tiler::new(TilerOptions {
+            projection: TileProjection::S2,
+            extent: Extent::Extent4096,
+            ..Default::default()
+        });
+        // a lon/lat point is projected to its face
+        tiler.add_feature(
+            "points",
+            &geojson(Geometry::Point(BaseGeometry {
+                _type: GeometryType::Point,
+                coordinates: GeoPoint(-79.5, 43.5),
+                ..Default::default()
+            })),
+        );
+        // S2 features are kept on their face
+        let mut feature = VectorFeature::<()>::new_s2(
+            Some(2),
+            Face::Face2,
+            Properties::default(),
+            S2Geometry::new_point(VectorPoint::new_xy(0.25, 0.75, None), None),
+            None,
+        );
+        feature._type = "S2Feature".into();
+        tiler.add_vector_feature("points", &feature);
+
+        let (face, s, t) = lon_lat_to_face_st(-79.5, 43.5);
+        let tile = tiler.get_tile(face, 0, 0, 0);
+        let BaseVectorFeature::BaseVectorPointsFeature(point) = &tile.layers["points"].features[0]
+        else {
+            panic!("expected a points feature")
+        };
+        assert_eq!(point.id, Some(1));
+        assert_eq!(
+            point.geometry,
+            vec![Point::new((s * 4096.0).round() as i32, (t * 4096.0).round() as i32)]
+        );
+
+        let tile = tiler.get_tile(Face::Face2, 1, 0, 1);
+        assert_eq!(tile.layers["points"].features.len(), 1);
+        let BaseVectorFeature::BaseVectorPointsFeature(point) = &tile.layers["points"].features[0]
+        else {
+            panic!("expected a points feature")
+        };
+        assert_eq!(point.id, Some(2));
+        assert_eq!(point.geometry, vec![Point::new(2048, 2048)]);
+
+        // S2 features on the Web Mercator face have lon/lat coordinates like WM features
+        let mut tiler = Tiler::new(TilerOptions {
+            projection: TileProjection::S2,
+            extent: Extent::Extent4096,
+            ..Default::default()
+        });
+        let mut feature = VectorFeature::<()>::new_s2(
+            Some(3),
+            Face::WM,
+            Properties::default(),
+            S2Geometry::new_point(VectorPoint::new_xy(-79.5, 43.5, None), None),
+            None,
+        );
+        feature._type = "S2Feature".into();
+        tiler.add_vector_feature("points", &feature);
+        let tile = tiler.get_tile(face, 0, 0, 0);
+        let BaseVectorFeature::BaseVectorPointsFeature(point) = &tile.layers["points"].features[0]
+        else {
+            panic!("expected a points feature")
+        };
+        assert_eq!(point.id, Some(3));
+        assert_eq!(
+            point.geometry,
+            vec![Point::new((s * 4096.0).round() as i32, (t * 4096.0).round() as i32)]
+        );
+    }
+}
@@ -132,6 +132,22 @@ mod tests {
         assert_eq!(array, decoded);
     }
 
+    #[test]
+    fn test_delta_decode_wraps() {
+        assert_eq!(
+            delta_decode_array(&[zigzag(i32::MAX), zigzag(1)]),
+            vec![i32::MAX as u32, i32::MIN as u32]
+        );
+        assert_eq!(delta_decode_sorted_array(&[i32::MAX as u32, 1]), vec![i32::MAX, i32::MIN]);
+        // each step moves x by i16::MAX, which passes i32::MAX after 65_538 steps
+        let steps = 70_000;
+        let decoded =
+            unweave_and_delta_decode_array(&vec![weave_2d(u16::MAX - 1, 0) as u64; steps]);
+        assert_eq!(decoded[steps - 1], Point::new(32_767i32.wrapping_mul(steps as i32), 0));
+        let decoded = unweave_and_delta_decode_3d_array(&vec![weave_3d(0, 0, u16::MAX - 1); steps]);
+        assert_eq!(decoded[steps - 1], Point3D::new(0, 0, 32_767i32.wrapping_mul(steps as i32)));
+    }
+
     #[test]
     fn test_quantize_dequantize_lon_lat() {
         assert_eq!(dequantize_lat(quantize_lat(0.0)), -5.364418356634815e-6);
@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use open_vector_tile::{
+        DecodeError, Extent, GridData, ImageData, ImageType, Point, VectorLineWithOffset,
+        VectorTile,
+        base::{
+            BaseVectorFeature, BaseVectorLayer, BaseVectorLinesFeature, BaseVectorPointsFeature,
+            BaseVectorPolysFeature, BaseVectorTile,
+        },
+        validate::{Location, Rule, Severity, Violation, validate_bytes, validate_tile},
+        write_tile,
+    };
+    use pbf::Protobuf;
+    use s2json::Properties;
+    use std::fs;
+
+    fn ring(points: &[(i32, i32)]) -> VectorLineWithOffset {
+        VectorLineWithOffset::new(0.0, points.iter().map(|(x, y)| Point::new(*x, *y)).collect())
+    }
+
+    fn line(id: u64, points: &[(i32, i32)]) -> BaseVectorFeature {
+        BaseVectorFeature::BaseVectorLinesFeature(BaseVectorLinesFeature::new(
+            Some(id),
+            vec![ring(points)],
+            Properties::default(),
+            None,
+        ))
+    }
+
+    fn poly(id: u64, rings: Vec<VectorLineWithOffset>, indices: Vec<u32>) -> BaseVectorFeature {
+        BaseVectorFeature::BaseVectorPolysFeature(BaseVectorPolysFeature::new(
+            Some(id),
+            vec![rings],
+            Properties::default(),
+            None,
+            indices,
+            vec![],
+        ))
+    }
+
+    fn feature(id: u64) -> Location {
+        Location::Feature { layer: "test".into(), index: 0, id: Some(id) }
+    }
+
+    #[test]
+    fn test_validate_valid_tile() {
+        let square = ring(&[(0, 0), (10, 0), (10, 10), (0, 10), (0, 0)]);
+        let features = vec![
+            BaseVectorFeature::BaseVectorPointsFeature(BaseVectorPointsFeature::new(
+                Some(1),
+                vec![Point::new(1, 1)],
+                Properties::default(),
+                None,
+            )),
+            line(2, &[(0, 0), (5, 5)]),
+            poly(3, vec![square], vec![0, 1, 2, 2, 3, 0]),
+        ];
+        let mut tile = BaseVectorTile::default();
+        tile.add_layer(BaseVectorLayer::new(
+            "test".into(),
+            Extent::Extent4096,
+            features,
+            None,
+            None,
+        ));
+        let image = ImageData::new("image".into(), ImageType::PNG, 4, 2, vec![1, 2, 3]);
+        let grid =
+            GridData::new("grid".into(), Extent::Extent512, 2.0, 0.0, 4.0, vec![0., 1., 2., 4.]);
+        let data = write_tile(Some(&mut tile), Some(vec![&image]), Some(vec![&grid]));
+        assert_eq!(validate_bytes(data), vec![]);
+
+        for name in ["12-1143-1497.vector.pbf", "multi-polygon.pbf", "polygon-with-inner.pbf"] {
+            let data = fs::read(format!("./tests/fixtures/{name}")).unwrap();
+            assert_eq!(validate_bytes(data), vec![], "{name}");
+        }
+    }
+
+    #[test]
+    fn test_validate_violations() {
+        let features = vec![
+            line(1, &[(0, 0)]),
+            // not closed
+            poly(2, vec![ring(&[(0, 0), (10, 0), (10, 10), (0, 10)])], vec![]),
+            // too short
+            poly(3, vec![ring(&[(0, 0), (10, 0), (0, 0)])], vec![]),
+            // indices past the end of the ring
+            poly(4, vec![ring(&[(0, 0), (10, 0), (10, 10), (0, 0)])], vec![0, 1, 4]),
+        ];
+        let mut tile = BaseVectorTile::default();
+        tile.add_layer(BaseVectorLayer::new(
+            "test".into(),
+            Extent::Extent4096,
+            features,
+            None,
+            None,
+        ));
+        tile.add_layer(BaseVectorLayer::new("empty".into(), Extent::Extent512, vec![], None, None));
+        let image = ImageData::new("image".into(), ImageType::UNKNOWN, 3, 2, vec![]);
+        let grid = GridData::new("grid".into(), Extent::Extent512, 3.0, 0.0, 4.0, vec![0.; 4]);
+        let data = write_tile(Some(&mut tile), Some(vec![&image]), Some(vec![&grid]));
+        let mut tile = VectorTile::new(data, None);
+        let mut violations = validate_tile(&mut tile);
+        // features are sorted by type when written, so compare them by id
+        violations.iter_mut().for_each(|v| {
+            if let Location::Feature { index, .. } = &mut v.location {
+                *index = 0;
+            }
+        });
+
+        let error = |location, rule| Violation { severity: Severity::Error, location, rule };
+        let warning = |location, rule| Violation { severity: Severity::Warning, location, rule };
+        assert_eq!(
+            violations,
+            vec![
+                warning(Location::Layer("empty".into()), Rule::EmptyLayer),
+                error(feature(1), Rule::ShortLine(1)),
+                error(feature(2), Rule::UnclosedRing),
+                error(feature(3), Rule::ShortRing(3)),
+                error(feature(4), Rule::InvalidIndices { len: 3, max: 4, vertices: 4 }),
+                error(Location::Grid("grid".into()), Rule::GridSize { size: 3.0, len: 4 }),
+                error(Location::Image("image".into()), Rule::ImageSize { width: 3, height: 2 }),
+                warning(Location::Image("image".into()), Rule::UnknownImageType),
+                error(Location::Image("image".into()), Rule::EmptyData),
+            ]
+        );
+        assert_eq!(
+            violations[2].to_string(),
+            "error: layer \"test\" feature 0 (id 2): polygon ring is not closed"
+        );
+    }
+
+    #[test]
+    fn test_validate_bytes() {
+        // undecodable
+        let violations = validate_bytes(vec![0x22, 0x10, 0x01]);
+        assert!(matches!(
+            &violations[..],
+            [Violation {
+                severity: Severity::Error,
+                rule: Rule::Decode(DecodeError::UnexpectedEof { .. }),
+                ..
+            }]
+        ));
+
+        // two layers with the same name
+        let mut data = fs::read("./tests/fixtures/multi-point.pbf").unwrap();
+        data.extend(data.clone());
+        let violations = validate_bytes(data);
+        assert_eq!(violations[0].rule, Rule::DuplicateLayerName);
+
+        // an open layer without its column cache
+        let mut tile = BaseVectorTile::default();
+        tile.add_layer(BaseVectorLayer::new(
+            "test".into(),
+            Extent::Extent4096,
+            vec![line(1, &[(0, 0), (1, 1)])],
+            None,
+            None,
+        ));
+        let mut pb = Protobuf::from_input(write_tile(Some(&mut tile), None, None));
+        let mut out = Protobuf::new();
+        while pb.get_pos() < pb.len() {
+            let field = pb.read_field();
+            let bytes = pb.read_bytes();
+            if field.tag != 5 {
+                out.write_bytes_field(field.tag, &bytes);
+            }
+        }
+        let violations = validate_bytes(out.take());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, Rule::MissingColumnCache);
+
+        // Mapbox layers may use any extent but 0
+        let mapbox_tile = |extent: u32| {
+            let mut feature = Protobuf::new();
+            feature.write_varint_field(3, 1);
+            feature.write_packed_varint::<u32>(4, &[9, 2, 2]);
+            let mut layer = Protobuf::new();
+            layer.write_varint_field(15, 2);
+            layer.write_string_field(1, "odd");
+            layer.write_bytes_field(2, &feature.take());
+            layer.write_varint_field(5, extent);
+            let mut tile = Protobuf::new();
+            tile.write_bytes_field(3, &layer.take());
+            tile.take()
+        };
+        assert_eq!(validate_bytes(mapbox_tile(100)), vec![]);
+        assert_eq!(
+            validate_bytes(mapbox_tile(0)),
+            vec![Violation {
+                severity: Severity::Error,
+                location: Location::Layer("odd".into()),
+                rule: Rule::InvalidExtent(0),
+            }]
+        );
+    }
+}